@@ -0,0 +1,3483 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{ed25519_program, sysvar};
+use anchor_lang::{system_program, AnchorDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, AcceptedMint, BatchRuling, BuyerGate, CancelReason, ConfirmationMode,
+    DeliveryRegion, DisputeReason, EmergencyVault, JuryConfig, LogisticsProviderAccount,
+    LoyaltyConfig, Milestone, OrderLimits, PayoutSplit, PresaleAllowlist, PurchaseAccount,
+    RelayedOrder, SellerAccount, SemVer, SwapRoute, TradeAccount,
+};
+use mpl_bubblegum::accounts::TreeConfig;
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+
+use crate::{pda, PROGRAM_ID};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    build_with_remaining(accounts, data, Vec::new())
+}
+
+fn build_with_remaining(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// Keys identifying a trade listing, as needed by purchase-side instructions.
+#[derive(Clone, Debug)]
+pub struct TradeKeys {
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub price_feed: Option<Pubkey>,
+    pub max_per_buyer: Option<u64>,
+    pub purchase_cooldown_slots: Option<u64>,
+    pub buyer_gate: Option<BuyerGate>,
+    pub ship_to_regions: u16,
+}
+
+impl TradeKeys {
+    pub fn from_account(trade: &TradeAccount) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            seller: trade.seller,
+            token_mint: trade.token_mint,
+            price_feed: trade.price_feed,
+            max_per_buyer: trade.max_per_buyer,
+            purchase_cooldown_slots: trade.purchase_cooldown_slots(),
+            buyer_gate: trade.buyer_gate(),
+            ship_to_regions: trade.ship_to_regions,
+        }
+    }
+
+    /// `buyer`'s quota account, needed when the trade limits units per buyer
+    /// or sets a purchase cooldown.
+    fn buyer_quota(&self, buyer: &Pubkey) -> Option<Pubkey> {
+        (self.max_per_buyer.is_some() || self.purchase_cooldown_slots.is_some())
+            .then(|| pda::buyer_quota_pda(self.trade_id, buyer).0)
+    }
+
+    /// Credential accounts `buyer` presents to a gated trade: their associated
+    /// token account for the gate mint, or their attestation from the verifier.
+    fn gate_accounts(&self, buyer: &Pubkey) -> (Option<Pubkey>, Option<Pubkey>) {
+        match self.buyer_gate {
+            Some(BuyerGate::TokenHolder { mint }) => {
+                (Some(get_associated_token_address(buyer, &mint)), None)
+            }
+            Some(BuyerGate::Attestation { verifier }) => {
+                (None, Some(pda::attestation_pda(&verifier, buyer).0))
+            }
+            None => (None, None),
+        }
+    }
+}
+
+/// Keys touched when a purchase is settled by confirmation or dispute.
+#[derive(Clone, Debug)]
+pub struct SettlementKeys {
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub logistics_provider: Pubkey,
+    pub token_mint: Pubkey,
+    /// Token accounts of the trade's payout split recipients, in split order.
+    pub payout_split_token_accounts: Vec<Pubkey>,
+    /// Storefront that routed the purchase, credited its fee share on settlement.
+    pub platform: Option<Pubkey>,
+    /// Payout account registered on the seller's `SellerAccount`; see
+    /// `with_seller_account`.
+    pub seller_payout_token_account: Option<Pubkey>,
+    /// Payout account registered on the provider's `LogisticsProviderAccount`;
+    /// see `with_provider_account`.
+    pub logistics_payout_token_account: Option<Pubkey>,
+}
+
+impl SettlementKeys {
+    /// Derives the settlement keys from decoded accounts, using each split
+    /// recipient's associated token account for the purchase mint.
+    pub fn from_accounts(trade: &TradeAccount, purchase: &PurchaseAccount) -> Self {
+        Self {
+            purchase_id: purchase.purchase_id,
+            trade_id: purchase.trade_id,
+            buyer: purchase.buyer,
+            seller: trade.seller,
+            logistics_provider: purchase.chosen_logistics_provider,
+            token_mint: purchase.token_mint,
+            payout_split_token_accounts: trade
+                .payout_splits
+                .iter()
+                .map(|split| get_associated_token_address(&split.recipient, &purchase.token_mint))
+                .collect(),
+            platform: purchase
+                .platform_referral()
+                .map(|referral| referral.platform),
+            seller_payout_token_account: None,
+            logistics_payout_token_account: None,
+        }
+    }
+
+    /// Pays the seller into the payout account registered on their decoded
+    /// `SellerAccount`, if any, instead of their associated token account.
+    pub fn with_seller_account(mut self, seller: &SellerAccount) -> Self {
+        self.seller_payout_token_account = seller.payout_token_account;
+        self
+    }
+
+    /// Pays the logistics provider into the payout account registered on their
+    /// decoded `LogisticsProviderAccount`, if any, instead of their associated
+    /// token account.
+    pub fn with_provider_account(mut self, provider: &LogisticsProviderAccount) -> Self {
+        self.logistics_payout_token_account = provider.payout_token_account;
+        self
+    }
+
+    fn split_metas(&self) -> Vec<AccountMeta> {
+        self.payout_split_token_accounts
+            .iter()
+            .map(|key| AccountMeta::new(*key, false))
+            .collect()
+    }
+}
+
+/// How settlement payouts reach the seller and logistics provider.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PayoutRoute {
+    /// Transfer into the recipients' associated token accounts, which must exist.
+    #[default]
+    Direct,
+    /// Credit the recipients' claimable balances for a later `claim_payout`.
+    /// The balances must have been opened with `open_claimable_balance`.
+    Deferred,
+}
+
+/// Order index shards to update, identified by shard number.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrderIndexShards {
+    pub buyer: Option<u32>,
+    pub seller: Option<u32>,
+}
+
+impl OrderIndexShards {
+    fn keys(&self, buyer: &Pubkey, seller: &Pubkey) -> (Option<Pubkey>, Option<Pubkey>) {
+        (
+            self.buyer.map(|shard_no| pda::order_index_pda(buyer, shard_no).0),
+            self.seller.map(|shard_no| pda::order_index_pda(seller, shard_no).0),
+        )
+    }
+}
+
+/// Optional penalties applied alongside a dispute resolution.
+#[derive(Clone, Debug, Default)]
+pub struct DisputePenalties {
+    /// Slash the logistics provider's stake into this buyer token account.
+    pub slash_provider_into: Option<Pubkey>,
+    /// Buyer token account receiving the seller bond once the loss threshold is
+    /// crossed. The loss itself is always recorded when the buyer wins.
+    pub seller_bond_into: Option<Pubkey>,
+}
+
+/// Swaps the seller's share into their preferred mint during settlement. The
+/// seller must co-sign the resulting instruction.
+#[derive(Clone, Debug)]
+pub struct SwapHook {
+    /// AMM program configured through `set_swap_program`.
+    pub swap_program: Pubkey,
+    /// Seller token account in their preferred mint.
+    pub destination: Pubkey,
+    pub route: SwapRoute,
+    /// Accounts of the quoted swap instruction, in the AMM's order.
+    pub route_accounts: Vec<AccountMeta>,
+}
+
+/// Deposit or redeem instruction for the lending program configured through
+/// `set_lending_config`.
+#[derive(Clone, Debug)]
+pub struct LendingHook {
+    pub lending_program: Pubkey,
+    pub route_data: Vec<u8>,
+    /// Accounts of the lending instruction, in the lending program's order.
+    pub route_accounts: Vec<AccountMeta>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateTradeArgs {
+    pub product_cost: u64,
+    pub logistics_providers: Vec<Pubkey>,
+    pub logistics_costs: Vec<u64>,
+    pub total_quantity: u64,
+    pub payout_splits: Vec<PayoutSplit>,
+    pub weight_grams: u64,
+    pub logistics_rates_per_kg: Vec<u64>,
+    pub category: u16,
+    pub tag_hashes: Vec<[u8; 32]>,
+    /// Pyth price account; when set, costs are given in USD cents.
+    pub price_feed: Option<Pubkey>,
+    pub accepted_mints: Vec<AcceptedMint>,
+    /// Insurance premium offered to buyers, in basis points of the product cost.
+    pub insurance_bps: u16,
+    /// Most units a single buyer may purchase; `None` for no limit.
+    pub max_per_buyer: Option<u64>,
+    /// `DeliveryRegion` bitmask the seller ships to; 0 ships everywhere.
+    pub ship_to_regions: u16,
+    /// Hold settlement until the logistics provider submits proof of delivery.
+    pub requires_proof_of_delivery: bool,
+    /// Who must sign off on delivery before escrow is released.
+    pub confirmation_mode: ConfirmationMode,
+}
+
+pub fn initialize(admin: Pubkey) -> Instruction {
+    build(
+        accounts::Initialize {
+            global_state: pda::global_state_pda().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::Initialize {},
+    )
+}
+
+/// Signed by the current governing authority: the admin, or the governance
+/// authority once one is set.
+pub fn set_governance_authority(authority: Pubkey, governance_authority: Pubkey) -> Instruction {
+    build(
+        accounts::SetGovernanceAuthority {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetGovernanceAuthority {
+            governance_authority,
+        },
+    )
+}
+
+pub fn set_stake_config(
+    authority: Pubkey,
+    stake_mint: Pubkey,
+    provider_stake_amount: u64,
+    slash_bps: u16,
+    stake_cooldown_seconds: i64,
+) -> Instruction {
+    build(
+        accounts::SetStakeConfig {
+            global_state: pda::global_state_pda().0,
+            stake_mint,
+            authority,
+        },
+        instruction::SetStakeConfig {
+            provider_stake_amount,
+            slash_bps,
+            stake_cooldown_seconds,
+        },
+    )
+}
+
+/// Omit `payout_token_account` to pay the provider's associated token account.
+pub fn register_logistics_provider(
+    provider: Pubkey,
+    stake_mint: Pubkey,
+    provider_token_account: Pubkey,
+    payout_token_account: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::RegisterLogisticsProvider {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            stake_vault: pda::stake_vault_pda(&provider).0,
+            stake_mint,
+            provider_token_account,
+            payout_token_account,
+            provider,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterLogisticsProvider {},
+    )
+}
+
+pub fn set_provider_profile(
+    provider: Pubkey,
+    regions: u16,
+    base_price: u64,
+    price_per_kg: u64,
+) -> Instruction {
+    build(
+        accounts::SetProviderProfile {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            provider,
+        },
+        instruction::SetProviderProfile {
+            regions,
+            base_price,
+            price_per_kg,
+        },
+    )
+}
+
+/// Omit `payout_token_account` to pay the provider's associated token account again.
+pub fn update_provider_payout(
+    provider: Pubkey,
+    payout_token_account: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::UpdateProviderPayout {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            payout_token_account,
+            provider,
+        },
+        instruction::UpdateProviderPayout {},
+    )
+}
+
+pub fn deregister_logistics_provider(provider: Pubkey) -> Instruction {
+    build(
+        accounts::DeregisterLogisticsProvider {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            provider,
+        },
+        instruction::DeregisterLogisticsProvider {},
+    )
+}
+
+pub fn withdraw_stake(provider: Pubkey, provider_token_account: Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawStake {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            stake_vault: pda::stake_vault_pda(&provider).0,
+            provider_token_account,
+            provider,
+            token_program: token::ID,
+        },
+        instruction::WithdrawStake {},
+    )
+}
+
+pub fn create_order_index_shard(payer: Pubkey, user: Pubkey, shard_no: u32) -> Instruction {
+    let previous_shard = shard_no
+        .checked_sub(1)
+        .map(|previous| pda::order_index_pda(&user, previous).0);
+    build(
+        accounts::CreateOrderIndexShard {
+            global_state: pda::global_state_pda().0,
+            order_index: pda::order_index_pda(&user, shard_no).0,
+            previous_shard,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateOrderIndexShard { user, shard_no },
+    )
+}
+
+pub fn set_seller_bond_config(
+    authority: Pubkey,
+    seller_loss_threshold: u32,
+    seller_bond_slash_bps: u16,
+    seller_probation_seconds: i64,
+) -> Instruction {
+    build(
+        accounts::SetSellerBondConfig {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetSellerBondConfig {
+            seller_loss_threshold,
+            seller_bond_slash_bps,
+            seller_probation_seconds,
+        },
+    )
+}
+
+pub fn set_crank_config(
+    authority: Pubkey,
+    auto_confirm_seconds: i64,
+    ship_timeout_seconds: i64,
+    crank_reward_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetCrankConfig {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetCrankConfig {
+            auto_confirm_seconds,
+            ship_timeout_seconds,
+            crank_reward_bps,
+        },
+    )
+}
+
+pub fn set_platform_fee_share(
+    authority: Pubkey,
+    platform: Pubkey,
+    fee_share_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetPlatformFeeShare {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetPlatformFeeShare {
+            platform,
+            fee_share_bps,
+        },
+    )
+}
+
+pub fn set_order_limits(
+    authority: Pubkey,
+    min_order_amount: u64,
+    max_order_amount: u64,
+) -> Instruction {
+    build(
+        accounts::SetOrderLimits {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetOrderLimits {
+            min_order_amount,
+            max_order_amount,
+        },
+    )
+}
+
+pub fn set_cancellation_policy(
+    authority: Pubkey,
+    cancellation_window_seconds: i64,
+    restocking_fee_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetCancellationPolicy {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetCancellationPolicy {
+            cancellation_window_seconds,
+            restocking_fee_bps,
+        },
+    )
+}
+
+pub fn set_fee(authority: Pubkey, fee_bps: u16) -> Instruction {
+    build(
+        accounts::SetFee {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetFee { fee_bps },
+    )
+}
+
+pub fn set_paused(authority: Pubkey, paused: bool) -> Instruction {
+    build(
+        accounts::SetPaused {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn set_swap_program(authority: Pubkey, swap_program: Pubkey) -> Instruction {
+    build(
+        accounts::SetSwapProgram {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetSwapProgram { swap_program },
+    )
+}
+
+pub fn set_lending_config(
+    authority: Pubkey,
+    lending_program: Pubkey,
+    yield_buyer_share_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetLendingConfig {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetLendingConfig {
+            lending_program,
+            yield_buyer_share_bps,
+        },
+    )
+}
+
+pub fn set_region_registrar(authority: Pubkey, region_registrar: Pubkey) -> Instruction {
+    build(
+        accounts::SetRegionRegistrar {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetRegionRegistrar { region_registrar },
+    )
+}
+
+pub fn set_escalation_policy(
+    authority: Pubkey,
+    escalation_window_seconds: i64,
+    escalation_fee_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetEscalationPolicy {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetEscalationPolicy {
+            escalation_window_seconds,
+            escalation_fee_bps,
+        },
+    )
+}
+
+pub fn initialize_program_version(admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitializeProgramVersion {
+            global_state: pda::global_state_pda().0,
+            program_version: pda::program_version_pda().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProgramVersion {},
+    )
+}
+
+/// Must run once before settlement builders are used; they always pass the
+/// stats account.
+pub fn set_program_version(
+    admin: Pubkey,
+    program_version: SemVer,
+    min_client_version: SemVer,
+) -> Instruction {
+    build(
+        accounts::SetProgramVersion {
+            global_state: pda::global_state_pda().0,
+            program_version: pda::program_version_pda().0,
+            admin,
+        },
+        instruction::SetProgramVersion {
+            program_version,
+            min_client_version,
+        },
+    )
+}
+
+pub fn initialize_receipt_config(admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitializeReceiptConfig {
+            global_state: pda::global_state_pda().0,
+            receipt_config: pda::receipt_config_pda().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeReceiptConfig {},
+    )
+}
+
+pub fn set_receipt_config(admin: Pubkey, merkle_tree: Pubkey, uri_prefix: String) -> Instruction {
+    build(
+        accounts::SetReceiptConfig {
+            global_state: pda::global_state_pda().0,
+            receipt_config: pda::receipt_config_pda().0,
+            admin,
+        },
+        instruction::SetReceiptConfig {
+            merkle_tree,
+            uri_prefix,
+        },
+    )
+}
+
+pub fn set_loyalty_config(authority: Pubkey, config: LoyaltyConfig) -> Instruction {
+    build(
+        accounts::SetLoyaltyConfig {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetLoyaltyConfig { config },
+    )
+}
+
+pub fn initialize_stats(admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitializeStats {
+            global_state: pda::global_state_pda().0,
+            stats: pda::stats_pda().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeStats {},
+    )
+}
+
+pub fn register_arbitrator(authority: Pubkey, arbitrator: Pubkey) -> Instruction {
+    build(
+        accounts::RegisterArbitrator {
+            global_state: pda::global_state_pda().0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator).0,
+            arbitrator,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterArbitrator {},
+    )
+}
+
+pub fn remove_arbitrator(authority: Pubkey, arbitrator: Pubkey) -> Instruction {
+    build(
+        accounts::RemoveArbitrator {
+            global_state: pda::global_state_pda().0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator).0,
+            authority,
+        },
+        instruction::RemoveArbitrator {},
+    )
+}
+
+pub fn init_jury_pool(authority: Pubkey, stake_mint: Pubkey, config: JuryConfig) -> Instruction {
+    build(
+        accounts::InitJuryPool {
+            global_state: pda::global_state_pda().0,
+            jury_pool: pda::jury_pool_pda().0,
+            jury_vault: pda::jury_vault_pda().0,
+            stake_mint,
+            authority,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitJuryPool { config },
+    )
+}
+
+pub fn set_jury_config(authority: Pubkey, config: JuryConfig) -> Instruction {
+    build(
+        accounts::SetJuryConfig {
+            global_state: pda::global_state_pda().0,
+            jury_pool: pda::jury_pool_pda().0,
+            authority,
+        },
+        instruction::SetJuryConfig { config },
+    )
+}
+
+pub fn add_allowed_mint(authority: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::AddAllowedMint {
+            global_state: pda::global_state_pda().0,
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            token_mint,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::AddAllowedMint {},
+    )
+}
+
+pub fn remove_allowed_mint(authority: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::RemoveAllowedMint {
+            global_state: pda::global_state_pda().0,
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            authority,
+        },
+        instruction::RemoveAllowedMint {},
+    )
+}
+
+pub fn create_escrow_vault(payer: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::CreateEscrowVault {
+            global_state: pda::global_state_pda().0,
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            token_mint,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateEscrowVault {},
+    )
+}
+
+pub fn create_fee_vault(payer: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::CreateFeeVault {
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            fee_vault: pda::fee_vault_pda(&token_mint).0,
+            token_mint,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateFeeVault {},
+    )
+}
+
+pub fn create_insurance_pool(payer: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::CreateInsurancePool {
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            insurance_pool: pda::insurance_pool_pda(&token_mint).0,
+            token_mint,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateInsurancePool {},
+    )
+}
+
+pub fn register_seller(admin: Pubkey, seller: Pubkey) -> Instruction {
+    build(
+        accounts::RegisterSeller {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            seller,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSeller {},
+    )
+}
+
+pub fn register_seller_with_bond(
+    admin: Pubkey,
+    seller: Pubkey,
+    bond_mint: Pubkey,
+    seller_token_account: Pubkey,
+    bond_amount: u64,
+) -> Instruction {
+    build(
+        accounts::RegisterSellerWithBond {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            bond_vault: pda::seller_bond_pda(&seller).0,
+            bond_mint,
+            seller_token_account,
+            seller,
+            admin,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSellerWithBond { bond_amount },
+    )
+}
+
+pub fn set_payout_preference(
+    seller: Pubkey,
+    preferred_mint: Option<Pubkey>,
+    max_slippage_bps: u16,
+) -> Instruction {
+    build(
+        accounts::SetPayoutPreference {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            seller,
+        },
+        instruction::SetPayoutPreference {
+            preferred_mint,
+            max_slippage_bps,
+        },
+    )
+}
+
+/// Omit `payout_token_account` to pay the seller's associated token account again.
+pub fn set_seller_payout_account(
+    seller: Pubkey,
+    payout_token_account: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetSellerPayoutAccount {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            payout_token_account,
+            seller,
+        },
+        instruction::SetSellerPayoutAccount {},
+    )
+}
+
+pub fn release_bond(seller: Pubkey, seller_token_account: Pubkey) -> Instruction {
+    build(
+        accounts::ReleaseBond {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            bond_vault: pda::seller_bond_pda(&seller).0,
+            seller_token_account,
+            seller,
+            token_program: token::ID,
+        },
+        instruction::ReleaseBond {},
+    )
+}
+
+pub fn migrate_trade(payer: Pubkey, trade_id: u64) -> Instruction {
+    build(
+        accounts::MigrateTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateTrade { trade_id },
+    )
+}
+
+pub fn migrate_purchase(payer: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::MigratePurchase {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePurchase { purchase_id },
+    )
+}
+
+pub fn set_confirmation_delegate(buyer: Pubkey, delegate: Option<Pubkey>) -> Instruction {
+    build(
+        accounts::SetConfirmationDelegate {
+            global_state: pda::global_state_pda().0,
+            buyer_account: pda::buyer_pda(&buyer).0,
+            buyer,
+        },
+        instruction::SetConfirmationDelegate { delegate },
+    )
+}
+
+pub fn register_buyer(buyer: Pubkey) -> Instruction {
+    build(
+        accounts::RegisterBuyer {
+            buyer_account: pda::buyer_pda(&buyer).0,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterBuyer {},
+    )
+}
+
+pub fn add_purchase_index_page(payer: Pubkey, user: Pubkey, shard_no: u32) -> Instruction {
+    build(
+        accounts::AddPurchaseIndexPage {
+            global_state: pda::global_state_pda().0,
+            order_index: pda::order_index_pda(&user, shard_no).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::AddPurchaseIndexPage { user, shard_no },
+    )
+}
+
+pub fn create_category_index_shard(payer: Pubkey, category: u16, shard_no: u32) -> Instruction {
+    let previous_shard = shard_no
+        .checked_sub(1)
+        .map(|previous| pda::category_index_pda(category, previous).0);
+    build(
+        accounts::CreateCategoryIndexShard {
+            global_state: pda::global_state_pda().0,
+            category_index: pda::category_index_pda(category, shard_no).0,
+            previous_shard,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateCategoryIndexShard { category, shard_no },
+    )
+}
+
+/// `trade_id` must be the next trade id, i.e. `GlobalState::trade_counter + 1`.
+/// When `category_shard` is set the trade is appended to that shard of the
+/// category index. `in_storefront` lists the trade under the seller's
+/// storefront, which must already exist.
+/// When `args.logistics_providers` is empty the storefront's default
+/// providers apply, and their account PDAs must be appended to the returned
+/// instruction.
+pub fn create_trade(
+    admin: Pubkey,
+    seller: Pubkey,
+    token_mint: Pubkey,
+    trade_id: u64,
+    args: CreateTradeArgs,
+    category_shard: Option<u32>,
+    in_storefront: bool,
+) -> Instruction {
+    let category_index =
+        category_shard.map(|shard_no| pda::category_index_pda(args.category, shard_no).0);
+    let provider_accounts = args
+        .logistics_providers
+        .iter()
+        .map(|provider| AccountMeta::new_readonly(pda::logistics_provider_pda(provider).0, false))
+        .collect();
+    build_with_remaining(
+        accounts::CreateTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+            token_mint,
+            allowed_mint: pda::allowed_mint_pda(&token_mint).0,
+            category_index,
+            storefront: in_storefront.then(|| pda::storefront_pda(&seller).0),
+            admin,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::CreateTrade {
+            product_cost: args.product_cost,
+            logistics_providers: args.logistics_providers,
+            logistics_costs: args.logistics_costs,
+            total_quantity: args.total_quantity,
+            payout_splits: args.payout_splits,
+            weight_grams: args.weight_grams,
+            logistics_rates_per_kg: args.logistics_rates_per_kg,
+            category: args.category,
+            tag_hashes: args.tag_hashes,
+            price_feed: args.price_feed,
+            accepted_mints: args.accepted_mints,
+            insurance_bps: args.insurance_bps,
+            max_per_buyer: args.max_per_buyer,
+            ship_to_regions: args.ship_to_regions,
+            requires_proof_of_delivery: args.requires_proof_of_delivery,
+            confirmation_mode: args.confirmation_mode,
+        },
+        provider_accounts,
+    )
+}
+
+pub fn create_trade_metadata(authority: Pubkey, trade_id: u64) -> Instruction {
+    build(
+        accounts::CreateTradeMetadata {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            trade_metadata: pda::trade_metadata_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::CreateTradeMetadata { trade_id },
+    )
+}
+
+pub fn set_trade_metadata(
+    authority: Pubkey,
+    trade_id: u64,
+    content_hash: [u8; 32],
+    uri: String,
+) -> Instruction {
+    build(
+        accounts::SetTradeMetadata {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            trade_metadata: pda::trade_metadata_pda(trade_id).0,
+            authority,
+        },
+        instruction::SetTradeMetadata {
+            trade_id,
+            content_hash,
+            uri,
+        },
+    )
+}
+
+pub fn set_purchase_cooldown(
+    authority: Pubkey,
+    trade_id: u64,
+    cooldown_slots: Option<u64>,
+) -> Instruction {
+    build(
+        accounts::SetPurchaseCooldown {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetPurchaseCooldown {
+            trade_id,
+            cooldown_slots,
+        },
+    )
+}
+
+pub fn set_presale_allowlist(
+    authority: Pubkey,
+    trade_id: u64,
+    presale: Option<PresaleAllowlist>,
+) -> Instruction {
+    build(
+        accounts::SetPresaleAllowlist {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetPresaleAllowlist { trade_id, presale },
+    )
+}
+
+pub fn set_settlement_stream(
+    authority: Pubkey,
+    trade_id: u64,
+    duration_seconds: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::SetSettlementStream {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetSettlementStream {
+            trade_id,
+            duration_seconds,
+        },
+    )
+}
+
+pub fn set_trade_milestones(
+    authority: Pubkey,
+    trade_id: u64,
+    milestones: Vec<Milestone>,
+) -> Instruction {
+    build(
+        accounts::SetTradeMilestones {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetTradeMilestones {
+            trade_id,
+            milestones,
+        },
+    )
+}
+
+pub fn set_low_stock_threshold(
+    authority: Pubkey,
+    trade_id: u64,
+    threshold: Option<u64>,
+) -> Instruction {
+    build(
+        accounts::SetLowStockThreshold {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetLowStockThreshold {
+            trade_id,
+            threshold,
+        },
+    )
+}
+
+/// Simulate to read the `TradeSummary` from the return data.
+pub fn get_trade_summary(trade_id: u64) -> Instruction {
+    build(
+        accounts::GetTradeSummary {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+        },
+        instruction::GetTradeSummary { trade_id },
+    )
+}
+
+pub fn restock_trade(seller: Pubkey, trade_id: u64, additional_quantity: u64) -> Instruction {
+    build(
+        accounts::RestockTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+        },
+        instruction::RestockTrade {
+            trade_id,
+            additional_quantity,
+        },
+    )
+}
+
+pub fn set_unlimited_quantity(seller: Pubkey, trade_id: u64, unlimited: bool) -> Instruction {
+    build(
+        accounts::SetUnlimitedQuantity {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+        },
+        instruction::SetUnlimitedQuantity {
+            trade_id,
+            unlimited,
+        },
+    )
+}
+
+pub fn set_preorder(
+    seller: Pubkey,
+    trade_id: u64,
+    preorder: bool,
+    fulfillment_start: i64,
+) -> Instruction {
+    build(
+        accounts::SetPreorder {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+        },
+        instruction::SetPreorder {
+            trade_id,
+            preorder,
+            fulfillment_start,
+        },
+    )
+}
+
+pub fn open_storefront(seller: Pubkey) -> Instruction {
+    build(
+        accounts::OpenStorefront {
+            storefront: pda::storefront_pda(&seller).0,
+            seller,
+            system_program: system_program::ID,
+        },
+        instruction::OpenStorefront {},
+    )
+}
+
+pub fn set_storefront(
+    seller: Pubkey,
+    metadata_hash: [u8; 32],
+    default_logistics_providers: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetStorefront {
+            global_state: pda::global_state_pda().0,
+            storefront: pda::storefront_pda(&seller).0,
+            seller,
+        },
+        instruction::SetStorefront {
+            metadata_hash,
+            default_logistics_providers,
+        },
+    )
+}
+
+/// `seller` is the trade's seller, whose storefront the trade is listed under.
+pub fn sync_storefront_listing(seller: Pubkey, trade_id: u64) -> Instruction {
+    build(
+        accounts::SyncStorefrontListing {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            storefront: pda::storefront_pda(&seller).0,
+        },
+        instruction::SyncStorefrontListing { trade_id },
+    )
+}
+
+pub fn set_trade_order_limits(
+    authority: Pubkey,
+    trade_id: u64,
+    limits: Option<OrderLimits>,
+) -> Instruction {
+    build(
+        accounts::SetTradeOrderLimits {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetTradeOrderLimits { trade_id, limits },
+    )
+}
+
+pub fn create_auction(
+    seller: Pubkey,
+    trade_id: u64,
+    quantity: u64,
+    start_price: u64,
+    min_increment: u64,
+    end_time: i64,
+) -> Instruction {
+    build(
+        accounts::CreateAuction {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            auction: pda::auction_pda(trade_id).0,
+            seller,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAuction {
+            trade_id,
+            quantity,
+            start_price,
+            min_increment,
+            end_time,
+        },
+    )
+}
+
+/// `previous_bidder_token_account` must be the current leader's account in
+/// `token_mint` once the auction has a bid.
+pub fn place_bid(
+    bidder: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    bidder_token_account: Pubkey,
+    previous_bidder_token_account: Option<Pubkey>,
+    unit_bid: u64,
+    logistics_provider: Pubkey,
+) -> Instruction {
+    build(
+        accounts::PlaceBid {
+            global_state: pda::global_state_pda().0,
+            auction: pda::auction_pda(trade_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            bidder_token_account,
+            previous_bidder_token_account,
+            bidder,
+            token_program: token::ID,
+        },
+        instruction::PlaceBid {
+            trade_id,
+            unit_bid,
+            logistics_provider,
+        },
+    )
+}
+
+/// `purchase_id` is the next purchase id when the auction has a winner, and
+/// `None` for an unsold lot. `logistics_provider` is the auction's leading
+/// logistics provider.
+pub fn finalize_auction(
+    payer: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    seller: Pubkey,
+    logistics_provider: Pubkey,
+    purchase_id: Option<u64>,
+) -> Instruction {
+    build(
+        accounts::FinalizeAuction {
+            global_state: pda::global_state_pda().0,
+            auction: pda::auction_pda(trade_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            purchase_account: purchase_id.map(|id| pda::purchase_pda(id).0),
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: purchase_id.map(|id| pda::product_escrow_pda(id).0),
+            logistics_escrow: purchase_id.map(|id| pda::logistics_escrow_pda(id).0),
+            token_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            seller,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::FinalizeAuction { trade_id },
+    )
+}
+
+pub fn create_sealed_drop(
+    seller: Pubkey,
+    trade_id: u64,
+    quantity: u64,
+    max_quantity_per_commit: u64,
+    commit_end: i64,
+    reveal_end: i64,
+) -> Instruction {
+    build(
+        accounts::CreateSealedDrop {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            sealed_drop: pda::sealed_drop_pda(trade_id).0,
+            seller,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSealedDrop {
+            trade_id,
+            quantity,
+            max_quantity_per_commit,
+            commit_end,
+            reveal_end,
+        },
+    )
+}
+
+/// `commitment` is `sealed_commitment(&buyer, quantity, &salt)`; keep the
+/// salt secret until `reveal_purchase`.
+pub fn commit_purchase(
+    buyer: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    buyer_token_account: Pubkey,
+    commitment: [u8; 32],
+    logistics_provider: Pubkey,
+) -> Instruction {
+    build(
+        accounts::CommitPurchase {
+            global_state: pda::global_state_pda().0,
+            sealed_drop: pda::sealed_drop_pda(trade_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            sealed_commit: pda::sealed_commit_pda(trade_id, &buyer).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CommitPurchase {
+            trade_id,
+            commitment,
+            logistics_provider,
+        },
+    )
+}
+
+pub fn reveal_purchase(buyer: Pubkey, trade_id: u64, quantity: u64, salt: [u8; 32]) -> Instruction {
+    build(
+        accounts::RevealPurchase {
+            global_state: pda::global_state_pda().0,
+            sealed_drop: pda::sealed_drop_pda(trade_id).0,
+            sealed_commit: pda::sealed_commit_pda(trade_id, &buyer).0,
+            buyer,
+        },
+        instruction::RevealPurchase {
+            trade_id,
+            quantity,
+            salt,
+        },
+    )
+}
+
+/// `purchase_id` is the next purchase id when the commit is allocated any
+/// units, and `None` for a commit that is refunded in full.
+/// `logistics_provider` is the provider named in the commit.
+pub fn claim_sealed_purchase(
+    payer: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    logistics_provider: Pubkey,
+    purchase_id: Option<u64>,
+) -> Instruction {
+    build(
+        accounts::ClaimSealedPurchase {
+            global_state: pda::global_state_pda().0,
+            sealed_drop: pda::sealed_drop_pda(trade_id).0,
+            sealed_commit: pda::sealed_commit_pda(trade_id, &buyer).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            purchase_account: purchase_id.map(|id| pda::purchase_pda(id).0),
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: purchase_id.map(|id| pda::product_escrow_pda(id).0),
+            logistics_escrow: purchase_id.map(|id| pda::logistics_escrow_pda(id).0),
+            token_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            buyer_token_account,
+            buyer,
+            payer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::ClaimSealedPurchase { trade_id },
+    )
+}
+
+pub fn close_sealed_drop(trade_id: u64, seller: Pubkey) -> Instruction {
+    build(
+        accounts::CloseSealedDrop {
+            global_state: pda::global_state_pda().0,
+            sealed_drop: pda::sealed_drop_pda(trade_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+        },
+        instruction::CloseSealedDrop { trade_id },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_trade_buyer_gate(
+    authority: Pubkey,
+    trade_id: u64,
+    gate: Option<BuyerGate>,
+) -> Instruction {
+    build(
+        accounts::SetTradeBuyerGate {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::SetTradeBuyerGate { trade_id, gate },
+    )
+}
+
+pub fn set_provider_status(seller: Pubkey, trade_id: u64, index: u8, enabled: bool) -> Instruction {
+    build(
+        accounts::SetProviderStatus {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller,
+            system_program: system_program::ID,
+        },
+        instruction::SetProviderStatus {
+            trade_id,
+            index,
+            enabled,
+        },
+    )
+}
+
+pub fn issue_attestation(
+    verifier: Pubkey,
+    subject: Pubkey,
+    expires_at: i64,
+    region: Option<DeliveryRegion>,
+) -> Instruction {
+    build(
+        accounts::IssueAttestation {
+            global_state: pda::global_state_pda().0,
+            attestation: pda::attestation_pda(&verifier, &subject).0,
+            verifier,
+            system_program: system_program::ID,
+        },
+        instruction::IssueAttestation {
+            subject,
+            expires_at,
+            region,
+        },
+    )
+}
+
+pub fn revoke_attestation(verifier: Pubkey, subject: Pubkey) -> Instruction {
+    build(
+        accounts::RevokeAttestation {
+            global_state: pda::global_state_pda().0,
+            attestation: pda::attestation_pda(&verifier, &subject).0,
+            verifier,
+        },
+        instruction::RevokeAttestation {},
+    )
+}
+
+pub fn create_subscription(
+    buyer: Pubkey,
+    trade: &TradeKeys,
+    buyer_token_account: Pubkey,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    interval_seconds: i64,
+    cycles: u32,
+) -> Instruction {
+    build(
+        accounts::CreateSubscription {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            subscription: pda::subscription_pda(trade.trade_id, &buyer).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSubscription {
+            trade_id: trade.trade_id,
+            quantity,
+            logistics_provider,
+            interval_seconds,
+            cycles,
+        },
+    )
+}
+
+/// `purchase_id` must be the next purchase id. `logistics_provider` is the
+/// provider the subscription ships with.
+pub fn process_subscription_cycle(
+    cranker: Pubkey,
+    trade: &TradeKeys,
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    logistics_provider: Pubkey,
+    purchase_id: u64,
+) -> Instruction {
+    build(
+        accounts::ProcessSubscriptionCycle {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            subscription: pda::subscription_pda(trade.trade_id, &buyer).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&trade.token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint: trade.token_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            buyer_token_account,
+            cranker,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::ProcessSubscriptionCycle {
+            trade_id: trade.trade_id,
+        },
+    )
+}
+
+pub fn cancel_subscription(buyer: Pubkey, trade_id: u64, buyer_token_account: Pubkey) -> Instruction {
+    build(
+        accounts::CancelSubscription {
+            global_state: pda::global_state_pda().0,
+            subscription: pda::subscription_pda(trade_id, &buyer).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+        },
+        instruction::CancelSubscription { trade_id },
+    )
+}
+
+pub fn make_offer(
+    buyer: Pubkey,
+    trade: &TradeKeys,
+    buyer_token_account: Pubkey,
+    unit_price: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    expires_at: i64,
+) -> Instruction {
+    build(
+        accounts::MakeOffer {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            offer: pda::offer_pda(trade.trade_id, &buyer).0,
+            escrow_token_account: pda::escrow_vault_pda(&trade.token_mint).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MakeOffer {
+            trade_id: trade.trade_id,
+            unit_price,
+            quantity,
+            logistics_provider,
+            expires_at,
+        },
+    )
+}
+
+/// `purchase_id` must be the next purchase id. `logistics_provider` is the
+/// provider named in the offer.
+pub fn accept_offer(
+    seller: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    buyer: Pubkey,
+    logistics_provider: Pubkey,
+    purchase_id: u64,
+) -> Instruction {
+    build(
+        accounts::AcceptOffer {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            offer: pda::offer_pda(trade_id, &buyer).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            buyer,
+            seller,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::AcceptOffer { trade_id },
+    )
+}
+
+pub fn withdraw_offer(
+    buyer: Pubkey,
+    trade_id: u64,
+    token_mint: Pubkey,
+    buyer_token_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::WithdrawOffer {
+            global_state: pda::global_state_pda().0,
+            offer: pda::offer_pda(trade_id, &buyer).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+        },
+        instruction::WithdrawOffer { trade_id },
+    )
+}
+
+pub fn reserve_quantity(buyer: Pubkey, trade_id: u64, quantity: u64) -> Instruction {
+    build(
+        accounts::ReserveQuantity {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            reservation: pda::reservation_pda(trade_id, &buyer).0,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::ReserveQuantity { trade_id, quantity },
+    )
+}
+
+/// `authority` is the buyer, or anyone once the reservation has expired.
+pub fn release_reservation(authority: Pubkey, trade_id: u64, buyer: Pubkey) -> Instruction {
+    build(
+        accounts::ReleaseReservation {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            reservation: pda::reservation_pda(trade_id, &buyer).0,
+            buyer,
+            authority,
+        },
+        instruction::ReleaseReservation { trade_id },
+    )
+}
+
+/// `payer` may be anyone, typically the buyer or their relayer.
+pub fn open_buyer_quota(payer: Pubkey, buyer: Pubkey, trade_id: u64) -> Instruction {
+    build(
+        accounts::OpenBuyerQuota {
+            trade_account: pda::trade_pda(trade_id).0,
+            buyer_quota: pda::buyer_quota_pda(trade_id, &buyer).0,
+            buyer,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenBuyerQuota { trade_id },
+    )
+}
+
+/// `purchase_id` must be the next purchase id, i.e. `GlobalState::purchase_counter + 1`.
+/// `payment_mint` is the mint of `buyer_token_account`: the trade's primary mint
+/// or one of its accepted mints. `logistics_provider_index` is the position of
+/// `logistics_provider` in the trade's provider list. `insured` adds the
+/// trade's insurance premium; `reserved` consumes the buyer's reservation on
+/// the trade. `platform` is the registered storefront co-signing the order, if
+/// any. `redeem_loyalty` spends the discount the buyer redeemed with
+/// `redeem_loyalty_points` on this purchase's escrow fee. `memo_hash` references
+/// the buyer's encrypted off-chain order notes, if any. Pass the configured
+/// `region_registrar` to present the buyer's region attestation to trades with
+/// ship-to restrictions. Pass `session_key` to sign with one of the buyer's
+/// sessions instead of the buyer's wallet.
+#[allow(clippy::too_many_arguments)]
+pub fn buy_trade(
+    buyer: Pubkey,
+    session_key: Option<Pubkey>,
+    buyer_token_account: Pubkey,
+    payment_mint: Pubkey,
+    trade: &TradeKeys,
+    purchase_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    insured: bool,
+    reserved: bool,
+    platform: Option<Pubkey>,
+    redeem_loyalty: bool,
+    buyer_region: DeliveryRegion,
+    memo_hash: Option<[u8; 64]>,
+    region_registrar: Option<Pubkey>,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let (buyer_order_index, seller_order_index) = shards.keys(&buyer, &trade.seller);
+    let (gate_token_account, buyer_attestation) = trade.gate_accounts(&buyer);
+    let region_attestation = region_registrar
+        .filter(|_| trade.ship_to_regions != 0)
+        .map(|registrar| pda::attestation_pda(&registrar, &buyer).0);
+    build(
+        accounts::BuyTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            buyer_account: pda::buyer_pda(&buyer).0,
+            buyer_token_account,
+            escrow_token_account: pda::escrow_vault_pda(&payment_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint: payment_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            allowed_mint: pda::allowed_mint_pda(&payment_mint).0,
+            price_feed: trade.price_feed.filter(|_| payment_mint == trade.token_mint),
+            insurance_pool: insured.then(|| pda::insurance_pool_pda(&payment_mint).0),
+            buyer_order_index,
+            seller_order_index,
+            reservation: reserved.then(|| pda::reservation_pda(trade.trade_id, &buyer).0),
+            buyer_quota: trade.buyer_quota(&buyer),
+            gate_token_account,
+            buyer_attestation,
+            region_attestation,
+            platform,
+            loyalty_account: redeem_loyalty.then(|| pda::loyalty_pda(&buyer).0),
+            session: session_key.map(|session_key| pda::session_pda(&buyer, &session_key).0),
+            buyer,
+            authority: session_key.unwrap_or(buyer),
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::BuyTrade {
+            trade_id: trade.trade_id,
+            quantity,
+            logistics_provider,
+            logistics_provider_index,
+            insured,
+            buyer_region,
+            memo_hash,
+        },
+    )
+}
+
+/// Turns an instruction from `buy_trade` into `buy_trade_allowlisted`, keeping
+/// its accounts and arguments and adding the buyer's allowlist `proof`.
+///
+/// # Panics
+///
+/// If `buy_trade_ix` was not built by `buy_trade`.
+pub fn buy_trade_allowlisted(buy_trade_ix: Instruction, proof: Vec<[u8; 32]>) -> Instruction {
+    let args = instruction::BuyTrade::try_from_slice(&buy_trade_ix.data[8..])
+        .expect("not a buy_trade instruction");
+    Instruction {
+        data: instruction::BuyTradeAllowlisted {
+            trade_id: args.trade_id,
+            quantity: args.quantity,
+            logistics_provider: args.logistics_provider,
+            logistics_provider_index: args.logistics_provider_index,
+            insured: args.insured,
+            buyer_region: args.buyer_region,
+            memo_hash: args.memo_hash,
+            proof,
+        }
+        .data(),
+        ..buy_trade_ix
+    }
+}
+
+/// Ed25519 program instruction carrying the buyer's `signature` over
+/// `order.signing_message()`. It must immediately precede `buy_trade_relayed`.
+pub fn relayed_order_signature(order: &RelayedOrder, signature: &[u8; 64]) -> Instruction {
+    const HEADER_LEN: u16 = 16;
+    let message = order.signing_message();
+    let public_key_offset = HEADER_LEN;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(order.buyer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(&message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// `payer` may be anyone, typically the relayer.
+pub fn open_relay_nonce(payer: Pubkey, buyer: Pubkey) -> Instruction {
+    build(
+        accounts::OpenRelayNonce {
+            relay_nonce: pda::relay_nonce_pda(&buyer).0,
+            buyer,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenRelayNonce {},
+    )
+}
+
+/// `purchase_id` must be the next purchase id. Pass `relayer_token_account`
+/// when the order pays a relayer fee.
+#[allow(clippy::too_many_arguments)]
+pub fn buy_trade_relayed(
+    relayer: Pubkey,
+    relayer_token_account: Option<Pubkey>,
+    buyer_token_account: Pubkey,
+    trade: &TradeKeys,
+    purchase_id: u64,
+    order: RelayedOrder,
+    region_registrar: Option<Pubkey>,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let buyer = order.buyer;
+    let (buyer_order_index, seller_order_index) = shards.keys(&buyer, &trade.seller);
+    let (gate_token_account, buyer_attestation) = trade.gate_accounts(&buyer);
+    let region_attestation = region_registrar
+        .filter(|_| trade.ship_to_regions != 0)
+        .map(|registrar| pda::attestation_pda(&registrar, &buyer).0);
+    build(
+        accounts::BuyTradeRelayed {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            buyer_account: pda::buyer_pda(&buyer).0,
+            relay_nonce: pda::relay_nonce_pda(&buyer).0,
+            relay_authority: pda::relay_authority_pda().0,
+            buyer_token_account,
+            escrow_token_account: pda::escrow_vault_pda(&trade.token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint: trade.token_mint,
+            provider_account: pda::logistics_provider_pda(&order.logistics_provider).0,
+            relayer_token_account,
+            buyer_order_index,
+            seller_order_index,
+            buyer_quota: trade.buyer_quota(&buyer),
+            gate_token_account,
+            buyer_attestation,
+            region_attestation,
+            instructions_sysvar: sysvar::instructions::ID,
+            relayer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::BuyTradeRelayed { order },
+    )
+}
+
+pub fn create_spending_permit(
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    delegate: Pubkey,
+    max_total_amount: u64,
+    max_order_amount: u64,
+    expires_at: i64,
+) -> Instruction {
+    build(
+        accounts::CreateSpendingPermit {
+            global_state: pda::global_state_pda().0,
+            spending_permit: pda::spending_permit_pda(&buyer, &delegate).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSpendingPermit {
+            delegate,
+            max_total_amount,
+            max_order_amount,
+            expires_at,
+        },
+    )
+}
+
+pub fn revoke_spending_permit(
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    delegate: Pubkey,
+) -> Instruction {
+    build(
+        accounts::RevokeSpendingPermit {
+            global_state: pda::global_state_pda().0,
+            spending_permit: pda::spending_permit_pda(&buyer, &delegate).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+        },
+        instruction::RevokeSpendingPermit {},
+    )
+}
+
+/// `purchase_id` must be the next purchase id. `buyer_token_account` must be
+/// the account the permit was created for.
+#[allow(clippy::too_many_arguments)]
+pub fn buy_trade_with_permit(
+    delegate: Pubkey,
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    trade: &TradeKeys,
+    purchase_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    buyer_region: DeliveryRegion,
+    region_registrar: Option<Pubkey>,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let (buyer_order_index, seller_order_index) = shards.keys(&buyer, &trade.seller);
+    let (gate_token_account, buyer_attestation) = trade.gate_accounts(&buyer);
+    let region_attestation = region_registrar
+        .filter(|_| trade.ship_to_regions != 0)
+        .map(|registrar| pda::attestation_pda(&registrar, &buyer).0);
+    build(
+        accounts::BuyTradeWithPermit {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade.trade_id).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            buyer_account: pda::buyer_pda(&buyer).0,
+            spending_permit: pda::spending_permit_pda(&buyer, &delegate).0,
+            buyer_token_account,
+            escrow_token_account: pda::escrow_vault_pda(&trade.token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint: trade.token_mint,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            buyer_order_index,
+            seller_order_index,
+            buyer_quota: trade.buyer_quota(&buyer),
+            gate_token_account,
+            buyer_attestation,
+            region_attestation,
+            delegate,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::BuyTradeWithPermit {
+            trade_id: trade.trade_id,
+            quantity,
+            logistics_provider,
+            buyer_region,
+        },
+    )
+}
+
+pub fn create_session(
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    session_key: Pubkey,
+    expires_at_slot: u64,
+    spend_cap: u64,
+) -> Instruction {
+    build(
+        accounts::CreateSession {
+            global_state: pda::global_state_pda().0,
+            session: pda::session_pda(&buyer, &session_key).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSession {
+            session_key,
+            expires_at_slot,
+            spend_cap,
+        },
+    )
+}
+
+pub fn revoke_session(
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    session_key: Pubkey,
+) -> Instruction {
+    build(
+        accounts::RevokeSession {
+            global_state: pda::global_state_pda().0,
+            session: pda::session_pda(&buyer, &session_key).0,
+            buyer_token_account,
+            buyer,
+            token_program: token::ID,
+        },
+        instruction::RevokeSession {},
+    )
+}
+
+/// Payout destination keys for the seller and logistics provider.
+struct PayoutKeys {
+    seller_token_account: Option<Pubkey>,
+    seller_payout_token_account: Option<Pubkey>,
+    logistics_token_account: Option<Pubkey>,
+    logistics_payout_token_account: Option<Pubkey>,
+    seller_claimable: Option<Pubkey>,
+    logistics_claimable: Option<Pubkey>,
+    platform_claimable: Option<Pubkey>,
+}
+
+impl PayoutKeys {
+    fn new(keys: &SettlementKeys, route: PayoutRoute) -> Self {
+        // Platform shares are always credited, whichever route the others take
+        let platform_claimable = keys
+            .platform
+            .map(|platform| pda::claimable_pda(&platform, &keys.token_mint).0);
+        match route {
+            PayoutRoute::Direct => Self {
+                seller_token_account: Some(get_associated_token_address(
+                    &keys.seller,
+                    &keys.token_mint,
+                )),
+                seller_payout_token_account: keys.seller_payout_token_account,
+                logistics_token_account: Some(get_associated_token_address(
+                    &keys.logistics_provider,
+                    &keys.token_mint,
+                )),
+                logistics_payout_token_account: keys.logistics_payout_token_account,
+                seller_claimable: None,
+                logistics_claimable: None,
+                platform_claimable,
+            },
+            PayoutRoute::Deferred => Self {
+                seller_token_account: None,
+                seller_payout_token_account: None,
+                logistics_token_account: None,
+                logistics_payout_token_account: None,
+                seller_claimable: Some(pda::claimable_pda(&keys.seller, &keys.token_mint).0),
+                logistics_claimable: Some(
+                    pda::claimable_pda(&keys.logistics_provider, &keys.token_mint).0,
+                ),
+                platform_claimable,
+            },
+        }
+    }
+}
+
+/// Accounts shared by `confirm_delivery_and_purchase` and `confirm_partial_delivery`.
+fn confirm_accounts(
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    swap: Option<&SwapHook>,
+) -> accounts::ConfirmDeliveryAndPurchase {
+    let payout = PayoutKeys::new(keys, route);
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    accounts::ConfirmDeliveryAndPurchase {
+        purchase_account: pda::purchase_pda(keys.purchase_id).0,
+        trade_account: pda::trade_pda(keys.trade_id).0,
+        escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+        product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+        logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+        seller: keys.seller,
+        logistics_provider: keys.logistics_provider,
+        token_mint: keys.token_mint,
+        seller_token_account: payout.seller_token_account,
+        seller_payout_token_account: payout.seller_payout_token_account,
+        logistics_token_account: payout.logistics_token_account,
+        logistics_payout_token_account: payout.logistics_payout_token_account,
+        provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+        seller_claimable: payout.seller_claimable,
+        logistics_claimable: payout.logistics_claimable,
+        fee_vault: pda::fee_vault_pda(&keys.token_mint).0,
+        platform_claimable: payout.platform_claimable,
+        buyer_order_index,
+        seller_order_index,
+        global_state: pda::global_state_pda().0,
+        // The seller account carries both the swap preference and the payout account
+        seller_account: (swap.is_some() || payout.seller_payout_token_account.is_some())
+            .then(|| pda::seller_pda(&keys.seller).0),
+        swap_program: swap.map(|swap| swap.swap_program),
+        seller_swap_token_account: swap.map(|swap| swap.destination),
+        stats: Some(pda::stats_pda().0),
+        buyer_account: None,
+        session: None,
+        buyer: keys.buyer,
+        token_program: token::ID,
+        system_program: system_program::ID,
+        #[cfg(feature = "cpi-events")]
+        event_authority: pda::event_authority_pda().0,
+        #[cfg(feature = "cpi-events")]
+        program: PROGRAM_ID,
+    }
+}
+
+pub fn confirm_delivery_and_purchase(
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    swap: Option<SwapHook>,
+) -> Instruction {
+    confirm_instruction(
+        confirm_accounts(keys, route, shards, swap.as_ref()),
+        keys,
+        swap,
+    )
+}
+
+/// `confirm_delivery_and_purchase` signed by the buyer's confirmation delegate.
+pub fn confirm_delivery_as_delegate(
+    delegate: Pubkey,
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    swap: Option<SwapHook>,
+) -> Instruction {
+    let mut accounts = confirm_accounts(keys, route, shards, swap.as_ref());
+    accounts.buyer_account = Some(pda::buyer_pda(&keys.buyer).0);
+    accounts.buyer = delegate;
+    confirm_instruction(accounts, keys, swap)
+}
+
+/// `confirm_delivery_and_purchase` signed by one of the buyer's session keys.
+pub fn confirm_delivery_as_session(
+    session_key: Pubkey,
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    swap: Option<SwapHook>,
+) -> Instruction {
+    let mut accounts = confirm_accounts(keys, route, shards, swap.as_ref());
+    accounts.session = Some(pda::session_pda(&keys.buyer, &session_key).0);
+    accounts.buyer = session_key;
+    confirm_instruction(accounts, keys, swap)
+}
+
+/// `confirm_delivery_and_purchase` signed by the seller or logistics provider
+/// of a two-of-three trade, once another party has already confirmed.
+pub fn confirm_delivery_as_cosigner(
+    cosigner: Pubkey,
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    swap: Option<SwapHook>,
+) -> Instruction {
+    let mut accounts = confirm_accounts(keys, route, shards, swap.as_ref());
+    accounts.buyer = cosigner;
+    confirm_instruction(accounts, keys, swap)
+}
+
+fn confirm_instruction(
+    accounts: accounts::ConfirmDeliveryAndPurchase,
+    keys: &SettlementKeys,
+    swap: Option<SwapHook>,
+) -> Instruction {
+    let mut remaining_accounts = keys.split_metas();
+    if let Some(swap) = swap.as_ref() {
+        remaining_accounts.extend(swap.route_accounts.iter().cloned());
+    }
+    let mut ix = build_with_remaining(
+        accounts,
+        instruction::ConfirmDeliveryAndPurchase {
+            purchase_id: keys.purchase_id,
+            swap_route: swap.as_ref().map(|swap| swap.route.clone()),
+        },
+        remaining_accounts,
+    );
+    if swap.is_some() {
+        for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == keys.seller) {
+            meta.is_signer = true;
+        }
+    }
+    ix
+}
+
+/// `confirm_deliveries_batch` for `purchase_ids`. `keys` may be those of any
+/// purchase in the batch; its `purchase_id` and `platform` are ignored, since
+/// every purchase shares the trade, buyer, mint and logistics provider.
+pub fn confirm_deliveries_batch(
+    keys: &SettlementKeys,
+    purchase_ids: &[u64],
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let payout = PayoutKeys::new(keys, route);
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    let mut remaining_accounts: Vec<AccountMeta> = purchase_ids
+        .iter()
+        .flat_map(|purchase_id| {
+            [
+                AccountMeta::new(pda::purchase_pda(*purchase_id).0, false),
+                AccountMeta::new(pda::product_escrow_pda(*purchase_id).0, false),
+                AccountMeta::new(pda::logistics_escrow_pda(*purchase_id).0, false),
+            ]
+        })
+        .collect();
+    remaining_accounts.extend(keys.split_metas());
+    build_with_remaining(
+        accounts::ConfirmDeliveriesBatch {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            seller: keys.seller,
+            logistics_provider: keys.logistics_provider,
+            token_mint: keys.token_mint,
+            seller_token_account: payout.seller_token_account,
+            seller_payout_token_account: payout.seller_payout_token_account,
+            logistics_token_account: payout.logistics_token_account,
+            logistics_payout_token_account: payout.logistics_payout_token_account,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            seller_claimable: payout.seller_claimable,
+            logistics_claimable: payout.logistics_claimable,
+            fee_vault: pda::fee_vault_pda(&keys.token_mint).0,
+            buyer_order_index,
+            seller_order_index,
+            seller_account: payout
+                .seller_payout_token_account
+                .map(|_| pda::seller_pda(&keys.seller).0),
+            stats: Some(pda::stats_pda().0),
+            buyer: keys.buyer,
+            token_program: token::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::ConfirmDeliveriesBatch {
+            trade_id: keys.trade_id,
+            purchase_count: purchase_ids.len() as u8,
+        },
+        remaining_accounts,
+    )
+}
+
+/// Permissionless crank; pass `cranker_token_account` to collect the reward.
+pub fn auto_settle_expired(
+    cranker: Pubkey,
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    cranker_token_account: Option<Pubkey>,
+) -> Instruction {
+    let payout = PayoutKeys::new(keys, route);
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    build_with_remaining(
+        accounts::AutoSettleExpired {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            seller: keys.seller,
+            logistics_provider: keys.logistics_provider,
+            token_mint: keys.token_mint,
+            seller_token_account: payout.seller_token_account,
+            seller_payout_token_account: payout.seller_payout_token_account,
+            logistics_token_account: payout.logistics_token_account,
+            logistics_payout_token_account: payout.logistics_payout_token_account,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            seller_claimable: payout.seller_claimable,
+            logistics_claimable: payout.logistics_claimable,
+            fee_vault: pda::fee_vault_pda(&keys.token_mint).0,
+            platform_claimable: payout.platform_claimable,
+            buyer_order_index,
+            seller_order_index,
+            seller_account: payout
+                .seller_payout_token_account
+                .map(|_| pda::seller_pda(&keys.seller).0),
+            stats: Some(pda::stats_pda().0),
+            cranker_token_account,
+            cranker,
+            token_program: token::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::AutoSettleExpired {
+            purchase_id: keys.purchase_id,
+        },
+        keys.split_metas(),
+    )
+}
+
+/// Permissionless crank; pass `cranker_token_account` to collect the reward.
+pub fn auto_refund_timeouts(
+    cranker: Pubkey,
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    insured: bool,
+    shards: OrderIndexShards,
+    cranker_token_account: Option<Pubkey>,
+) -> Instruction {
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    build(
+        accounts::AutoRefundTimeouts {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            insurance_pool: insured.then(|| pda::insurance_pool_pda(&keys.token_mint).0),
+            buyer_token_account,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            buyer_order_index,
+            seller_order_index,
+            stats: Some(pda::stats_pda().0),
+            cranker_token_account,
+            cranker,
+            token_program: token::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::AutoRefundTimeouts {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+/// Settles `quantity_received` units of a purchase, leaving the rest escrowed.
+pub fn confirm_partial_delivery(
+    keys: &SettlementKeys,
+    route: PayoutRoute,
+    quantity_received: u64,
+) -> Instruction {
+    build_with_remaining(
+        confirm_accounts(keys, route, OrderIndexShards::default(), None),
+        instruction::ConfirmPartialDelivery {
+            purchase_id: keys.purchase_id,
+            quantity_received,
+        },
+        keys.split_metas(),
+    )
+}
+
+/// Releases milestone `index` of a made-to-order purchase to the seller.
+pub fn confirm_milestone(keys: &SettlementKeys, route: PayoutRoute, index: u8) -> Instruction {
+    build_with_remaining(
+        confirm_accounts(keys, route, OrderIndexShards::default(), None),
+        instruction::ConfirmMilestone {
+            purchase_id: keys.purchase_id,
+            index,
+        },
+        keys.split_metas(),
+    )
+}
+
+/// Batches one seller's purchases into a single shipment. `purchases` pairs
+/// each purchase id with its trade id. The purchase accounts are appended as
+/// writable remaining accounts, followed by their trade accounts and then
+/// their logistics sub-vaults, all in `purchases` order.
+pub fn consolidate_shipments(
+    provider: Pubkey,
+    buyer: Pubkey,
+    buyer_token_account: Pubkey,
+    payment_mint: Pubkey,
+    purchases: &[(u64, u64)],
+    consolidated_logistics_cost: u64,
+) -> Instruction {
+    let purchase_ids: Vec<u64> = purchases
+        .iter()
+        .map(|(purchase_id, _)| *purchase_id)
+        .collect();
+    let shipment_id = purchase_ids.first().copied().unwrap_or_default();
+    let purchase_accounts = purchase_ids
+        .iter()
+        .map(|purchase_id| AccountMeta::new(pda::purchase_pda(*purchase_id).0, false));
+    let trade_accounts = purchases
+        .iter()
+        .map(|(_, trade_id)| AccountMeta::new_readonly(pda::trade_pda(*trade_id).0, false));
+    let escrow_accounts = purchase_ids
+        .iter()
+        .map(|purchase_id| AccountMeta::new(pda::logistics_escrow_pda(*purchase_id).0, false));
+    let remaining_accounts = purchase_accounts
+        .chain(trade_accounts)
+        .chain(escrow_accounts)
+        .collect();
+    build_with_remaining(
+        accounts::ConsolidateShipments {
+            global_state: pda::global_state_pda().0,
+            shipment_account: pda::shipment_pda(&provider, shipment_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&payment_mint).0,
+            buyer_token_account,
+            buyer,
+            provider,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ConsolidateShipments {
+            shipment_id,
+            purchase_ids,
+            consolidated_logistics_cost,
+        },
+        remaining_accounts,
+    )
+}
+
+pub fn raise_dispute(
+    user: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    reason: DisputeReason,
+) -> Instruction {
+    build(
+        accounts::RaiseDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            stats: Some(pda::stats_pda().0),
+            user,
+        },
+        instruction::RaiseDispute {
+            purchase_id,
+            reason,
+        },
+    )
+}
+
+/// `authority` is the admin, or anyone once an unescalated first-tier ruling
+/// is final; `winner` and the slashing choice must then match the ruling.
+#[allow(clippy::too_many_arguments)]
+/// `buyer_token_account` receives the unvested remainder when the buyer wins.
+pub fn resolve_stream_dispute(
+    admin: Pubkey,
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    winner: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ResolveStreamDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: pda::product_escrow_pda(keys.purchase_id).0,
+            buyer_token_account,
+            admin,
+            token_program: token::ID,
+        },
+        instruction::ResolveStreamDispute {
+            purchase_id: keys.purchase_id,
+            winner,
+        },
+    )
+}
+
+pub fn appeal_resolution(
+    appellant: Pubkey,
+    keys: &SettlementKeys,
+    appellant_token_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::AppealResolution {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            appellant_token_account,
+            appellant,
+            token_program: token::ID,
+        },
+        instruction::AppealResolution {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+/// `recipient_token_account` belongs to the resolution's winner when
+/// `upheld`, otherwise to the appellant.
+pub fn review_appeal(
+    admin: Pubkey,
+    purchase_id: u64,
+    token_mint: Pubkey,
+    recipient_token_account: Pubkey,
+    upheld: bool,
+) -> Instruction {
+    build(
+        accounts::ReviewAppeal {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            recipient_token_account,
+            admin,
+            token_program: token::ID,
+        },
+        instruction::ReviewAppeal {
+            purchase_id,
+            upheld,
+        },
+    )
+}
+
+pub fn resolve_dispute(
+    authority: Pubkey,
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    winner: Pubkey,
+    route: PayoutRoute,
+    shards: OrderIndexShards,
+    penalties: DisputePenalties,
+) -> Instruction {
+    let payout = PayoutKeys::new(keys, route);
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    let slash_provider = penalties.slash_provider_into.is_some();
+    let stake_vault = slash_provider.then(|| pda::stake_vault_pda(&keys.logistics_provider).0);
+    let bond_vault = penalties
+        .seller_bond_into
+        .map(|_| pda::seller_bond_pda(&keys.seller).0);
+    build_with_remaining(
+        accounts::ResolveDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            buyer_token_account,
+            seller: keys.seller,
+            logistics_provider: keys.logistics_provider,
+            token_mint: keys.token_mint,
+            seller_token_account: payout.seller_token_account,
+            seller_payout_token_account: payout.seller_payout_token_account,
+            logistics_token_account: payout.logistics_token_account,
+            logistics_payout_token_account: payout.logistics_payout_token_account,
+            seller_claimable: payout.seller_claimable,
+            logistics_claimable: payout.logistics_claimable,
+            fee_vault: pda::fee_vault_pda(&keys.token_mint).0,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            stake_vault,
+            buyer_stake_token_account: penalties.slash_provider_into,
+            seller_account: pda::seller_pda(&keys.seller).0,
+            bond_vault,
+            buyer_bond_token_account: bond_vault.and(penalties.seller_bond_into),
+            buyer_order_index,
+            seller_order_index,
+            stats: Some(pda::stats_pda().0),
+            authority,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::ResolveDispute {
+            purchase_id: keys.purchase_id,
+            winner,
+            slash_provider,
+        },
+        keys.split_metas(),
+    )
+}
+
+pub fn arbitrate_dispute(
+    arbitrator: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    winner: Pubkey,
+    slash_provider: bool,
+) -> Instruction {
+    build(
+        accounts::ArbitrateDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator).0,
+            arbitrator,
+            system_program: system_program::ID,
+        },
+        instruction::ArbitrateDispute {
+            purchase_id,
+            winner,
+            slash_provider,
+        },
+    )
+}
+
+/// `trade_ids` holds the trade of each ruling's purchase, in the same order.
+pub fn resolve_disputes_batch(
+    arbitrator: Pubkey,
+    token_mint: Pubkey,
+    rulings: Vec<BatchRuling>,
+    trade_ids: &[u64],
+) -> Instruction {
+    let remaining_accounts = rulings
+        .iter()
+        .zip(trade_ids)
+        .flat_map(|(ruling, trade_id)| {
+            [
+                AccountMeta::new(pda::purchase_pda(ruling.purchase_id).0, false),
+                AccountMeta::new_readonly(pda::trade_pda(*trade_id).0, false),
+            ]
+        })
+        .collect();
+    build_with_remaining(
+        accounts::ResolveDisputesBatch {
+            global_state: pda::global_state_pda().0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator).0,
+            arbitrator,
+            system_program: system_program::ID,
+        },
+        instruction::ResolveDisputesBatch {
+            token_mint,
+            rulings,
+        },
+        remaining_accounts,
+    )
+}
+
+pub fn escalate_dispute(
+    escalator: Pubkey,
+    keys: &SettlementKeys,
+    escalator_token_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::EscalateDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            escalator_token_account,
+            escalator,
+            token_program: token::ID,
+        },
+        instruction::EscalateDispute {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+pub fn join_jury_pool(juror: Pubkey, juror_token_account: Pubkey) -> Instruction {
+    build(
+        accounts::JoinJuryPool {
+            global_state: pda::global_state_pda().0,
+            jury_pool: pda::jury_pool_pda().0,
+            jury_vault: pda::jury_vault_pda().0,
+            juror_token_account,
+            juror,
+            token_program: token::ID,
+        },
+        instruction::JoinJuryPool {},
+    )
+}
+
+pub fn leave_jury_pool(juror: Pubkey, juror_token_account: Pubkey) -> Instruction {
+    build(
+        accounts::LeaveJuryPool {
+            global_state: pda::global_state_pda().0,
+            jury_pool: pda::jury_pool_pda().0,
+            jury_vault: pda::jury_vault_pda().0,
+            juror_token_account,
+            juror,
+            token_program: token::ID,
+        },
+        instruction::LeaveJuryPool {},
+    )
+}
+
+pub fn summon_jury(summoner: Pubkey, purchase_id: u64, trade_id: u64) -> Instruction {
+    build(
+        accounts::SummonJury {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            jury_pool: pda::jury_pool_pda().0,
+            slot_hashes: sysvar::slot_hashes::ID,
+            summoner,
+            system_program: system_program::ID,
+        },
+        instruction::SummonJury { purchase_id },
+    )
+}
+
+pub fn cast_jury_vote(
+    juror: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    winner: Pubkey,
+) -> Instruction {
+    build(
+        accounts::CastJuryVote {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            juror,
+        },
+        instruction::CastJuryVote {
+            purchase_id,
+            winner,
+        },
+    )
+}
+
+pub fn finalize_jury_verdict(payer: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::FinalizeJuryVerdict {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            jury_pool: pda::jury_pool_pda().0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::FinalizeJuryVerdict { purchase_id },
+    )
+}
+
+pub fn preview_resolution(purchase_id: u64, trade_id: u64, winner: Pubkey) -> Instruction {
+    build(
+        accounts::PreviewResolution {
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+        },
+        instruction::PreviewResolution {
+            purchase_id,
+            winner,
+        },
+    )
+}
+
+pub fn mark_shipped(authority: Pubkey, purchase_id: u64, trade_id: u64) -> Instruction {
+    build(
+        accounts::MarkShipped {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            authority,
+        },
+        instruction::MarkShipped { purchase_id },
+    )
+}
+
+pub fn record_tracking(
+    logistics_provider: Pubkey,
+    purchase_id: u64,
+    carrier_code: u16,
+    tracking_hash: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RecordTracking {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            logistics_provider,
+            system_program: system_program::ID,
+        },
+        instruction::RecordTracking {
+            purchase_id,
+            carrier_code,
+            tracking_hash,
+        },
+    )
+}
+
+pub fn submit_pod(logistics_provider: Pubkey, purchase_id: u64, pod_hash: [u8; 32]) -> Instruction {
+    build(
+        accounts::SubmitPod {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            logistics_provider,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitPod {
+            purchase_id,
+            pod_hash,
+        },
+    )
+}
+
+pub fn provider_accept_order(logistics_provider: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::AnswerAssignment {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            logistics_provider,
+            system_program: system_program::ID,
+        },
+        instruction::ProviderAcceptOrder { purchase_id },
+    )
+}
+
+pub fn provider_decline_order(logistics_provider: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::AnswerAssignment {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            logistics_provider,
+            system_program: system_program::ID,
+        },
+        instruction::ProviderDeclineOrder { purchase_id },
+    )
+}
+
+/// `logistics_provider_index` is the new provider's position in the trade's
+/// provider list. `max_logistics_cost` caps the re-priced leg, and with it
+/// any top-up taken from `buyer_token_account`.
+pub fn reassign_logistics(
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    max_logistics_cost: u64,
+) -> Instruction {
+    build(
+        accounts::ReassignLogistics {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            buyer_token_account,
+            token_mint: keys.token_mint,
+            previous_provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            provider_account: pda::logistics_provider_pda(&logistics_provider).0,
+            buyer: keys.buyer,
+            token_program: token::ID,
+        },
+        instruction::ReassignLogistics {
+            purchase_id: keys.purchase_id,
+            logistics_provider,
+            logistics_provider_index,
+            max_logistics_cost,
+        },
+    )
+}
+
+/// `receipt_mint` is the lending program's receipt token for `token_mint`.
+pub fn deposit_escrow(
+    buyer: Pubkey,
+    purchase_id: u64,
+    token_mint: Pubkey,
+    receipt_mint: Pubkey,
+    hook: LendingHook,
+) -> Instruction {
+    build_with_remaining(
+        accounts::DepositEscrow {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            receipt_mint,
+            lending_receipt: pda::lending_receipt_pda(purchase_id).0,
+            lending_program: hook.lending_program,
+            buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::DepositEscrow {
+            purchase_id,
+            route_data: hook.route_data,
+        },
+        hook.route_accounts,
+    )
+}
+
+/// Only the buyer may withdraw; their yield share goes to `buyer_token_account`.
+pub fn withdraw_escrow(
+    buyer: Pubkey,
+    purchase_id: u64,
+    token_mint: Pubkey,
+    buyer_token_account: Pubkey,
+    hook: LendingHook,
+) -> Instruction {
+    build_with_remaining(
+        accounts::WithdrawEscrow {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            lending_receipt: pda::lending_receipt_pda(purchase_id).0,
+            buyer_token_account,
+            token_mint,
+            fee_vault: pda::fee_vault_pda(&token_mint).0,
+            lending_program: hook.lending_program,
+            buyer,
+            token_program: token::ID,
+        },
+        instruction::WithdrawEscrow {
+            purchase_id,
+            route_data: hook.route_data,
+        },
+        hook.route_accounts,
+    )
+}
+
+/// `payer` may be anyone, typically the buyer.
+pub fn open_loyalty_account(payer: Pubkey, buyer: Pubkey) -> Instruction {
+    build(
+        accounts::OpenLoyaltyAccount {
+            loyalty_account: pda::loyalty_pda(&buyer).0,
+            buyer,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenLoyaltyAccount {},
+    )
+}
+
+/// `buyer` is the purchase's buyer; `payer` covers the purchase account's
+/// growth and may be anyone.
+pub fn accrue_loyalty_points(payer: Pubkey, buyer: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::AccrueLoyaltyPoints {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            loyalty_account: pda::loyalty_pda(&buyer).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::AccrueLoyaltyPoints { purchase_id },
+    )
+}
+
+pub fn redeem_loyalty_points(buyer: Pubkey, points: u64) -> Instruction {
+    build(
+        accounts::RedeemLoyaltyPoints {
+            global_state: pda::global_state_pda().0,
+            loyalty_account: pda::loyalty_pda(&buyer).0,
+            buyer,
+        },
+        instruction::RedeemLoyaltyPoints { points },
+    )
+}
+
+/// `merkle_tree` must be the tree in the receipt config.
+pub fn mint_purchase_receipt(buyer: Pubkey, purchase_id: u64, merkle_tree: Pubkey) -> Instruction {
+    build(
+        accounts::MintPurchaseReceipt {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            receipt_config: pda::receipt_config_pda().0,
+            tree_config: TreeConfig::find_pda(&merkle_tree).0,
+            merkle_tree,
+            receipt_authority: pda::receipt_authority_pda().0,
+            buyer,
+            log_wrapper: SPL_NOOP_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+            bubblegum_program: mpl_bubblegum::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MintPurchaseReceipt { purchase_id },
+    )
+}
+
+/// Simulate to read the `PurchaseStatus` from the return data.
+pub fn get_purchase_status(purchase_id: u64) -> Instruction {
+    build(
+        accounts::GetPurchaseStatus {
+            purchase_account: pda::purchase_pda(purchase_id).0,
+        },
+        instruction::GetPurchaseStatus { purchase_id },
+    )
+}
+
+/// Audits the escrow of `purchase_ids`, all purchases of `trade_id`. Settled
+/// and cancelled purchases are skipped on-chain, so any of the trade's
+/// purchases may be listed.
+pub fn audit_escrow(trade_id: u64, purchase_ids: &[u64]) -> Instruction {
+    build_with_remaining(
+        accounts::AuditEscrow {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+        },
+        instruction::AuditEscrow { trade_id },
+        purchase_ids
+            .iter()
+            .flat_map(|&purchase_id| {
+                [
+                    pda::purchase_pda(purchase_id).0,
+                    pda::product_escrow_pda(purchase_id).0,
+                    pda::logistics_escrow_pda(purchase_id).0,
+                ]
+            })
+            .map(|key| AccountMeta::new_readonly(key, false))
+            .collect(),
+    )
+}
+
+/// `authority` is the buyer, seller, logistics provider or the buyer's
+/// confirmation delegate.
+pub fn sign_confirmation(authority: Pubkey, keys: &SettlementKeys) -> Instruction {
+    let is_party = [keys.buyer, keys.seller, keys.logistics_provider].contains(&authority);
+    build(
+        accounts::SignConfirmation {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            buyer_account: (!is_party).then(|| pda::buyer_pda(&keys.buyer).0),
+            authority,
+        },
+        instruction::SignConfirmation {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+/// `insured` must be set when the purchase carries an active insurance
+/// premium, which is refunded from the pool.
+pub fn seller_cancel_purchase(
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    insured: bool,
+    reason: CancelReason,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    build(
+        accounts::SellerCancelPurchase {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            seller_account: pda::seller_pda(&keys.seller).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            insurance_pool: insured.then(|| pda::insurance_pool_pda(&keys.token_mint).0),
+            buyer_token_account,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            buyer_order_index,
+            seller_order_index,
+            stats: Some(pda::stats_pda().0),
+            seller: keys.seller,
+            token_program: token::ID,
+        },
+        instruction::SellerCancelPurchase {
+            purchase_id: keys.purchase_id,
+            reason,
+        },
+    )
+}
+
+/// `seller_cosigns` waives the restocking fee after the cooling-off window;
+/// otherwise pass `seller_token_account` to receive it.
+pub fn cancel_purchase(
+    keys: &SettlementKeys,
+    buyer_token_account: Pubkey,
+    seller_cosigns: bool,
+    seller_token_account: Option<Pubkey>,
+    reason: CancelReason,
+    shards: OrderIndexShards,
+) -> Instruction {
+    let (buyer_order_index, seller_order_index) = shards.keys(&keys.buyer, &keys.seller);
+    build(
+        accounts::CancelPurchase {
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            buyer_token_account,
+            provider_account: pda::logistics_provider_pda(&keys.logistics_provider).0,
+            buyer_order_index,
+            seller_order_index,
+            global_state: pda::global_state_pda().0,
+            seller: seller_cosigns.then_some(keys.seller),
+            seller_token_account,
+            stats: Some(pda::stats_pda().0),
+            buyer: keys.buyer,
+            token_program: token::ID,
+            #[cfg(feature = "cpi-events")]
+            event_authority: pda::event_authority_pda().0,
+            #[cfg(feature = "cpi-events")]
+            program: PROGRAM_ID,
+        },
+        instruction::CancelPurchase {
+            purchase_id: keys.purchase_id,
+            reason,
+        },
+    )
+}
+
+/// Releases into the seller's associated token account, or the payout account
+/// set through `SettlementKeys::with_seller_account`.
+pub fn release_seller_stream(keys: &SettlementKeys) -> Instruction {
+    build(
+        accounts::ReleaseSellerStream {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: pda::product_escrow_pda(keys.purchase_id).0,
+            seller: keys.seller,
+            seller_token_account: Some(get_associated_token_address(
+                &keys.seller,
+                &keys.token_mint,
+            )),
+            seller_payout_token_account: keys.seller_payout_token_account,
+            seller_account: keys
+                .seller_payout_token_account
+                .map(|_| pda::seller_pda(&keys.seller).0),
+            token_program: token::ID,
+        },
+        instruction::ReleaseSellerStream {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+/// `payer` may be anyone, typically whoever settles the purchases paying
+/// `recipient`.
+pub fn open_claimable_balance(payer: Pubkey, recipient: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::OpenClaimableBalance {
+            claimable_balance: pda::claimable_pda(&recipient, &token_mint).0,
+            recipient,
+            token_mint,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenClaimableBalance {},
+    )
+}
+
+pub fn claim_payout(
+    recipient: Pubkey,
+    token_mint: Pubkey,
+    recipient_token_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClaimPayout {
+            global_state: pda::global_state_pda().0,
+            claimable_balance: pda::claimable_pda(&recipient, &token_mint).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            recipient_token_account,
+            recipient,
+            token_program: token::ID,
+        },
+        instruction::ClaimPayout {},
+    )
+}
+
+pub fn claim_insurance(
+    seller: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    token_mint: Pubkey,
+    seller_token_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClaimInsurance {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            insurance_pool: pda::insurance_pool_pda(&token_mint).0,
+            seller_token_account,
+            seller,
+            token_program: token::ID,
+        },
+        instruction::ClaimInsurance { purchase_id },
+    )
+}
+
+pub fn open_returns_reserve(seller: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::OpenReturnsReserve {
+            returns_reserve: pda::returns_reserve_pda(&seller, &token_mint).0,
+            token_mint,
+            seller,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::OpenReturnsReserve {},
+    )
+}
+
+pub fn fund_returns_reserve(
+    seller: Pubkey,
+    token_mint: Pubkey,
+    seller_token_account: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::FundReturnsReserve {
+            global_state: pda::global_state_pda().0,
+            returns_reserve: pda::returns_reserve_pda(&seller, &token_mint).0,
+            token_mint,
+            seller_token_account,
+            seller,
+            token_program: token::ID,
+        },
+        instruction::FundReturnsReserve { amount },
+    )
+}
+
+pub fn withdraw_returns_reserve(
+    seller: Pubkey,
+    token_mint: Pubkey,
+    seller_token_account: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawReturnsReserve {
+            global_state: pda::global_state_pda().0,
+            returns_reserve: pda::returns_reserve_pda(&seller, &token_mint).0,
+            seller_token_account,
+            seller,
+            token_program: token::ID,
+        },
+        instruction::WithdrawReturnsReserve { amount },
+    )
+}
+
+pub fn request_return(
+    buyer: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    logistics_provider: Pubkey,
+) -> Instruction {
+    build(
+        accounts::RequestReturn {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::RequestReturn {
+            purchase_id,
+            logistics_provider,
+        },
+    )
+}
+
+pub fn approve_return(keys: &SettlementKeys, logistics_cost: u64) -> Instruction {
+    build(
+        accounts::ApproveReturn {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            returns_reserve: pda::returns_reserve_pda(&keys.seller, &keys.token_mint).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            seller: keys.seller,
+            token_program: token::ID,
+        },
+        instruction::ApproveReturn {
+            purchase_id: keys.purchase_id,
+            logistics_cost,
+        },
+    )
+}
+
+/// `return_provider` is the provider chosen in `request_return`; its token
+/// account is only passed when the approved return shipping cost is non-zero.
+pub fn complete_return(
+    authority: Pubkey,
+    keys: &SettlementKeys,
+    return_provider: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::CompleteReturn {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(keys.purchase_id).0,
+            trade_account: pda::trade_pda(keys.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&keys.token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(keys.purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(keys.purchase_id).0),
+            buyer_token_account: get_associated_token_address(&keys.buyer, &keys.token_mint),
+            logistics_token_account: return_provider
+                .map(|provider| get_associated_token_address(&provider, &keys.token_mint)),
+            authority,
+            token_program: token::ID,
+        },
+        instruction::CompleteReturn {
+            purchase_id: keys.purchase_id,
+        },
+    )
+}
+
+pub fn close_purchase(
+    authority: Pubkey,
+    payer: Pubkey,
+    purchase_id: u64,
+    trade_id: u64,
+    token_mint: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClosePurchase {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&token_mint).0,
+            product_escrow: Some(pda::product_escrow_pda(purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(purchase_id).0),
+            payer,
+            authority,
+            token_program: token::ID,
+        },
+        instruction::ClosePurchase { purchase_id },
+    )
+}
+
+pub fn open_shipping_info(buyer: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::OpenShippingInfo {
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            shipping_info: pda::shipping_info_pda(purchase_id).0,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenShippingInfo { purchase_id },
+    )
+}
+
+/// `seller_ciphertext` and `provider_ciphertext` are the buyer's address sealed
+/// to the seller and the logistics provider with the X25519 secret shared with
+/// `ephemeral_pubkey`. Send alongside `buy_trade` and `open_shipping_info` for
+/// the same purchase.
+pub fn post_shipping_info(
+    buyer: Pubkey,
+    purchase_id: u64,
+    ephemeral_pubkey: [u8; 32],
+    seller_ciphertext: Vec<u8>,
+    provider_ciphertext: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::PostShippingInfo {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            shipping_info: pda::shipping_info_pda(purchase_id).0,
+            buyer,
+        },
+        instruction::PostShippingInfo {
+            purchase_id,
+            ephemeral_pubkey,
+            seller_ciphertext,
+            provider_ciphertext,
+        },
+    )
+}
+
+pub fn close_shipping_info(buyer: Pubkey, purchase_id: u64) -> Instruction {
+    build(
+        accounts::CloseShippingInfo {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            shipping_info: pda::shipping_info_pda(purchase_id).0,
+            buyer,
+        },
+        instruction::CloseShippingInfo { purchase_id },
+    )
+}
+
+pub fn withdraw_escrow_fees(
+    authority: Pubkey,
+    mint: Pubkey,
+    authority_token_account: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawEscrowFees {
+            global_state: pda::global_state_pda().0,
+            fee_vault: pda::fee_vault_pda(&mint).0,
+            authority_token_account,
+            authority,
+            token_program: token::ID,
+        },
+        instruction::WithdrawEscrowFees { mint, amount },
+    )
+}
+
+pub fn propose_emergency_withdrawal(
+    admin: Pubkey,
+    token_mint: Pubkey,
+    source: EmergencyVault,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::ProposeEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&token_mint).0,
+            token_mint,
+            source_vault: source.address(&token_mint).0,
+            destination,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::ProposeEmergencyWithdrawal { source, amount },
+    )
+}
+
+/// `source` and `destination` must be the vault and token account named in
+/// the proposal.
+pub fn execute_emergency_withdrawal(
+    admin: Pubkey,
+    token_mint: Pubkey,
+    source: EmergencyVault,
+    destination: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExecuteEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&token_mint).0,
+            source_vault: source.address(&token_mint).0,
+            escrow_token_account: source
+                .escrow_is_authority()
+                .then(|| pda::escrow_vault_pda(&token_mint).0),
+            destination,
+            admin,
+            token_program: token::ID,
+        },
+        instruction::ExecuteEmergencyWithdrawal {},
+    )
+}
+
+pub fn cancel_emergency_withdrawal(admin: Pubkey, token_mint: Pubkey) -> Instruction {
+    build(
+        accounts::CancelEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&token_mint).0,
+            admin,
+        },
+        instruction::CancelEmergencyWithdrawal {},
+    )
+}
+
+pub fn set_fee_splits(authority: Pubkey, splits: Vec<PayoutSplit>) -> Instruction {
+    build(
+        accounts::SetFeeSplits {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetFeeSplits { splits },
+    )
+}
+
+/// `recipient_token_accounts` are the fee split recipients' token accounts for
+/// `mint`, in split order.
+pub fn distribute_fees(
+    cranker: Pubkey,
+    mint: Pubkey,
+    recipient_token_accounts: &[Pubkey],
+) -> Instruction {
+    build_with_remaining(
+        accounts::DistributeFees {
+            global_state: pda::global_state_pda().0,
+            fee_vault: pda::fee_vault_pda(&mint).0,
+            cranker,
+            token_program: token::ID,
+        },
+        instruction::DistributeFees { mint },
+        recipient_token_accounts
+            .iter()
+            .map(|key| AccountMeta::new(*key, false))
+            .collect(),
+    )
+}
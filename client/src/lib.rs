@@ -0,0 +1,22 @@
+//! Off-chain client for the Dezenmart logistics program: PDA derivation,
+//! typed instruction builders and account fetch/decode helpers.
+
+pub mod instructions;
+pub mod rpc;
+
+pub use dezenmart_rust_smart_contract::pda;
+pub use dezenmart_rust_smart_contract::SemVer;
+pub use dezenmart_rust_smart_contract::ID as PROGRAM_ID;
+pub use instructions::{
+    CreateTradeArgs, DisputePenalties, LendingHook, OrderIndexShards, PayoutRoute, SettlementKeys,
+    SwapHook, TradeKeys,
+};
+pub use rpc::{decode, ClientError, DezenmartClient};
+
+/// Version of the program interface this client builds instructions for;
+/// checked against the on-chain `ProgramVersion` account.
+pub const CLIENT_VERSION: SemVer = SemVer {
+    major: 0,
+    minor: 1,
+    patch: 0,
+};
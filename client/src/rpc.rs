@@ -0,0 +1,225 @@
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use dezenmart_rust_smart_contract::{
+    AllowedMint, ArbitratorAccount, AuctionAccount, BuyerAccount, BuyerAttestation, BuyerQuota,
+    CategoryIndex, ClaimableBalance, GlobalState, JuryPool, LogisticsProviderAccount, OfferAccount,
+    OrderIndex, ProgramVersion, PurchaseAccount, ReservationAccount, SealedCommitAccount,
+    SealedDropAccount, SellerAccount, SemVer, Stats, SubscriptionAccount, TradeAccount,
+    TradeMetadata,
+};
+use solana_client::client_error::ClientError as RpcError;
+use solana_client::rpc_client::RpcClient;
+
+use crate::instructions::{SettlementKeys, TradeKeys};
+use crate::{pda, CLIENT_VERSION};
+
+#[derive(Debug)]
+pub enum ClientError {
+    Rpc(Box<RpcError>),
+    Decode(anchor_lang::error::Error),
+    /// The deployed program is newer than this client understands, or no
+    /// longer supports it.
+    IncompatibleProgram {
+        program_version: SemVer,
+        min_client_version: SemVer,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Rpc(err) => write!(f, "rpc error: {}", err),
+            ClientError::Decode(err) => write!(f, "account decode error: {}", err),
+            ClientError::IncompatibleProgram {
+                program_version,
+                min_client_version,
+            } => write!(
+                f,
+                "program {}.{}.{} requires a client of at least {}.{}.{}, this is {}.{}.{}",
+                program_version.major,
+                program_version.minor,
+                program_version.patch,
+                min_client_version.major,
+                min_client_version.minor,
+                min_client_version.patch,
+                CLIENT_VERSION.major,
+                CLIENT_VERSION.minor,
+                CLIENT_VERSION.patch,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<RpcError> for ClientError {
+    fn from(err: RpcError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+impl From<anchor_lang::error::Error> for ClientError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        ClientError::Decode(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Decodes raw account data, checking the Anchor discriminator.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    let mut data = data;
+    Ok(T::try_deserialize(&mut data)?)
+}
+
+/// Typed account fetching over a blocking RPC client.
+pub struct DezenmartClient {
+    rpc: RpcClient,
+}
+
+impl DezenmartClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let data = self.rpc.get_account_data(address)?;
+        decode(&data)
+    }
+
+    pub fn global_state(&self) -> Result<GlobalState> {
+        self.fetch(&pda::global_state_pda().0)
+    }
+
+    pub fn stats(&self) -> Result<Stats> {
+        self.fetch(&pda::stats_pda().0)
+    }
+
+    pub fn program_version(&self) -> Result<ProgramVersion> {
+        self.fetch(&pda::program_version_pda().0)
+    }
+
+    /// Fails unless the deployed program supports `CLIENT_VERSION`. Call it
+    /// before building instructions so an incompatible upgrade is caught
+    /// before anything is signed.
+    pub fn check_program_version(&self) -> Result<()> {
+        let version = self.program_version()?;
+        if !version.supports_client(CLIENT_VERSION) {
+            return Err(ClientError::IncompatibleProgram {
+                program_version: version.program_version,
+                min_client_version: version.min_client_version,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn trade(&self, trade_id: u64) -> Result<TradeAccount> {
+        self.fetch(&pda::trade_pda(trade_id).0)
+    }
+
+    pub fn trade_metadata(&self, trade_id: u64) -> Result<TradeMetadata> {
+        self.fetch(&pda::trade_metadata_pda(trade_id).0)
+    }
+
+    pub fn auction(&self, trade_id: u64) -> Result<AuctionAccount> {
+        self.fetch(&pda::auction_pda(trade_id).0)
+    }
+
+    pub fn jury_pool(&self) -> Result<JuryPool> {
+        self.fetch(&pda::jury_pool_pda().0)
+    }
+
+    pub fn sealed_drop(&self, trade_id: u64) -> Result<SealedDropAccount> {
+        self.fetch(&pda::sealed_drop_pda(trade_id).0)
+    }
+
+    pub fn sealed_commit(&self, trade_id: u64, buyer: &Pubkey) -> Result<SealedCommitAccount> {
+        self.fetch(&pda::sealed_commit_pda(trade_id, buyer).0)
+    }
+
+    pub fn offer(&self, trade_id: u64, buyer: &Pubkey) -> Result<OfferAccount> {
+        self.fetch(&pda::offer_pda(trade_id, buyer).0)
+    }
+
+    pub fn subscription(&self, trade_id: u64, buyer: &Pubkey) -> Result<SubscriptionAccount> {
+        self.fetch(&pda::subscription_pda(trade_id, buyer).0)
+    }
+
+    pub fn buyer_quota(&self, trade_id: u64, buyer: &Pubkey) -> Result<BuyerQuota> {
+        self.fetch(&pda::buyer_quota_pda(trade_id, buyer).0)
+    }
+
+    pub fn reservation(&self, trade_id: u64, buyer: &Pubkey) -> Result<ReservationAccount> {
+        self.fetch(&pda::reservation_pda(trade_id, buyer).0)
+    }
+
+    pub fn purchase(&self, purchase_id: u64) -> Result<PurchaseAccount> {
+        self.fetch(&pda::purchase_pda(purchase_id).0)
+    }
+
+    pub fn buyer_account(&self, buyer: &Pubkey) -> Result<BuyerAccount> {
+        self.fetch(&pda::buyer_pda(buyer).0)
+    }
+
+    pub fn attestation(&self, verifier: &Pubkey, subject: &Pubkey) -> Result<BuyerAttestation> {
+        self.fetch(&pda::attestation_pda(verifier, subject).0)
+    }
+
+    pub fn seller_account(&self, seller: &Pubkey) -> Result<SellerAccount> {
+        self.fetch(&pda::seller_pda(seller).0)
+    }
+
+    pub fn arbitrator(&self, arbitrator: &Pubkey) -> Result<ArbitratorAccount> {
+        self.fetch(&pda::arbitrator_pda(arbitrator).0)
+    }
+
+    pub fn allowed_mint(&self, token_mint: &Pubkey) -> Result<AllowedMint> {
+        self.fetch(&pda::allowed_mint_pda(token_mint).0)
+    }
+
+    pub fn logistics_provider(&self, provider: &Pubkey) -> Result<LogisticsProviderAccount> {
+        self.fetch(&pda::logistics_provider_pda(provider).0)
+    }
+
+    pub fn claimable_balance(
+        &self,
+        recipient: &Pubkey,
+        token_mint: &Pubkey,
+    ) -> Result<ClaimableBalance> {
+        self.fetch(&pda::claimable_pda(recipient, token_mint).0)
+    }
+
+    pub fn order_index(&self, user: &Pubkey, shard_no: u32) -> Result<OrderIndex> {
+        self.fetch(&pda::order_index_pda(user, shard_no).0)
+    }
+
+    pub fn category_index(&self, category: u16, shard_no: u32) -> Result<CategoryIndex> {
+        self.fetch(&pda::category_index_pda(category, shard_no).0)
+    }
+
+    /// Id the next `create_trade` will assign.
+    pub fn next_trade_id(&self) -> Result<u64> {
+        Ok(self.global_state()?.trade_counter + 1)
+    }
+
+    /// Id the next `buy_trade` will assign.
+    pub fn next_purchase_id(&self) -> Result<u64> {
+        Ok(self.global_state()?.purchase_counter + 1)
+    }
+
+    pub fn trade_keys(&self, trade_id: u64) -> Result<TradeKeys> {
+        Ok(TradeKeys::from_account(&self.trade(trade_id)?))
+    }
+
+    pub fn settlement_keys(&self, purchase_id: u64) -> Result<SettlementKeys> {
+        let purchase = self.purchase(purchase_id)?;
+        let trade = self.trade(purchase.trade_id)?;
+        Ok(SettlementKeys::from_accounts(&trade, &purchase))
+    }
+}
@@ -0,0 +1,53 @@
+//! Program-wide limits and default policy values.
+
+pub const ESCROW_FEE_PERCENT: u64 = 250; // 2.5% (in basis points)
+pub const BASIS_POINTS: u64 = 10000;
+pub const MAX_LOGISTICS_PROVIDERS: usize = 10;
+pub const MAX_PURCHASE_IDS: usize = 100;
+pub const MAX_PAYOUT_SPLITS: usize = 5;
+pub const MAX_MILESTONES: usize = 5;
+pub const MAX_CONSOLIDATED_PURCHASES: usize = 10;
+pub const CONSOLIDATION_WINDOW_SECONDS: i64 = 3 * 24 * 60 * 60; // 3 days
+pub const EXTENSION_HEADER_LEN: usize = 4; // u16 type + u16 length
+pub const MAX_EXTENSION_BYTES: usize = 1024;
+pub const DEFAULT_SLASH_BPS: u16 = 5000; // 50% of stake
+pub const DEFAULT_STAKE_COOLDOWN_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
+pub const DEFAULT_SELLER_PROBATION_SECONDS: i64 = 30 * 24 * 60 * 60; // 30 days
+pub const DEFAULT_SELLER_LOSS_THRESHOLD: u32 = 2;
+pub const DEFAULT_CANCELLATION_WINDOW_SECONDS: i64 = 30 * 60; // 30 minutes
+pub const DEFAULT_RESTOCKING_FEE_BPS: u16 = 500; // 5% of the product cost
+pub const DEFAULT_ESCALATION_WINDOW_SECONDS: i64 = 3 * 24 * 60 * 60; // 3 days
+pub const DEFAULT_ESCALATION_FEE_BPS: u16 = 200; // 2% of the purchase total
+pub const DEFAULT_AUTO_CONFIRM_SECONDS: i64 = 14 * 24 * 60 * 60; // 14 days after shipping
+pub const DEFAULT_SHIP_TIMEOUT_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days after purchase
+pub const DEFAULT_CRANK_REWARD_BPS: u16 = 1000; // 10% of the escrow fee
+pub const PURCHASE_RETENTION_SECONDS: i64 = 30 * 24 * 60 * 60; // 30 days
+pub const RETURN_WINDOW_SECONDS: i64 = 14 * 24 * 60 * 60; // 14 days
+pub const EVENT_SCHEMA_VERSION: u8 = 5;
+pub const MAX_ORDER_INDEX_ENTRIES: usize = 64; // entries allocated when a shard is created
+pub const ORDER_INDEX_ENTRY_LEN: usize = 8 + 1;
+pub const ORDER_INDEX_PAGE_BYTES: usize = 10 * 1024; // largest realloc allowed per instruction
+pub const MAX_TRADE_TAGS: usize = 4;
+pub const MAX_CATEGORY_INDEX_ENTRIES: usize = 128;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+pub const ORACLE_MAX_STALENESS_SECONDS: u64 = 60;
+pub const MAX_ACCEPTED_MINTS: usize = 4;
+pub const MAX_INSURANCE_BPS: u16 = 1000;
+pub const MAX_STATS_MINTS: usize = 16;
+pub const RESERVATION_TTL_SECONDS: i64 = 15 * 60; // 15 minutes
+pub const ACCOUNT_VERSION: u8 = 1; // 0 marks accounts written before versioning
+pub const MIN_SUBSCRIPTION_INTERVAL_SECONDS: i64 = 24 * 60 * 60; // 1 day
+pub const MAX_SUBSCRIPTION_CYCLES: u32 = 52;
+pub const MAX_PLATFORMS: usize = 8;
+pub const MAX_FEE_SPLITS: usize = 5;
+pub const EMERGENCY_TIMELOCK_SECONDS: i64 = 72 * 60 * 60; // 72 hours
+pub const MAX_RECEIPT_URI_PREFIX_LEN: usize = 100; // leaves room for the query within 200
+pub const RECEIPT_SYMBOL: &str = "DZMRCPT";
+pub const RELAYED_ORDER_DOMAIN: &[u8] = b"dezenmart:relayed_order:v1";
+pub const MAX_SESSION_SLOTS: u64 = 7 * 216_000; // about 7 days of 400ms slots
+pub const MAX_JURY_POOL_SIZE: usize = 32;
+pub const MAX_JURY_SIZE: u8 = 7;
+pub const MAX_SHIPPING_CIPHERTEXT_LEN: usize = 256; // nonce, sealed address and tag
+pub const MAX_BATCH_CONFIRMATIONS: usize = 8;
+pub const MAX_BATCH_RULINGS: usize = 10;
+pub const MAX_ESCROW_FEE_BPS: u16 = 1000; // 10% of the purchase total
@@ -0,0 +1,404 @@
+use anchor_lang::prelude::*;
+
+// Limit checks on hot paths fail through `require_gte!`, which attaches the
+// compared values to the error. Ceilings log the limit or available amount as
+// `Left` and the requested amount as `Right`; floors, such as a minimum order
+// or a cooldown, log the requested amount as `Left` and the floor as `Right`.
+// Clients read both from `AnchorError::compared_values`.
+#[error_code]
+pub enum LogisticsError {
+    #[msg("Mismatched arrays length")]
+    MismatchedArrays,
+    #[msg("No logistics providers provided")]
+    NoLogisticsProviders,
+    #[msg("Too many logistics providers")]
+    TooManyProviders,
+    #[msg("Invalid quantity")]
+    InvalidQuantity,
+    #[msg("Trade is inactive")]
+    TradeInactive,
+    #[msg("Insufficient quantity available")]
+    InsufficientQuantity,
+    #[msg("Buyer cannot be the seller")]
+    BuyerIsSeller,
+    #[msg("Invalid logistics provider")]
+    InvalidLogisticsProvider,
+    #[msg("Not authorized")]
+    NotAuthorized,
+    #[msg("Already confirmed")]
+    AlreadyConfirmed,
+    #[msg("Purchase is disputed")]
+    Disputed,
+    #[msg("Already settled")]
+    AlreadySettled,
+    #[msg("Already disputed")]
+    AlreadyDisputed,
+    #[msg("Not disputed")]
+    NotDisputed,
+    #[msg("Invalid winner")]
+    InvalidWinner,
+    #[msg("No fees to withdraw")]
+    NoFeesToWithdraw,
+    #[msg("Too many payout splits")]
+    TooManyPayoutSplits,
+    #[msg("Payout splits must be non-zero and sum to 10000 basis points")]
+    InvalidPayoutSplits,
+    #[msg("Missing payout split token accounts")]
+    MissingPayoutAccounts,
+    #[msg("Payout token account does not match split recipient")]
+    InvalidPayoutAccount,
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
+    #[msg("Invalid delivery regions")]
+    InvalidRegions,
+    #[msg("Logistics provider is not registered")]
+    ProviderNotRegistered,
+    #[msg("Invalid purchase account")]
+    InvalidPurchaseAccount,
+    #[msg("Invalid shipment consolidation")]
+    InvalidConsolidation,
+    #[msg("Purchase already consolidated")]
+    AlreadyConsolidated,
+    #[msg("Purchases fall outside the consolidation window")]
+    ConsolidationWindowExceeded,
+    #[msg("Invalid account extension")]
+    InvalidExtension,
+    #[msg("Account extension space exhausted")]
+    ExtensionSpaceExhausted,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Invalid stake configuration")]
+    InvalidStakeConfig,
+    #[msg("Stake mint does not match configuration")]
+    InvalidStakeMint,
+    #[msg("Logistics provider is still registered")]
+    ProviderStillRegistered,
+    #[msg("Stake cooldown has not elapsed")]
+    StakeCooldownActive,
+    #[msg("No stake to withdraw")]
+    NoStakeToWithdraw,
+    #[msg("Missing stake accounts for slashing")]
+    MissingStakeAccounts,
+    #[msg("Invalid seller bond configuration")]
+    InvalidBondConfig,
+    #[msg("Bond amount must be greater than zero")]
+    InvalidBondAmount,
+    #[msg("Bond is locked by lost disputes")]
+    BondLocked,
+    #[msg("Seller probation period has not elapsed")]
+    ProbationActive,
+    #[msg("No bond to release")]
+    NoBondToRelease,
+    #[msg("Missing bond accounts for slashing")]
+    MissingBondAccounts,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Token account mint does not match the trade mint")]
+    InvalidTokenMint,
+    #[msg("Token account owner does not match the expected party")]
+    InvalidTokenOwner,
+    #[msg("Purchase is not settled")]
+    NotSettled,
+    #[msg("Retention window has not elapsed")]
+    RetentionWindowActive,
+    #[msg("Order index shard is full")]
+    OrderIndexShardFull,
+    #[msg("Previous index shard is missing or not full")]
+    PreviousShardNotFull,
+    #[msg("Too many tags")]
+    TooManyTags,
+    #[msg("Category index shard is full")]
+    CategoryIndexShardFull,
+    #[msg("Category index does not match the trade category")]
+    CategoryMismatch,
+    #[msg("Metadata URI is too long")]
+    MetadataUriTooLong,
+    #[msg("Price feed account is missing")]
+    MissingPriceFeed,
+    #[msg("Price feed does not match the trade")]
+    InvalidPriceFeed,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    #[msg("Oracle price is not positive")]
+    InvalidOraclePrice,
+    #[msg("Invalid accepted mints")]
+    InvalidAcceptedMints,
+    #[msg("Slippage tolerance exceeds 100%")]
+    InvalidSlippage,
+    #[msg("Swap settlement requires the seller account, swap program and destination")]
+    MissingSwapAccounts,
+    #[msg("Invalid swap route")]
+    InvalidSwapRoute,
+    #[msg("Swap spent more than the seller's share")]
+    SwapOverspent,
+    #[msg("Swap output below the quoted amount minus slippage")]
+    SlippageExceeded,
+    #[msg("Insurance premium exceeds the maximum")]
+    InvalidInsuranceBps,
+    #[msg("This trade does not offer insurance")]
+    InsuranceNotOffered,
+    #[msg("Insured purchases require the insurance pool account")]
+    MissingInsurancePool,
+    #[msg("A dispute reason is required")]
+    InvalidDisputeReason,
+    #[msg("A cancellation reason is required")]
+    InvalidCancelReason,
+    #[msg("Invalid escalation policy")]
+    InvalidEscalationPolicy,
+    #[msg("Dispute has already been ruled on")]
+    AlreadyRuled,
+    #[msg("Dispute has no first-tier ruling")]
+    NoDisputeRuling,
+    #[msg("Dispute has already been escalated")]
+    AlreadyEscalated,
+    #[msg("Escalation window has closed")]
+    EscalationWindowClosed,
+    #[msg("Escalation window is still open")]
+    EscalationWindowOpen,
+    #[msg("Resolution does not match the first-tier ruling")]
+    RulingMismatch,
+    #[msg("Invalid crank configuration")]
+    InvalidCrankConfig,
+    #[msg("Purchase has not shipped")]
+    NotShipped,
+    #[msg("Deadline has not been reached")]
+    DeadlineNotReached,
+    #[msg("Reservation has not expired")]
+    ReservationActive,
+    #[msg("Invalid auction parameters")]
+    InvalidAuction,
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    #[msg("Auction has not ended")]
+    AuctionNotEnded,
+    #[msg("Bid is below the minimum")]
+    BidTooLow,
+    #[msg("Invalid offer parameters")]
+    InvalidOffer,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired")]
+    OfferNotExpired,
+    #[msg("Invalid subscription parameters")]
+    InvalidSubscription,
+    #[msg("Subscription has no cycles left")]
+    SubscriptionComplete,
+    #[msg("Subscription cycle is not due yet")]
+    SubscriptionCycleNotDue,
+    #[msg("Listing price exceeds the subscription's approved amount")]
+    SubscriptionPriceAboveCap,
+    #[msg("Confirmation delegate cannot be the buyer")]
+    InvalidConfirmationDelegate,
+    #[msg("No insurance claim is available for this purchase")]
+    InsuranceNotClaimable,
+    #[msg("Insurance pool balance is too low to pay this claim")]
+    InsufficientInsurancePool,
+    #[msg("Invalid cancellation policy")]
+    InvalidCancellationPolicy,
+    #[msg("Purchase has already shipped")]
+    AlreadyShipped,
+    #[msg("Purchase was partially delivered")]
+    PartiallyDelivered,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Only delivered purchases can be returned")]
+    NotReturnable,
+    #[msg("The return window has closed")]
+    ReturnWindowClosed,
+    #[msg("A return was already requested for this purchase")]
+    ReturnAlreadyRequested,
+    #[msg("The return is not in the expected state")]
+    InvalidReturnState,
+    #[msg("Account already uses a versioned layout")]
+    AlreadyMigrated,
+    #[msg("Platform is not registered for a fee share")]
+    PlatformNotRegistered,
+    #[msg("Invalid platform fee share")]
+    InvalidPlatformFeeShare,
+    #[msg("Order total is below the minimum order amount")]
+    OrderBelowMinimum,
+    #[msg("Order total is above the maximum order amount")]
+    OrderAboveMaximum,
+    #[msg("Minimum order amount exceeds the maximum")]
+    InvalidOrderLimits,
+    #[msg("Purchase would exceed the per-buyer limit for this trade")]
+    PurchaseLimitExceeded,
+    #[msg("Trades with a per-buyer limit require the buyer quota account")]
+    MissingBuyerQuota,
+    #[msg("Buyer does not hold the credential required by this trade")]
+    BuyerGateNotSatisfied,
+    #[msg("Attestation expiry must be in the future")]
+    InvalidAttestation,
+    #[msg("The seller does not ship to the buyer's region")]
+    RegionNotServed,
+    #[msg("Buyer region is not certified by the region registrar")]
+    RegionNotVerified,
+    #[msg("The logistics provider has not submitted proof of delivery")]
+    ProofOfDeliveryMissing,
+    #[msg("Two of the buyer, seller and logistics provider must confirm delivery")]
+    ConfirmationQuorumNotMet,
+    #[msg("This party has already confirmed delivery")]
+    ConfirmationAlreadySigned,
+    #[msg("The purchase's product and logistics escrow accounts are required")]
+    MissingEscrowBucket,
+    #[msg("Escrow lending is not enabled")]
+    LendingDisabled,
+    #[msg("The purchase's escrow is deposited with the lending program; withdraw it first")]
+    EscrowLent,
+    #[msg("The purchase's escrow is not deposited with the lending program")]
+    EscrowNotLent,
+    #[msg("The lending program returned less than the deposited principal")]
+    LendingShortfall,
+    #[msg("Invalid lending route")]
+    InvalidLendingRoute,
+    #[msg("The relayed order has expired")]
+    RelayedOrderExpired,
+    #[msg("The relayed order is not signed by the buyer")]
+    InvalidOrderSignature,
+    #[msg("The relayed order's nonce has already been used or is out of order")]
+    InvalidRelayNonce,
+    #[msg("The order total exceeds the amount the buyer signed for")]
+    RelayedOrderPriceAboveCap,
+    #[msg("The relayer's token account is required to collect the relayer fee")]
+    MissingRelayerTokenAccount,
+    #[msg("Orders placed on the buyer's behalf are only accepted for fixed-price listings")]
+    DelegatedOrderNotSupported,
+    #[msg("Invalid spending permit parameters")]
+    InvalidSpendingPermit,
+    #[msg("The spending permit has expired")]
+    SpendingPermitExpired,
+    #[msg("The order exceeds the spending permit's per-order or remaining allowance")]
+    SpendingPermitExceeded,
+    #[msg("Invalid session parameters")]
+    InvalidSession,
+    #[msg("The session has expired")]
+    SessionExpired,
+    #[msg("The purchase exceeds the session's spend cap")]
+    SessionSpendCapExceeded,
+    #[msg("Another settlement of this purchase is in progress")]
+    SettlementInProgress,
+    #[msg("A logistics provider is listed more than once")]
+    DuplicateProvider,
+    #[msg("The seller has paused this logistics provider")]
+    ProviderDisabled,
+    #[msg("The logistics provider has already answered this assignment")]
+    AssignmentAlreadyAnswered,
+    #[msg("The logistics provider declined this purchase")]
+    ProviderDeclined,
+    #[msg("The logistics provider has not declined this purchase")]
+    ProviderNotDeclined,
+    #[msg("The new logistics cost exceeds the buyer's limit")]
+    LogisticsCostAboveLimit,
+    #[msg("Escrow balance does not match the purchase's obligations")]
+    EscrowMismatch,
+    #[msg("Fee splits must be non-zero and sum to 10000 basis points")]
+    InvalidFeeSplits,
+    #[msg("No fee splits are configured")]
+    FeeSplitsNotConfigured,
+    #[msg("The emergency withdrawal timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Program versions may only move forward and must support their minimum client")]
+    InvalidProgramVersion,
+    #[msg("Receipt metadata URI prefix is too long")]
+    ReceiptUriTooLong,
+    #[msg("A receipt has already been minted for this purchase")]
+    ReceiptAlreadyMinted,
+    #[msg("Merkle tree does not match the receipt configuration")]
+    InvalidReceiptTree,
+    #[msg("Invalid loyalty configuration")]
+    InvalidLoyaltyConfig,
+    #[msg("Loyalty points have already been credited for this purchase")]
+    LoyaltyAlreadyAccrued,
+    #[msg("Not enough loyalty points or discount above the maximum")]
+    InvalidLoyaltyRedemption,
+    #[msg("Trade is not listed under a storefront")]
+    TradeNotInStorefront,
+    #[msg("Buyer is purchasing from this trade too quickly")]
+    RateLimited,
+    #[msg("Purchase cooldown must be greater than zero")]
+    InvalidPurchaseCooldown,
+    #[msg("Invalid sealed drop parameters")]
+    InvalidSealedDrop,
+    #[msg("Sealed drop commit window has closed")]
+    CommitWindowClosed,
+    #[msg("Sealed drop reveal window is not open")]
+    RevealWindowNotOpen,
+    #[msg("Revealed quantity does not match the commitment")]
+    InvalidReveal,
+    #[msg("Sealed drop is still revealing or has unclaimed commits")]
+    SealedDropNotEnded,
+    #[msg("Invalid pre-sale allowlist")]
+    InvalidPresale,
+    #[msg("Only allowlisted buyers may purchase before the public sale")]
+    PresaleActive,
+    #[msg("Merkle proof does not include the buyer in the allowlist")]
+    NotAllowlisted,
+    #[msg("Settlement stream needs a positive duration and a trade without payout splits")]
+    InvalidSettlementStream,
+    #[msg("Purchase has no running seller stream")]
+    StreamNotRunning,
+    #[msg("Seller stream has not finished")]
+    StreamActive,
+    #[msg("Milestones must total 10000 basis points, at most 5, each above zero")]
+    InvalidMilestones,
+    #[msg("Milestones cannot change once the trade has sold")]
+    MilestonesLocked,
+    #[msg("Invalid milestone index")]
+    InvalidMilestone,
+    #[msg("Milestone has already been confirmed")]
+    MilestoneAlreadyConfirmed,
+    #[msg("Not allowed once a milestone has been released")]
+    MilestoneReleased,
+    #[msg("Jury size must be odd and at most 7, with a positive stake and voting window")]
+    InvalidJuryConfig,
+    #[msg("Jury pool is full")]
+    JuryPoolFull,
+    #[msg("Already in the jury pool")]
+    AlreadyJuror,
+    #[msg("Not a juror")]
+    NotJuror,
+    #[msg("Juror still sits on undecided disputes")]
+    JurorHasActiveCases,
+    #[msg("Not enough eligible jurors to draw a jury")]
+    NotEnoughJurors,
+    #[msg("A jury is already hearing this dispute")]
+    JuryActive,
+    #[msg("No jury is hearing this dispute")]
+    NoJuryCase,
+    #[msg("Juror has already voted")]
+    AlreadyVoted,
+    #[msg("Jury voting has closed")]
+    VotingClosed,
+    #[msg("Jury voting is still open")]
+    VotingOpen,
+    #[msg("Invalid SlotHashes sysvar")]
+    InvalidSlotHashes,
+    #[msg("Dispute resolution is not final yet")]
+    ResolutionNotFinal,
+    #[msg("Appeal window has closed")]
+    AppealWindowClosed,
+    #[msg("Resolution has already been appealed")]
+    AlreadyAppealed,
+    #[msg("Resolution has not been appealed")]
+    NotAppealed,
+    #[msg("Buyer is not registered")]
+    BuyerNotRegistered,
+    #[msg("Encrypted shipping info is too long")]
+    ShippingInfoTooLong,
+    #[msg("Invalid batch of purchases")]
+    InvalidBatch,
+    #[msg("Pre-order fulfillment must start in the future")]
+    InvalidFulfillmentStart,
+    #[msg("Escrow fee is above the maximum")]
+    InvalidFeeBps,
+    #[msg("Purchases and settlements are paused")]
+    ProgramPaused,
+    #[msg("Provider still has open purchases")]
+    ProviderHasOpenPurchases,
+    #[msg("Account is not the vault named in the emergency withdrawal")]
+    InvalidEmergencySource,
+    #[msg("The mint's escrow vault is required to sign for a purchase escrow")]
+    MissingEscrowVault,
+    #[msg("Logistics cost differs from the provider's published rate")]
+    PublishedRateMismatch,
+}
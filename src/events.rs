@@ -0,0 +1,1195 @@
+//! Program events. Each carries `event_seq`, taken from
+//! `GlobalState::event_seq`, so indexers can detect missed events and
+//! request backfills by sequence number.
+
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[event]
+pub struct TradeCreated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub product_cost: u64,
+    pub total_quantity: u64,
+    pub token_address: Pubkey,
+    pub category: u16,
+    /// Address of the listing's `TradeMetadata`, populated by `set_trade_metadata`.
+    pub metadata: Pubkey,
+}
+
+/// Emitted whenever a storefront's branding or listings change, so indexers
+/// can follow a seller's catalogue without scanning trades.
+#[event]
+pub struct StorefrontUpdated {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub metadata_hash: [u8; 32],
+    /// Trade whose listing changed, if any.
+    pub trade_id: Option<u64>,
+    pub trade_count: u64,
+    pub active_trade_count: u64,
+}
+
+/// An open purchase's sub-vaults hold a different balance than its escrow
+/// records say they should, pointing at an accounting bug.
+#[event]
+pub struct EscrowInvariantViolated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub purchase_id: u64,
+    pub expected: u64,
+    pub held: u64,
+}
+
+#[event]
+pub struct EscrowAudited {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    /// Open purchases whose escrow was checked.
+    pub purchases: u32,
+    pub expected: u64,
+    pub held: u64,
+}
+
+#[event]
+pub struct PurchaseCooldownUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub cooldown_slots: Option<u64>,
+}
+
+#[event]
+pub struct PresaleAllowlistUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub presale: Option<PresaleAllowlist>,
+}
+
+#[event]
+pub struct SettlementStreamUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub duration_seconds: Option<i64>,
+}
+
+#[event]
+pub struct SellerStreamStarted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub duration_seconds: i64,
+}
+
+#[event]
+pub struct SellerStreamReleased {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub seller: Pubkey,
+    pub amount: u64,
+    /// Total released so far.
+    pub released: u64,
+}
+
+#[event]
+pub struct SellerStreamHalted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub vested: u64,
+    /// Unvested amount under dispute.
+    pub disputed_amount: u64,
+}
+
+#[event]
+pub struct StreamDisputeResolved {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub winner: Pubkey,
+    /// Unvested amount refunded to the buyer; zero when the stream resumes.
+    pub refunded: u64,
+}
+
+#[event]
+pub struct TradeMilestonesUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub milestones: Vec<Milestone>,
+}
+
+#[event]
+pub struct MilestoneConfirmed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub index: u8,
+    pub seller_amount: u64,
+    /// Total released to the seller through milestones so far.
+    pub seller_released: u64,
+}
+
+#[event]
+pub struct LowStockThresholdUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub threshold: Option<u64>,
+}
+
+/// Emitted by `buy_trade` when a purchase leaves the trade at or below its
+/// low-stock threshold, for restock workflows to pick up.
+#[event]
+pub struct LowStock {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub remaining: u64,
+}
+
+/// Lets watchers notify buyers who wishlisted a listing that it is back in stock.
+#[event]
+pub struct TradeRestocked {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub additional_quantity: u64,
+    pub remaining_quantity: u64,
+    /// Whether the trade had sold out and is buyable again.
+    pub reactivated: bool,
+}
+
+#[event]
+pub struct TradeQuantityModeSet {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub unlimited: bool,
+    pub active: bool,
+}
+
+#[event]
+pub struct TradePreorderSet {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub preorder: bool,
+    pub fulfillment_start: i64,
+}
+
+#[event]
+pub struct TradeMetadataUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+}
+
+#[event]
+pub struct PurchaseCreated {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub token_mint: Pubkey,
+    pub memo_hash: Option<[u8; 64]>,
+}
+
+#[event]
+pub struct DeliveriesBatchConfirmed {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub purchases: u8,
+}
+
+#[event]
+pub struct PaymentHeld {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub total_amount: u64,
+}
+
+#[event]
+pub struct PurchaseCompletedAndConfirmed {
+    pub schema_version: u8,
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub logistics_provider: Pubkey,
+    pub token_mint: Pubkey,
+    pub seller_amount: u64,
+    pub logistics_amount: u64,
+    pub fee_amount: u64,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseCancelled {
+    pub schema_version: u8,
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub token_mint: Pubkey,
+    pub refund_amount: u64,
+    pub restocking_fee: u64,
+    pub reason: CancelReason,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    pub start_price: u64,
+    pub min_increment: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub bidder: Pubkey,
+    pub unit_bid: u64,
+    pub logistics_provider: Pubkey,
+    pub escrowed_amount: u64,
+}
+
+#[event]
+pub struct AuctionFinalized {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub winner: Option<Pubkey>,
+    pub purchase_id: Option<u64>,
+    pub unit_price: u64,
+}
+
+#[event]
+pub struct SealedDropCreated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    pub unit_price: u64,
+    pub max_quantity_per_commit: u64,
+    pub commit_end: i64,
+    pub reveal_end: i64,
+}
+
+#[event]
+pub struct PurchaseCommitted {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub escrowed_amount: u64,
+}
+
+#[event]
+pub struct PurchaseRevealed {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+}
+
+#[event]
+pub struct SealedPurchaseClaimed {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub revealed_quantity: u64,
+    pub allocated_quantity: u64,
+    pub purchase_id: Option<u64>,
+    pub refund_amount: u64,
+}
+
+#[event]
+pub struct SealedDropClosed {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub allocated_quantity: u64,
+    pub returned_quantity: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub event_seq: u64,
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct ConfirmationDelegateUpdated {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub delegate: Option<Pubkey>,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub logistics_provider: Pubkey,
+    pub interval_seconds: i64,
+    pub cycles: u32,
+    pub max_cycle_amount: u64,
+}
+
+#[event]
+pub struct SubscriptionCycleProcessed {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub purchase_id: u64,
+    pub cycle: u32,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub cycles_completed: u32,
+}
+
+#[event]
+pub struct OfferMade {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub unit_price: u64,
+    pub quantity: u64,
+    pub logistics_provider: Pubkey,
+    pub escrowed_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub purchase_id: u64,
+    pub unit_price: u64,
+}
+
+#[event]
+pub struct OfferWithdrawn {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub refund_amount: u64,
+}
+
+#[event]
+pub struct ShippingInfoPosted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub logistics_provider: Pubkey,
+}
+
+#[event]
+pub struct ShippingInfoClosed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+}
+
+#[event]
+pub struct QuantityReserved {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ReservationReleased {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+}
+
+#[event]
+pub struct CrankConfigUpdated {
+    pub event_seq: u64,
+    pub auto_confirm_seconds: i64,
+    pub ship_timeout_seconds: i64,
+    pub crank_reward_bps: u16,
+}
+
+#[event]
+pub struct AllowedMintAdded {
+    pub event_seq: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct AllowedMintRemoved {
+    pub event_seq: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct EscrowVaultCreated {
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub escrow_vault: Pubkey,
+}
+
+#[event]
+pub struct OrderLimitsUpdated {
+    pub event_seq: u64,
+    pub min_order_amount: u64,
+    pub max_order_amount: u64,
+}
+
+#[event]
+pub struct TradeOrderLimitsUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    /// `None` when the trade falls back to the platform-wide limits.
+    pub limits: Option<OrderLimits>,
+}
+
+#[event]
+pub struct TradeBuyerGateUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub gate: Option<BuyerGate>,
+}
+
+#[event]
+pub struct ProviderStatusUpdated {
+    pub event_seq: u64,
+    pub trade_id: u64,
+    pub logistics_provider: Pubkey,
+    pub index: u8,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct AttestationIssued {
+    pub event_seq: u64,
+    pub verifier: Pubkey,
+    pub subject: Pubkey,
+    pub expires_at: i64,
+    pub region: Option<DeliveryRegion>,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub event_seq: u64,
+    pub verifier: Pubkey,
+    pub subject: Pubkey,
+}
+
+#[event]
+pub struct RegionRegistrarUpdated {
+    pub event_seq: u64,
+    pub region_registrar: Pubkey,
+}
+
+#[event]
+pub struct PlatformFeeShareUpdated {
+    pub event_seq: u64,
+    pub platform: Pubkey,
+    pub fee_share_bps: u16,
+}
+
+#[event]
+pub struct CrankRewardPaid {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub cranker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscalationPolicyUpdated {
+    pub event_seq: u64,
+    pub escalation_window_seconds: i64,
+    pub escalation_fee_bps: u16,
+}
+
+#[event]
+pub struct DisputeRuled {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub arbitrator: Pubkey,
+    pub winner: Pubkey,
+    pub slash_provider: bool,
+    pub escalation_deadline: i64,
+}
+
+#[event]
+pub struct DisputeRulingSkipped {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub arbitrator: Pubkey,
+    /// Program error code the single-purchase ruling would have failed with.
+    pub error_code: u32,
+}
+
+#[event]
+pub struct DisputesBatchRuled {
+    pub event_seq: u64,
+    pub arbitrator: Pubkey,
+    pub token_mint: Pubkey,
+    pub ruled: u8,
+    pub skipped: u8,
+}
+
+#[event]
+pub struct ResolutionAppealed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub appellant: Pubkey,
+    pub appeal_bond: u64,
+}
+
+#[event]
+pub struct AppealReviewed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub upheld: bool,
+    /// Winner of the resolution when upheld, otherwise the appellant.
+    pub bond_recipient: Pubkey,
+    pub appeal_bond: u64,
+}
+
+#[event]
+pub struct JuryConfigUpdated {
+    pub event_seq: u64,
+    pub config: JuryConfig,
+}
+
+#[event]
+pub struct JurorJoined {
+    pub event_seq: u64,
+    pub juror: Pubkey,
+    pub stake: u64,
+}
+
+#[event]
+pub struct JurorLeft {
+    pub event_seq: u64,
+    pub juror: Pubkey,
+    /// Stake returned, after any slashing.
+    pub stake: u64,
+}
+
+#[event]
+pub struct JurySummoned {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub summoned_by: Pubkey,
+    pub jurors: Vec<Pubkey>,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct JuryVoteCast {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub juror: Pubkey,
+    pub winner: Pubkey,
+}
+
+#[event]
+pub struct JuryVerdict {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    /// `None` when no winner won a majority of the votes cast.
+    pub winner: Option<Pubkey>,
+    pub votes_for_winner: u8,
+    pub votes_cast: u8,
+    pub stake_slashed: u64,
+}
+
+#[event]
+pub struct DisputeEscalated {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub escalated_by: Pubkey,
+    pub escalation_fee: u64,
+}
+
+#[event]
+pub struct EscalationFeeReleased {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CancellationPolicyUpdated {
+    pub event_seq: u64,
+    pub cancellation_window_seconds: i64,
+    pub restocking_fee_bps: u16,
+}
+
+#[event]
+pub struct EscrowFeeUpdated {
+    pub event_seq: u64,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct PauseSet {
+    pub event_seq: u64,
+    pub paused: bool,
+}
+
+#[event]
+pub struct ShipmentsConsolidated {
+    pub event_seq: u64,
+    pub shipment: Pubkey,
+    pub provider: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub purchase_ids: Vec<u64>,
+    pub logistics_cost: u64,
+    pub refund_amount: u64,
+}
+
+#[event]
+pub struct PayoutDeferred {
+    pub event_seq: u64,
+    pub recipient: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PayoutClaimed {
+    pub event_seq: u64,
+    pub recipient: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PurchaseClosed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub rent_recipient: Pubkey,
+}
+
+#[event]
+pub struct OrderIndexShardCreated {
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub shard_no: u32,
+}
+
+#[event]
+pub struct OrderIndexPageAdded {
+    pub event_seq: u64,
+    pub user: Pubkey,
+    pub shard_no: u32,
+    pub capacity: u32,
+}
+
+#[event]
+pub struct CategoryIndexShardCreated {
+    pub event_seq: u64,
+    pub category: u16,
+    pub shard_no: u32,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub initiator: Pubkey,
+    pub reason: DisputeReason,
+}
+
+#[event]
+pub struct PurchaseShipped {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub shipped_by: Pubkey,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseCancelledBySeller {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub token_mint: Pubkey,
+    pub refund_amount: u64,
+    pub cancellations: u32,
+    pub reason: CancelReason,
+}
+
+#[event]
+pub struct PartialDeliveryConfirmed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub quantity_received: u64,
+    pub remaining_quantity: u64,
+    pub seller_amount: u64,
+    pub logistics_amount: u64,
+    pub fee_amount: u64,
+}
+
+#[event]
+pub struct ReturnsReserveFunded {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReturnsReserveWithdrawn {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReturnRequested {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub logistics_provider: Pubkey,
+}
+
+#[event]
+pub struct ReturnApproved {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub refund_amount: u64,
+    pub logistics_cost: u64,
+}
+
+#[event]
+pub struct ReturnCompleted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub refund_amount: u64,
+    pub logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+}
+
+#[event]
+pub struct TrackingRecorded {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub logistics_provider: Pubkey,
+    pub carrier_code: u16,
+    pub tracking_hash: [u8; 32],
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct ProofOfDeliverySubmitted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub logistics_provider: Pubkey,
+    pub pod_hash: [u8; 32],
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct ProviderAssignmentAnswered {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub logistics_provider: Pubkey,
+    pub accepted: bool,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct LogisticsReassigned {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub previous_provider: Pubkey,
+    pub logistics_provider: Pubkey,
+    pub previous_cost: u64,
+    pub logistics_cost: u64,
+}
+
+#[event]
+pub struct ConfirmationSigned {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub signer: Pubkey,
+    pub party: ConfirmingParty,
+    pub confirmations: u8,
+}
+
+#[event]
+pub struct PurchaseInsured {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub token_mint: Pubkey,
+    pub premium: u64,
+}
+
+#[event]
+pub struct PlatformOrderRouted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub platform: Pubkey,
+    pub fee_share_bps: u16,
+}
+
+#[event]
+pub struct PlatformFeeCredited {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub platform: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InsuranceClaimed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub schema_version: u8,
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub winner: Pubkey,
+    pub reason: DisputeReason,
+    pub token_mint: Pubkey,
+    pub buyer_refund_amount: u64,
+    pub seller_amount: u64,
+    pub logistics_amount: u64,
+    pub fee_amount: u64,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct LogisticsProviderRegistered {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+}
+
+#[event]
+pub struct PayoutPreferenceUpdated {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub preferred_mint: Option<Pubkey>,
+    pub max_slippage_bps: u16,
+}
+
+#[event]
+pub struct SellerPayoutAccountUpdated {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub payout_token_account: Option<Pubkey>,
+}
+
+#[event]
+pub struct ProviderPayoutAccountUpdated {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+    pub payout_token_account: Option<Pubkey>,
+}
+
+#[event]
+pub struct SellerProceedsSwapped {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub seller: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[event]
+pub struct StakeConfigUpdated {
+    pub event_seq: u64,
+    pub stake_mint: Pubkey,
+    pub provider_stake_amount: u64,
+    pub slash_bps: u16,
+    pub stake_cooldown_seconds: i64,
+}
+
+#[event]
+pub struct LogisticsProviderDeregistered {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+    pub unlocks_at: i64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProviderSlashed {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+    pub purchase_id: u64,
+    pub amount: u64,
+    pub remaining_stake: u64,
+}
+
+#[event]
+pub struct SellerBondPosted {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub bond_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SellerBondSlashed {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub purchase_id: u64,
+    pub amount: u64,
+    pub remaining_bond: u64,
+}
+
+#[event]
+pub struct SellerBondReleased {
+    pub event_seq: u64,
+    pub seller: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProviderProfileUpdated {
+    pub event_seq: u64,
+    pub provider: Pubkey,
+    pub regions: u16,
+    pub base_price: u64,
+    pub price_per_kg: u64,
+}
+
+/// Escrow fee a purchase would have paid on settlement, forgone because the
+/// escrow went back to the buyer.
+#[event]
+pub struct FeeReversed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub token_mint: Pubkey,
+    pub fee_amount: u64,
+    pub unix_timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseRelayed {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub relayer: Pubkey,
+    pub nonce: u64,
+    pub relayer_fee: u64,
+}
+
+#[event]
+pub struct LendingConfigUpdated {
+    pub event_seq: u64,
+    pub lending_program: Pubkey,
+    pub yield_buyer_share_bps: u16,
+}
+
+#[event]
+pub struct EscrowDeposited {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub lending_program: Pubkey,
+    pub principal: u64,
+    pub receipt_amount: u64,
+}
+
+#[event]
+pub struct EscrowWithdrawn {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub principal: u64,
+    pub yield_amount: u64,
+    pub buyer_yield: u64,
+    pub platform_yield: u64,
+}
+
+#[event]
+pub struct SpendingPermitCreated {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub delegate: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub max_total_amount: u64,
+    pub max_order_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SpendingPermitRevoked {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub delegate: Pubkey,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct PurchaseViaPermit {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at_slot: u64,
+    pub spend_cap: u64,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub session_key: Pubkey,
+    pub spent: u64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LoyaltyConfigUpdated {
+    pub event_seq: u64,
+    pub points_bps: u16,
+    pub points_per_discount_bps: u64,
+    pub max_discount_bps: u16,
+}
+
+#[event]
+pub struct LoyaltyPointsAccrued {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub points: u64,
+}
+
+#[event]
+pub struct LoyaltyPointsRedeemed {
+    pub event_seq: u64,
+    pub buyer: Pubkey,
+    pub points: u64,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct LoyaltyDiscountApplied {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct ReceiptConfigUpdated {
+    pub event_seq: u64,
+    pub merkle_tree: Pubkey,
+    pub uri_prefix: String,
+}
+
+#[event]
+pub struct PurchaseReceiptMinted {
+    pub event_seq: u64,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    pub merkle_tree: Pubkey,
+}
+
+#[event]
+pub struct ProgramVersionUpdated {
+    pub event_seq: u64,
+    pub program_version: SemVer,
+    pub min_client_version: SemVer,
+}
+
+#[event]
+pub struct EmergencyWithdrawalProposed {
+    pub event_seq: u64,
+    pub token_mint: Pubkey,
+    pub source: EmergencyVault,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawalExecuted {
+    pub event_seq: u64,
+    pub token_mint: Pubkey,
+    pub source: EmergencyVault,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EmergencyWithdrawalCancelled {
+    pub event_seq: u64,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct GovernanceAuthorityUpdated {
+    pub event_seq: u64,
+    pub governance_authority: Pubkey,
+}
+
+#[event]
+pub struct FeeSplitsUpdated {
+    pub event_seq: u64,
+    pub splits: Vec<PayoutSplit>,
+}
+
+#[event]
+pub struct FeesDistributed {
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// Error types
@@ -0,0 +1,1221 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use anchor_spl::token::{self, spl_token, Token, TokenAccount, Transfer};
+use pyth_sdk_solana::state::SolanaPriceAccount;
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::pda;
+use crate::state::*;
+
+// Settlement helpers
+
+/// Logistics cost attributable to `quantity_received` of `quantity` units,
+/// rounded down so the final confirmation settles any remainder.
+pub fn compute_partial_logistics_cost(
+    logistics_cost: u64,
+    quantity_received: u64,
+    quantity: u64,
+) -> u64 {
+    if quantity == 0 {
+        return 0;
+    }
+    ((logistics_cost as u128 * quantity_received.min(quantity) as u128) / quantity as u128) as u64
+}
+
+/// Restocking fee charged on a late buyer cancellation, rounded down.
+pub fn compute_restocking_fee(product_amount: u64, restocking_fee_bps: u16) -> u64 {
+    let fee_bps = (restocking_fee_bps as u64).min(BASIS_POINTS);
+    ((product_amount as u128 * fee_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Share of `fee_amount` paid to whoever runs a settlement crank.
+pub fn compute_crank_reward(fee_amount: u64, crank_reward_bps: u16) -> u64 {
+    let reward_bps = (crank_reward_bps as u64).min(BASIS_POINTS);
+    ((fee_amount as u128 * reward_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Buyer's share of the yield earned on deposited escrow, rounded down so any
+/// dust goes to the platform.
+pub fn compute_buyer_yield(yield_amount: u64, yield_buyer_share_bps: u16) -> u64 {
+    let share_bps = (yield_buyer_share_bps as u64).min(BASIS_POINTS);
+    ((yield_amount as u128 * share_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Which party `signer` confirms as. Anyone other than the seller or the
+/// logistics provider is taken to be the buyer or their confirmation
+/// delegate, so callers must authorize the signer first.
+pub fn confirming_party(
+    signer: &Pubkey,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    logistics_provider: &Pubkey,
+) -> ConfirmingParty {
+    if signer == buyer {
+        ConfirmingParty::Buyer
+    } else if signer == seller {
+        ConfirmingParty::Seller
+    } else if signer == logistics_provider {
+        ConfirmingParty::LogisticsProvider
+    } else {
+        ConfirmingParty::Buyer
+    }
+}
+
+/// Checks that `buyer` presents the credential `gate` requires: a token
+/// account holding the gate mint, or a valid attestation from its verifier.
+pub(crate) fn check_buyer_gate(
+    gate: &BuyerGate,
+    buyer: &Pubkey,
+    gate_token_account: Option<&TokenAccount>,
+    attestation: Option<&BuyerAttestation>,
+    now: i64,
+) -> Result<()> {
+    let satisfied = match gate {
+        BuyerGate::TokenHolder { mint } => gate_token_account.is_some_and(|account| {
+            account.owner == *buyer && account.mint == *mint && account.amount > 0
+        }),
+        BuyerGate::Attestation { verifier } => attestation.is_some_and(|attestation| {
+            attestation.subject == *buyer
+                && attestation.verifier == *verifier
+                && attestation.is_valid(now)
+        }),
+    };
+    require!(satisfied, LogisticsError::BuyerGateNotSatisfied);
+    Ok(())
+}
+
+/// Checks that `trade_account` ships to the buyer's claimed `region`. Once a
+/// region registrar is configured, restricted trades also require the buyer's
+/// unexpired attestation from it certifying that region.
+pub(crate) fn check_buyer_region(
+    trade_account: &TradeAccount,
+    region: DeliveryRegion,
+    region_registrar: &Pubkey,
+    attestation: Option<&BuyerAttestation>,
+    now: i64,
+) -> Result<()> {
+    require!(
+        trade_account.ships_to(region),
+        LogisticsError::RegionNotServed
+    );
+    if trade_account.ship_to_regions == 0 || *region_registrar == Pubkey::default() {
+        return Ok(());
+    }
+    let verified = attestation.is_some_and(|attestation| {
+        attestation.verifier == *region_registrar
+            && attestation.region == Some(region)
+            && attestation.is_valid(now)
+    });
+    require!(verified, LogisticsError::RegionNotVerified);
+    Ok(())
+}
+
+/// Share of `fee_amount` owed to the platform that referred a purchase.
+pub fn compute_platform_share(fee_amount: u64, fee_share_bps: u16) -> u64 {
+    let share_bps = (fee_share_bps as u64).min(BASIS_POINTS);
+    ((fee_amount as u128 * share_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Credits the platform that referred `purchase_account` with its share of
+/// `fee_amount`, which must already sit in the mint's escrow vault, and returns
+/// the amount credited. Direct purchases, and shares that round down to zero,
+/// are skipped.
+pub(crate) fn credit_platform_share<'info>(
+    global_state: &mut GlobalState,
+    token_program: &AccountInfo<'info>,
+    escrow_token_account: &AccountInfo<'info>,
+    platform_claimable: Option<&mut Account<'info, ClaimableBalance>>,
+    purchase_account: &PurchaseAccount,
+    fee_amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<u64> {
+    let Some(referral) = purchase_account.platform_referral() else {
+        return Ok(0);
+    };
+    let amount = compute_platform_share(fee_amount, referral.fee_share_bps);
+    if amount == 0 {
+        return Ok(0);
+    }
+    transfer_or_credit(
+        global_state,
+        token_program,
+        escrow_token_account,
+        escrow_token_account,
+        None,
+        platform_claimable,
+        referral.platform,
+        purchase_account.token_mint,
+        amount,
+        signer,
+    )?;
+
+    emit!(PlatformFeeCredited {
+        event_seq: global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        platform: referral.platform,
+        token_mint: purchase_account.token_mint,
+        amount,
+    });
+    Ok(amount)
+}
+
+/// Pays a crank caller out of the escrow vault. Callers that pass no token
+/// account, or cranks that earn nothing, are skipped.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_crank_reward<'info>(
+    global_state: &mut GlobalState,
+    token_program: &Program<'info, Token>,
+    escrow_token_account: &Account<'info, TokenAccount>,
+    cranker_token_account: Option<&Account<'info, TokenAccount>>,
+    reward: u64,
+    purchase_id: u64,
+    cranker: Pubkey,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let Some(cranker_token_account) = cranker_token_account else {
+        return Ok(());
+    };
+    if reward == 0 {
+        return Ok(());
+    }
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        Transfer {
+            from: escrow_token_account.to_account_info(),
+            to: cranker_token_account.to_account_info(),
+            authority: escrow_token_account.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, reward)?;
+
+    emit!(CrankRewardPaid {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        cranker,
+        amount: reward,
+    });
+    Ok(())
+}
+
+/// Fee escrowed by the party escalating a first-tier ruling, rounded down.
+pub fn compute_escalation_fee(total_amount: u64, escalation_fee_bps: u16) -> u64 {
+    let fee_bps = (escalation_fee_bps as u64).min(BASIS_POINTS);
+    ((total_amount as u128 * fee_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Insurance premium for `product_amount` at `insurance_bps`, rounded up so
+/// small purchases still pay for their cover.
+pub fn compute_insurance_premium(product_amount: u64, insurance_bps: u16) -> Result<u64> {
+    let basis_points = BASIS_POINTS as u128;
+    let premium = (product_amount as u128)
+        .checked_mul(insurance_bps as u128)
+        .and_then(|scaled| scaled.checked_add(basis_points - 1))
+        .ok_or(LogisticsError::MathOverflow)?
+        / basis_points;
+    u64::try_from(premium).map_err(|_| error!(LogisticsError::MathOverflow))
+}
+
+/// Splits `amount` across the trade's payout splits by basis points. Rounding
+/// dust is credited to the first recipient so the shares always sum to `amount`.
+pub fn compute_split_amounts(amount: u64, splits: &[PayoutSplit]) -> Vec<u64> {
+    let mut shares: Vec<u64> = splits
+        .iter()
+        .map(|split| ((amount as u128 * split.bps as u128) / BASIS_POINTS as u128) as u64)
+        .collect();
+    let distributed: u64 = shares.iter().sum();
+    if let Some(first) = shares.first_mut() {
+        *first += amount - distributed;
+    }
+    shares
+}
+
+/// Escrow fee the unsettled remainder of a purchase would pay on settlement.
+/// Refunding the full escrow to the buyer reverses it.
+pub fn compute_fee_reversal(purchase_account: &PurchaseAccount) -> Result<u64> {
+    Ok(purchase_account.settlement_payouts()?.fee_amount)
+}
+
+/// Loyalty points earned on `volume` settled token units at `points_bps`.
+pub fn compute_loyalty_points(volume: u64, points_bps: u16) -> u64 {
+    ((volume as u128 * points_bps as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Commitment a buyer stores with `commit_purchase` and opens with
+/// `reveal_purchase`. Binding the buyer stops others replaying a revealed
+/// commitment.
+pub fn sealed_commitment(buyer: &Pubkey, quantity: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[buyer.as_ref(), &quantity.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Leaf of `buyer` in a trade's pre-sale allowlist tree.
+pub fn allowlist_leaf(buyer: &Pubkey) -> [u8; 32] {
+    hashv(&[b"allowlist_leaf", buyer.as_ref()]).to_bytes()
+}
+
+/// Parent of two allowlist tree nodes. Pairs are hashed in sorted order, so
+/// proofs need no left/right flags.
+pub fn hash_allowlist_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[first, second]).to_bytes()
+}
+
+/// Hash of the most recent slot in the SlotHashes sysvar. Serialized as a u64
+/// entry count followed by (slot, hash) pairs, newest first.
+pub(crate) fn recent_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(
+        data.get(16..48)
+            .ok_or_else(|| error!(LogisticsError::InvalidSlotHashes))?,
+    );
+    Ok(hash)
+}
+
+/// Draws `count` distinct jurors from `candidates` with a partial
+/// Fisher-Yates shuffle driven by `seed`. A recent slot hash is only
+/// pseudo-random: the leader of that slot could bias it, which is acceptable
+/// for picking among staked jurors but not for anything of greater value.
+pub fn draw_jurors(candidates: &[Pubkey], seed: &[u8; 32], count: usize) -> Vec<Pubkey> {
+    let mut pool = candidates.to_vec();
+    let count = count.min(pool.len());
+    for i in 0..count {
+        let draw = hashv(&[seed.as_ref(), &(i as u64).to_le_bytes()]).to_bytes();
+        let mut roll = [0u8; 8];
+        roll.copy_from_slice(&draw[..8]);
+        let j = i + (u64::from_le_bytes(roll) % (pool.len() - i) as u64) as usize;
+        pool.swap(i, j);
+    }
+    pool.truncate(count);
+    pool
+}
+
+/// Logistics cost for `quantity` units. Flat-priced trades (`weight_grams == 0`)
+/// charge `base_cost` per unit; weight-priced trades charge `base_cost` once plus
+/// `rate_per_kg` for the shipment's total weight, rounded up to the next token unit.
+pub fn compute_logistics_cost(
+    base_cost: u64,
+    rate_per_kg: u64,
+    weight_grams: u64,
+    quantity: u64,
+) -> Result<u64> {
+    if weight_grams == 0 {
+        return base_cost
+            .checked_mul(quantity)
+            .ok_or_else(|| error!(LogisticsError::MathOverflow));
+    }
+
+    let weighted = (weight_grams as u128)
+        .checked_mul(rate_per_kg as u128)
+        .and_then(|value| value.checked_mul(quantity as u128))
+        .ok_or_else(|| error!(LogisticsError::MathOverflow))?;
+    let weight_cost = weighted.div_ceil(1000);
+    u64::try_from(weight_cost)
+        .ok()
+        .and_then(|cost| cost.checked_add(base_cost))
+        .ok_or_else(|| error!(LogisticsError::MathOverflow))
+}
+
+/// Fills a listing's zero `given` cost or rate with the provider's `published`
+/// one, and rejects any other value that differs from it.
+pub fn take_published_rate(given: &mut u64, published: u64) -> Result<()> {
+    if *given == 0 {
+        *given = published;
+    }
+    require!(*given == published, LogisticsError::PublishedRateMismatch);
+    Ok(())
+}
+
+/// Rejects a provider list naming the same provider twice, which would leave
+/// two prices for one carrier. Lists are short, so a pairwise check is enough.
+pub fn check_unique_providers(providers: &[Pubkey]) -> Result<()> {
+    for (i, provider) in providers.iter().enumerate() {
+        require!(
+            !providers[..i].contains(provider),
+            LogisticsError::DuplicateProvider
+        );
+    }
+    Ok(())
+}
+
+/// Transfers `resolve_dispute` performs for `winner`. A buyer win refunds the
+/// full escrowed amount; any other valid winner settles the purchase as if it
+/// had been confirmed, with the seller's share fanned out across payout splits.
+pub fn compute_resolution_preview(
+    trade_account: &TradeAccount,
+    purchase_account: &PurchaseAccount,
+    winner: Pubkey,
+) -> Result<ResolutionPreview> {
+    let valid_winner = winner == purchase_account.buyer
+        || winner == trade_account.seller
+        || winner == purchase_account.chosen_logistics_provider;
+    require!(valid_winner, LogisticsError::InvalidWinner);
+
+    let mut transfers = Vec::new();
+    if winner == purchase_account.buyer {
+        transfers.push(PlannedTransfer {
+            recipient: purchase_account.buyer,
+            amount: purchase_account.total_amount,
+        });
+    } else {
+        let amounts = purchase_account.settlement_payouts()?;
+
+        if trade_account.payout_splits.is_empty() {
+            transfers.push(PlannedTransfer {
+                recipient: trade_account.seller,
+                amount: amounts.seller_amount,
+            });
+        } else {
+            let shares = compute_split_amounts(amounts.seller_amount, &trade_account.payout_splits);
+            for (split, amount) in trade_account.payout_splits.iter().zip(shares) {
+                transfers.push(PlannedTransfer {
+                    recipient: split.recipient,
+                    amount,
+                });
+            }
+        }
+
+        transfers.push(PlannedTransfer {
+            recipient: purchase_account.chosen_logistics_provider,
+            amount: amounts.logistics_amount,
+        });
+    }
+
+    let paid_out: u64 = transfers.iter().map(|transfer| transfer.amount).sum();
+    Ok(ResolutionPreview {
+        purchase_id: purchase_account.purchase_id,
+        winner,
+        token_mint: purchase_account.token_mint,
+        transfers,
+        fee_amount: purchase_account.total_amount - paid_out,
+    })
+}
+
+/// Moves `slash_bps` of a provider's remaining stake to the buyer as compensation
+/// for a dispute the provider lost.
+pub(crate) fn slash_provider_stake<'info>(
+    global_state: &mut GlobalState,
+    provider_account: Option<&mut LogisticsProviderAccount>,
+    stake_vault: Option<&Account<'info, TokenAccount>>,
+    buyer_stake_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: &Program<'info, Token>,
+    purchase_id: u64,
+) -> Result<()> {
+    let (Some(provider_account), Some(stake_vault), Some(buyer_stake_token_account)) =
+        (provider_account, stake_vault, buyer_stake_token_account)
+    else {
+        return err!(LogisticsError::MissingStakeAccounts);
+    };
+
+    let slash_amount = ((provider_account.staked_amount as u128 * global_state.slash_bps as u128)
+        / BASIS_POINTS as u128) as u64;
+    if slash_amount == 0 {
+        return Ok(());
+    }
+    provider_account.staked_amount -= slash_amount;
+
+    let provider_key = provider_account.provider;
+    let seeds = &[
+        pda::STAKE_VAULT_SEED,
+        provider_key.as_ref(),
+        &[provider_account.stake_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        Transfer {
+            from: stake_vault.to_account_info(),
+            to: buyer_stake_token_account.to_account_info(),
+            authority: stake_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, slash_amount)?;
+
+    emit!(ProviderSlashed {
+        event_seq: global_state.next_event_seq(),
+        provider: provider_key,
+        purchase_id,
+        amount: slash_amount,
+        remaining_stake: provider_account.staked_amount,
+    });
+    Ok(())
+}
+
+/// Decodes the seller account PDA passed unchecked as `info`, or `None` when
+/// the seller never registered. Handlers the seller could otherwise run
+/// without their account take it this way, so it is always passed.
+pub(crate) fn load_seller_account(info: &AccountInfo) -> Result<Option<SellerAccount>> {
+    if *info.owner != crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let seller_account = SellerAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    Ok(Some(seller_account))
+}
+
+/// Writes a seller account decoded with `load_seller_account` back in place.
+pub(crate) fn store_seller_account(
+    info: &AccountInfo,
+    seller_account: &SellerAccount,
+) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data[..];
+    seller_account.try_serialize(&mut writer)
+}
+
+/// Decodes the account PDA of `provider` passed unchecked as `info`, or `None`
+/// when the provider never registered. Every path that opens or settles a
+/// purchase takes it this way so the provider's open purchases stay counted.
+pub(crate) fn load_provider_account(
+    info: &AccountInfo,
+    provider: &Pubkey,
+) -> Result<Option<LogisticsProviderAccount>> {
+    require_keys_eq!(
+        info.key(),
+        pda::logistics_provider_pda(provider).0,
+        LogisticsError::InvalidLogisticsProvider
+    );
+    if *info.owner != crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let provider_account =
+        LogisticsProviderAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    Ok(Some(provider_account))
+}
+
+/// Writes a provider account decoded with `load_provider_account` back in place.
+pub(crate) fn store_provider_account(
+    info: &AccountInfo,
+    provider_account: &LogisticsProviderAccount,
+) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data[..];
+    provider_account.try_serialize(&mut writer)
+}
+
+/// Counts a purchase opened for `provider`. A provider who has deregistered
+/// takes no new purchases, so their count can drain before the stake is
+/// withdrawn.
+pub(crate) fn count_provider_purchase_opened(info: &AccountInfo, provider: &Pubkey) -> Result<()> {
+    let Some(mut provider_account) = load_provider_account(info, provider)? else {
+        return Ok(());
+    };
+    require!(
+        provider_account.is_registered,
+        LogisticsError::ProviderNotRegistered
+    );
+    provider_account.open_purchases += 1;
+    store_provider_account(info, &provider_account)
+}
+
+/// Releases `count` purchases of `provider` that settled or were cancelled,
+/// returning the provider's account for the payout that follows.
+pub(crate) fn count_provider_purchases_closed(
+    info: &AccountInfo,
+    provider: &Pubkey,
+    count: u32,
+) -> Result<Option<LogisticsProviderAccount>> {
+    let Some(mut provider_account) = load_provider_account(info, provider)? else {
+        return Ok(None);
+    };
+    // Purchases opened before the provider registered were never counted
+    provider_account.open_purchases = provider_account.open_purchases.saturating_sub(count);
+    store_provider_account(info, &provider_account)?;
+    Ok(Some(provider_account))
+}
+
+/// Counts a lost dispute against the seller. Once losses exceed the configured
+/// threshold, `seller_bond_slash_bps` of the remaining bond compensates the buyer.
+pub(crate) fn record_seller_dispute_loss<'info>(
+    global_state: &mut GlobalState,
+    seller_account: &mut SellerAccount,
+    bond_vault: Option<&Account<'info, TokenAccount>>,
+    buyer_bond_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: &Program<'info, Token>,
+    purchase_id: u64,
+) -> Result<()> {
+    seller_account.disputes_lost += 1;
+    if seller_account.disputes_lost <= global_state.seller_loss_threshold
+        || seller_account.bond_amount == 0
+    {
+        return Ok(());
+    }
+
+    let (Some(bond_vault), Some(buyer_bond_token_account)) = (bond_vault, buyer_bond_token_account)
+    else {
+        return err!(LogisticsError::MissingBondAccounts);
+    };
+
+    let slash_amount = ((seller_account.bond_amount as u128
+        * global_state.seller_bond_slash_bps as u128)
+        / BASIS_POINTS as u128) as u64;
+    if slash_amount == 0 {
+        return Ok(());
+    }
+    seller_account.bond_amount -= slash_amount;
+
+    let seller_key = seller_account.seller;
+    let seeds = &[
+        pda::SELLER_BOND_SEED,
+        seller_key.as_ref(),
+        &[seller_account.bond_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        Transfer {
+            from: bond_vault.to_account_info(),
+            to: buyer_bond_token_account.to_account_info(),
+            authority: bond_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, slash_amount)?;
+
+    emit!(SellerBondSlashed {
+        event_seq: global_state.next_event_seq(),
+        seller: seller_key,
+        purchase_id,
+        amount: slash_amount,
+        remaining_bond: seller_account.bond_amount,
+    });
+    Ok(())
+}
+
+/// Marks a purchase as settled in whichever order index shards were supplied.
+pub(crate) fn mark_order_settled(
+    buyer_order_index: Option<&mut Account<OrderIndex>>,
+    seller_order_index: Option<&mut Account<OrderIndex>>,
+    purchase_id: u64,
+) {
+    if let Some(order_index) = buyer_order_index {
+        order_index.mark_settled(purchase_id);
+    }
+    if let Some(order_index) = seller_order_index {
+        order_index.mark_settled(purchase_id);
+    }
+}
+
+/// Scales per-purchase logistics costs so they sum to `new_total`, keeping each
+/// purchase's share proportional. Rounding dust goes to the last purchase.
+pub fn prorate_logistics_costs(costs: &[u64], new_total: u64) -> Vec<u64> {
+    let current_total: u128 = costs.iter().map(|cost| *cost as u128).sum();
+    if current_total == 0 {
+        return vec![0; costs.len()];
+    }
+
+    let mut adjusted: Vec<u64> = costs
+        .iter()
+        .map(|cost| ((*cost as u128 * new_total as u128) / current_total) as u64)
+        .collect();
+    let distributed: u64 = adjusted.iter().sum();
+    if let Some(last) = adjusted.last_mut() {
+        *last += new_total - distributed;
+    }
+    adjusted
+}
+
+/// Token amount worth `usd_cents` at an oracle price of `price * 10^expo` USD
+/// per whole token, rounded up so the escrow never receives less than quoted.
+pub fn usd_cents_to_token_amount(
+    usd_cents: u64,
+    price: i64,
+    expo: i32,
+    decimals: u8,
+) -> Result<u64> {
+    require!(price > 0, LogisticsError::InvalidOraclePrice);
+    let pow10 = |exp: u32| 10u128.checked_pow(exp).ok_or(LogisticsError::MathOverflow);
+
+    let mut numerator = (usd_cents as u128)
+        .checked_mul(pow10(decimals as u32)?)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let mut denominator = (price as u128) * 100;
+    if expo < 0 {
+        numerator = numerator
+            .checked_mul(pow10(expo.unsigned_abs())?)
+            .ok_or(LogisticsError::MathOverflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(pow10(expo as u32)?)
+            .ok_or(LogisticsError::MathOverflow)?;
+    }
+
+    let amount = numerator.div_ceil(denominator);
+    u64::try_from(amount).map_err(|_| error!(LogisticsError::MathOverflow))
+}
+
+/// Reads a Pyth price no older than `ORACLE_MAX_STALENESS_SECONDS`.
+pub(crate) fn load_oracle_price(price_feed: &AccountInfo, now: i64) -> Result<(i64, i32)> {
+    let feed = SolanaPriceAccount::account_info_to_feed(price_feed)
+        .map_err(|_| error!(LogisticsError::InvalidPriceFeed))?;
+    let price = feed
+        .get_price_no_older_than(now, ORACLE_MAX_STALENESS_SECONDS)
+        .ok_or(LogisticsError::StaleOraclePrice)?;
+    Ok((price.price, price.expo))
+}
+
+/// Reads an SPL token account passed through `remaining_accounts`.
+pub(crate) fn load_token_account(info: &AccountInfo) -> Result<TokenAccount> {
+    require_keys_eq!(*info.owner, token::ID, LogisticsError::InvalidTokenAccount);
+    let data = info.try_borrow_data()?;
+    TokenAccount::try_deserialize(&mut &data[..])
+}
+
+/// Token account holding one portion of a purchase's escrow, given the
+/// purchase's product or logistics sub-vault. Purchases opened before escrow
+/// was split keep both portions in the mint's escrow vault.
+pub(crate) fn escrow_bucket<'info>(
+    purchase_account: &PurchaseAccount,
+    bucket: Option<&Account<'info, TokenAccount>>,
+    escrow_vault: &Account<'info, TokenAccount>,
+) -> Result<AccountInfo<'info>> {
+    if !purchase_account.split_escrow {
+        return Ok(escrow_vault.to_account_info());
+    }
+    require!(
+        purchase_account.lending_position().is_none(),
+        LogisticsError::EscrowLent
+    );
+    bucket
+        .map(|bucket| bucket.to_account_info())
+        .ok_or_else(|| error!(LogisticsError::MissingEscrowBucket))
+}
+
+/// Moves `amount` from a purchase sub-vault into the mint's escrow vault, which
+/// holds collected fees and backs claimable balances. Nothing moves when the
+/// source already is the vault.
+pub(crate) fn sweep_to_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 || source.key == escrow_vault.key {
+        return Ok(());
+    }
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        Transfer {
+            from: source.clone(),
+            to: escrow_vault.clone(),
+            authority: escrow_vault.clone(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)
+}
+
+/// Moves escrow fees kept by the protocol out of the mint's escrow vault into
+/// its fee vault, the only account `withdraw_escrow_fees` draws from.
+pub(crate) fn collect_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    fee_vault: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    release_escrow(
+        token_program,
+        escrow_vault,
+        escrow_vault,
+        fee_vault,
+        amount,
+        signer,
+    )
+}
+
+/// Locks a purchase for settlement and writes its pending state to the account
+/// ahead of the path's first transfer. Anchor only persists accounts when the
+/// instruction returns, so without this a program invoked mid-settlement would
+/// still read the purchase as open. The write is not a checkpoint: a transfer
+/// that fails afterwards reverts the whole transaction, this exit included,
+/// and the purchase is retried from its state before the call.
+pub(crate) fn commit_settlement(purchase_account: &mut Account<PurchaseAccount>) -> Result<()> {
+    purchase_account.lock_settlement()?;
+    purchase_account.exit(&crate::ID)
+}
+
+/// Pays `amount` out of an escrow account to `destination`, signed for by the
+/// mint's escrow vault.
+pub(crate) fn release_escrow<'info>(
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        Transfer {
+            from: source.clone(),
+            to: destination.clone(),
+            authority: escrow_vault.clone(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)
+}
+
+/// Moves a purchase's escrow out of the mint vault into its product and
+/// logistics sub-vaults, for purchases opened from an escrowed offer or bid.
+pub(crate) fn fund_escrow_buckets<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    product_escrow: &AccountInfo<'info>,
+    logistics_escrow: &AccountInfo<'info>,
+    purchase_account: &PurchaseAccount,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let product_amount = purchase_account
+        .total_amount
+        .checked_sub(purchase_account.logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    release_escrow(
+        token_program,
+        escrow_vault,
+        escrow_vault,
+        product_escrow,
+        product_amount,
+        signer,
+    )?;
+    release_escrow(
+        token_program,
+        escrow_vault,
+        escrow_vault,
+        logistics_escrow,
+        purchase_account.logistics_cost,
+        signer,
+    )
+}
+
+/// Refunds the open remainder of a purchase to the buyer, each portion from
+/// its own bucket, less `withheld` from the product portion.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn refund_escrow<'info>(
+    token_program: &AccountInfo<'info>,
+    purchase_account: &PurchaseAccount,
+    product_escrow: Option<&Account<'info, TokenAccount>>,
+    logistics_escrow: Option<&Account<'info, TokenAccount>>,
+    escrow_vault: &Account<'info, TokenAccount>,
+    buyer_token_account: &AccountInfo<'info>,
+    withheld: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let product_amount = purchase_account
+        .total_amount
+        .checked_sub(purchase_account.logistics_cost)
+        .and_then(|amount| amount.checked_sub(withheld))
+        .ok_or(LogisticsError::MathOverflow)?;
+    let vault_info = escrow_vault.to_account_info();
+    release_escrow(
+        token_program,
+        &escrow_bucket(purchase_account, product_escrow, escrow_vault)?,
+        &vault_info,
+        buyer_token_account,
+        product_amount,
+        signer,
+    )?;
+    release_escrow(
+        token_program,
+        &escrow_bucket(purchase_account, logistics_escrow, escrow_vault)?,
+        &vault_info,
+        buyer_token_account,
+        purchase_account.logistics_cost,
+        signer,
+    )
+}
+
+/// Pays `amount` out of the escrow account `source` to `destination`. When the
+/// recipient has no token account to receive it, the amount is moved into the
+/// mint's escrow vault and credited to their `ClaimableBalance` instead, until
+/// they call `claim_payout`. The balance is opened with
+/// `open_claimable_balance`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn transfer_or_credit<'info>(
+    global_state: &mut GlobalState,
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    destination: Option<&Account<'info, TokenAccount>>,
+    claimable: Option<&mut Account<'info, ClaimableBalance>>,
+    recipient: Pubkey,
+    token_mint: Pubkey,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if let Some(destination) = destination {
+        return release_escrow(
+            token_program,
+            source,
+            escrow_vault,
+            &destination.to_account_info(),
+            amount,
+            signer,
+        );
+    }
+
+    let Some(claimable) = claimable else {
+        return err!(LogisticsError::MissingPayoutAccounts);
+    };
+    sweep_to_vault(token_program, source, escrow_vault, amount, signer)?;
+    claimable.amount += amount;
+
+    emit!(PayoutDeferred {
+        event_seq: global_state.next_event_seq(),
+        recipient,
+        token_mint,
+        amount,
+    });
+    Ok(())
+}
+
+/// Smallest output accepted for a quote of `quoted_out_amount` when the seller
+/// tolerates `max_slippage_bps` of slippage.
+pub fn min_swap_output(quoted_out_amount: u64, max_slippage_bps: u16) -> u64 {
+    let tolerance = BASIS_POINTS.saturating_sub(max_slippage_bps as u64);
+    ((quoted_out_amount as u128 * tolerance as u128) / BASIS_POINTS as u128) as u64
+}
+
+/// Whether `info` is a token account that `authority` owns, or may spend from
+/// or close as its delegate or close authority.
+fn is_controlled_token_account(info: &AccountInfo, authority: &Pubkey) -> bool {
+    if *info.owner != token::ID {
+        return false;
+    }
+    let Ok(data) = info.try_borrow_data() else {
+        return false;
+    };
+    let Ok(account) = spl_token::state::Account::unpack(&data) else {
+        return false;
+    };
+    account.owner == *authority
+        || account.delegate == Some(*authority).into()
+        || account.close_authority == Some(*authority).into()
+}
+
+/// Account metas for a client-built route the mint's escrow vault signs.
+/// The vault is the token authority of every purchase's sub-vaults and
+/// lending receipt, so among the accounts it controls the route may only
+/// reference those in `allowed`; the vault itself is passed read-only unless
+/// it is one of them.
+pub(crate) fn escrow_route_metas(
+    route_accounts: &[AccountInfo],
+    escrow_vault: &Pubkey,
+    allowed: &[Pubkey],
+    error: LogisticsError,
+) -> Result<Vec<AccountMeta>> {
+    route_accounts
+        .iter()
+        .map(|info| {
+            let is_vault = info.key == escrow_vault;
+            let controlled = is_vault || is_controlled_token_account(info, escrow_vault);
+            let is_allowed = allowed.contains(info.key);
+            if controlled && !is_allowed && !is_vault {
+                return Err(error.into());
+            }
+            Ok(AccountMeta {
+                pubkey: *info.key,
+                is_signer: info.is_signer || is_vault,
+                is_writable: info.is_writable && (!controlled || is_allowed),
+            })
+        })
+        .collect()
+}
+
+/// Swaps part of the seller's share out of escrow through the configured AMM.
+/// The mint's escrow vault signs as the authority of `source`. Returns the
+/// amount taken from `source`, which may not exceed `max_amount_in`, and the
+/// amount the seller received in their preferred mint. The route may not touch
+/// any other account the vault controls.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_seller_proceeds<'info>(
+    swap_program: &AccountInfo<'info>,
+    source: &mut Account<'info, TokenAccount>,
+    escrow_vault: &Pubkey,
+    destination: &mut Account<'info, TokenAccount>,
+    route_accounts: &[AccountInfo<'info>],
+    seller_account: &SellerAccount,
+    route: &SwapRoute,
+    max_amount_in: u64,
+    signer: &[&[&[u8]]],
+) -> Result<(u64, u64)> {
+    let preferred_mint = seller_account
+        .preferred_mint
+        .ok_or(LogisticsError::InvalidSwapRoute)?;
+    require_keys_eq!(
+        destination.mint,
+        preferred_mint,
+        LogisticsError::InvalidSwapRoute
+    );
+    require!(
+        route_accounts
+            .iter()
+            .all(|info| info.key != swap_program.key),
+        LogisticsError::InvalidSwapRoute
+    );
+
+    let metas = escrow_route_metas(
+        route_accounts,
+        escrow_vault,
+        &[source.key()],
+        LogisticsError::InvalidSwapRoute,
+    )?;
+    let mut infos = route_accounts.to_vec();
+    infos.push(swap_program.clone());
+
+    let source_before = source.amount;
+    let destination_before = destination.amount;
+    invoke_signed(
+        &Instruction {
+            program_id: *swap_program.key,
+            accounts: metas,
+            data: route.data.clone(),
+        },
+        &infos,
+        signer,
+    )?;
+    source.reload()?;
+    destination.reload()?;
+
+    let amount_in = source_before
+        .checked_sub(source.amount)
+        .ok_or(LogisticsError::InvalidSwapRoute)?;
+    require!(amount_in <= max_amount_in, LogisticsError::SwapOverspent);
+    let amount_out = destination.amount.saturating_sub(destination_before);
+    require!(
+        amount_out >= min_swap_output(route.quoted_out_amount, seller_account.max_slippage_bps),
+        LogisticsError::SlippageExceeded
+    );
+
+    Ok((amount_in, amount_out))
+}
+
+/// Invokes the configured lending program with a client-built deposit or
+/// redeem instruction. The mint's escrow vault signs as the authority of the
+/// purchase's product sub-vault and lending receipt, the only accounts it
+/// controls that `route_accounts` may reference.
+pub(crate) fn invoke_lending_route<'info>(
+    lending_program: &AccountInfo<'info>,
+    escrow_vault: &Pubkey,
+    purchase_accounts: &[Pubkey; 2],
+    route_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    require!(
+        route_accounts
+            .iter()
+            .all(|info| info.key != lending_program.key),
+        LogisticsError::InvalidLendingRoute
+    );
+
+    let metas = escrow_route_metas(
+        route_accounts,
+        escrow_vault,
+        purchase_accounts,
+        LogisticsError::InvalidLendingRoute,
+    )?;
+    let mut infos = route_accounts.to_vec();
+    infos.push(lending_program.clone());
+
+    invoke_signed(
+        &Instruction {
+            program_id: *lending_program.key,
+            accounts: metas,
+            data,
+        },
+        &infos,
+        signer,
+    )?;
+    Ok(())
+}
+
+/// Checks that the instruction just before the current one is an Ed25519
+/// program instruction verifying `signer`'s signature over `message`. The
+/// key, signature and message must all be carried in that instruction.
+pub(crate) fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, LogisticsError::InvalidOrderSignature);
+    let instruction = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        instruction.program_id,
+        ed25519_program::ID,
+        LogisticsError::InvalidOrderSignature
+    );
+
+    // One signature: a two-byte header followed by seven u16 offsets
+    let data = &instruction.data;
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        LogisticsError::InvalidOrderSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_len = read_u16(12) as usize;
+    require!(
+        [read_u16(4), read_u16(8), read_u16(14)]
+            .iter()
+            .all(|index| *index == u16::MAX),
+        LogisticsError::InvalidOrderSignature
+    );
+
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_len);
+    require!(
+        public_key == Some(signer.as_ref()) && signed_message == Some(message),
+        LogisticsError::InvalidOrderSignature
+    );
+    Ok(())
+}
+
+/// Token account the seller is paid into. A payout account registered on the
+/// seller's `SellerAccount` replaces their associated token account, unless it
+/// holds a different mint than `token_mint`; without it, the seller is
+/// credited through their claimable balance instead.
+pub(crate) fn seller_payout_account<'a, 'info>(
+    seller_account: Option<&SellerAccount>,
+    seller_token_account: Option<&'a Account<'info, TokenAccount>>,
+    seller_payout_token_account: Option<&'a Account<'info, TokenAccount>>,
+    token_mint: Pubkey,
+) -> Result<Option<&'a Account<'info, TokenAccount>>> {
+    registered_payout_account(
+        seller_account.and_then(|seller| seller.payout_token_account),
+        seller_token_account,
+        seller_payout_token_account,
+        token_mint,
+    )
+}
+
+/// Token account the logistics provider is paid into, resolved the same way as
+/// [`seller_payout_account`] from the payout account on their
+/// `LogisticsProviderAccount`.
+pub(crate) fn provider_payout_account<'a, 'info>(
+    provider_account: Option<&LogisticsProviderAccount>,
+    logistics_token_account: Option<&'a Account<'info, TokenAccount>>,
+    logistics_payout_token_account: Option<&'a Account<'info, TokenAccount>>,
+    token_mint: Pubkey,
+) -> Result<Option<&'a Account<'info, TokenAccount>>> {
+    registered_payout_account(
+        provider_account.and_then(|provider| provider.payout_token_account),
+        logistics_token_account,
+        logistics_payout_token_account,
+        token_mint,
+    )
+}
+
+fn registered_payout_account<'a, 'info>(
+    registered: Option<Pubkey>,
+    token_account: Option<&'a Account<'info, TokenAccount>>,
+    payout_token_account: Option<&'a Account<'info, TokenAccount>>,
+    token_mint: Pubkey,
+) -> Result<Option<&'a Account<'info, TokenAccount>>> {
+    let Some(registered) = registered else {
+        return Ok(token_account);
+    };
+    let Some(payout_token_account) = payout_token_account else {
+        return Ok(None);
+    };
+    require_keys_eq!(
+        payout_token_account.key(),
+        registered,
+        LogisticsError::InvalidPayoutAccount
+    );
+    if payout_token_account.mint != token_mint {
+        return Ok(token_account);
+    }
+    Ok(Some(payout_token_account))
+}
+
+/// Pays the seller's share of a settlement out of `source`. Trades without payout
+/// splits pay the seller (or their claimable balance) directly; otherwise the
+/// token accounts for each split recipient must be supplied, in order, as
+/// remaining accounts.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_seller_proceeds<'info>(
+    global_state: &mut GlobalState,
+    token_program: &AccountInfo<'info>,
+    source: &AccountInfo<'info>,
+    escrow_vault: &AccountInfo<'info>,
+    seller_token_account: Option<&Account<'info, TokenAccount>>,
+    seller_claimable: Option<&mut Account<'info, ClaimableBalance>>,
+    split_token_accounts: &[AccountInfo<'info>],
+    trade_account: &TradeAccount,
+    token_mint: Pubkey,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if trade_account.payout_splits.is_empty() {
+        return transfer_or_credit(
+            global_state,
+            token_program,
+            source,
+            escrow_vault,
+            seller_token_account,
+            seller_claimable,
+            trade_account.seller,
+            token_mint,
+            amount,
+            signer,
+        );
+    }
+
+    require!(
+        split_token_accounts.len() >= trade_account.payout_splits.len(),
+        LogisticsError::MissingPayoutAccounts
+    );
+
+    let shares = compute_split_amounts(amount, &trade_account.payout_splits);
+    for ((split, share), recipient_info) in trade_account
+        .payout_splits
+        .iter()
+        .zip(shares)
+        .zip(split_token_accounts.iter())
+    {
+        let recipient_token_account = load_token_account(recipient_info)?;
+        require_keys_eq!(
+            recipient_token_account.owner,
+            split.recipient,
+            LogisticsError::InvalidPayoutAccount
+        );
+        require_keys_eq!(
+            recipient_token_account.mint,
+            token_mint,
+            LogisticsError::InvalidPayoutAccount
+        );
+        if share == 0 {
+            continue;
+        }
+
+        release_escrow(
+            token_program,
+            source,
+            escrow_vault,
+            recipient_info,
+            share,
+            signer,
+        )?;
+    }
+
+    Ok(())
+}
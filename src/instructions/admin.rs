@@ -0,0 +1,1380 @@
+//! Platform configuration, arbitrators, the jury pool and account maintenance.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::legacy;
+use crate::pda;
+use crate::state::*;
+
+pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.admin = ctx.accounts.admin.key();
+    global_state.trade_counter = 0;
+    global_state.purchase_counter = 0;
+    global_state.stake_mint = Pubkey::default();
+    global_state.provider_stake_amount = 0;
+    global_state.slash_bps = DEFAULT_SLASH_BPS;
+    global_state.stake_cooldown_seconds = DEFAULT_STAKE_COOLDOWN_SECONDS;
+    global_state.seller_loss_threshold = DEFAULT_SELLER_LOSS_THRESHOLD;
+    global_state.seller_bond_slash_bps = DEFAULT_SLASH_BPS;
+    global_state.seller_probation_seconds = DEFAULT_SELLER_PROBATION_SECONDS;
+    global_state.swap_program = Pubkey::default();
+    global_state.cancellation_window_seconds = DEFAULT_CANCELLATION_WINDOW_SECONDS;
+    global_state.restocking_fee_bps = DEFAULT_RESTOCKING_FEE_BPS;
+    global_state.escalation_window_seconds = DEFAULT_ESCALATION_WINDOW_SECONDS;
+    global_state.escalation_fee_bps = DEFAULT_ESCALATION_FEE_BPS;
+    global_state.auto_confirm_seconds = DEFAULT_AUTO_CONFIRM_SECONDS;
+    global_state.ship_timeout_seconds = DEFAULT_SHIP_TIMEOUT_SECONDS;
+    global_state.crank_reward_bps = DEFAULT_CRANK_REWARD_BPS;
+    global_state.platforms = Vec::new();
+    global_state.min_order_amount = 0;
+    global_state.max_order_amount = 0;
+    global_state.region_registrar = Pubkey::default();
+    global_state.lending_program = Pubkey::default();
+    global_state.yield_buyer_share_bps = 0;
+    global_state.fee_split_config = FeeSplitConfig::default();
+    global_state.governance_authority = Pubkey::default();
+    global_state.loyalty_config = LoyaltyConfig::default();
+    global_state.event_seq = 0;
+    global_state.fee_bps = ESCROW_FEE_PERCENT as u16;
+    global_state.paused = false;
+    global_state.version = ACCOUNT_VERSION;
+    global_state.bump = ctx.bumps.global_state;
+    Ok(())
+}
+
+pub fn set_governance_authority(
+    ctx: Context<SetGovernanceAuthority>,
+    governance_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.global_state.governance_authority = governance_authority;
+
+    emit!(GovernanceAuthorityUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        governance_authority,
+    });
+    Ok(())
+}
+
+pub fn set_stake_config(
+    ctx: Context<SetStakeConfig>,
+    provider_stake_amount: u64,
+    slash_bps: u16,
+    stake_cooldown_seconds: i64,
+) -> Result<()> {
+    require!(
+        slash_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidStakeConfig
+    );
+    require!(
+        stake_cooldown_seconds >= 0,
+        LogisticsError::InvalidStakeConfig
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.stake_mint = ctx.accounts.stake_mint.key();
+    global_state.provider_stake_amount = provider_stake_amount;
+    global_state.slash_bps = slash_bps;
+    global_state.stake_cooldown_seconds = stake_cooldown_seconds;
+
+    emit!(StakeConfigUpdated {
+        event_seq: global_state.next_event_seq(),
+        stake_mint: global_state.stake_mint,
+        provider_stake_amount,
+        slash_bps,
+        stake_cooldown_seconds,
+    });
+    Ok(())
+}
+
+pub fn set_seller_bond_config(
+    ctx: Context<SetSellerBondConfig>,
+    seller_loss_threshold: u32,
+    seller_bond_slash_bps: u16,
+    seller_probation_seconds: i64,
+) -> Result<()> {
+    require!(
+        seller_bond_slash_bps as u64 <= BASIS_POINTS && seller_probation_seconds >= 0,
+        LogisticsError::InvalidBondConfig
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.seller_loss_threshold = seller_loss_threshold;
+    global_state.seller_bond_slash_bps = seller_bond_slash_bps;
+    global_state.seller_probation_seconds = seller_probation_seconds;
+    Ok(())
+}
+
+pub fn set_crank_config(
+    ctx: Context<SetCrankConfig>,
+    auto_confirm_seconds: i64,
+    ship_timeout_seconds: i64,
+    crank_reward_bps: u16,
+) -> Result<()> {
+    require!(
+        auto_confirm_seconds > 0
+            && ship_timeout_seconds > 0
+            && crank_reward_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidCrankConfig
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.auto_confirm_seconds = auto_confirm_seconds;
+    global_state.ship_timeout_seconds = ship_timeout_seconds;
+    global_state.crank_reward_bps = crank_reward_bps;
+
+    emit!(CrankConfigUpdated {
+        event_seq: global_state.next_event_seq(),
+        auto_confirm_seconds,
+        ship_timeout_seconds,
+        crank_reward_bps,
+    });
+    Ok(())
+}
+
+pub fn set_platform_fee_share(
+    ctx: Context<SetPlatformFeeShare>,
+    platform: Pubkey,
+    fee_share_bps: u16,
+) -> Result<()> {
+    require!(
+        platform != Pubkey::default() && fee_share_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidPlatformFeeShare
+    );
+
+    ctx.accounts
+        .global_state
+        .set_platform_fee_share(platform, fee_share_bps)?;
+
+    emit!(PlatformFeeShareUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        platform,
+        fee_share_bps,
+    });
+    Ok(())
+}
+
+pub fn set_loyalty_config(ctx: Context<SetLoyaltyConfig>, config: LoyaltyConfig) -> Result<()> {
+    require!(config.is_valid(), LogisticsError::InvalidLoyaltyConfig);
+
+    ctx.accounts.global_state.loyalty_config = config;
+
+    emit!(LoyaltyConfigUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        points_bps: config.points_bps,
+        points_per_discount_bps: config.points_per_discount_bps,
+        max_discount_bps: config.max_discount_bps,
+    });
+    Ok(())
+}
+
+pub fn set_fee_splits(ctx: Context<SetFeeSplits>, splits: Vec<PayoutSplit>) -> Result<()> {
+    let config = FeeSplitConfig { splits };
+    require!(config.is_valid(), LogisticsError::InvalidFeeSplits);
+
+    emit!(FeeSplitsUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        splits: config.splits.clone(),
+    });
+    ctx.accounts.global_state.fee_split_config = config;
+    Ok(())
+}
+
+pub fn set_order_limits(
+    ctx: Context<SetOrderLimits>,
+    min_order_amount: u64,
+    max_order_amount: u64,
+) -> Result<()> {
+    let limits = OrderLimits {
+        min_order_amount,
+        max_order_amount,
+    };
+    require!(limits.is_valid(), LogisticsError::InvalidOrderLimits);
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.min_order_amount = min_order_amount;
+    global_state.max_order_amount = max_order_amount;
+
+    emit!(OrderLimitsUpdated {
+        event_seq: global_state.next_event_seq(),
+        min_order_amount,
+        max_order_amount,
+    });
+    Ok(())
+}
+
+pub fn set_cancellation_policy(
+    ctx: Context<SetCancellationPolicy>,
+    cancellation_window_seconds: i64,
+    restocking_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        cancellation_window_seconds >= 0 && restocking_fee_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidCancellationPolicy
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.cancellation_window_seconds = cancellation_window_seconds;
+    global_state.restocking_fee_bps = restocking_fee_bps;
+
+    emit!(CancellationPolicyUpdated {
+        event_seq: global_state.next_event_seq(),
+        cancellation_window_seconds,
+        restocking_fee_bps,
+    });
+    Ok(())
+}
+
+pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+    require!(fee_bps <= MAX_ESCROW_FEE_BPS, LogisticsError::InvalidFeeBps);
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.fee_bps = fee_bps;
+
+    emit!(EscrowFeeUpdated {
+        event_seq: global_state.next_event_seq(),
+        fee_bps,
+    });
+    Ok(())
+}
+
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.paused = paused;
+
+    emit!(PauseSet {
+        event_seq: global_state.next_event_seq(),
+        paused,
+    });
+    Ok(())
+}
+
+pub fn set_swap_program(ctx: Context<SetSwapProgram>, swap_program: Pubkey) -> Result<()> {
+    ctx.accounts.global_state.swap_program = swap_program;
+    Ok(())
+}
+
+pub fn set_lending_config(
+    ctx: Context<SetLendingConfig>,
+    lending_program: Pubkey,
+    yield_buyer_share_bps: u16,
+) -> Result<()> {
+    require!(
+        yield_buyer_share_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidLendingRoute
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.lending_program = lending_program;
+    global_state.yield_buyer_share_bps = yield_buyer_share_bps;
+
+    emit!(LendingConfigUpdated {
+        event_seq: global_state.next_event_seq(),
+        lending_program,
+        yield_buyer_share_bps,
+    });
+    Ok(())
+}
+
+pub fn set_region_registrar(
+    ctx: Context<SetRegionRegistrar>,
+    region_registrar: Pubkey,
+) -> Result<()> {
+    ctx.accounts.global_state.region_registrar = region_registrar;
+
+    emit!(RegionRegistrarUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        region_registrar,
+    });
+    Ok(())
+}
+
+pub fn set_escalation_policy(
+    ctx: Context<SetEscalationPolicy>,
+    escalation_window_seconds: i64,
+    escalation_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        escalation_window_seconds >= 0 && escalation_fee_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidEscalationPolicy
+    );
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.escalation_window_seconds = escalation_window_seconds;
+    global_state.escalation_fee_bps = escalation_fee_bps;
+
+    emit!(EscalationPolicyUpdated {
+        event_seq: global_state.next_event_seq(),
+        escalation_window_seconds,
+        escalation_fee_bps,
+    });
+    Ok(())
+}
+
+pub fn initialize_stats(ctx: Context<InitializeStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.stats;
+    stats.purchases_settled = 0;
+    stats.purchases_cancelled = 0;
+    stats.disputes_raised = 0;
+    stats.disputes_resolved = 0;
+    stats.disputes_won_by_buyer = 0;
+    stats.mint_totals = Vec::new();
+    stats.version = ACCOUNT_VERSION;
+    stats.bump = ctx.bumps.stats;
+    Ok(())
+}
+
+pub fn initialize_program_version(ctx: Context<InitializeProgramVersion>) -> Result<()> {
+    let account = &mut ctx.accounts.program_version;
+    account.version = ACCOUNT_VERSION;
+    account.bump = ctx.bumps.program_version;
+    Ok(())
+}
+
+pub fn set_program_version(
+    ctx: Context<SetProgramVersion>,
+    program_version: SemVer,
+    min_client_version: SemVer,
+) -> Result<()> {
+    let account = &mut ctx.accounts.program_version;
+    require!(
+        program_version >= account.program_version && min_client_version <= program_version,
+        LogisticsError::InvalidProgramVersion
+    );
+    account.program_version = program_version;
+    account.min_client_version = min_client_version;
+    account.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(ProgramVersionUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        program_version,
+        min_client_version,
+    });
+    Ok(())
+}
+
+pub fn initialize_receipt_config(ctx: Context<InitializeReceiptConfig>) -> Result<()> {
+    let receipt_config = &mut ctx.accounts.receipt_config;
+    receipt_config.version = ACCOUNT_VERSION;
+    receipt_config.bump = ctx.bumps.receipt_config;
+    Ok(())
+}
+
+pub fn set_receipt_config(
+    ctx: Context<SetReceiptConfig>,
+    merkle_tree: Pubkey,
+    uri_prefix: String,
+) -> Result<()> {
+    require!(
+        uri_prefix.len() <= MAX_RECEIPT_URI_PREFIX_LEN,
+        LogisticsError::ReceiptUriTooLong
+    );
+
+    let receipt_config = &mut ctx.accounts.receipt_config;
+    receipt_config.merkle_tree = merkle_tree;
+    receipt_config.uri_prefix = uri_prefix.clone();
+
+    emit!(ReceiptConfigUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        merkle_tree,
+        uri_prefix,
+    });
+    Ok(())
+}
+
+pub fn register_arbitrator(ctx: Context<RegisterArbitrator>) -> Result<()> {
+    let arbitrator_account = &mut ctx.accounts.arbitrator_account;
+    arbitrator_account.arbitrator = ctx.accounts.arbitrator.key();
+    arbitrator_account.version = ACCOUNT_VERSION;
+    arbitrator_account.bump = ctx.bumps.arbitrator_account;
+    Ok(())
+}
+
+pub fn remove_arbitrator(_ctx: Context<RemoveArbitrator>) -> Result<()> {
+    Ok(())
+}
+
+pub fn init_jury_pool(ctx: Context<InitJuryPool>, config: JuryConfig) -> Result<()> {
+    require!(config.is_valid(), LogisticsError::InvalidJuryConfig);
+
+    let jury_pool = &mut ctx.accounts.jury_pool;
+    jury_pool.config = config;
+    jury_pool.vault_bump = ctx.bumps.jury_vault;
+    jury_pool.version = ACCOUNT_VERSION;
+    jury_pool.bump = ctx.bumps.jury_pool;
+
+    emit!(JuryConfigUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        config,
+    });
+    Ok(())
+}
+
+pub fn set_jury_config(ctx: Context<SetJuryConfig>, config: JuryConfig) -> Result<()> {
+    require!(config.is_valid(), LogisticsError::InvalidJuryConfig);
+    ctx.accounts.jury_pool.config = config;
+
+    emit!(JuryConfigUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        config,
+    });
+    Ok(())
+}
+
+pub fn add_allowed_mint(ctx: Context<AddAllowedMint>) -> Result<()> {
+    let allowed_mint = &mut ctx.accounts.allowed_mint;
+    allowed_mint.mint = ctx.accounts.token_mint.key();
+    allowed_mint.version = ACCOUNT_VERSION;
+    allowed_mint.bump = ctx.bumps.allowed_mint;
+
+    emit!(AllowedMintAdded {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        mint: allowed_mint.mint,
+    });
+    Ok(())
+}
+
+pub fn remove_allowed_mint(ctx: Context<RemoveAllowedMint>) -> Result<()> {
+    emit!(AllowedMintRemoved {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        mint: ctx.accounts.allowed_mint.mint,
+    });
+    Ok(())
+}
+
+pub fn create_escrow_vault(ctx: Context<CreateEscrowVault>) -> Result<()> {
+    emit!(EscrowVaultCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        mint: ctx.accounts.token_mint.key(),
+        escrow_vault: ctx.accounts.escrow_token_account.key(),
+    });
+    Ok(())
+}
+
+pub fn create_fee_vault(_ctx: Context<CreateFeeVault>) -> Result<()> {
+    Ok(())
+}
+
+pub fn create_insurance_pool(_ctx: Context<CreateInsurancePool>) -> Result<()> {
+    Ok(())
+}
+
+pub fn migrate_trade(ctx: Context<MigrateTrade>, _trade_id: u64) -> Result<()> {
+    let account = ctx.accounts.trade_account.to_account_info();
+    let legacy_trade = legacy::load_legacy::<TradeAccount, legacy::LegacyTradeAccount>(&account)?;
+    legacy::store_migrated(
+        &account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &TradeAccount::from(legacy_trade),
+    )?;
+
+    emit!(AccountMigrated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+    });
+    Ok(())
+}
+
+pub fn migrate_purchase(ctx: Context<MigratePurchase>, _purchase_id: u64) -> Result<()> {
+    let account = ctx.accounts.purchase_account.to_account_info();
+    let legacy_purchase =
+        legacy::load_legacy::<PurchaseAccount, legacy::LegacyPurchaseAccount>(&account)?;
+    legacy::store_migrated(
+        &account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &PurchaseAccount::from(legacy_purchase),
+    )?;
+
+    emit!(AccountMigrated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+    });
+    Ok(())
+}
+
+pub fn withdraw_escrow_fees(
+    ctx: Context<WithdrawEscrowFees>,
+    mint: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, LogisticsError::InvalidAmount);
+    require_gte!(
+        ctx.accounts.fee_vault.amount,
+        amount,
+        LogisticsError::NoFeesToWithdraw
+    );
+
+    let seeds = &[pda::FEE_VAULT_SEED, mint.as_ref(), &[ctx.bumps.fee_vault]];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.fee_vault.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: ctx.accounts.fee_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(FeesWithdrawn {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        mint,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn propose_emergency_withdrawal(
+    ctx: Context<ProposeEmergencyWithdrawal>,
+    source: EmergencyVault,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, LogisticsError::InvalidAmount);
+    let token_mint = ctx.accounts.token_mint.key();
+    require_keys_eq!(
+        ctx.accounts.source_vault.key(),
+        source.address(&token_mint).0,
+        LogisticsError::InvalidEmergencySource
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.emergency_withdrawal;
+    proposal.token_mint = token_mint;
+    proposal.source = source;
+    proposal.destination = ctx.accounts.destination.key();
+    proposal.amount = amount;
+    proposal.proposed_at = now;
+    proposal.executable_at = now + EMERGENCY_TIMELOCK_SECONDS;
+    proposal.version = ACCOUNT_VERSION;
+    proposal.bump = ctx.bumps.emergency_withdrawal;
+
+    emit!(EmergencyWithdrawalProposed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        token_mint: proposal.token_mint,
+        source,
+        destination: proposal.destination,
+        amount,
+        executable_at: proposal.executable_at,
+    });
+    Ok(())
+}
+
+pub fn execute_emergency_withdrawal(ctx: Context<ExecuteEmergencyWithdrawal>) -> Result<()> {
+    let proposal = &ctx.accounts.emergency_withdrawal;
+    require!(
+        Clock::get()?.unix_timestamp >= proposal.executable_at,
+        LogisticsError::TimelockNotElapsed
+    );
+
+    // The vault is derived again from the proposal, so execution cannot be
+    // pointed at any other account
+    let source = proposal.source;
+    let (vault, vault_bump) = source.address(&proposal.token_mint);
+    require_keys_eq!(
+        ctx.accounts.source_vault.key(),
+        vault,
+        LogisticsError::InvalidEmergencySource
+    );
+
+    let (authority, authority_seeds, authority_bump) = if source.escrow_is_authority() {
+        let Some(escrow_token_account) = ctx.accounts.escrow_token_account.as_ref() else {
+            return err!(LogisticsError::MissingEscrowVault);
+        };
+        (
+            escrow_token_account.to_account_info(),
+            EmergencyVault::Escrow.seeds(&proposal.token_mint),
+            ctx.bumps.escrow_token_account,
+        )
+    } else {
+        (
+            ctx.accounts.source_vault.to_account_info(),
+            source.seeds(&proposal.token_mint),
+            vault_bump,
+        )
+    };
+    let bump = [authority_bump];
+    let mut seeds: Vec<&[u8]> = authority_seeds.iter().map(Vec::as_slice).collect();
+    seeds.push(&bump);
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.source_vault.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority,
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, proposal.amount)?;
+
+    emit!(EmergencyWithdrawalExecuted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        token_mint: proposal.token_mint,
+        source,
+        destination: proposal.destination,
+        amount: proposal.amount,
+    });
+    Ok(())
+}
+
+pub fn cancel_emergency_withdrawal(ctx: Context<CancelEmergencyWithdrawal>) -> Result<()> {
+    let proposal = &ctx.accounts.emergency_withdrawal;
+    emit!(EmergencyWithdrawalCancelled {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        token_mint: proposal.token_mint,
+        amount: proposal.amount,
+    });
+    Ok(())
+}
+
+pub fn distribute_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>,
+    mint: Pubkey,
+) -> Result<()> {
+    let splits = &ctx.accounts.global_state.fee_split_config.splits;
+    require!(!splits.is_empty(), LogisticsError::FeeSplitsNotConfigured);
+    let amount = ctx.accounts.fee_vault.amount;
+    require!(amount > 0, LogisticsError::NoFeesToWithdraw);
+    require!(
+        ctx.remaining_accounts.len() >= splits.len(),
+        LogisticsError::MissingPayoutAccounts
+    );
+
+    let seeds = &[pda::FEE_VAULT_SEED, mint.as_ref(), &[ctx.bumps.fee_vault]];
+    let signer = &[&seeds[..]];
+
+    let shares = compute_split_amounts(amount, splits);
+    for ((split, share), recipient_info) in
+        splits.iter().zip(shares).zip(ctx.remaining_accounts.iter())
+    {
+        let recipient_token_account = load_token_account(recipient_info)?;
+        require_keys_eq!(
+            recipient_token_account.owner,
+            split.recipient,
+            LogisticsError::InvalidPayoutAccount
+        );
+        require_keys_eq!(
+            recipient_token_account.mint,
+            mint,
+            LogisticsError::InvalidPayoutAccount
+        );
+        if share == 0 {
+            continue;
+        }
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.fee_vault.to_account_info(),
+                to: recipient_info.clone(),
+                authority: ctx.accounts.fee_vault.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, share)?;
+    }
+
+    emit!(FeesDistributed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        mint,
+        amount,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalState::INIT_SPACE,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGovernanceAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStakeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub stake_mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSellerBondConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetEscalationPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStats<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [pda::STATS_SEED],
+        bump
+    )]
+    pub stats: Account<'info, Stats>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramVersion<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramVersion::INIT_SPACE,
+        seeds = [pda::PROGRAM_VERSION_SEED],
+        bump
+    )]
+    pub program_version: Account<'info, ProgramVersion>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramVersion<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PROGRAM_VERSION_SEED],
+        bump = program_version.bump
+    )]
+    pub program_version: Account<'info, ProgramVersion>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReceiptConfig<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ReceiptConfig::INIT_SPACE,
+        seeds = [pda::RECEIPT_CONFIG_SEED],
+        bump
+    )]
+    pub receipt_config: Account<'info, ReceiptConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReceiptConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::RECEIPT_CONFIG_SEED],
+        bump = receipt_config.bump
+    )]
+    pub receipt_config: Account<'info, ReceiptConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterArbitrator<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ArbitratorAccount::INIT_SPACE,
+        seeds = [pda::ARBITRATOR_SEED, arbitrator.key().as_ref()],
+        bump
+    )]
+    pub arbitrator_account: Account<'info, ArbitratorAccount>,
+    /// CHECK: This is the arbitrator being registered
+    pub arbitrator: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveArbitrator<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::ARBITRATOR_SEED, arbitrator_account.arbitrator.as_ref()],
+        bump = arbitrator_account.bump,
+        close = authority
+    )]
+    pub arbitrator_account: Account<'info, ArbitratorAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitJuryPool<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + JuryPool::INIT_SPACE,
+        seeds = [pda::JURY_POOL_SEED],
+        bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [pda::JURY_VAULT_SEED],
+        bump,
+        token::mint = stake_mint,
+        token::authority = jury_vault
+    )]
+    pub jury_vault: Account<'info, TokenAccount>,
+    #[account(address = global_state.stake_mint @ LogisticsError::InvalidStakeMint)]
+    pub stake_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetJuryConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_POOL_SEED],
+        bump = jury_pool.bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddAllowedMint<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AllowedMint::INIT_SPACE,
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowedMint<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::ALLOWED_MINT_SEED, allowed_mint.mint.as_ref()],
+        bump = allowed_mint.bump,
+        close = authority
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateEscrowVault<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump = allowed_mint.bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    /// Anyone may open the vault of a whitelisted mint
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFeeVault<'info> {
+    #[account(
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump = allowed_mint.bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = fee_vault
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    /// Anyone may open the fee vault of a whitelisted mint
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInsurancePool<'info> {
+    #[account(
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump = allowed_mint.bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::INSURANCE_POOL_SEED, token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = insurance_pool
+    )]
+    pub insurance_pool: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    /// Anyone may open the insurance pool of a whitelisted mint
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrankConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPlatformFeeShare<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLoyaltyConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSplits<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOrderLimits<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCancellationPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSwapProgram<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLendingConfig<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRegionRegistrar<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct MigrateTrade<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: Pre-versioning trade layout, decoded in the handler
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub trade_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct MigratePurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: Pre-versioning purchase layout, decoded in the handler
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub purchase_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct WithdrawEscrowFees<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        constraint = global_state.governing_authority() == authority.key() @ LogisticsError::NotAuthorized
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, mint.as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = authority_token_account.mint == mint @ LogisticsError::InvalidTokenMint,
+        constraint = authority_token_account.owner == authority.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeEmergencyWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EmergencyWithdrawal::INIT_SPACE,
+        seeds = [pda::EMERGENCY_WITHDRAWAL_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    pub token_mint: Account<'info, Mint>,
+    /// Vault the withdrawal drains; must be the PDA `source` names
+    #[account(constraint = source_vault.mint == token_mint.key() @ LogisticsError::InvalidTokenMint)]
+    pub source_vault: Account<'info, TokenAccount>,
+    #[account(constraint = destination.mint == token_mint.key() @ LogisticsError::InvalidTokenMint)]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteEmergencyWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::EMERGENCY_WITHDRAWAL_SEED, emergency_withdrawal.token_mint.as_ref()],
+        bump = emergency_withdrawal.bump,
+        has_one = destination,
+        close = admin
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    /// Vault named in the proposal; checked against its seeds by the handler
+    #[account(mut)]
+    pub source_vault: Account<'info, TokenAccount>,
+    /// The mint's escrow vault, which signs when the source is a purchase's
+    /// product or logistics escrow
+    #[account(
+        seeds = [pda::ESCROW_SEED, emergency_withdrawal.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEmergencyWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::EMERGENCY_WITHDRAWAL_SEED, emergency_withdrawal.token_mint.as_ref()],
+        bump = emergency_withdrawal.bump,
+        close = admin
+    )]
+    pub emergency_withdrawal: Account<'info, EmergencyWithdrawal>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct DistributeFees<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, mint.as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// Anyone may crank the distribution
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
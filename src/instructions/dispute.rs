@@ -0,0 +1,1516 @@
+//! Disputes, arbitration and their resolution.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn raise_dispute(
+    ctx: Context<RaiseDispute>,
+    _purchase_id: u64,
+    reason: DisputeReason,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let now = Clock::get()?.unix_timestamp;
+    // The buyer may still dispute what has not vested of a streamed payout
+    let stream = purchase_account
+        .seller_stream()
+        .filter(|stream| stream.is_running(now));
+    require!(
+        !purchase_account.delivered_and_confirmed || stream.is_some(),
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::AlreadyDisputed);
+    require!(
+        reason != DisputeReason::None,
+        LogisticsError::InvalidDisputeReason
+    );
+
+    if let Some(mut stream) = stream {
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            purchase_account.buyer,
+            LogisticsError::NotAuthorized
+        );
+        stream.halted_at = now;
+        purchase_account.set_seller_stream(&stream)?;
+        let vested = stream.vested(now);
+        emit!(SellerStreamHalted {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id: purchase_account.purchase_id,
+            vested,
+            disputed_amount: stream.amount - vested,
+        });
+    }
+
+    purchase_account.disputed = true;
+    purchase_account.dispute_reason = reason;
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.disputes_raised += 1;
+    }
+
+    emit!(DisputeRaised {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        initiator: ctx.accounts.user.key(),
+        reason,
+    });
+
+    Ok(())
+}
+
+pub fn arbitrate_dispute(
+    ctx: Context<ArbitrateDispute>,
+    _purchase_id: u64,
+    winner: Pubkey,
+    slash_provider: bool,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    if let Some(error) =
+        purchase_account.ruling_error(&ctx.accounts.trade_account.seller, &winner, slash_provider)
+    {
+        return Err(error.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.set_dispute_ruling(&DisputeRuling {
+        arbitrator: ctx.accounts.arbitrator.key(),
+        winner,
+        slash_provider,
+        ruled_at: now,
+        escalated_by: Pubkey::default(),
+        escalation_fee: 0,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.arbitrator.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(DisputeRuled {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        arbitrator: ctx.accounts.arbitrator.key(),
+        winner,
+        slash_provider,
+        escalation_deadline: now + ctx.accounts.global_state.escalation_window_seconds,
+    });
+    Ok(())
+}
+
+pub fn resolve_disputes_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ResolveDisputesBatch<'info>>,
+    token_mint: Pubkey,
+    rulings: Vec<BatchRuling>,
+) -> Result<()> {
+    // Each ruling's purchase is passed with its trade in remaining accounts
+    require!(
+        !rulings.is_empty() && rulings.len() <= MAX_BATCH_RULINGS,
+        LogisticsError::InvalidBatch
+    );
+    require!(
+        ctx.remaining_accounts.len() == 2 * rulings.len(),
+        LogisticsError::InvalidBatch
+    );
+
+    let arbitrator = ctx.accounts.arbitrator.key();
+    let now = Clock::get()?.unix_timestamp;
+    let escalation_deadline = now + ctx.accounts.global_state.escalation_window_seconds;
+    let mut ruled = 0u8;
+    let mut skipped = 0u8;
+    for (ruling, accounts) in rulings.iter().zip(ctx.remaining_accounts.chunks(2)) {
+        let (purchase_info, trade_info) = (&accounts[0], &accounts[1]);
+        require_keys_eq!(
+            purchase_info.key(),
+            pda::purchase_pda(ruling.purchase_id).0,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        let mut purchase_account = Account::<PurchaseAccount>::try_from(purchase_info)?;
+        require_keys_eq!(
+            trade_info.key(),
+            pda::trade_pda(purchase_account.trade_id).0,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        let trade_account = Account::<TradeAccount>::try_from(trade_info)?;
+
+        // A purchase that cannot be ruled is reported and the rest go ahead
+        let error = if purchase_account.token_mint != token_mint {
+            Some(LogisticsError::InvalidTokenMint)
+        } else {
+            purchase_account.ruling_error(
+                &trade_account.seller,
+                &ruling.winner,
+                ruling.slash_provider,
+            )
+        };
+        if let Some(error) = error {
+            emit!(DisputeRulingSkipped {
+                event_seq: ctx.accounts.global_state.next_event_seq(),
+                purchase_id: ruling.purchase_id,
+                arbitrator,
+                error_code: error.into(),
+            });
+            skipped += 1;
+            continue;
+        }
+
+        purchase_account.set_dispute_ruling(&DisputeRuling {
+            arbitrator,
+            winner: ruling.winner,
+            slash_provider: ruling.slash_provider,
+            ruled_at: now,
+            escalated_by: Pubkey::default(),
+            escalation_fee: 0,
+        })?;
+        let new_len = 8 + purchase_account.try_to_vec()?.len();
+        grow_account(
+            purchase_info,
+            &ctx.accounts.arbitrator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+        purchase_account.exit(&crate::ID)?;
+
+        emit!(DisputeRuled {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id: ruling.purchase_id,
+            arbitrator,
+            winner: ruling.winner,
+            slash_provider: ruling.slash_provider,
+            escalation_deadline,
+        });
+        ruled += 1;
+    }
+
+    emit!(DisputesBatchRuled {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        arbitrator,
+        token_mint,
+        ruled,
+        skipped,
+    });
+    Ok(())
+}
+
+pub fn escalate_dispute(ctx: Context<EscalateDispute>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let escalator = ctx.accounts.escalator.key();
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    let mut ruling = purchase_account
+        .dispute_ruling()
+        .ok_or(LogisticsError::NoDisputeRuling)?;
+    require!(
+        ruling.escalated_by == Pubkey::default(),
+        LogisticsError::AlreadyEscalated
+    );
+    let is_party = escalator == purchase_account.buyer
+        || escalator == ctx.accounts.trade_account.seller
+        || escalator == purchase_account.chosen_logistics_provider;
+    require!(
+        is_party && escalator != ruling.winner,
+        LogisticsError::NotAuthorized
+    );
+
+    let global_state = &ctx.accounts.global_state;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now <= ruling.ruled_at + global_state.escalation_window_seconds,
+        LogisticsError::EscalationWindowClosed
+    );
+
+    let escalation_fee = compute_escalation_fee(
+        purchase_account.total_amount,
+        global_state.escalation_fee_bps,
+    );
+    if escalation_fee > 0 {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escalator_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.escalator.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, escalation_fee)?;
+    }
+
+    ruling.escalated_by = escalator;
+    ruling.escalation_fee = escalation_fee;
+    purchase_account.set_dispute_ruling(&ruling)?;
+
+    emit!(DisputeEscalated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        escalated_by: escalator,
+        escalation_fee,
+    });
+    Ok(())
+}
+
+pub fn join_jury_pool(ctx: Context<JoinJuryPool>) -> Result<()> {
+    let juror = ctx.accounts.juror.key();
+    let jury_pool = &mut ctx.accounts.jury_pool;
+    require!(
+        jury_pool.position(&juror).is_none(),
+        LogisticsError::AlreadyJuror
+    );
+    require!(
+        jury_pool.jurors.len() < MAX_JURY_POOL_SIZE,
+        LogisticsError::JuryPoolFull
+    );
+
+    let stake = jury_pool.config.juror_stake;
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.juror_token_account.to_account_info(),
+            to: ctx.accounts.jury_vault.to_account_info(),
+            authority: ctx.accounts.juror.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, stake)?;
+    jury_pool.jurors.push(JurorEntry {
+        juror,
+        stake,
+        active_cases: 0,
+    });
+
+    emit!(JurorJoined {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        juror,
+        stake,
+    });
+    Ok(())
+}
+
+pub fn leave_jury_pool(ctx: Context<LeaveJuryPool>) -> Result<()> {
+    let juror = ctx.accounts.juror.key();
+    let jury_pool = &mut ctx.accounts.jury_pool;
+    let position = jury_pool.position(&juror).ok_or(LogisticsError::NotJuror)?;
+    require!(
+        jury_pool.jurors[position].active_cases == 0,
+        LogisticsError::JurorHasActiveCases
+    );
+    let entry = jury_pool.jurors.remove(position);
+
+    let seeds = &[pda::JURY_VAULT_SEED, &[jury_pool.vault_bump]];
+    let signer = &[&seeds[..]];
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.jury_vault.to_account_info(),
+            to: ctx.accounts.juror_token_account.to_account_info(),
+            authority: ctx.accounts.jury_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, entry.stake)?;
+
+    emit!(JurorLeft {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        juror,
+        stake: entry.stake,
+    });
+    Ok(())
+}
+
+pub fn summon_jury(ctx: Context<SummonJury>, purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(purchase_account.disputed, LogisticsError::NotDisputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    // Streamed payouts disputed after confirmation go to `resolve_stream_dispute`
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(
+        purchase_account.dispute_ruling().is_none(),
+        LogisticsError::AlreadyRuled
+    );
+    require!(
+        purchase_account.jury_case().is_none(),
+        LogisticsError::JuryActive
+    );
+
+    let summoned_by = ctx.accounts.summoner.key();
+    let parties = [
+        purchase_account.buyer,
+        ctx.accounts.trade_account.seller,
+        purchase_account.chosen_logistics_provider,
+    ];
+    require!(
+        parties.contains(&summoned_by),
+        LogisticsError::NotAuthorized
+    );
+
+    let jury_pool = &mut ctx.accounts.jury_pool;
+    let jury_size = jury_pool.config.jury_size as usize;
+    let candidates = jury_pool.eligible_jurors(&parties);
+    require!(
+        candidates.len() >= jury_size,
+        LogisticsError::NotEnoughJurors
+    );
+    let slot_hash = recent_slot_hash(&ctx.accounts.slot_hashes)?;
+    let seed = hashv(&[slot_hash.as_ref(), &purchase_id.to_le_bytes()]).to_bytes();
+    let jurors = draw_jurors(&candidates, &seed, jury_size);
+    for juror in &jurors {
+        if let Some(i) = jury_pool.position(juror) {
+            jury_pool.jurors[i].active_cases += 1;
+        }
+    }
+
+    let voting_ends_at = Clock::get()?.unix_timestamp + jury_pool.config.voting_window_seconds;
+    purchase_account.set_jury_case(&JuryCase {
+        jurors: jurors.clone(),
+        votes: vec![Pubkey::default(); jurors.len()],
+        voting_ends_at,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.summoner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(JurySummoned {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        summoned_by,
+        jurors,
+        voting_ends_at,
+    });
+    Ok(())
+}
+
+pub fn cast_jury_vote(ctx: Context<CastJuryVote>, purchase_id: u64, winner: Pubkey) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut case = purchase_account
+        .jury_case()
+        .ok_or(LogisticsError::NoJuryCase)?;
+    require!(
+        Clock::get()?.unix_timestamp <= case.voting_ends_at,
+        LogisticsError::VotingClosed
+    );
+
+    let juror = ctx.accounts.juror.key();
+    let seat = case
+        .jurors
+        .iter()
+        .position(|seated| *seated == juror)
+        .ok_or(LogisticsError::NotJuror)?;
+    require!(
+        case.votes[seat] == Pubkey::default(),
+        LogisticsError::AlreadyVoted
+    );
+    let valid_winner = winner == purchase_account.buyer
+        || winner == ctx.accounts.trade_account.seller
+        || winner == purchase_account.chosen_logistics_provider;
+    require!(valid_winner, LogisticsError::InvalidWinner);
+
+    case.votes[seat] = winner;
+    purchase_account.set_jury_case(&case)?;
+
+    emit!(JuryVoteCast {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        juror,
+        winner,
+    });
+    Ok(())
+}
+
+pub fn finalize_jury_verdict(ctx: Context<FinalizeJuryVerdict>, purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let case = purchase_account
+        .jury_case()
+        .ok_or(LogisticsError::NoJuryCase)?;
+    let now = Clock::get()?.unix_timestamp;
+    // A dispute the admin settled in the meantime only releases the jurors
+    require!(
+        purchase_account.settled || case.all_voted() || now > case.voting_ends_at,
+        LogisticsError::VotingOpen
+    );
+
+    let majority = case.majority().filter(|_| !purchase_account.settled);
+    let winner = majority.map(|(winner, _)| winner);
+    let stake_slashed = ctx.accounts.jury_pool.settle_case(&case, winner);
+    remove_extension(&mut purchase_account.extensions, ExtensionType::JuryCase);
+
+    // The verdict stands as a first-tier ruling: parties may escalate it to
+    // the admin, and `resolve_dispute` executes it once the window passes
+    if let Some(winner) = winner {
+        let jury_pool = ctx.accounts.jury_pool.key();
+        purchase_account.set_dispute_ruling(&DisputeRuling {
+            arbitrator: jury_pool,
+            winner,
+            slash_provider: false,
+            ruled_at: now,
+            escalated_by: Pubkey::default(),
+            escalation_fee: 0,
+        })?;
+        let new_len = 8 + purchase_account.try_to_vec()?.len();
+        grow_account(
+            &purchase_account.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        emit!(DisputeRuled {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id,
+            arbitrator: jury_pool,
+            winner,
+            slash_provider: false,
+            escalation_deadline: now + ctx.accounts.global_state.escalation_window_seconds,
+        });
+    }
+
+    emit!(JuryVerdict {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        winner,
+        votes_for_winner: majority.map_or(0, |(_, votes)| votes as u8),
+        votes_cast: case.votes_cast().len() as u8,
+        stake_slashed,
+    });
+    Ok(())
+}
+
+pub fn resolve_dispute<'info>(
+    ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+    purchase_id: u64,
+    winner: Pubkey,
+    slash_provider: bool,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let trade_account = &mut ctx.accounts.trade_account;
+    // Always passed, so a seller resolving a ruling against them cannot leave
+    // the loss unrecorded
+    let seller_info = ctx.accounts.seller_account.to_account_info();
+    let mut seller_account = load_seller_account(&seller_info)?;
+
+    require!(purchase_account.disputed, LogisticsError::NotDisputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+
+    // Validate winner
+    let valid_winner = winner == purchase_account.buyer
+        || winner == trade_account.seller
+        || winner == purchase_account.chosen_logistics_provider;
+    require!(valid_winner, LogisticsError::InvalidWinner);
+
+    let now = Clock::get()?.unix_timestamp;
+    let ruling = purchase_account.dispute_ruling();
+    if ctx.accounts.authority.key() != ctx.accounts.global_state.admin {
+        let ruling = ruling.ok_or(LogisticsError::NotAuthorized)?;
+        require!(
+            ruling.escalated_by == Pubkey::default(),
+            LogisticsError::AlreadyEscalated
+        );
+        require!(
+            now > ruling.ruled_at + ctx.accounts.global_state.escalation_window_seconds,
+            LogisticsError::EscalationWindowOpen
+        );
+        require!(
+            winner == ruling.winner && slash_provider == ruling.slash_provider,
+            LogisticsError::RulingMismatch
+        );
+    }
+
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_id,
+    );
+    let provider_info = ctx.accounts.provider_account.to_account_info();
+    let mut provider_account = count_provider_purchases_closed(
+        &provider_info,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+
+    let mut fee_reversed = 0u64;
+    if winner == purchase_account.buyer {
+        // The escrow fee is never collected on a refund
+        fee_reversed = compute_fee_reversal(purchase_account)?;
+
+        // Insured loss or damage lets the seller recover the goods' value
+        if purchase_account.insurance_status == InsuranceStatus::Active
+            && purchase_account.dispute_reason.is_insured()
+        {
+            purchase_account.insurance_status = InsuranceStatus::Claimable;
+        }
+
+        // Restore quantity
+        trade_account.record_refund(purchase_account.quantity);
+    }
+    // A resolution of an escalated ruling has already been re-reviewed
+    purchase_account.set_dispute_resolution(&DisputeResolution {
+        winner,
+        appeal_deadline: now + ctx.accounts.global_state.escalation_window_seconds,
+        appealed_by: Pubkey::default(),
+        appeal_bond: 0,
+        is_final: ruling.is_some_and(|ruling| ruling.escalated_by != Pubkey::default()),
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+    commit_settlement(purchase_account)?;
+
+    let escrow_bump = ctx.bumps.escrow_token_account;
+
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[escrow_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mut buyer_refund_amount = 0u64;
+    let mut seller_amount = 0u64;
+    let mut logistics_amount = 0u64;
+    let mut fee_amount = 0u64;
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+
+    if winner == purchase_account.buyer {
+        // Refund buyer
+        refund_escrow(
+            &token_program,
+            purchase_account,
+            ctx.accounts.product_escrow.as_ref(),
+            ctx.accounts.logistics_escrow.as_ref(),
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            0,
+            signer,
+        )?;
+        buyer_refund_amount = purchase_account.total_amount;
+    } else {
+        // Pay seller and logistics provider
+        let amounts = purchase_account.settlement_payouts()?;
+        seller_amount = amounts.seller_amount;
+        logistics_amount = amounts.logistics_amount;
+        fee_amount = amounts.fee_amount;
+
+        let product_escrow = escrow_bucket(
+            purchase_account,
+            ctx.accounts.product_escrow.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?;
+        let logistics_escrow = escrow_bucket(
+            purchase_account,
+            ctx.accounts.logistics_escrow.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?;
+
+        pay_seller_proceeds(
+            &mut ctx.accounts.global_state,
+            &token_program,
+            &product_escrow,
+            &escrow_vault,
+            seller_payout_account(
+                seller_account.as_ref(),
+                ctx.accounts.seller_token_account.as_ref(),
+                ctx.accounts.seller_payout_token_account.as_ref(),
+                purchase_account.token_mint,
+            )?,
+            ctx.accounts.seller_claimable.as_mut(),
+            ctx.remaining_accounts,
+            trade_account,
+            purchase_account.token_mint,
+            seller_amount,
+            signer,
+        )?;
+
+        transfer_or_credit(
+            &mut ctx.accounts.global_state,
+            &token_program,
+            &logistics_escrow,
+            &escrow_vault,
+            provider_payout_account(
+                provider_account.as_ref(),
+                ctx.accounts.logistics_token_account.as_ref(),
+                ctx.accounts.logistics_payout_token_account.as_ref(),
+                purchase_account.token_mint,
+            )?,
+            ctx.accounts.logistics_claimable.as_mut(),
+            purchase_account.chosen_logistics_provider,
+            purchase_account.token_mint,
+            logistics_amount,
+            signer,
+        )?;
+
+        sweep_to_vault(
+            &token_program,
+            &product_escrow,
+            &escrow_vault,
+            amounts.product_fee,
+            signer,
+        )?;
+        sweep_to_vault(
+            &token_program,
+            &logistics_escrow,
+            &escrow_vault,
+            amounts.logistics_fee,
+            signer,
+        )?;
+        collect_fees(
+            &token_program,
+            &escrow_vault,
+            &ctx.accounts.fee_vault.to_account_info(),
+            fee_amount,
+            signer,
+        )?;
+    }
+
+    if slash_provider {
+        require!(
+            winner != purchase_account.chosen_logistics_provider,
+            LogisticsError::InvalidWinner
+        );
+        slash_provider_stake(
+            &mut ctx.accounts.global_state,
+            provider_account.as_mut(),
+            ctx.accounts.stake_vault.as_ref(),
+            ctx.accounts.buyer_stake_token_account.as_ref(),
+            &ctx.accounts.token_program,
+            purchase_id,
+        )?;
+        if let Some(provider_account) = provider_account.as_ref() {
+            store_provider_account(&provider_info, provider_account)?;
+        }
+    } else if winner == purchase_account.buyer {
+        if let Some(seller_account) = seller_account.as_mut() {
+            record_seller_dispute_loss(
+                &mut ctx.accounts.global_state,
+                seller_account,
+                ctx.accounts.bond_vault.as_ref(),
+                ctx.accounts.buyer_bond_token_account.as_ref(),
+                &ctx.accounts.token_program,
+                purchase_id,
+            )?;
+            store_seller_account(&seller_info, seller_account)?;
+        }
+    }
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.disputes_resolved += 1;
+        if winner == purchase_account.buyer {
+            stats.disputes_won_by_buyer += 1;
+            stats.record_fee_reversal(purchase_account.token_mint, fee_reversed);
+        } else {
+            stats.purchases_settled += 1;
+            stats.record_volume(
+                purchase_account.token_mint,
+                purchase_account.total_amount,
+                fee_amount,
+            );
+        }
+    }
+
+    // The escalation fee goes to whoever won the re-adjudication
+    if let Some(ruling) = ruling.filter(|ruling| ruling.escalation_fee > 0) {
+        if winner == purchase_account.buyer {
+            release_escrow(
+                &token_program,
+                &escrow_vault,
+                &escrow_vault,
+                &ctx.accounts.buyer_token_account.to_account_info(),
+                ruling.escalation_fee,
+                signer,
+            )?;
+        } else if winner == trade_account.seller {
+            transfer_or_credit(
+                &mut ctx.accounts.global_state,
+                &token_program,
+                &escrow_vault,
+                &escrow_vault,
+                seller_payout_account(
+                    seller_account.as_ref(),
+                    ctx.accounts.seller_token_account.as_ref(),
+                    ctx.accounts.seller_payout_token_account.as_ref(),
+                    purchase_account.token_mint,
+                )?,
+                ctx.accounts.seller_claimable.as_mut(),
+                trade_account.seller,
+                purchase_account.token_mint,
+                ruling.escalation_fee,
+                signer,
+            )?;
+        } else {
+            transfer_or_credit(
+                &mut ctx.accounts.global_state,
+                &token_program,
+                &escrow_vault,
+                &escrow_vault,
+                provider_payout_account(
+                    provider_account.as_ref(),
+                    ctx.accounts.logistics_token_account.as_ref(),
+                    ctx.accounts.logistics_payout_token_account.as_ref(),
+                    purchase_account.token_mint,
+                )?,
+                ctx.accounts.logistics_claimable.as_mut(),
+                purchase_account.chosen_logistics_provider,
+                purchase_account.token_mint,
+                ruling.escalation_fee,
+                signer,
+            )?;
+        }
+        emit!(EscalationFeeReleased {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id,
+            recipient: winner,
+            amount: ruling.escalation_fee,
+        });
+    }
+    purchase_account.unlock_settlement();
+
+    if fee_reversed > 0 {
+        emit!(FeeReversed {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id,
+            buyer: purchase_account.buyer,
+            token_mint: purchase_account.token_mint,
+            fee_amount: fee_reversed,
+            unix_timestamp: now,
+        });
+    }
+
+    let event = DisputeResolved {
+        schema_version: EVENT_SCHEMA_VERSION,
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        winner,
+        reason: purchase_account.dispute_reason,
+        token_mint: purchase_account.token_mint,
+        buyer_refund_amount,
+        seller_amount,
+        logistics_amount,
+        fee_amount,
+        unix_timestamp: now,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn appeal_resolution(ctx: Context<AppealResolution>, purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut resolution = purchase_account
+        .dispute_resolution()
+        .ok_or(LogisticsError::NotSettled)?;
+    require!(!resolution.is_final, LogisticsError::AlreadyAppealed);
+    require!(!resolution.is_appealed(), LogisticsError::AlreadyAppealed);
+    require!(
+        Clock::get()?.unix_timestamp <= resolution.appeal_deadline,
+        LogisticsError::AppealWindowClosed
+    );
+    let appellant = ctx.accounts.appellant.key();
+    let is_party = appellant == purchase_account.buyer
+        || appellant == ctx.accounts.trade_account.seller
+        || appellant == purchase_account.chosen_logistics_provider;
+    require!(
+        is_party && appellant != resolution.winner,
+        LogisticsError::NotAuthorized
+    );
+
+    let appeal_bond = compute_escalation_fee(
+        purchase_account.total_amount,
+        ctx.accounts.global_state.escalation_fee_bps,
+    );
+    if appeal_bond > 0 {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.appellant_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.appellant.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, appeal_bond)?;
+    }
+
+    resolution.appealed_by = appellant;
+    resolution.appeal_bond = appeal_bond;
+    purchase_account.set_dispute_resolution(&resolution)?;
+
+    emit!(ResolutionAppealed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        appellant,
+        appeal_bond,
+    });
+    Ok(())
+}
+
+pub fn review_appeal(ctx: Context<ReviewAppeal>, purchase_id: u64, upheld: bool) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut resolution = purchase_account
+        .dispute_resolution()
+        .filter(|resolution| resolution.is_appealed() && !resolution.is_final)
+        .ok_or(LogisticsError::NotAppealed)?;
+
+    // The bond compensates the winner for a failed appeal and is returned
+    // when the appeal succeeds
+    let bond_recipient = if upheld {
+        resolution.winner
+    } else {
+        resolution.appealed_by
+    };
+    require_keys_eq!(
+        ctx.accounts.recipient_token_account.owner,
+        bond_recipient,
+        LogisticsError::InvalidTokenOwner
+    );
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    release_escrow(
+        &ctx.accounts.token_program.to_account_info(),
+        &escrow_vault,
+        &escrow_vault,
+        &ctx.accounts.recipient_token_account.to_account_info(),
+        resolution.appeal_bond,
+        &[&seeds[..]],
+    )?;
+
+    resolution.is_final = true;
+    purchase_account.set_dispute_resolution(&resolution)?;
+
+    emit!(AppealReviewed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        upheld,
+        bond_recipient,
+        appeal_bond: resolution.appeal_bond,
+    });
+    Ok(())
+}
+
+pub fn resolve_stream_dispute(
+    ctx: Context<ResolveStreamDispute>,
+    _purchase_id: u64,
+    winner: Pubkey,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut stream = purchase_account
+        .seller_stream()
+        .filter(|stream| stream.halted_at > 0 && stream.refunded == 0)
+        .ok_or(LogisticsError::StreamNotRunning)?;
+
+    let mut refunded = 0;
+    if winner == purchase_account.buyer {
+        refunded = stream.amount - stream.vested(stream.halted_at);
+        stream.refunded = refunded;
+        let seeds = &[
+            pda::ESCROW_SEED,
+            purchase_account.token_mint.as_ref(),
+            &[ctx.bumps.escrow_token_account],
+        ];
+        release_escrow(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.product_escrow.to_account_info(),
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            refunded,
+            &[&seeds[..]],
+        )?;
+    } else if winner == ctx.accounts.trade_account.seller {
+        // Vesting resumes from the original schedule, catching up on the halt
+        stream.halted_at = 0;
+    } else {
+        return err!(LogisticsError::InvalidWinner);
+    }
+    purchase_account.set_seller_stream(&stream)?;
+
+    emit!(StreamDisputeResolved {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        winner,
+        refunded,
+    });
+    Ok(())
+}
+
+pub fn preview_resolution(
+    ctx: Context<PreviewResolution>,
+    _purchase_id: u64,
+    winner: Pubkey,
+) -> Result<ResolutionPreview> {
+    let purchase_account = &ctx.accounts.purchase_account;
+    require!(purchase_account.disputed, LogisticsError::NotDisputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+
+    compute_resolution_preview(&ctx.accounts.trade_account, purchase_account, winner)
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct RaiseDispute<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    /// Buyer, seller or the chosen logistics provider.
+    #[account(
+        mut,
+        constraint = user.key() == purchase_account.buyer
+            || user.key() == trade_account.seller
+            || user.key() == purchase_account.chosen_logistics_provider
+            @ LogisticsError::NotAuthorized
+    )]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ArbitrateDispute<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        seeds = [pda::ARBITRATOR_SEED, arbitrator.key().as_ref()],
+        bump = arbitrator_account.bump,
+        has_one = arbitrator @ LogisticsError::NotAuthorized
+    )]
+    pub arbitrator_account: Account<'info, ArbitratorAccount>,
+    #[account(mut)]
+    pub arbitrator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDisputesBatch<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::ARBITRATOR_SEED, arbitrator.key().as_ref()],
+        bump = arbitrator_account.bump,
+        has_one = arbitrator @ LogisticsError::NotAuthorized
+    )]
+    pub arbitrator_account: Account<'info, ArbitratorAccount>,
+    #[account(mut)]
+    pub arbitrator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct EscalateDispute<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = escalator_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = escalator_token_account.owner == escalator.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub escalator_token_account: Account<'info, TokenAccount>,
+    pub escalator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct JoinJuryPool<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_POOL_SEED],
+        bump = jury_pool.bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_VAULT_SEED],
+        bump = jury_pool.vault_bump
+    )]
+    pub jury_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = jury_vault.mint,
+        token::authority = juror
+    )]
+    pub juror_token_account: Account<'info, TokenAccount>,
+    pub juror: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveJuryPool<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_POOL_SEED],
+        bump = jury_pool.bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_VAULT_SEED],
+        bump = jury_pool.vault_bump
+    )]
+    pub jury_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = jury_vault.mint,
+        token::authority = juror
+    )]
+    pub juror_token_account: Account<'info, TokenAccount>,
+    pub juror: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct SummonJury<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_POOL_SEED],
+        bump = jury_pool.bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    /// CHECK: SlotHashes sysvar, whose latest hash seeds the draw
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// Buyer, seller or logistics provider of the disputed purchase
+    #[account(mut)]
+    pub summoner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct CastJuryVote<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    pub juror: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct FinalizeJuryVerdict<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::JURY_POOL_SEED],
+        bump = jury_pool.bump
+    )]
+    pub jury_pool: Account<'info, JuryPool>,
+    /// Anyone may finalize once voting ends
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Seller wallet, only used as the authority of its associated token account
+    #[account(address = trade_account.seller @ LogisticsError::InvalidTokenOwner)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Logistics provider wallet, only used as the authority of its associated token account
+    #[account(address = purchase_account.chosen_logistics_provider @ LogisticsError::InvalidTokenOwner)]
+    pub logistics_provider: UncheckedAccount<'info>,
+    #[account(address = purchase_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `seller_account`, which replaces
+    /// `seller_token_account` when set
+    #[account(mut)]
+    pub seller_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = logistics_provider
+    )]
+    pub logistics_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `provider_account`, which replaces
+    /// `logistics_token_account` when set
+    #[account(mut)]
+    pub logistics_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, trade_account.seller.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = seller_claimable.bump
+    )]
+    pub seller_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, purchase_account.chosen_logistics_provider.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = logistics_claimable.bump
+    )]
+    pub logistics_claimable: Option<Account<'info, ClaimableBalance>>,
+    /// Token account the protocol's share of escrow fees is collected in
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::STAKE_VAULT_SEED, purchase_account.chosen_logistics_provider.as_ref()],
+        bump
+    )]
+    pub stake_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = global_state.stake_mint,
+        token::authority = purchase_account.buyer
+    )]
+    pub buyer_stake_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Seller account PDA, which may not exist; parsed by the handler when initialized
+    #[account(
+        mut,
+        seeds = [pda::SELLER_SEED, trade_account.seller.as_ref()],
+        bump
+    )]
+    pub seller_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::SELLER_BOND_SEED, trade_account.seller.as_ref()],
+        bump
+    )]
+    pub bond_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::authority = purchase_account.buyer
+    )]
+    pub buyer_bond_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    /// Admin, or anyone executing a final first-tier ruling
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ResolveStreamDispute<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct AppealResolution<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = appellant_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = appellant_token_account.owner == appellant.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub appellant_token_account: Account<'info, TokenAccount>,
+    pub appellant: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ReviewAppeal<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Token account of the resolution's winner if the appeal is rejected,
+    /// otherwise of the appellant
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct PreviewResolution<'info> {
+    #[account(
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+}
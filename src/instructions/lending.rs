@@ -0,0 +1,301 @@
+//! Opt-in deposits of pending escrow into the configured lending program.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount};
+
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn deposit_escrow<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositEscrow<'info>>,
+    _purchase_id: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        purchase_account.split_escrow,
+        LogisticsError::MissingEscrowBucket
+    );
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(
+        purchase_account.lending_position().is_none(),
+        LogisticsError::EscrowLent
+    );
+    let principal = ctx.accounts.product_escrow.amount;
+    require!(principal > 0, LogisticsError::InvalidAmount);
+
+    // Record the position before any tokens leave escrow
+    purchase_account.set_lending_position(&LendingPosition {
+        principal,
+        lending_program: ctx.accounts.lending_program.key(),
+        deposited_at: Clock::get()?.unix_timestamp,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+    commit_settlement(purchase_account)?;
+
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    invoke_lending_route(
+        &ctx.accounts.lending_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.key(),
+        &[
+            ctx.accounts.product_escrow.key(),
+            ctx.accounts.lending_receipt.key(),
+        ],
+        ctx.remaining_accounts,
+        route_data,
+        signer,
+    )?;
+    ctx.accounts.product_escrow.reload()?;
+    ctx.accounts.lending_receipt.reload()?;
+
+    // The whole product portion is lent, so settlement never sees a partial balance
+    require!(
+        ctx.accounts.product_escrow.amount == 0,
+        LogisticsError::InvalidLendingRoute
+    );
+    let receipt_amount = ctx.accounts.lending_receipt.amount;
+    require!(receipt_amount > 0, LogisticsError::InvalidLendingRoute);
+    purchase_account.unlock_settlement();
+
+    emit!(EscrowDeposited {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        lending_program: ctx.accounts.lending_program.key(),
+        principal,
+        receipt_amount,
+    });
+    Ok(())
+}
+
+pub fn withdraw_escrow<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawEscrow<'info>>,
+    _purchase_id: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let position = purchase_account
+        .lending_position()
+        .ok_or(LogisticsError::EscrowNotLent)?;
+    require_keys_eq!(
+        ctx.accounts.lending_program.key(),
+        position.lending_program,
+        LogisticsError::InvalidLendingRoute
+    );
+    purchase_account.clear_lending_position();
+    commit_settlement(purchase_account)?;
+
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    let balance_before = ctx.accounts.product_escrow.amount;
+    invoke_lending_route(
+        &ctx.accounts.lending_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.key(),
+        &[
+            ctx.accounts.product_escrow.key(),
+            ctx.accounts.lending_receipt.key(),
+        ],
+        ctx.remaining_accounts,
+        route_data,
+        signer,
+    )?;
+    ctx.accounts.product_escrow.reload()?;
+    ctx.accounts.lending_receipt.reload()?;
+
+    require!(
+        ctx.accounts.lending_receipt.amount == 0,
+        LogisticsError::InvalidLendingRoute
+    );
+    let returned = ctx
+        .accounts
+        .product_escrow
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(LogisticsError::InvalidLendingRoute)?;
+    require_gte!(
+        returned,
+        position.principal,
+        LogisticsError::LendingShortfall
+    );
+
+    let yield_amount = returned - position.principal;
+    let buyer_yield = compute_buyer_yield(
+        yield_amount,
+        ctx.accounts.global_state.yield_buyer_share_bps,
+    );
+    let platform_yield = yield_amount - buyer_yield;
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    let product_escrow = ctx.accounts.product_escrow.to_account_info();
+    release_escrow(
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        &ctx.accounts.buyer_token_account.to_account_info(),
+        buyer_yield,
+        signer,
+    )?;
+    // The platform's share joins the escrow fees in the mint's fee vault
+    release_escrow(
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        &ctx.accounts.fee_vault.to_account_info(),
+        platform_yield,
+        signer,
+    )?;
+
+    let close_ctx = CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+            account: ctx.accounts.lending_receipt.to_account_info(),
+            destination: ctx.accounts.buyer.to_account_info(),
+            authority: escrow_vault,
+        },
+        signer,
+    );
+    token::close_account(close_ctx)?;
+    purchase_account.unlock_settlement();
+
+    emit!(EscrowWithdrawn {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        principal: position.principal,
+        yield_amount,
+        buyer_yield,
+        platform_yield,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct DepositEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    /// Receipt token minted by the lending program for the purchase mint
+    pub receipt_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [pda::LENDING_RECEIPT_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump,
+        token::mint = receipt_mint,
+        token::authority = escrow_token_account
+    )]
+    pub lending_receipt: Account<'info, TokenAccount>,
+    /// CHECK: Lending program configured by the admin; only invoked with a buyer-approved route
+    #[account(
+        executable,
+        constraint = global_state.lending_program != Pubkey::default() @ LogisticsError::LendingDisabled,
+        address = global_state.lending_program @ LogisticsError::InvalidLendingRoute
+    )]
+    pub lending_program: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct WithdrawEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::LENDING_RECEIPT_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lending_receipt: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = purchase_account.token_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(address = purchase_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// Fee vault of the purchase mint, credited the platform's share of the yield
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// CHECK: Must be the program the escrow was deposited into
+    #[account(executable)]
+    pub lending_program: UncheckedAccount<'info>,
+    /// Chooses the redeem route, so only the buyer may withdraw; receives the
+    /// receipt account's rent
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
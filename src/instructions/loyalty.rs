@@ -0,0 +1,161 @@
+//! Buyer loyalty points earned on settled purchases and redeemed for escrow
+//! fee discounts.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn open_loyalty_account(ctx: Context<OpenLoyaltyAccount>) -> Result<()> {
+    let loyalty_account = &mut ctx.accounts.loyalty_account;
+    loyalty_account.buyer = ctx.accounts.buyer.key();
+    loyalty_account.version = ACCOUNT_VERSION;
+    loyalty_account.bump = ctx.bumps.loyalty_account;
+    Ok(())
+}
+
+pub fn accrue_loyalty_points(ctx: Context<AccrueLoyaltyPoints>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        purchase_account.settled && purchase_account.delivered_and_confirmed,
+        LogisticsError::NotSettled
+    );
+    require!(
+        purchase_account.loyalty_points_accrued().is_none(),
+        LogisticsError::LoyaltyAlreadyAccrued
+    );
+
+    // Units delivered through partial confirmations count towards the volume
+    let volume = purchase_account
+        .quantity
+        .checked_add(purchase_account.delivered_quantity)
+        .and_then(|quantity| quantity.checked_mul(purchase_account.unit_price))
+        .ok_or(LogisticsError::MathOverflow)?;
+    let points =
+        compute_loyalty_points(volume, ctx.accounts.global_state.loyalty_config.points_bps);
+
+    purchase_account.set_loyalty_points_accrued(points)?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    let loyalty_account = &mut ctx.accounts.loyalty_account;
+    loyalty_account.points = loyalty_account
+        .points
+        .checked_add(points)
+        .ok_or(LogisticsError::MathOverflow)?;
+    loyalty_account.lifetime_points = loyalty_account
+        .lifetime_points
+        .checked_add(points)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    emit!(LoyaltyPointsAccrued {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        points,
+    });
+    Ok(())
+}
+
+pub fn redeem_loyalty_points(ctx: Context<RedeemLoyaltyPoints>, points: u64) -> Result<()> {
+    let config = ctx.accounts.global_state.loyalty_config;
+    let loyalty_account = &mut ctx.accounts.loyalty_account;
+    require!(
+        config.points_per_discount_bps > 0 && points <= loyalty_account.points,
+        LogisticsError::InvalidLoyaltyRedemption
+    );
+
+    // Only whole basis points are bought; leftover points stay in the balance
+    let discount_bps = points / config.points_per_discount_bps;
+    let total_discount_bps = (loyalty_account.discount_bps as u64)
+        .checked_add(discount_bps)
+        .ok_or(LogisticsError::MathOverflow)?;
+    require!(
+        discount_bps > 0 && total_discount_bps <= config.max_discount_bps as u64,
+        LogisticsError::InvalidLoyaltyRedemption
+    );
+
+    let spent = discount_bps * config.points_per_discount_bps;
+    loyalty_account.points -= spent;
+    loyalty_account.discount_bps = total_discount_bps as u16;
+
+    emit!(LoyaltyPointsRedeemed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer: loyalty_account.buyer,
+        points: spent,
+        discount_bps: discount_bps as u16,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct OpenLoyaltyAccount<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LoyaltyAccount::INIT_SPACE,
+        seeds = [pda::LOYALTY_SEED, buyer.key().as_ref()],
+        bump
+    )]
+    pub loyalty_account: Account<'info, LoyaltyAccount>,
+    /// CHECK: Wallet the loyalty account belongs to
+    pub buyer: UncheckedAccount<'info>,
+    /// Anyone may open a buyer's loyalty account, typically the buyer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct AccrueLoyaltyPoints<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::LOYALTY_SEED, purchase_account.buyer.as_ref()],
+        bump = loyalty_account.bump
+    )]
+    pub loyalty_account: Account<'info, LoyaltyAccount>,
+    /// Anyone may credit a settled purchase, typically the buyer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemLoyaltyPoints<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::LOYALTY_SEED, buyer.key().as_ref()],
+        bump = loyalty_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub loyalty_account: Account<'info, LoyaltyAccount>,
+    pub buyer: Signer<'info>,
+}
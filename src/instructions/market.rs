@@ -0,0 +1,1572 @@
+//! Auctions, sealed drops, offers and subscriptions that open purchases
+//! outside `buy_trade`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn create_subscription(
+    ctx: Context<CreateSubscription>,
+    trade_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    interval_seconds: i64,
+    cycles: u32,
+) -> Result<()> {
+    let trade_account = &ctx.accounts.trade_account;
+    let buyer = ctx.accounts.buyer.key();
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    require!(buyer != trade_account.seller, LogisticsError::BuyerIsSeller);
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    require!(
+        trade_account.price_feed.is_none()
+            && interval_seconds >= MIN_SUBSCRIPTION_INTERVAL_SECONDS
+            && cycles > 0
+            && cycles <= MAX_SUBSCRIPTION_CYCLES,
+        LogisticsError::InvalidSubscription
+    );
+
+    let max_cycle_amount = trade_account.fixed_price_amount(&logistics_provider, quantity)?;
+    let allowance = max_cycle_amount
+        .checked_mul(cycles as u64)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.trade_id = trade_id;
+    subscription.buyer = buyer;
+    subscription.buyer_token_account = ctx.accounts.buyer_token_account.key();
+    subscription.token_mint = trade_account.token_mint;
+    subscription.quantity = quantity;
+    subscription.logistics_provider = logistics_provider;
+    subscription.max_cycle_amount = max_cycle_amount;
+    subscription.interval_seconds = interval_seconds;
+    subscription.cycles_total = cycles;
+    subscription.cycles_completed = 0;
+    subscription.next_cycle_at = Clock::get()?.unix_timestamp;
+    subscription.version = ACCOUNT_VERSION;
+    subscription.bump = ctx.bumps.subscription;
+
+    let approve_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            delegate: ctx.accounts.subscription.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::approve(approve_ctx, allowance)?;
+
+    emit!(SubscriptionCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer,
+        quantity,
+        logistics_provider,
+        interval_seconds,
+        cycles,
+        max_cycle_amount,
+    });
+    Ok(())
+}
+
+pub fn process_subscription_cycle(
+    ctx: Context<ProcessSubscriptionCycle>,
+    trade_id: u64,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let subscription = &mut ctx.accounts.subscription;
+    let trade_account = &mut ctx.accounts.trade_account;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        subscription.cycles_completed < subscription.cycles_total,
+        LogisticsError::SubscriptionComplete
+    );
+    require!(
+        now >= subscription.next_cycle_at,
+        LogisticsError::SubscriptionCycleNotDue
+    );
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    trade_account.check_stock(subscription.quantity)?;
+
+    let quantity = subscription.quantity;
+    let logistics_cost =
+        trade_account.primary_logistics_cost(&subscription.logistics_provider, quantity)?;
+    count_provider_purchase_opened(
+        &ctx.accounts.provider_account,
+        &subscription.logistics_provider,
+    )?;
+    let product_amount = trade_account
+        .product_cost
+        .checked_mul(quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let cycle_amount = product_amount
+        .checked_add(logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    require!(
+        cycle_amount <= subscription.max_cycle_amount,
+        LogisticsError::SubscriptionPriceAboveCap
+    );
+
+    let trade_id_bytes = trade_id.to_le_bytes();
+    let seeds = &[
+        pda::SUBSCRIPTION_SEED,
+        trade_id_bytes.as_ref(),
+        subscription.buyer.as_ref(),
+        &[subscription.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.product_escrow.to_account_info(),
+            authority: subscription.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, product_amount)?;
+    if logistics_cost > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.logistics_escrow.to_account_info(),
+                authority: subscription.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, logistics_cost)?;
+    }
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    ctx.accounts.purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id,
+        buyer: subscription.buyer,
+        payer: ctx.accounts.cranker.key(),
+        token_mint: subscription.token_mint,
+        quantity,
+        unit_price: trade_account.product_cost,
+        logistics_provider: subscription.logistics_provider,
+        logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash: None,
+        fill_price: 0,
+        fill_expo: 0,
+        insurance_premium: 0,
+        insured: false,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: ctx.bumps.purchase_account,
+    })?;
+
+    trade_account.record_sale(quantity)?;
+    if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        trade_account.purchase_ids.push(purchase_id);
+    }
+
+    subscription.cycles_completed += 1;
+    subscription.next_cycle_at = now
+        .checked_add(subscription.interval_seconds)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id,
+        buyer: subscription.buyer,
+        quantity,
+        token_mint: subscription.token_mint,
+        memo_hash: None,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(SubscriptionCycleProcessed {
+        event_seq: global_state.next_event_seq(),
+        trade_id,
+        buyer: subscription.buyer,
+        purchase_id,
+        cycle: subscription.cycles_completed,
+        amount: cycle_amount,
+    });
+    Ok(())
+}
+
+pub fn cancel_subscription(ctx: Context<CancelSubscription>, _trade_id: u64) -> Result<()> {
+    let revoke_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Revoke {
+            source: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::revoke(revoke_ctx)?;
+
+    let subscription = &ctx.accounts.subscription;
+    emit!(SubscriptionCancelled {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id: subscription.trade_id,
+        buyer: subscription.buyer,
+        cycles_completed: subscription.cycles_completed,
+    });
+    Ok(())
+}
+
+pub fn make_offer(
+    ctx: Context<MakeOffer>,
+    trade_id: u64,
+    unit_price: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    expires_at: i64,
+) -> Result<()> {
+    let trade_account = &ctx.accounts.trade_account;
+    let buyer = ctx.accounts.buyer.key();
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    require!(
+        trade_account.price_feed.is_none(),
+        LogisticsError::InvalidOffer
+    );
+    require!(buyer != trade_account.seller, LogisticsError::BuyerIsSeller);
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    trade_account.check_stock(quantity)?;
+    require!(
+        unit_price > 0 && expires_at > Clock::get()?.unix_timestamp,
+        LogisticsError::InvalidOffer
+    );
+
+    let logistics_cost = trade_account.primary_logistics_cost(&logistics_provider, quantity)?;
+    let offer = &mut ctx.accounts.offer;
+    offer.trade_id = trade_id;
+    offer.buyer = buyer;
+    offer.token_mint = trade_account.token_mint;
+    offer.unit_price = unit_price;
+    offer.quantity = quantity;
+    offer.logistics_provider = logistics_provider;
+    offer.logistics_cost = logistics_cost;
+    offer.expires_at = expires_at;
+    offer.version = ACCOUNT_VERSION;
+    offer.bump = ctx.bumps.offer;
+    let escrowed_amount = offer.escrowed_amount()?;
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, escrowed_amount)?;
+
+    emit!(OfferMade {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer,
+        unit_price,
+        quantity,
+        logistics_provider,
+        escrowed_amount,
+        expires_at,
+    });
+    Ok(())
+}
+
+pub fn accept_offer(ctx: Context<AcceptOffer>, trade_id: u64) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let offer = &ctx.accounts.offer;
+    let trade_account = &mut ctx.accounts.trade_account;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < offer.expires_at, LogisticsError::OfferExpired);
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    trade_account.check_stock(offer.quantity)?;
+    count_provider_purchase_opened(&ctx.accounts.provider_account, &offer.logistics_provider)?;
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    ctx.accounts.purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id,
+        buyer: offer.buyer,
+        payer: ctx.accounts.seller.key(),
+        token_mint: offer.token_mint,
+        quantity: offer.quantity,
+        unit_price: offer.unit_price,
+        logistics_provider: offer.logistics_provider,
+        logistics_cost: offer.logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash: None,
+        fill_price: 0,
+        fill_expo: 0,
+        insurance_premium: 0,
+        insured: false,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: ctx.bumps.purchase_account,
+    })?;
+
+    // The offer was escrowed in the mint vault; split it into the sub-vaults
+    let seeds = &[
+        pda::ESCROW_SEED,
+        offer.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+    fund_escrow_buckets(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.to_account_info(),
+        &ctx.accounts.product_escrow.to_account_info(),
+        &ctx.accounts.logistics_escrow.to_account_info(),
+        &ctx.accounts.purchase_account,
+        signer,
+    )?;
+
+    trade_account.record_sale(offer.quantity)?;
+    if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        trade_account.purchase_ids.push(purchase_id);
+    }
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id,
+        buyer: offer.buyer,
+        quantity: offer.quantity,
+        token_mint: offer.token_mint,
+        memo_hash: None,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(OfferAccepted {
+        event_seq: global_state.next_event_seq(),
+        trade_id,
+        buyer: offer.buyer,
+        purchase_id,
+        unit_price: offer.unit_price,
+    });
+    Ok(())
+}
+
+pub fn withdraw_offer(ctx: Context<WithdrawOffer>, _trade_id: u64) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    require!(
+        Clock::get()?.unix_timestamp >= offer.expires_at,
+        LogisticsError::OfferNotExpired
+    );
+
+    let refund_amount = offer.escrowed_amount()?;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        offer.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow_token_account.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.escrow_token_account.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, refund_amount)?;
+
+    emit!(OfferWithdrawn {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id: offer.trade_id,
+        buyer: offer.buyer,
+        refund_amount,
+    });
+    Ok(())
+}
+
+pub fn create_auction(
+    ctx: Context<CreateAuction>,
+    trade_id: u64,
+    quantity: u64,
+    start_price: u64,
+    min_increment: u64,
+    end_time: i64,
+) -> Result<()> {
+    let trade_account = &mut ctx.accounts.trade_account;
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    require!(
+        trade_account.price_feed.is_none(),
+        LogisticsError::InvalidAuction
+    );
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    trade_account.check_stock(quantity)?;
+    require!(
+        start_price > 0 && min_increment > 0 && end_time > Clock::get()?.unix_timestamp,
+        LogisticsError::InvalidAuction
+    );
+
+    trade_account.take_stock(quantity)?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.trade_id = trade_id;
+    auction.seller = trade_account.seller;
+    auction.token_mint = trade_account.token_mint;
+    auction.quantity = quantity;
+    auction.start_price = start_price;
+    auction.min_increment = min_increment;
+    auction.end_time = end_time;
+    auction.leading_bidder = Pubkey::default();
+    auction.leading_bid = 0;
+    auction.leading_logistics_provider = Pubkey::default();
+    auction.leading_logistics_cost = 0;
+    auction.version = ACCOUNT_VERSION;
+    auction.bump = ctx.bumps.auction;
+
+    emit!(AuctionCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        seller: auction.seller,
+        token_mint: auction.token_mint,
+        quantity,
+        start_price,
+        min_increment,
+        end_time,
+    });
+    Ok(())
+}
+
+pub fn place_bid(
+    ctx: Context<PlaceBid>,
+    _trade_id: u64,
+    unit_bid: u64,
+    logistics_provider: Pubkey,
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    let trade_account = &ctx.accounts.trade_account;
+    let bidder = ctx.accounts.bidder.key();
+    require!(
+        Clock::get()?.unix_timestamp < auction.end_time,
+        LogisticsError::AuctionEnded
+    );
+    require!(bidder != auction.seller, LogisticsError::BuyerIsSeller);
+    require!(
+        unit_bid >= auction.min_next_bid()?,
+        LogisticsError::BidTooLow
+    );
+
+    let logistics_cost =
+        trade_account.primary_logistics_cost(&logistics_provider, auction.quantity)?;
+    let bid_amount = unit_bid
+        .checked_mul(auction.quantity)
+        .and_then(|product| product.checked_add(logistics_cost))
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.bidder_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.bidder.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, bid_amount)?;
+
+    if auction.leading_bidder != Pubkey::default() {
+        let previous_bidder_token_account = ctx
+            .accounts
+            .previous_bidder_token_account
+            .as_ref()
+            .ok_or(LogisticsError::MissingPayoutAccounts)?;
+        let refund_amount = auction.leading_amount()?;
+        let seeds = &[
+            pda::ESCROW_SEED,
+            auction.token_mint.as_ref(),
+            &[ctx.bumps.escrow_token_account],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: previous_bidder_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_token_account.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, refund_amount)?;
+    }
+
+    auction.leading_bidder = bidder;
+    auction.leading_bid = unit_bid;
+    auction.leading_logistics_provider = logistics_provider;
+    auction.leading_logistics_cost = logistics_cost;
+
+    emit!(BidPlaced {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id: auction.trade_id,
+        bidder,
+        unit_bid,
+        logistics_provider,
+        escrowed_amount: bid_amount,
+    });
+    Ok(())
+}
+
+pub fn finalize_auction(ctx: Context<FinalizeAuction>, trade_id: u64) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let auction = &ctx.accounts.auction;
+    let trade_account = &mut ctx.accounts.trade_account;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= auction.end_time, LogisticsError::AuctionNotEnded);
+
+    if auction.leading_bidder == Pubkey::default() {
+        trade_account.return_stock(auction.quantity);
+        emit!(AuctionFinalized {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            trade_id,
+            winner: None,
+            purchase_id: None,
+            unit_price: 0,
+        });
+        return Ok(());
+    }
+
+    let Some(purchase_account) = ctx.accounts.purchase_account.as_mut() else {
+        return err!(LogisticsError::InvalidPurchaseAccount);
+    };
+    let purchase_bump = ctx.bumps.purchase_account;
+    count_provider_purchase_opened(
+        &ctx.accounts.provider_account,
+        &auction.leading_logistics_provider,
+    )?;
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id,
+        buyer: auction.leading_bidder,
+        payer: ctx.accounts.payer.key(),
+        token_mint: auction.token_mint,
+        quantity: auction.quantity,
+        unit_price: auction.leading_bid,
+        logistics_provider: auction.leading_logistics_provider,
+        logistics_cost: auction.leading_logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash: None,
+        fill_price: 0,
+        fill_expo: 0,
+        insurance_premium: 0,
+        insured: false,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: purchase_bump,
+    })?;
+
+    // The winning bid was escrowed in the mint vault; split it into the sub-vaults
+    let (Some(product_escrow), Some(logistics_escrow)) = (
+        ctx.accounts.product_escrow.as_ref(),
+        ctx.accounts.logistics_escrow.as_ref(),
+    ) else {
+        return err!(LogisticsError::MissingEscrowBucket);
+    };
+    let seeds = &[
+        pda::ESCROW_SEED,
+        auction.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+    fund_escrow_buckets(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.escrow_token_account.to_account_info(),
+        &product_escrow.to_account_info(),
+        &logistics_escrow.to_account_info(),
+        purchase_account,
+        signer,
+    )?;
+
+    // The lot left stock when the auction opened
+    trade_account.sold_quantity += auction.quantity;
+    if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        trade_account.purchase_ids.push(purchase_id);
+    }
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id,
+        buyer: auction.leading_bidder,
+        quantity: auction.quantity,
+        token_mint: auction.token_mint,
+        memo_hash: None,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(AuctionFinalized {
+        event_seq: global_state.next_event_seq(),
+        trade_id,
+        winner: Some(auction.leading_bidder),
+        purchase_id: Some(purchase_id),
+        unit_price: auction.leading_bid,
+    });
+    Ok(())
+}
+
+pub fn create_sealed_drop(
+    ctx: Context<CreateSealedDrop>,
+    trade_id: u64,
+    quantity: u64,
+    max_quantity_per_commit: u64,
+    commit_end: i64,
+    reveal_end: i64,
+) -> Result<()> {
+    let trade_account = &mut ctx.accounts.trade_account;
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    require!(
+        trade_account.price_feed.is_none(),
+        LogisticsError::InvalidSealedDrop
+    );
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    trade_account.check_stock(quantity)?;
+    require!(
+        max_quantity_per_commit > 0
+            && max_quantity_per_commit <= quantity
+            && commit_end > Clock::get()?.unix_timestamp
+            && reveal_end > commit_end,
+        LogisticsError::InvalidSealedDrop
+    );
+
+    trade_account.take_stock(quantity)?;
+
+    let sealed_drop = &mut ctx.accounts.sealed_drop;
+    sealed_drop.trade_id = trade_id;
+    sealed_drop.seller = trade_account.seller;
+    sealed_drop.token_mint = trade_account.token_mint;
+    sealed_drop.quantity = quantity;
+    sealed_drop.unit_price = trade_account.product_cost;
+    sealed_drop.max_quantity_per_commit = max_quantity_per_commit;
+    sealed_drop.commit_end = commit_end;
+    sealed_drop.reveal_end = reveal_end;
+    sealed_drop.commit_count = 0;
+    sealed_drop.claimed_count = 0;
+    sealed_drop.revealed_quantity = 0;
+    sealed_drop.allocated_quantity = 0;
+    sealed_drop.version = ACCOUNT_VERSION;
+    sealed_drop.bump = ctx.bumps.sealed_drop;
+
+    emit!(SealedDropCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        seller: sealed_drop.seller,
+        token_mint: sealed_drop.token_mint,
+        quantity,
+        unit_price: sealed_drop.unit_price,
+        max_quantity_per_commit,
+        commit_end,
+        reveal_end,
+    });
+    Ok(())
+}
+
+pub fn commit_purchase(
+    ctx: Context<CommitPurchase>,
+    trade_id: u64,
+    commitment: [u8; 32],
+    logistics_provider: Pubkey,
+) -> Result<()> {
+    let sealed_drop = &mut ctx.accounts.sealed_drop;
+    let buyer = ctx.accounts.buyer.key();
+    require!(
+        Clock::get()?.unix_timestamp < sealed_drop.commit_end,
+        LogisticsError::CommitWindowClosed
+    );
+    require!(buyer != sealed_drop.seller, LogisticsError::BuyerIsSeller);
+
+    // Every commit escrows the largest allowed order, so the deposit says
+    // nothing about the quantity sealed in the commitment
+    let logistics_cost = ctx
+        .accounts
+        .trade_account
+        .primary_logistics_cost(&logistics_provider, sealed_drop.max_quantity_per_commit)?;
+    let escrowed_amount = sealed_drop
+        .unit_price
+        .checked_mul(sealed_drop.max_quantity_per_commit)
+        .and_then(|product| product.checked_add(logistics_cost))
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token_account.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, escrowed_amount)?;
+
+    sealed_drop.commit_count = sealed_drop
+        .commit_count
+        .checked_add(1)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    let sealed_commit = &mut ctx.accounts.sealed_commit;
+    sealed_commit.trade_id = trade_id;
+    sealed_commit.buyer = buyer;
+    sealed_commit.commitment = commitment;
+    sealed_commit.logistics_provider = logistics_provider;
+    sealed_commit.escrowed_amount = escrowed_amount;
+    sealed_commit.revealed_quantity = 0;
+    sealed_commit.version = ACCOUNT_VERSION;
+    sealed_commit.bump = ctx.bumps.sealed_commit;
+
+    emit!(PurchaseCommitted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer,
+        escrowed_amount,
+    });
+    Ok(())
+}
+
+pub fn reveal_purchase(
+    ctx: Context<RevealPurchase>,
+    trade_id: u64,
+    quantity: u64,
+    salt: [u8; 32],
+) -> Result<()> {
+    let sealed_drop = &mut ctx.accounts.sealed_drop;
+    let sealed_commit = &mut ctx.accounts.sealed_commit;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= sealed_drop.commit_end && now < sealed_drop.reveal_end,
+        LogisticsError::RevealWindowNotOpen
+    );
+    require!(
+        sealed_commit.revealed_quantity == 0
+            && quantity > 0
+            && quantity <= sealed_drop.max_quantity_per_commit
+            && sealed_commitment(&sealed_commit.buyer, quantity, &salt) == sealed_commit.commitment,
+        LogisticsError::InvalidReveal
+    );
+
+    sealed_commit.revealed_quantity = quantity;
+    sealed_drop.revealed_quantity = sealed_drop
+        .revealed_quantity
+        .checked_add(quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    emit!(PurchaseRevealed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer: sealed_commit.buyer,
+        quantity,
+    });
+    Ok(())
+}
+
+pub fn claim_sealed_purchase(ctx: Context<ClaimSealedPurchase>, trade_id: u64) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let sealed_drop = &mut ctx.accounts.sealed_drop;
+    let sealed_commit = &ctx.accounts.sealed_commit;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= sealed_drop.reveal_end,
+        LogisticsError::SealedDropNotEnded
+    );
+
+    // Unrevealed commits get nothing and are refunded in full
+    let allocated_quantity = sealed_drop.allocation(sealed_commit.revealed_quantity);
+    let seeds = &[
+        pda::ESCROW_SEED,
+        sealed_drop.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mut purchase_id = None;
+    let mut purchase_amount = 0;
+    if allocated_quantity > 0 {
+        let Some(purchase_account) = ctx.accounts.purchase_account.as_mut() else {
+            return err!(LogisticsError::InvalidPurchaseAccount);
+        };
+        let purchase_bump = ctx.bumps.purchase_account;
+        let logistics_cost = ctx
+            .accounts
+            .trade_account
+            .primary_logistics_cost(&sealed_commit.logistics_provider, allocated_quantity)?;
+        count_provider_purchase_opened(
+            &ctx.accounts.provider_account,
+            &sealed_commit.logistics_provider,
+        )?;
+
+        let global_state = &mut ctx.accounts.global_state;
+        global_state.purchase_counter += 1;
+        let id = global_state.purchase_counter;
+
+        purchase_account.open(PurchaseTerms {
+            purchase_id: id,
+            trade_id,
+            buyer: sealed_commit.buyer,
+            payer: ctx.accounts.payer.key(),
+            token_mint: sealed_drop.token_mint,
+            quantity: allocated_quantity,
+            unit_price: sealed_drop.unit_price,
+            logistics_provider: sealed_commit.logistics_provider,
+            logistics_cost,
+            fee_bps: global_state.fee_bps,
+            memo_hash: None,
+            fill_price: 0,
+            fill_expo: 0,
+            insurance_premium: 0,
+            insured: false,
+            created_at: now,
+            fulfillment_start: ctx.accounts.trade_account.preorder_start(),
+            bump: purchase_bump,
+        })?;
+        purchase_amount = purchase_account.total_amount;
+
+        let (Some(product_escrow), Some(logistics_escrow)) = (
+            ctx.accounts.product_escrow.as_ref(),
+            ctx.accounts.logistics_escrow.as_ref(),
+        ) else {
+            return err!(LogisticsError::MissingEscrowBucket);
+        };
+        fund_escrow_buckets(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &product_escrow.to_account_info(),
+            &logistics_escrow.to_account_info(),
+            purchase_account,
+            signer,
+        )?;
+
+        let trade_account = &mut ctx.accounts.trade_account;
+        trade_account.sold_quantity += allocated_quantity;
+        if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+            trade_account.purchase_ids.push(id);
+        }
+
+        let event = PurchaseCreated {
+            event_seq: global_state.next_event_seq(),
+            purchase_id: id,
+            trade_id,
+            buyer: sealed_commit.buyer,
+            quantity: allocated_quantity,
+            token_mint: sealed_drop.token_mint,
+            memo_hash: None,
+        };
+        #[cfg(feature = "cpi-events")]
+        emit_cpi!(event);
+        #[cfg(not(feature = "cpi-events"))]
+        emit!(event);
+        purchase_id = Some(id);
+    }
+
+    let refund_amount = sealed_commit
+        .escrowed_amount
+        .checked_sub(purchase_amount)
+        .ok_or(LogisticsError::MathOverflow)?;
+    if refund_amount > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_token_account.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, refund_amount)?;
+    }
+
+    sealed_drop.claimed_count += 1;
+    sealed_drop.allocated_quantity = sealed_drop
+        .allocated_quantity
+        .checked_add(allocated_quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    emit!(SealedPurchaseClaimed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer: sealed_commit.buyer,
+        revealed_quantity: sealed_commit.revealed_quantity,
+        allocated_quantity,
+        purchase_id,
+        refund_amount,
+    });
+    Ok(())
+}
+
+pub fn close_sealed_drop(ctx: Context<CloseSealedDrop>, trade_id: u64) -> Result<()> {
+    let sealed_drop = &ctx.accounts.sealed_drop;
+    require!(
+        Clock::get()?.unix_timestamp >= sealed_drop.reveal_end
+            && sealed_drop.claimed_count == sealed_drop.commit_count,
+        LogisticsError::SealedDropNotEnded
+    );
+
+    // Rounding dust and unsold units go back on sale
+    let returned_quantity = sealed_drop.quantity - sealed_drop.allocated_quantity;
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.return_stock(returned_quantity);
+
+    emit!(SealedDropClosed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        allocated_quantity: sealed_drop.allocated_quantity,
+        returned_quantity,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CreateAuction<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + AuctionAccount::INIT_SPACE,
+        seeds = [pda::AUCTION_SEED, trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionAccount>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct PlaceBid<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::AUCTION_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Account<'info, AuctionAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, auction.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = bidder_token_account.mint == auction.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = bidder_token_account.owner == bidder.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub bidder_token_account: Account<'info, TokenAccount>,
+    /// Required once the auction has a leading bid
+    #[account(
+        mut,
+        constraint = previous_bidder_token_account.mint == auction.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = previous_bidder_token_account.owner == auction.leading_bidder.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub previous_bidder_token_account: Option<Account<'info, TokenAccount>>,
+    pub bidder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct FinalizeAuction<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::AUCTION_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        has_one = seller,
+        close = seller
+    )]
+    pub auction: Account<'info, AuctionAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// Required when the auction has a winner
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Option<Account<'info, PurchaseAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Required when the auction has a winner
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Required when the auction has a winner
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(address = auction.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    /// CHECK: Seller wallet receiving the auction account's rent
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CreateSealedDrop<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + SealedDropAccount::INIT_SPACE,
+        seeds = [pda::SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub sealed_drop: Account<'info, SealedDropAccount>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CommitPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = sealed_drop.bump
+    )]
+    pub sealed_drop: Account<'info, SealedDropAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SealedCommitAccount::INIT_SPACE,
+        seeds = [pda::SEALED_COMMIT_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub sealed_commit: Account<'info, SealedCommitAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, sealed_drop.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == sealed_drop.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct RevealPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = sealed_drop.bump
+    )]
+    pub sealed_drop: Account<'info, SealedDropAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_COMMIT_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = sealed_commit.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub sealed_commit: Account<'info, SealedCommitAccount>,
+    pub buyer: Signer<'info>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct ClaimSealedPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = sealed_drop.bump
+    )]
+    pub sealed_drop: Account<'info, SealedDropAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_COMMIT_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = sealed_commit.bump,
+        has_one = buyer,
+        close = buyer
+    )]
+    pub sealed_commit: Account<'info, SealedCommitAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// Required when the commit is allocated any units
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Option<Account<'info, PurchaseAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Required when the commit is allocated any units
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Required when the commit is allocated any units
+    #[account(
+        init,
+        payer = payer,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(address = sealed_drop.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Buyer wallet receiving the commit account's rent
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    /// Anyone may claim once reveals close, typically the buyer or seller
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CloseSealedDrop<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = sealed_drop.bump,
+        has_one = seller,
+        close = seller
+    )]
+    pub sealed_drop: Account<'info, SealedDropAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// CHECK: Seller wallet receiving the drop account's rent
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CreateSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SubscriptionAccount::INIT_SPACE,
+        seeds = [pda::SUBSCRIPTION_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, SubscriptionAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == trade_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct ProcessSubscriptionCycle<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SUBSCRIPTION_SEED, trade_id.to_le_bytes().as_ref(), subscription.buyer.as_ref()],
+        bump = subscription.bump,
+        has_one = buyer_token_account @ LogisticsError::InvalidTokenOwner
+    )]
+    pub subscription: Account<'info, SubscriptionAccount>,
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, subscription.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = cranker,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = cranker,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Account<'info, TokenAccount>,
+    #[account(address = subscription.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SUBSCRIPTION_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = subscription.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized,
+        has_one = buyer_token_account @ LogisticsError::InvalidTokenOwner,
+        close = buyer
+    )]
+    pub subscription: Account<'info, SubscriptionAccount>,
+    #[account(mut)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct MakeOffer<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + OfferAccount::INIT_SPACE,
+        seeds = [pda::OFFER_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, OfferAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, trade_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == trade_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct AcceptOffer<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::OFFER_SEED, trade_id.to_le_bytes().as_ref(), offer.buyer.as_ref()],
+        bump = offer.bump,
+        has_one = buyer,
+        close = buyer
+    )]
+    pub offer: Account<'info, OfferAccount>,
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, offer.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = seller,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = seller,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Account<'info, TokenAccount>,
+    #[account(address = offer.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    /// CHECK: Buyer wallet receiving the offer account's rent
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct WithdrawOffer<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::OFFER_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized,
+        close = buyer
+    )]
+    pub offer: Account<'info, OfferAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, offer.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == offer.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,21 @@
+pub mod admin;
+pub mod dispute;
+pub mod lending;
+pub mod loyalty;
+pub mod market;
+pub mod participant;
+pub mod purchase;
+pub mod receipt;
+pub mod returns;
+pub mod trade;
+
+pub use admin::*;
+pub use dispute::*;
+pub use lending::*;
+pub use loyalty::*;
+pub use market::*;
+pub use participant::*;
+pub use purchase::*;
+pub use receipt::*;
+pub use returns::*;
+pub use trade::*;
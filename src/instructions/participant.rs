@@ -0,0 +1,943 @@
+//! Registration and profiles of sellers, buyers and logistics providers, and
+//! buyer attestations.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn register_logistics_provider(ctx: Context<RegisterLogisticsProvider>) -> Result<()> {
+    let stake_amount = ctx.accounts.global_state.provider_stake_amount;
+    if stake_amount > 0 {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.provider_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.provider.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, stake_amount)?;
+    }
+
+    let provider_account = &mut ctx.accounts.provider_account;
+    provider_account.provider = ctx.accounts.provider.key();
+    provider_account.is_registered = true;
+    provider_account.staked_amount = stake_amount;
+    provider_account.deregistered_at = 0;
+    provider_account.open_purchases = 0;
+    provider_account.payout_token_account = ctx
+        .accounts
+        .payout_token_account
+        .as_ref()
+        .map(|account| account.key());
+    provider_account.stake_vault_bump = ctx.bumps.stake_vault;
+    provider_account.version = ACCOUNT_VERSION;
+    provider_account.bump = ctx.bumps.provider_account;
+
+    emit!(LogisticsProviderRegistered {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        provider: ctx.accounts.provider.key(),
+    });
+    Ok(())
+}
+
+pub fn set_provider_profile(
+    ctx: Context<SetProviderProfile>,
+    regions: u16,
+    base_price: u64,
+    price_per_kg: u64,
+) -> Result<()> {
+    require!(regions != 0, LogisticsError::InvalidRegions);
+    require!(
+        regions & !DeliveryRegion::ALL_MASK == 0,
+        LogisticsError::InvalidRegions
+    );
+
+    let provider_account = &mut ctx.accounts.provider_account;
+    require!(
+        provider_account.is_registered,
+        LogisticsError::ProviderNotRegistered
+    );
+    provider_account.regions = regions;
+    provider_account.base_price = base_price;
+    provider_account.price_per_kg = price_per_kg;
+
+    emit!(ProviderProfileUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        provider: ctx.accounts.provider.key(),
+        regions,
+        base_price,
+        price_per_kg,
+    });
+    Ok(())
+}
+
+pub fn update_provider_payout(ctx: Context<UpdateProviderPayout>) -> Result<()> {
+    let payout_token_account = ctx
+        .accounts
+        .payout_token_account
+        .as_ref()
+        .map(|account| account.key());
+    let provider_account = &mut ctx.accounts.provider_account;
+    require!(
+        provider_account.is_registered,
+        LogisticsError::ProviderNotRegistered
+    );
+    provider_account.payout_token_account = payout_token_account;
+
+    emit!(ProviderPayoutAccountUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        provider: provider_account.provider,
+        payout_token_account,
+    });
+    Ok(())
+}
+
+pub fn deregister_logistics_provider(ctx: Context<DeregisterLogisticsProvider>) -> Result<()> {
+    let provider_account = &mut ctx.accounts.provider_account;
+    require!(
+        provider_account.is_registered,
+        LogisticsError::ProviderNotRegistered
+    );
+    // Deregistering stops new purchases; the stake stays held until the open
+    // ones settle
+    provider_account.is_registered = false;
+    provider_account.deregistered_at = Clock::get()?.unix_timestamp;
+
+    emit!(LogisticsProviderDeregistered {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        provider: ctx.accounts.provider.key(),
+        unlocks_at: provider_account.deregistered_at
+            + ctx.accounts.global_state.stake_cooldown_seconds,
+    });
+    Ok(())
+}
+
+pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+    let provider_account = &mut ctx.accounts.provider_account;
+    require!(
+        !provider_account.is_registered,
+        LogisticsError::ProviderStillRegistered
+    );
+    require!(
+        provider_account.open_purchases == 0,
+        LogisticsError::ProviderHasOpenPurchases
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= provider_account.deregistered_at + ctx.accounts.global_state.stake_cooldown_seconds,
+        LogisticsError::StakeCooldownActive
+    );
+
+    let amount = ctx.accounts.stake_vault.amount;
+    require!(amount > 0, LogisticsError::NoStakeToWithdraw);
+    provider_account.staked_amount = 0;
+
+    let provider_key = ctx.accounts.provider.key();
+    let seeds = &[
+        pda::STAKE_VAULT_SEED,
+        provider_key.as_ref(),
+        &[provider_account.stake_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.stake_vault.to_account_info(),
+            to: ctx.accounts.provider_token_account.to_account_info(),
+            authority: ctx.accounts.stake_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(StakeWithdrawn {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        provider: provider_key,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn register_seller(ctx: Context<RegisterSeller>) -> Result<()> {
+    let seller_account = &mut ctx.accounts.seller_account;
+    seller_account.seller = ctx.accounts.seller.key();
+    seller_account.is_registered = true;
+    seller_account.version = ACCOUNT_VERSION;
+    seller_account.bump = ctx.bumps.seller_account;
+    Ok(())
+}
+
+pub fn set_payout_preference(
+    ctx: Context<SetPayoutPreference>,
+    preferred_mint: Option<Pubkey>,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    require!(
+        max_slippage_bps as u64 <= BASIS_POINTS,
+        LogisticsError::InvalidSlippage
+    );
+
+    let seller_account = &mut ctx.accounts.seller_account;
+    seller_account.preferred_mint = preferred_mint;
+    seller_account.max_slippage_bps = max_slippage_bps;
+
+    emit!(PayoutPreferenceUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: seller_account.seller,
+        preferred_mint,
+        max_slippage_bps,
+    });
+    Ok(())
+}
+
+pub fn set_seller_payout_account(ctx: Context<SetSellerPayoutAccount>) -> Result<()> {
+    let payout_token_account = ctx
+        .accounts
+        .payout_token_account
+        .as_ref()
+        .map(|account| account.key());
+    let seller_account = &mut ctx.accounts.seller_account;
+    seller_account.payout_token_account = payout_token_account;
+
+    emit!(SellerPayoutAccountUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: seller_account.seller,
+        payout_token_account,
+    });
+    Ok(())
+}
+
+pub fn register_seller_with_bond(
+    ctx: Context<RegisterSellerWithBond>,
+    bond_amount: u64,
+) -> Result<()> {
+    require!(bond_amount > 0, LogisticsError::InvalidBondAmount);
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.seller_token_account.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, bond_amount)?;
+
+    let seller_account = &mut ctx.accounts.seller_account;
+    seller_account.seller = ctx.accounts.seller.key();
+    seller_account.is_registered = true;
+    seller_account.bond_mint = ctx.accounts.bond_mint.key();
+    seller_account.bond_amount = bond_amount;
+    seller_account.bonded_at = Clock::get()?.unix_timestamp;
+    seller_account.disputes_lost = 0;
+    seller_account.cancellations = 0;
+    seller_account.bond_vault_bump = ctx.bumps.bond_vault;
+    seller_account.version = ACCOUNT_VERSION;
+    seller_account.bump = ctx.bumps.seller_account;
+
+    emit!(SellerBondPosted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: seller_account.seller,
+        bond_mint: seller_account.bond_mint,
+        amount: bond_amount,
+    });
+    Ok(())
+}
+
+pub fn release_bond(ctx: Context<ReleaseBond>) -> Result<()> {
+    let seller_account = &mut ctx.accounts.seller_account;
+    require!(
+        seller_account.disputes_lost == 0,
+        LogisticsError::BondLocked
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= seller_account.bonded_at + ctx.accounts.global_state.seller_probation_seconds,
+        LogisticsError::ProbationActive
+    );
+
+    let amount = ctx.accounts.bond_vault.amount;
+    require!(amount > 0, LogisticsError::NoBondToRelease);
+    seller_account.bond_amount = 0;
+
+    let seller_key = ctx.accounts.seller.key();
+    let seeds = &[
+        pda::SELLER_BOND_SEED,
+        seller_key.as_ref(),
+        &[seller_account.bond_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.bond_vault.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(SellerBondReleased {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: seller_key,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn set_confirmation_delegate(
+    ctx: Context<SetConfirmationDelegate>,
+    delegate: Option<Pubkey>,
+) -> Result<()> {
+    let buyer_account = &mut ctx.accounts.buyer_account;
+    require!(
+        delegate != Some(buyer_account.buyer),
+        LogisticsError::InvalidConfirmationDelegate
+    );
+    buyer_account.confirmation_delegate = delegate;
+
+    emit!(ConfirmationDelegateUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer: buyer_account.buyer,
+        delegate,
+    });
+    Ok(())
+}
+
+pub fn issue_attestation(
+    ctx: Context<IssueAttestation>,
+    subject: Pubkey,
+    expires_at: i64,
+    region: Option<DeliveryRegion>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at == 0 || expires_at > now,
+        LogisticsError::InvalidAttestation
+    );
+
+    let attestation = &mut ctx.accounts.attestation;
+    attestation.verifier = ctx.accounts.verifier.key();
+    attestation.subject = subject;
+    attestation.issued_at = now;
+    attestation.expires_at = expires_at;
+    attestation.region = region;
+    attestation.version = ACCOUNT_VERSION;
+    attestation.bump = ctx.bumps.attestation;
+
+    emit!(AttestationIssued {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        verifier: attestation.verifier,
+        subject,
+        expires_at,
+        region,
+    });
+    Ok(())
+}
+
+pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+    emit!(AttestationRevoked {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        verifier: ctx.accounts.attestation.verifier,
+        subject: ctx.accounts.attestation.subject,
+    });
+    Ok(())
+}
+
+pub fn create_spending_permit(
+    ctx: Context<CreateSpendingPermit>,
+    delegate: Pubkey,
+    max_total_amount: u64,
+    max_order_amount: u64,
+    expires_at: i64,
+) -> Result<()> {
+    let buyer = ctx.accounts.buyer.key();
+    require!(
+        delegate != buyer
+            && max_order_amount > 0
+            && max_order_amount <= max_total_amount
+            && expires_at > Clock::get()?.unix_timestamp,
+        LogisticsError::InvalidSpendingPermit
+    );
+
+    let spending_permit = &mut ctx.accounts.spending_permit;
+    spending_permit.buyer = buyer;
+    spending_permit.delegate = delegate;
+    spending_permit.buyer_token_account = ctx.accounts.buyer_token_account.key();
+    spending_permit.remaining_amount = max_total_amount;
+    spending_permit.max_order_amount = max_order_amount;
+    spending_permit.expires_at = expires_at;
+    spending_permit.version = ACCOUNT_VERSION;
+    spending_permit.bump = ctx.bumps.spending_permit;
+
+    let approve_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            delegate: ctx.accounts.spending_permit.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::approve(approve_ctx, max_total_amount)?;
+
+    emit!(SpendingPermitCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer,
+        delegate,
+        buyer_token_account: ctx.accounts.buyer_token_account.key(),
+        max_total_amount,
+        max_order_amount,
+        expires_at,
+    });
+    Ok(())
+}
+
+pub fn revoke_spending_permit(ctx: Context<RevokeSpendingPermit>) -> Result<()> {
+    let revoke_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Revoke {
+            source: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    token::revoke(revoke_ctx)?;
+
+    let spending_permit = &ctx.accounts.spending_permit;
+    emit!(SpendingPermitRevoked {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer: spending_permit.buyer,
+        delegate: spending_permit.delegate,
+        remaining_amount: spending_permit.remaining_amount,
+    });
+    Ok(())
+}
+
+pub fn create_session(
+    ctx: Context<CreateSession>,
+    session_key: Pubkey,
+    expires_at_slot: u64,
+    spend_cap: u64,
+) -> Result<()> {
+    let buyer = ctx.accounts.buyer.key();
+    let slot = Clock::get()?.slot;
+    require!(
+        session_key != buyer
+            && expires_at_slot > slot
+            && expires_at_slot - slot <= MAX_SESSION_SLOTS,
+        LogisticsError::InvalidSession
+    );
+
+    let session = &mut ctx.accounts.session;
+    session.buyer = buyer;
+    session.session_key = session_key;
+    session.buyer_token_account = ctx.accounts.buyer_token_account.key();
+    session.expires_at_slot = expires_at_slot;
+    session.spend_cap = spend_cap;
+    session.spent = 0;
+    session.version = ACCOUNT_VERSION;
+    session.bump = ctx.bumps.session;
+
+    // Confirmation-only sessions leave any existing delegation in place
+    if spend_cap > 0 {
+        let approve_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                delegate: ctx.accounts.session.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::approve(approve_ctx, spend_cap)?;
+    }
+
+    emit!(SessionCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer,
+        session_key,
+        expires_at_slot,
+        spend_cap,
+    });
+    Ok(())
+}
+
+pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    if ctx.accounts.buyer_token_account.delegate == COption::Some(session.key()) {
+        let revoke_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::revoke(revoke_ctx)?;
+    }
+
+    emit!(SessionRevoked {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        buyer: session.buyer,
+        session_key: session.session_key,
+        spent: session.spent,
+    });
+    Ok(())
+}
+
+pub fn register_buyer(ctx: Context<RegisterBuyer>) -> Result<()> {
+    let buyer_account = &mut ctx.accounts.buyer_account;
+    buyer_account.buyer = ctx.accounts.buyer.key();
+    buyer_account.is_registered = true;
+    buyer_account.purchase_ids = Vec::new();
+    buyer_account.version = ACCOUNT_VERSION;
+    buyer_account.bump = ctx.bumps.buyer_account;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterLogisticsProvider<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = provider,
+        space = 8 + LogisticsProviderAccount::INIT_SPACE,
+        seeds = [pda::LOGISTICS_PROVIDER_SEED, provider.key().as_ref()],
+        bump
+    )]
+    pub provider_account: Account<'info, LogisticsProviderAccount>,
+    #[account(
+        init,
+        payer = provider,
+        seeds = [pda::STAKE_VAULT_SEED, provider.key().as_ref()],
+        bump,
+        token::mint = stake_mint,
+        token::authority = stake_vault
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(address = global_state.stake_mint @ LogisticsError::InvalidStakeMint)]
+    pub stake_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = provider
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    /// Payout account settlements pay into; omit it to use the provider's
+    /// associated token account
+    pub payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProviderPayout<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_PROVIDER_SEED, provider.key().as_ref()],
+        bump = provider_account.bump,
+        has_one = provider
+    )]
+    pub provider_account: Account<'info, LogisticsProviderAccount>,
+    /// New payout account; omit it to pay the provider's associated token account again
+    pub payout_token_account: Option<Account<'info, TokenAccount>>,
+    pub provider: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterLogisticsProvider<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_PROVIDER_SEED, provider.key().as_ref()],
+        bump = provider_account.bump,
+        has_one = provider
+    )]
+    pub provider_account: Account<'info, LogisticsProviderAccount>,
+    pub provider: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_PROVIDER_SEED, provider.key().as_ref()],
+        bump = provider_account.bump,
+        has_one = provider
+    )]
+    pub provider_account: Account<'info, LogisticsProviderAccount>,
+    #[account(
+        mut,
+        seeds = [pda::STAKE_VAULT_SEED, provider.key().as_ref()],
+        bump = provider_account.stake_vault_bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = stake_vault.mint,
+        token::authority = provider
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    pub provider: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetProviderProfile<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_PROVIDER_SEED, provider.key().as_ref()],
+        bump = provider_account.bump,
+        has_one = provider
+    )]
+    pub provider_account: Account<'info, LogisticsProviderAccount>,
+    pub provider: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterSeller<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SellerAccount::INIT_SPACE,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub seller_account: Account<'info, SellerAccount>,
+    /// CHECK: This is the seller being registered
+    pub seller: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutPreference<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump = seller_account.bump,
+        has_one = seller
+    )]
+    pub seller_account: Account<'info, SellerAccount>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSellerPayoutAccount<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump = seller_account.bump,
+        has_one = seller
+    )]
+    pub seller_account: Account<'info, SellerAccount>,
+    /// New payout account; omit it to pay the seller's associated token account again
+    pub payout_token_account: Option<Account<'info, TokenAccount>>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterSellerWithBond<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SellerAccount::INIT_SPACE,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub seller_account: Account<'info, SellerAccount>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [pda::SELLER_BOND_SEED, seller.key().as_ref()],
+        bump,
+        token::mint = bond_mint,
+        token::authority = bond_vault
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+    pub bond_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = bond_mint,
+        token::authority = seller
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseBond<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump = seller_account.bump,
+        has_one = seller
+    )]
+    pub seller_account: Account<'info, SellerAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SELLER_BOND_SEED, seller.key().as_ref()],
+        bump = seller_account.bond_vault_bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = seller_account.bond_mint,
+        token::authority = seller
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetConfirmationDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::BUYER_SEED, buyer.key().as_ref()],
+        bump = buyer_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub buyer_account: Account<'info, BuyerAccount>,
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct IssueAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = verifier,
+        space = 8 + BuyerAttestation::INIT_SPACE,
+        seeds = [pda::ATTESTATION_SEED, verifier.key().as_ref(), subject.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, BuyerAttestation>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::ATTESTATION_SEED, verifier.key().as_ref(), attestation.subject.as_ref()],
+        bump = attestation.bump,
+        has_one = verifier @ LogisticsError::NotAuthorized,
+        close = verifier
+    )]
+    pub attestation: Account<'info, BuyerAttestation>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct CreateSpendingPermit<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SpendingPermit::INIT_SPACE,
+        seeds = [pda::SPENDING_PERMIT_SEED, buyer.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub spending_permit: Account<'info, SpendingPermit>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSpendingPermit<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SPENDING_PERMIT_SEED, buyer.key().as_ref(), spending_permit.delegate.as_ref()],
+        bump = spending_permit.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized,
+        has_one = buyer_token_account @ LogisticsError::InvalidTokenOwner,
+        close = buyer
+    )]
+    pub spending_permit: Account<'info, SpendingPermit>,
+    #[account(mut)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SessionAccount::INIT_SPACE,
+        seeds = [pda::SESSION_SEED, buyer.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::SESSION_SEED, buyer.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized,
+        has_one = buyer_token_account @ LogisticsError::InvalidTokenOwner,
+        close = buyer
+    )]
+    pub session: Account<'info, SessionAccount>,
+    #[account(mut)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterBuyer<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + BuyerAccount::INIT_SPACE,
+        seeds = [pda::BUYER_SEED, buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_account: Account<'info, BuyerAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,4661 @@
+//! Purchase lifecycle from checkout through settlement, cancellation and cleanup.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::settlement;
+use crate::state::*;
+
+pub fn create_order_index_shard(
+    ctx: Context<CreateOrderIndexShard>,
+    user: Pubkey,
+    shard_no: u32,
+) -> Result<()> {
+    if shard_no > 0 {
+        let previous_shard = ctx
+            .accounts
+            .previous_shard
+            .as_ref()
+            .ok_or(LogisticsError::PreviousShardNotFull)?;
+        require!(
+            previous_shard.is_full(),
+            LogisticsError::PreviousShardNotFull
+        );
+    }
+
+    let order_index = &mut ctx.accounts.order_index;
+    order_index.user = user;
+    order_index.shard_no = shard_no;
+    order_index.entries = Vec::new();
+    order_index.capacity = MAX_ORDER_INDEX_ENTRIES as u32;
+    order_index.version = ACCOUNT_VERSION;
+    order_index.bump = ctx.bumps.order_index;
+
+    emit!(OrderIndexShardCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        user,
+        shard_no,
+    });
+    Ok(())
+}
+
+pub fn add_purchase_index_page(
+    ctx: Context<AddPurchaseIndexPage>,
+    user: Pubkey,
+    shard_no: u32,
+) -> Result<()> {
+    let order_index = &mut ctx.accounts.order_index;
+    order_index.capacity = order_index
+        .capacity
+        .checked_add((ORDER_INDEX_PAGE_BYTES / ORDER_INDEX_ENTRY_LEN) as u32)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    emit!(OrderIndexPageAdded {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        user,
+        shard_no,
+        capacity: order_index.capacity,
+    });
+    Ok(())
+}
+
+pub fn open_buyer_quota(ctx: Context<OpenBuyerQuota>, trade_id: u64) -> Result<()> {
+    let buyer_quota = &mut ctx.accounts.buyer_quota;
+    buyer_quota.trade_id = trade_id;
+    buyer_quota.buyer = ctx.accounts.buyer.key();
+    buyer_quota.version = ACCOUNT_VERSION;
+    buyer_quota.bump = ctx.bumps.buyer_quota;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn buy_trade(
+    ctx: Context<BuyTrade>,
+    trade_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    insured: bool,
+    buyer_region: DeliveryRegion,
+    memo_hash: Option<[u8; 64]>,
+) -> Result<()> {
+    purchase_trade(
+        ctx,
+        trade_id,
+        quantity,
+        logistics_provider,
+        logistics_provider_index,
+        insured,
+        buyer_region,
+        memo_hash,
+        None,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn buy_trade_allowlisted(
+    ctx: Context<BuyTrade>,
+    trade_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    insured: bool,
+    buyer_region: DeliveryRegion,
+    memo_hash: Option<[u8; 64]>,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    purchase_trade(
+        ctx,
+        trade_id,
+        quantity,
+        logistics_provider,
+        logistics_provider_index,
+        insured,
+        buyer_region,
+        memo_hash,
+        Some(proof),
+    )
+}
+
+/// Shared body of `buy_trade` and `buy_trade_allowlisted`. `allowlist_proof`
+/// is only consulted while the trade's pre-sale is running.
+#[allow(clippy::too_many_arguments)]
+fn purchase_trade(
+    ctx: Context<BuyTrade>,
+    trade_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    insured: bool,
+    buyer_region: DeliveryRegion,
+    memo_hash: Option<[u8; 64]>,
+    allowlist_proof: Option<Vec<[u8; 32]>>,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    let buyer = ctx.accounts.buyer.key();
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    if let Some(reservation) = ctx.accounts.reservation.as_ref() {
+        trade_account.return_stock(reservation.quantity);
+    }
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    trade_account.check_stock(quantity)?;
+    require!(buyer != trade_account.seller, LogisticsError::BuyerIsSeller);
+
+    let now = Clock::get()?.unix_timestamp;
+    check_buyer_region(
+        trade_account,
+        buyer_region,
+        &ctx.accounts.global_state.region_registrar,
+        ctx.accounts.region_attestation.as_deref(),
+        now,
+    )?;
+    if let Some(gate) = trade_account.buyer_gate() {
+        check_buyer_gate(
+            &gate,
+            &buyer,
+            ctx.accounts.gate_token_account.as_deref(),
+            ctx.accounts.buyer_attestation.as_deref(),
+            now,
+        )?;
+    }
+    if let Some(presale) = trade_account.presale_allowlist() {
+        if presale.is_active(now) {
+            let proof = allowlist_proof.ok_or(LogisticsError::PresaleActive)?;
+            require!(
+                presale.includes(&buyer, &proof),
+                LogisticsError::NotAllowlisted
+            );
+        }
+    }
+
+    // Per-customer limits count every unit the buyer has bought from the trade
+    charge_buyer_quota(
+        trade_account,
+        quantity,
+        ctx.accounts.buyer_quota.as_deref_mut(),
+        Clock::get()?.slot,
+    )?;
+
+    // Prices depend on which accepted mint the buyer pays with
+    let token_mint = ctx.accounts.token_mint.key();
+    let (product_cost, logistics_costs) = trade_account
+        .prices_for_mint(&token_mint)
+        .ok_or(LogisticsError::InvalidTokenMint)?;
+
+    // The buyer names the provider's position, so no scan of the list is needed
+    let provider_index = trade_account
+        .provider_at(logistics_provider_index, &logistics_provider)
+        .ok_or(LogisticsError::InvalidLogisticsProvider)?;
+    require!(
+        trade_account.provider_enabled(provider_index),
+        LogisticsError::ProviderDisabled
+    );
+    // Held against the provider's stake until the purchase settles
+    count_provider_purchase_opened(&ctx.accounts.provider_account, &logistics_provider)?;
+    let chosen_logistics_cost = logistics_costs[provider_index];
+    let chosen_rate_per_kg = if trade_account.weight_grams > 0 {
+        trade_account.logistics_rates_per_kg[provider_index]
+    } else {
+        0
+    };
+
+    // Calculate costs
+    let mut unit_price = product_cost;
+    let mut total_logistics_cost = compute_logistics_cost(
+        chosen_logistics_cost,
+        chosen_rate_per_kg,
+        trade_account.weight_grams,
+        quantity,
+    )?;
+    let mut fill_price = 0i64;
+    let mut fill_expo = 0i32;
+
+    // USD-priced trades store primary-mint costs in cents; convert at the oracle rate
+    if trade_account.price_feed.is_some() && token_mint == trade_account.token_mint {
+        let price_feed = ctx
+            .accounts
+            .price_feed
+            .as_ref()
+            .ok_or(LogisticsError::MissingPriceFeed)?;
+        let (price, expo) = load_oracle_price(&price_feed.to_account_info(), now)?;
+        let decimals = ctx.accounts.token_mint.decimals;
+        unit_price = usd_cents_to_token_amount(unit_price, price, expo, decimals)?;
+        total_logistics_cost =
+            usd_cents_to_token_amount(total_logistics_cost, price, expo, decimals)?;
+        fill_price = price;
+        fill_expo = expo;
+    }
+
+    let total_product_cost = unit_price
+        .checked_mul(quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let total_amount = total_product_cost
+        .checked_add(total_logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    trade_account
+        .effective_order_limits(&ctx.accounts.global_state)
+        .check(total_amount)?;
+
+    let mut insurance_premium = 0u64;
+    if insured {
+        require!(
+            trade_account.insurance_bps > 0,
+            LogisticsError::InsuranceNotOffered
+        );
+        insurance_premium =
+            compute_insurance_premium(total_product_cost, trade_account.insurance_bps)?;
+    }
+
+    // A session key pays through the session's delegation, within its cap
+    let authority = ctx.accounts.authority.key();
+    let session_bump = match ctx.accounts.session.as_mut() {
+        Some(session) => {
+            let charged = total_amount
+                .checked_add(insurance_premium)
+                .ok_or(LogisticsError::MathOverflow)?;
+            session.spend(charged, Clock::get()?.slot)?;
+            Some(session.bump)
+        }
+        None => None,
+    };
+    let bump_seed = [session_bump.unwrap_or_default()];
+    let session_seeds = [
+        pda::SESSION_SEED,
+        buyer.as_ref(),
+        authority.as_ref(),
+        &bump_seed,
+    ];
+    let session_signer = [&session_seeds[..]];
+    let signer: &[&[&[u8]]] = if session_bump.is_some() {
+        &session_signer
+    } else {
+        &[]
+    };
+    let payment_authority = match ctx.accounts.session.as_ref() {
+        Some(session) => session.to_account_info(),
+        None => ctx.accounts.authority.to_account_info(),
+    };
+
+    // Escrow the product and logistics portions in their own sub-vaults
+    let insurance_pool = if insured {
+        let insurance_pool = ctx
+            .accounts
+            .insurance_pool
+            .as_ref()
+            .ok_or(LogisticsError::MissingInsurancePool)?;
+        Some((insurance_pool.to_account_info(), insurance_premium))
+    } else {
+        None
+    };
+    let transfers = [
+        Some((
+            ctx.accounts.product_escrow.to_account_info(),
+            total_product_cost,
+        )),
+        (total_logistics_cost > 0).then(|| {
+            (
+                ctx.accounts.logistics_escrow.to_account_info(),
+                total_logistics_cost,
+            )
+        }),
+        insurance_pool,
+    ];
+    for (destination, amount) in transfers.into_iter().flatten() {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: destination,
+                authority: payment_authority.clone(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    // Update global counter
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    // Create purchase
+    ctx.accounts.purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id,
+        buyer,
+        payer: authority,
+        token_mint,
+        quantity,
+        unit_price,
+        logistics_provider,
+        logistics_cost: total_logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash,
+        fill_price,
+        fill_expo,
+        insurance_premium,
+        insured,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: ctx.bumps.purchase_account,
+    })?;
+
+    // Update trade state
+    trade_account.record_sale(quantity)?;
+    if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        trade_account.purchase_ids.push(purchase_id);
+    }
+    if trade_account.is_low_stock() {
+        emit!(LowStock {
+            event_seq: global_state.next_event_seq(),
+            trade_id,
+            remaining: trade_account.remaining_quantity,
+        });
+    }
+
+    if ctx.accounts.buyer_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        ctx.accounts.buyer_account.purchase_ids.push(purchase_id);
+    }
+
+    if let Some(buyer_order_index) = ctx.accounts.buyer_order_index.as_mut() {
+        buyer_order_index.append(purchase_id)?;
+    }
+    if let Some(seller_order_index) = ctx.accounts.seller_order_index.as_mut() {
+        seller_order_index.append(purchase_id)?;
+    }
+
+    // Orders routed by a storefront record the fee share it was promised
+    if let Some(platform) = ctx.accounts.platform.as_ref() {
+        let fee_share_bps = global_state
+            .platform_fee_share(&platform.key())
+            .ok_or(LogisticsError::PlatformNotRegistered)?;
+        let purchase_account = &mut ctx.accounts.purchase_account;
+        purchase_account.set_platform_referral(&PlatformReferral {
+            platform: platform.key(),
+            fee_share_bps,
+        })?;
+        let new_len = 8 + purchase_account.try_to_vec()?.len();
+        grow_account(
+            &purchase_account.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        emit!(PlatformOrderRouted {
+            event_seq: global_state.next_event_seq(),
+            purchase_id,
+            platform: platform.key(),
+            fee_share_bps,
+        });
+    }
+
+    // A redeemed loyalty discount is spent on this purchase's escrow fee
+    if let Some(loyalty_account) = ctx.accounts.loyalty_account.as_mut() {
+        let discount_bps = loyalty_account.discount_bps;
+        if discount_bps > 0 {
+            loyalty_account.discount_bps = 0;
+            let purchase_account = &mut ctx.accounts.purchase_account;
+            purchase_account.set_fee_discount_bps(discount_bps)?;
+            let new_len = 8 + purchase_account.try_to_vec()?.len();
+            grow_account(
+                &purchase_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+
+            emit!(LoyaltyDiscountApplied {
+                event_seq: global_state.next_event_seq(),
+                purchase_id,
+                buyer,
+                discount_bps,
+            });
+        }
+    }
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id,
+        buyer,
+        quantity,
+        token_mint,
+        memo_hash,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(PaymentHeld {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        total_amount,
+    });
+
+    if insured {
+        emit!(PurchaseInsured {
+            event_seq: global_state.next_event_seq(),
+            purchase_id,
+            token_mint,
+            premium: insurance_premium,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks an order placed on the buyer's behalf against the listing and returns
+/// its product and logistics amounts. The buyer approved a fixed amount, so only
+/// fixed-price listings paid in the primary mint qualify.
+#[allow(clippy::too_many_arguments)]
+fn price_delegated_order(
+    trade_account: &TradeAccount,
+    global_state: &GlobalState,
+    buyer: &Pubkey,
+    quantity: u64,
+    logistics_provider: &Pubkey,
+    buyer_region: DeliveryRegion,
+    gate_token_account: Option<&TokenAccount>,
+    buyer_attestation: Option<&BuyerAttestation>,
+    region_attestation: Option<&BuyerAttestation>,
+    buyer_quota: Option<&mut BuyerQuota>,
+    now: i64,
+) -> Result<(u64, u64)> {
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    trade_account.check_stock(quantity)?;
+    require!(
+        *buyer != trade_account.seller,
+        LogisticsError::BuyerIsSeller
+    );
+    // Oracle-priced listings move between approval and submission
+    require!(
+        trade_account.price_feed.is_none(),
+        LogisticsError::DelegatedOrderNotSupported
+    );
+
+    check_buyer_region(
+        trade_account,
+        buyer_region,
+        &global_state.region_registrar,
+        region_attestation,
+        now,
+    )?;
+    if let Some(gate) = trade_account.buyer_gate() {
+        check_buyer_gate(&gate, buyer, gate_token_account, buyer_attestation, now)?;
+    }
+    // Pre-sale proofs are only accepted through `buy_trade_allowlisted`
+    require!(
+        !trade_account
+            .presale_allowlist()
+            .is_some_and(|presale| presale.is_active(now)),
+        LogisticsError::PresaleActive
+    );
+    charge_buyer_quota(trade_account, quantity, buyer_quota, Clock::get()?.slot)?;
+
+    let logistics_cost = trade_account.primary_logistics_cost(logistics_provider, quantity)?;
+    let product_amount = trade_account
+        .product_cost
+        .checked_mul(quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let total_amount = product_amount
+        .checked_add(logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    trade_account
+        .effective_order_limits(global_state)
+        .check(total_amount)?;
+    Ok((product_amount, logistics_cost))
+}
+
+/// Records a purchase against the buyer's per-trade quota, enforcing the
+/// trade's per-buyer unit limit and its cooldown between purchases. The quota
+/// account is only required when the trade sets either.
+fn charge_buyer_quota(
+    trade_account: &TradeAccount,
+    quantity: u64,
+    buyer_quota: Option<&mut BuyerQuota>,
+    slot: u64,
+) -> Result<()> {
+    let cooldown_slots = trade_account.purchase_cooldown_slots();
+    if trade_account.max_per_buyer.is_none() && cooldown_slots.is_none() {
+        return Ok(());
+    }
+
+    let buyer_quota = buyer_quota.ok_or(LogisticsError::MissingBuyerQuota)?;
+    if let Some(cooldown_slots) = cooldown_slots {
+        buyer_quota.check_cooldown(cooldown_slots, slot)?;
+    }
+    if let Some(max_per_buyer) = trade_account.max_per_buyer {
+        buyer_quota.consume(quantity, max_per_buyer)?;
+    }
+    buyer_quota.last_purchase_slot = slot;
+    Ok(())
+}
+
+/// Books a purchase opened on the buyer's behalf against the trade, the buyer's
+/// account and any order index shards passed.
+fn record_delegated_purchase(
+    trade_account: &mut TradeAccount,
+    buyer_account: &mut BuyerAccount,
+    buyer_order_index: Option<&mut OrderIndex>,
+    seller_order_index: Option<&mut OrderIndex>,
+    purchase_id: u64,
+    quantity: u64,
+) -> Result<()> {
+    trade_account.record_sale(quantity)?;
+    if trade_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        trade_account.purchase_ids.push(purchase_id);
+    }
+
+    if buyer_account.purchase_ids.len() < MAX_PURCHASE_IDS {
+        buyer_account.purchase_ids.push(purchase_id);
+    }
+
+    if let Some(buyer_order_index) = buyer_order_index {
+        buyer_order_index.append(purchase_id)?;
+    }
+    if let Some(seller_order_index) = seller_order_index {
+        seller_order_index.append(purchase_id)?;
+    }
+    Ok(())
+}
+
+pub fn open_relay_nonce(ctx: Context<OpenRelayNonce>) -> Result<()> {
+    let relay_nonce = &mut ctx.accounts.relay_nonce;
+    relay_nonce.buyer = ctx.accounts.buyer.key();
+    relay_nonce.next_nonce = 0;
+    relay_nonce.version = ACCOUNT_VERSION;
+    relay_nonce.bump = ctx.bumps.relay_nonce;
+    Ok(())
+}
+
+pub fn buy_trade_relayed(ctx: Context<BuyTradeRelayed>, order: RelayedOrder) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= order.expires_at, LogisticsError::RelayedOrderExpired);
+    verify_ed25519_signature(
+        &ctx.accounts.instructions_sysvar,
+        &order.buyer,
+        &order.signing_message(),
+    )?;
+
+    let relay_nonce = &mut ctx.accounts.relay_nonce;
+    require!(
+        order.nonce == relay_nonce.next_nonce,
+        LogisticsError::InvalidRelayNonce
+    );
+    relay_nonce.next_nonce += 1;
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    let (product_amount, logistics_cost) = price_delegated_order(
+        trade_account,
+        &ctx.accounts.global_state,
+        &order.buyer,
+        order.quantity,
+        &order.logistics_provider,
+        order.buyer_region,
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.buyer_attestation.as_deref(),
+        ctx.accounts.region_attestation.as_deref(),
+        ctx.accounts.buyer_quota.as_deref_mut(),
+        now,
+    )?;
+    count_provider_purchase_opened(&ctx.accounts.provider_account, &order.logistics_provider)?;
+    let total_amount = product_amount
+        .checked_add(logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    require_gte!(
+        order.max_total_amount,
+        total_amount,
+        LogisticsError::RelayedOrderPriceAboveCap
+    );
+
+    // The buyer's approval of the relay authority stands in for their signature
+    let seeds = &[pda::RELAY_AUTHORITY_SEED, &[ctx.bumps.relay_authority]];
+    let signer = &[&seeds[..]];
+    let mut transfers = vec![(
+        ctx.accounts.product_escrow.to_account_info(),
+        product_amount,
+    )];
+    if logistics_cost > 0 {
+        transfers.push((
+            ctx.accounts.logistics_escrow.to_account_info(),
+            logistics_cost,
+        ));
+    }
+    if order.relayer_fee > 0 {
+        let relayer_token_account = ctx
+            .accounts
+            .relayer_token_account
+            .as_ref()
+            .ok_or(LogisticsError::MissingRelayerTokenAccount)?;
+        transfers.push((relayer_token_account.to_account_info(), order.relayer_fee));
+    }
+    for (destination, amount) in transfers {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.relay_authority.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    let token_mint = trade_account.token_mint;
+    ctx.accounts.purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id: order.trade_id,
+        buyer: order.buyer,
+        payer: ctx.accounts.relayer.key(),
+        token_mint,
+        quantity: order.quantity,
+        unit_price: trade_account.product_cost,
+        logistics_provider: order.logistics_provider,
+        logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash: None,
+        fill_price: 0,
+        fill_expo: 0,
+        insurance_premium: 0,
+        insured: false,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: ctx.bumps.purchase_account,
+    })?;
+
+    record_delegated_purchase(
+        trade_account,
+        &mut ctx.accounts.buyer_account,
+        ctx.accounts.buyer_order_index.as_deref_mut(),
+        ctx.accounts.seller_order_index.as_deref_mut(),
+        purchase_id,
+        order.quantity,
+    )?;
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id: order.trade_id,
+        buyer: order.buyer,
+        quantity: order.quantity,
+        token_mint,
+        memo_hash: None,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(PaymentHeld {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        total_amount,
+    });
+    emit!(PurchaseRelayed {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        buyer: order.buyer,
+        relayer: ctx.accounts.relayer.key(),
+        nonce: order.nonce,
+        relayer_fee: order.relayer_fee,
+    });
+
+    Ok(())
+}
+
+pub fn buy_trade_with_permit(
+    ctx: Context<BuyTradeWithPermit>,
+    trade_id: u64,
+    quantity: u64,
+    logistics_provider: Pubkey,
+    buyer_region: DeliveryRegion,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let now = Clock::get()?.unix_timestamp;
+    let buyer = ctx.accounts.spending_permit.buyer;
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    let (product_amount, logistics_cost) = price_delegated_order(
+        trade_account,
+        &ctx.accounts.global_state,
+        &buyer,
+        quantity,
+        &logistics_provider,
+        buyer_region,
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.buyer_attestation.as_deref(),
+        ctx.accounts.region_attestation.as_deref(),
+        ctx.accounts.buyer_quota.as_deref_mut(),
+        now,
+    )?;
+    count_provider_purchase_opened(&ctx.accounts.provider_account, &logistics_provider)?;
+    let total_amount = product_amount
+        .checked_add(logistics_cost)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let spending_permit = &mut ctx.accounts.spending_permit;
+    spending_permit.spend(total_amount, now)?;
+
+    let delegate = spending_permit.delegate;
+    let seeds = &[
+        pda::SPENDING_PERMIT_SEED,
+        buyer.as_ref(),
+        delegate.as_ref(),
+        &[spending_permit.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let mut transfers = vec![(
+        ctx.accounts.product_escrow.to_account_info(),
+        product_amount,
+    )];
+    if logistics_cost > 0 {
+        transfers.push((
+            ctx.accounts.logistics_escrow.to_account_info(),
+            logistics_cost,
+        ));
+    }
+    for (destination, amount) in transfers {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: destination,
+                authority: ctx.accounts.spending_permit.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.purchase_counter += 1;
+    let purchase_id = global_state.purchase_counter;
+
+    let token_mint = trade_account.token_mint;
+    ctx.accounts.purchase_account.open(PurchaseTerms {
+        purchase_id,
+        trade_id,
+        buyer,
+        payer: delegate,
+        token_mint,
+        quantity,
+        unit_price: trade_account.product_cost,
+        logistics_provider,
+        logistics_cost,
+        fee_bps: global_state.fee_bps,
+        memo_hash: None,
+        fill_price: 0,
+        fill_expo: 0,
+        insurance_premium: 0,
+        insured: false,
+        created_at: now,
+        fulfillment_start: trade_account.preorder_start(),
+        bump: ctx.bumps.purchase_account,
+    })?;
+
+    record_delegated_purchase(
+        trade_account,
+        &mut ctx.accounts.buyer_account,
+        ctx.accounts.buyer_order_index.as_deref_mut(),
+        ctx.accounts.seller_order_index.as_deref_mut(),
+        purchase_id,
+        quantity,
+    )?;
+
+    let event = PurchaseCreated {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        trade_id,
+        buyer,
+        quantity,
+        token_mint,
+        memo_hash: None,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    emit!(PaymentHeld {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        total_amount,
+    });
+    emit!(PurchaseViaPermit {
+        event_seq: global_state.next_event_seq(),
+        purchase_id,
+        buyer,
+        delegate,
+        amount: total_amount,
+        remaining_amount: ctx.accounts.spending_permit.remaining_amount,
+    });
+
+    Ok(())
+}
+
+pub fn confirm_delivery_and_purchase<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+    _purchase_id: u64,
+    swap_route: Option<SwapRoute>,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(
+        !ctx.accounts.trade_account.requires_proof_of_delivery
+            || purchase_account.proof_of_delivery().is_some(),
+        LogisticsError::ProofOfDeliveryMissing
+    );
+    // A session key confirms as the buyer while it is active
+    let signer_key = match ctx.accounts.session.as_ref() {
+        Some(session) => {
+            session.check_active(Clock::get()?.slot)?;
+            purchase_account.buyer
+        }
+        None => ctx.accounts.buyer.key(),
+    };
+    let party = confirming_party(
+        &signer_key,
+        &purchase_account.buyer,
+        &ctx.accounts.trade_account.seller,
+        &purchase_account.chosen_logistics_provider,
+    );
+    purchase_account.record_confirmation(party);
+    require!(
+        purchase_account.has_confirmation_quorum(ctx.accounts.trade_account.confirmation_mode),
+        LogisticsError::ConfirmationQuorumNotMet
+    );
+
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_account.purchase_id,
+    );
+    let provider_account = count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+
+    // Settle payments
+    let trade_account = &ctx.accounts.trade_account;
+    let amounts = purchase_account.settlement_payouts()?;
+    commit_settlement(purchase_account)?;
+
+    // Transfer to seller
+    let escrow_bump = ctx.bumps.escrow_token_account;
+
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[pda::ESCROW_SEED, token_mint.as_ref(), &[escrow_bump]];
+    let signer = &[&seeds[..]];
+
+    let mut seller_payout = amounts.seller_amount;
+    let escrow_vault_key = ctx.accounts.escrow_token_account.key();
+    let stream_seconds = trade_account
+        .settlement_stream_seconds()
+        .filter(|_| purchase_account.split_escrow);
+    if let Some(duration_seconds) = stream_seconds {
+        // Service listings leave the seller's share in the product escrow and
+        // release it over time with `release_seller_stream`
+        require!(swap_route.is_none(), LogisticsError::InvalidSwapRoute);
+        purchase_account.set_seller_stream(&SellerStream {
+            amount: seller_payout,
+            released: 0,
+            refunded: 0,
+            started_at: now,
+            duration_seconds,
+            halted_at: 0,
+        })?;
+        let new_len = 8 + purchase_account.try_to_vec()?.len();
+        grow_account(
+            &purchase_account.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        emit!(SellerStreamStarted {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id: purchase_account.purchase_id,
+            seller: trade_account.seller,
+            amount: seller_payout,
+            duration_seconds,
+        });
+        seller_payout = 0;
+    } else if let Some(route) = swap_route.as_ref() {
+        require!(ctx.accounts.seller.is_signer, LogisticsError::NotAuthorized);
+        require!(
+            trade_account.payout_splits.is_empty(),
+            LogisticsError::InvalidSwapRoute
+        );
+        let (Some(seller_account), Some(swap_program), Some(destination)) = (
+            ctx.accounts.seller_account.as_ref(),
+            ctx.accounts.swap_program.as_ref(),
+            ctx.accounts.seller_swap_token_account.as_mut(),
+        ) else {
+            return err!(LogisticsError::MissingSwapAccounts);
+        };
+        let source = if purchase_account.split_escrow {
+            ctx.accounts
+                .product_escrow
+                .as_mut()
+                .ok_or(LogisticsError::MissingEscrowBucket)?
+        } else {
+            &mut ctx.accounts.escrow_token_account
+        };
+        let (amount_in, amount_out) = swap_seller_proceeds(
+            &swap_program.to_account_info(),
+            source,
+            &escrow_vault_key,
+            destination,
+            ctx.remaining_accounts,
+            seller_account,
+            route,
+            seller_payout,
+            signer,
+        )?;
+        seller_payout -= amount_in;
+
+        emit!(SellerProceedsSwapped {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id: purchase_account.purchase_id,
+            seller: trade_account.seller,
+            input_mint: purchase_account.token_mint,
+            output_mint: destination.mint,
+            amount_in,
+            amount_out,
+        });
+    }
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    let product_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    let logistics_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+
+    if seller_payout > 0 {
+        pay_seller_proceeds(
+            &mut ctx.accounts.global_state,
+            &token_program,
+            &product_escrow,
+            &escrow_vault,
+            seller_payout_account(
+                ctx.accounts.seller_account.as_deref(),
+                ctx.accounts.seller_token_account.as_ref(),
+                ctx.accounts.seller_payout_token_account.as_ref(),
+                purchase_account.token_mint,
+            )?,
+            ctx.accounts.seller_claimable.as_mut(),
+            ctx.remaining_accounts,
+            trade_account,
+            token_mint,
+            seller_payout,
+            signer,
+        )?;
+    }
+
+    // Transfer to logistics provider
+    transfer_or_credit(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        provider_payout_account(
+            provider_account.as_ref(),
+            ctx.accounts.logistics_token_account.as_ref(),
+            ctx.accounts.logistics_payout_token_account.as_ref(),
+            token_mint,
+        )?,
+        ctx.accounts.logistics_claimable.as_mut(),
+        purchase_account.chosen_logistics_provider,
+        token_mint,
+        amounts.logistics_amount,
+        signer,
+    )?;
+
+    // The fee taken from each portion is collected in the mint vault
+    sweep_to_vault(
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        amounts.product_fee,
+        signer,
+    )?;
+    sweep_to_vault(
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        amounts.logistics_fee,
+        signer,
+    )?;
+
+    let platform_share = credit_platform_share(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &escrow_vault,
+        ctx.accounts.platform_claimable.as_mut(),
+        purchase_account,
+        amounts.fee_amount,
+        signer,
+    )?;
+    collect_fees(
+        &token_program,
+        &escrow_vault,
+        &ctx.accounts.fee_vault.to_account_info(),
+        amounts.fee_amount - platform_share,
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.purchases_settled += 1;
+        stats.record_volume(
+            purchase_account.token_mint,
+            purchase_account.total_amount,
+            amounts.fee_amount,
+        );
+    }
+
+    let event = PurchaseCompletedAndConfirmed {
+        schema_version: EVENT_SCHEMA_VERSION,
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        trade_id: purchase_account.trade_id,
+        buyer: purchase_account.buyer,
+        seller: trade_account.seller,
+        logistics_provider: purchase_account.chosen_logistics_provider,
+        token_mint: purchase_account.token_mint,
+        seller_amount: amounts.seller_amount,
+        logistics_amount: amounts.logistics_amount,
+        fee_amount: amounts.fee_amount,
+        unix_timestamp: now,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn confirm_deliveries_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ConfirmDeliveriesBatch<'info>>,
+    trade_id: u64,
+    purchase_count: u8,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    // Each purchase is passed as (purchase, product sub-vault, logistics
+    // sub-vault), followed by the trade's payout split token accounts
+    let purchase_count = purchase_count as usize;
+    require!(
+        purchase_count > 0 && purchase_count <= MAX_BATCH_CONFIRMATIONS,
+        LogisticsError::InvalidBatch
+    );
+    require!(
+        ctx.remaining_accounts.len() >= 3 * purchase_count,
+        LogisticsError::InvalidBatch
+    );
+    let (purchase_infos, split_token_accounts) =
+        ctx.remaining_accounts.split_at(3 * purchase_count);
+    // Streamed and swapped payouts need per-purchase accounts; settle those
+    // one at a time
+    require!(
+        ctx.accounts
+            .trade_account
+            .settlement_stream_seconds()
+            .is_none(),
+        LogisticsError::InvalidBatch
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let buyer = ctx.accounts.buyer.key();
+    let logistics_provider = ctx.accounts.logistics_provider.key();
+    let provider_account = count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &logistics_provider,
+        purchase_count as u32,
+    )?;
+    let token_mint = ctx.accounts.token_mint.key();
+    let escrow_bump = ctx.bumps.escrow_token_account;
+    let seeds = &[pda::ESCROW_SEED, token_mint.as_ref(), &[escrow_bump]];
+    let signer = &[&seeds[..]];
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+
+    for accounts in purchase_infos.chunks(3) {
+        let mut purchase_account = Account::<PurchaseAccount>::try_from(&accounts[0])?;
+        let purchase_id = purchase_account.purchase_id;
+        require_keys_eq!(
+            accounts[0].key(),
+            pda::purchase_pda(purchase_id).0,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        require!(
+            purchase_account.trade_id == trade_id,
+            LogisticsError::InvalidBatch
+        );
+        require_keys_eq!(purchase_account.buyer, buyer, LogisticsError::NotAuthorized);
+        require_keys_eq!(
+            purchase_account.token_mint,
+            token_mint,
+            LogisticsError::InvalidTokenMint
+        );
+        require_keys_eq!(
+            purchase_account.chosen_logistics_provider,
+            logistics_provider,
+            LogisticsError::InvalidLogisticsProvider
+        );
+        // Platform-routed purchases go through `confirm_delivery_and_purchase`
+        require!(
+            purchase_account.platform_referral().is_none(),
+            LogisticsError::InvalidBatch
+        );
+        require!(
+            !purchase_account.delivered_and_confirmed,
+            LogisticsError::AlreadyConfirmed
+        );
+        require!(!purchase_account.disputed, LogisticsError::Disputed);
+        require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+        require!(
+            !ctx.accounts.trade_account.requires_proof_of_delivery
+                || purchase_account.proof_of_delivery().is_some(),
+            LogisticsError::ProofOfDeliveryMissing
+        );
+        purchase_account.record_confirmation(ConfirmingParty::Buyer);
+        require!(
+            purchase_account.has_confirmation_quorum(ctx.accounts.trade_account.confirmation_mode),
+            LogisticsError::ConfirmationQuorumNotMet
+        );
+
+        purchase_account.delivered_and_confirmed = true;
+        purchase_account.mark_settled(now);
+        mark_order_settled(
+            ctx.accounts.buyer_order_index.as_mut(),
+            ctx.accounts.seller_order_index.as_mut(),
+            purchase_id,
+        );
+
+        let (product_bucket, logistics_bucket) = if purchase_account.split_escrow {
+            require_keys_eq!(
+                accounts[1].key(),
+                pda::product_escrow_pda(purchase_id).0,
+                LogisticsError::MissingEscrowBucket
+            );
+            require_keys_eq!(
+                accounts[2].key(),
+                pda::logistics_escrow_pda(purchase_id).0,
+                LogisticsError::MissingEscrowBucket
+            );
+            (
+                Some(Account::<TokenAccount>::try_from(&accounts[1])?),
+                Some(Account::<TokenAccount>::try_from(&accounts[2])?),
+            )
+        } else {
+            (None, None)
+        };
+        let product_escrow = escrow_bucket(
+            &purchase_account,
+            product_bucket.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?;
+        let logistics_escrow = escrow_bucket(
+            &purchase_account,
+            logistics_bucket.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?;
+
+        let amounts = purchase_account.settlement_payouts()?;
+        commit_settlement(&mut purchase_account)?;
+
+        if amounts.seller_amount > 0 {
+            pay_seller_proceeds(
+                &mut ctx.accounts.global_state,
+                &token_program,
+                &product_escrow,
+                &escrow_vault,
+                seller_payout_account(
+                    ctx.accounts.seller_account.as_deref(),
+                    ctx.accounts.seller_token_account.as_ref(),
+                    ctx.accounts.seller_payout_token_account.as_ref(),
+                    token_mint,
+                )?,
+                ctx.accounts.seller_claimable.as_mut(),
+                split_token_accounts,
+                &ctx.accounts.trade_account,
+                token_mint,
+                amounts.seller_amount,
+                signer,
+            )?;
+        }
+
+        transfer_or_credit(
+            &mut ctx.accounts.global_state,
+            &token_program,
+            &logistics_escrow,
+            &escrow_vault,
+            provider_payout_account(
+                provider_account.as_ref(),
+                ctx.accounts.logistics_token_account.as_ref(),
+                ctx.accounts.logistics_payout_token_account.as_ref(),
+                token_mint,
+            )?,
+            ctx.accounts.logistics_claimable.as_mut(),
+            logistics_provider,
+            token_mint,
+            amounts.logistics_amount,
+            signer,
+        )?;
+
+        // The fee taken from each portion is collected in the mint vault
+        sweep_to_vault(
+            &token_program,
+            &product_escrow,
+            &escrow_vault,
+            amounts.product_fee,
+            signer,
+        )?;
+        sweep_to_vault(
+            &token_program,
+            &logistics_escrow,
+            &escrow_vault,
+            amounts.logistics_fee,
+            signer,
+        )?;
+        collect_fees(
+            &token_program,
+            &escrow_vault,
+            &ctx.accounts.fee_vault.to_account_info(),
+            amounts.fee_amount,
+            signer,
+        )?;
+        purchase_account.unlock_settlement();
+        purchase_account.exit(&crate::ID)?;
+
+        if let Some(stats) = ctx.accounts.stats.as_mut() {
+            stats.purchases_settled += 1;
+            stats.record_volume(
+                token_mint,
+                purchase_account.total_amount,
+                amounts.fee_amount,
+            );
+        }
+
+        let event = PurchaseCompletedAndConfirmed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id,
+            trade_id,
+            buyer,
+            seller: ctx.accounts.trade_account.seller,
+            logistics_provider,
+            token_mint,
+            seller_amount: amounts.seller_amount,
+            logistics_amount: amounts.logistics_amount,
+            fee_amount: amounts.fee_amount,
+            unix_timestamp: now,
+        };
+        #[cfg(feature = "cpi-events")]
+        emit_cpi!(event);
+        #[cfg(not(feature = "cpi-events"))]
+        emit!(event);
+    }
+
+    emit!(DeliveriesBatchConfirmed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer,
+        purchases: purchase_count as u8,
+    });
+    Ok(())
+}
+
+pub fn auto_settle_expired<'info>(
+    ctx: Context<'_, '_, '_, 'info, AutoSettleExpired<'info>>,
+    _purchase_id: u64,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let global_state = &ctx.accounts.global_state;
+    let now = Clock::get()?.unix_timestamp;
+    if purchase_account.settled {
+        return Ok(());
+    }
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(purchase_account.shipped_at != 0, LogisticsError::NotShipped);
+    require!(
+        !ctx.accounts.trade_account.requires_proof_of_delivery
+            || purchase_account.proof_of_delivery().is_some(),
+        LogisticsError::ProofOfDeliveryMissing
+    );
+    require!(
+        purchase_account.has_confirmation_quorum(ctx.accounts.trade_account.confirmation_mode),
+        LogisticsError::ConfirmationQuorumNotMet
+    );
+    require!(
+        now >= purchase_account.auto_confirm_start() + global_state.auto_confirm_seconds,
+        LogisticsError::DeadlineNotReached
+    );
+
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_account.purchase_id,
+    );
+    let provider_account = count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+
+    let trade_account = &ctx.accounts.trade_account;
+    let amounts = purchase_account.settlement_payouts()?;
+    commit_settlement(purchase_account)?;
+
+    let escrow_bump = ctx.bumps.escrow_token_account;
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[pda::ESCROW_SEED, token_mint.as_ref(), &[escrow_bump]];
+    let signer = &[&seeds[..]];
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    let product_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    let logistics_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+
+    pay_seller_proceeds(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        seller_payout_account(
+            ctx.accounts.seller_account.as_deref(),
+            ctx.accounts.seller_token_account.as_ref(),
+            ctx.accounts.seller_payout_token_account.as_ref(),
+            purchase_account.token_mint,
+        )?,
+        ctx.accounts.seller_claimable.as_mut(),
+        ctx.remaining_accounts,
+        trade_account,
+        token_mint,
+        amounts.seller_amount,
+        signer,
+    )?;
+
+    transfer_or_credit(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        provider_payout_account(
+            provider_account.as_ref(),
+            ctx.accounts.logistics_token_account.as_ref(),
+            ctx.accounts.logistics_payout_token_account.as_ref(),
+            token_mint,
+        )?,
+        ctx.accounts.logistics_claimable.as_mut(),
+        purchase_account.chosen_logistics_provider,
+        token_mint,
+        amounts.logistics_amount,
+        signer,
+    )?;
+
+    // The fee taken from each portion is collected in the mint vault
+    sweep_to_vault(
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        amounts.product_fee,
+        signer,
+    )?;
+    sweep_to_vault(
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        amounts.logistics_fee,
+        signer,
+    )?;
+
+    // A cranker without a token account forgoes the reward to the fee vault
+    let crank_reward = if ctx.accounts.cranker_token_account.is_some() {
+        compute_crank_reward(
+            amounts.fee_amount,
+            ctx.accounts.global_state.crank_reward_bps,
+        )
+    } else {
+        0
+    };
+    pay_crank_reward(
+        &mut ctx.accounts.global_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        ctx.accounts.cranker_token_account.as_ref(),
+        crank_reward,
+        purchase_account.purchase_id,
+        ctx.accounts.cranker.key(),
+        signer,
+    )?;
+
+    // The platform shares in what is left of the fee after the crank reward
+    let platform_share = credit_platform_share(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &escrow_vault,
+        ctx.accounts.platform_claimable.as_mut(),
+        purchase_account,
+        amounts.fee_amount - crank_reward,
+        signer,
+    )?;
+    collect_fees(
+        &token_program,
+        &escrow_vault,
+        &ctx.accounts.fee_vault.to_account_info(),
+        amounts.fee_amount - crank_reward - platform_share,
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.purchases_settled += 1;
+        stats.record_volume(
+            purchase_account.token_mint,
+            purchase_account.total_amount,
+            amounts.fee_amount,
+        );
+    }
+
+    let event = PurchaseCompletedAndConfirmed {
+        schema_version: EVENT_SCHEMA_VERSION,
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        trade_id: purchase_account.trade_id,
+        buyer: purchase_account.buyer,
+        seller: trade_account.seller,
+        logistics_provider: purchase_account.chosen_logistics_provider,
+        token_mint: purchase_account.token_mint,
+        seller_amount: amounts.seller_amount,
+        logistics_amount: amounts.logistics_amount,
+        fee_amount: amounts.fee_amount,
+        unix_timestamp: now,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn auto_refund_timeouts(ctx: Context<AutoRefundTimeouts>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let trade_account = &mut ctx.accounts.trade_account;
+    if purchase_account.settled {
+        return Ok(());
+    }
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(
+        purchase_account.shipped_at == 0 && purchase_account.delivered_quantity == 0,
+        LogisticsError::AlreadyShipped
+    );
+
+    let global_state = &ctx.accounts.global_state;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= purchase_account.timers_start() + global_state.ship_timeout_seconds,
+        LogisticsError::DeadlineNotReached
+    );
+
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+    purchase_account.cancelled = true;
+    purchase_account.cancel_reason = CancelReason::ShipTimeout;
+    count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+    trade_account.record_refund(purchase_account.quantity);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_account.purchase_id,
+    );
+
+    // The premium is refunded along with the escrow
+    let refund_premium = purchase_account.insurance_status == InsuranceStatus::Active;
+    if refund_premium {
+        purchase_account.insurance_status = InsuranceStatus::None;
+    }
+    commit_settlement(purchase_account)?;
+
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    refund_escrow(
+        &ctx.accounts.token_program.to_account_info(),
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.buyer_token_account.to_account_info(),
+        0,
+        signer,
+    )?;
+    let mut refund_amount = purchase_account.total_amount;
+
+    if refund_premium {
+        let Some(insurance_pool) = ctx.accounts.insurance_pool.as_ref() else {
+            return err!(LogisticsError::MissingInsurancePool);
+        };
+        let pool_bump = ctx.bumps.insurance_pool;
+        let seeds = &[pda::INSURANCE_POOL_SEED, token_mint.as_ref(), &[pool_bump]];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: insurance_pool.to_account_info(),
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: insurance_pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, purchase_account.insurance_premium)?;
+        refund_amount += purchase_account.insurance_premium;
+    }
+
+    let forgone_fee = purchase_account
+        .unit_price
+        .checked_mul(purchase_account.quantity)
+        .and_then(|product| product.checked_mul(purchase_account.escrow_fee_bps()))
+        .ok_or(LogisticsError::MathOverflow)?
+        / BASIS_POINTS;
+    let crank_reward = compute_crank_reward(forgone_fee, global_state.crank_reward_bps);
+    pay_crank_reward(
+        &mut ctx.accounts.global_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.escrow_token_account,
+        ctx.accounts.cranker_token_account.as_ref(),
+        crank_reward,
+        purchase_account.purchase_id,
+        ctx.accounts.cranker.key(),
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.purchases_cancelled += 1;
+    }
+
+    let event = PurchaseCancelled {
+        schema_version: EVENT_SCHEMA_VERSION,
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        token_mint,
+        refund_amount,
+        restocking_fee: 0,
+        reason: CancelReason::ShipTimeout,
+        unix_timestamp: now,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn confirm_partial_delivery<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+    _purchase_id: u64,
+    quantity_received: u64,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(
+        quantity_received > 0 && quantity_received < purchase_account.quantity,
+        LogisticsError::InvalidQuantity
+    );
+    // Milestone releases are taken from the whole order's seller share
+    require!(
+        purchase_account.milestone_progress().is_none(),
+        LogisticsError::MilestoneReleased
+    );
+    // A session key confirms as the buyer while it is active
+    let signer_key = match ctx.accounts.session.as_ref() {
+        Some(session) => {
+            session.check_active(Clock::get()?.slot)?;
+            purchase_account.buyer
+        }
+        None => ctx.accounts.buyer.key(),
+    };
+    let party = confirming_party(
+        &signer_key,
+        &purchase_account.buyer,
+        &ctx.accounts.trade_account.seller,
+        &purchase_account.chosen_logistics_provider,
+    );
+    purchase_account.record_confirmation(party);
+    require!(
+        purchase_account.has_confirmation_quorum(ctx.accounts.trade_account.confirmation_mode),
+        LogisticsError::ConfirmationQuorumNotMet
+    );
+
+    let trade_account = &ctx.accounts.trade_account;
+    let logistics_share = compute_partial_logistics_cost(
+        purchase_account.logistics_cost,
+        quantity_received,
+        purchase_account.quantity,
+    );
+    let amounts = settlement::compute_payouts(
+        purchase_account.unit_price,
+        logistics_share,
+        quantity_received,
+        purchase_account.escrow_fee_bps(),
+    )?;
+    let released_amount = purchase_account
+        .unit_price
+        .checked_mul(quantity_received)
+        .and_then(|product| product.checked_add(logistics_share))
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    purchase_account.quantity -= quantity_received;
+    // Each release of the remainder needs its own sign-offs.
+    purchase_account.confirmations = 0;
+    purchase_account.delivered_quantity += quantity_received;
+    purchase_account.logistics_cost -= logistics_share;
+    purchase_account.total_amount = purchase_account
+        .total_amount
+        .checked_sub(released_amount)
+        .ok_or(LogisticsError::MathOverflow)?;
+    commit_settlement(purchase_account)?;
+    let provider_account = load_provider_account(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+    )?;
+
+    let escrow_bump = ctx.bumps.escrow_token_account;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[escrow_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    let product_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    let logistics_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+
+    pay_seller_proceeds(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        seller_payout_account(
+            ctx.accounts.seller_account.as_deref(),
+            ctx.accounts.seller_token_account.as_ref(),
+            ctx.accounts.seller_payout_token_account.as_ref(),
+            purchase_account.token_mint,
+        )?,
+        ctx.accounts.seller_claimable.as_mut(),
+        ctx.remaining_accounts,
+        trade_account,
+        purchase_account.token_mint,
+        amounts.seller_amount,
+        signer,
+    )?;
+
+    transfer_or_credit(
+        &mut ctx.accounts.global_state,
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        provider_payout_account(
+            provider_account.as_ref(),
+            ctx.accounts.logistics_token_account.as_ref(),
+            ctx.accounts.logistics_payout_token_account.as_ref(),
+            purchase_account.token_mint,
+        )?,
+        ctx.accounts.logistics_claimable.as_mut(),
+        purchase_account.chosen_logistics_provider,
+        purchase_account.token_mint,
+        amounts.logistics_amount,
+        signer,
+    )?;
+
+    // The fee taken from each portion is collected in the mint vault
+    sweep_to_vault(
+        &token_program,
+        &product_escrow,
+        &escrow_vault,
+        amounts.product_fee,
+        signer,
+    )?;
+    sweep_to_vault(
+        &token_program,
+        &logistics_escrow,
+        &escrow_vault,
+        amounts.logistics_fee,
+        signer,
+    )?;
+    collect_fees(
+        &token_program,
+        &escrow_vault,
+        &ctx.accounts.fee_vault.to_account_info(),
+        amounts.fee_amount,
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.record_volume(
+            purchase_account.token_mint,
+            released_amount,
+            amounts.fee_amount,
+        );
+    }
+
+    emit!(PartialDeliveryConfirmed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        quantity_received,
+        remaining_quantity: purchase_account.quantity,
+        seller_amount: amounts.seller_amount,
+        logistics_amount: amounts.logistics_amount,
+        fee_amount: amounts.fee_amount,
+    });
+
+    Ok(())
+}
+
+pub fn confirm_milestone<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+    _purchase_id: u64,
+    index: u8,
+) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    // A session key confirms as the buyer while it is active
+    let signer_key = match ctx.accounts.session.as_ref() {
+        Some(session) => {
+            session.check_active(Clock::get()?.slot)?;
+            purchase_account.buyer
+        }
+        None => ctx.accounts.buyer.key(),
+    };
+    require!(
+        signer_key == purchase_account.buyer,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &ctx.accounts.trade_account;
+    let milestones = trade_account.milestones();
+    let milestone = milestones
+        .get(index as usize)
+        .ok_or(LogisticsError::InvalidMilestone)?;
+    let mut progress = purchase_account.milestone_progress().unwrap_or_default();
+    require!(
+        !progress.is_confirmed(index),
+        LogisticsError::MilestoneAlreadyConfirmed
+    );
+
+    // Slices are taken from the seller's share of the whole order; the escrow
+    // fee stays in escrow until the purchase settles
+    let gross = settlement::compute_payouts(
+        purchase_account.unit_price,
+        purchase_account.logistics_cost,
+        purchase_account.quantity,
+        purchase_account.escrow_fee_bps(),
+    )?;
+    let seller_amount =
+        ((gross.seller_amount as u128 * milestone.bps as u128) / BASIS_POINTS as u128) as u64;
+    progress.confirmed |= 1 << index;
+    progress.seller_released = progress
+        .seller_released
+        .checked_add(seller_amount)
+        .ok_or(LogisticsError::MathOverflow)?;
+    purchase_account.set_milestone_progress(&progress)?;
+    purchase_account.total_amount = purchase_account
+        .total_amount
+        .checked_sub(seller_amount)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+    commit_settlement(purchase_account)?;
+
+    let escrow_bump = ctx.bumps.escrow_token_account;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[escrow_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    let product_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    pay_seller_proceeds(
+        &mut ctx.accounts.global_state,
+        &ctx.accounts.token_program.to_account_info(),
+        &product_escrow,
+        &escrow_vault,
+        seller_payout_account(
+            ctx.accounts.seller_account.as_deref(),
+            ctx.accounts.seller_token_account.as_ref(),
+            ctx.accounts.seller_payout_token_account.as_ref(),
+            purchase_account.token_mint,
+        )?,
+        ctx.accounts.seller_claimable.as_mut(),
+        ctx.remaining_accounts,
+        trade_account,
+        purchase_account.token_mint,
+        seller_amount,
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    emit!(MilestoneConfirmed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        index,
+        seller_amount,
+        seller_released: progress.seller_released,
+    });
+    Ok(())
+}
+
+pub fn consolidate_shipments<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ConsolidateShipments<'info>>,
+    shipment_id: u64,
+    purchase_ids: Vec<u64>,
+    consolidated_logistics_cost: u64,
+) -> Result<()> {
+    require!(
+        purchase_ids.len() >= 2 && purchase_ids.len() <= MAX_CONSOLIDATED_PURCHASES,
+        LogisticsError::InvalidConsolidation
+    );
+    require!(
+        shipment_id == purchase_ids[0],
+        LogisticsError::InvalidConsolidation
+    );
+    require!(
+        ctx.remaining_accounts.len() == 3 * purchase_ids.len(),
+        LogisticsError::InvalidConsolidation
+    );
+    let (purchase_infos, rest) = ctx.remaining_accounts.split_at(purchase_ids.len());
+    let (trade_infos, escrow_infos) = rest.split_at(purchase_ids.len());
+    let mut unique_ids = purchase_ids.clone();
+    unique_ids.sort_unstable();
+    unique_ids.dedup();
+    require!(
+        unique_ids.len() == purchase_ids.len(),
+        LogisticsError::InvalidConsolidation
+    );
+
+    let provider = ctx.accounts.provider.key();
+    let buyer = ctx.accounts.buyer.key();
+
+    let mut seller = None;
+    let mut purchases = Vec::with_capacity(purchase_ids.len());
+    for ((purchase_id, info), trade_info) in purchase_ids
+        .iter()
+        .zip(purchase_infos.iter())
+        .zip(trade_infos.iter())
+    {
+        require_keys_eq!(
+            *info.owner,
+            crate::ID,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        require!(info.is_writable, LogisticsError::InvalidPurchaseAccount);
+        require_keys_eq!(
+            info.key(),
+            pda::purchase_pda(*purchase_id).0,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        let purchase = PurchaseAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+
+        require!(
+            purchase.purchase_id == *purchase_id,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        // Purchases may come from different listings, as long as one seller
+        // ships them all
+        let trade_account = Account::<TradeAccount>::try_from(trade_info)?;
+        require_keys_eq!(
+            trade_info.key(),
+            pda::trade_pda(purchase.trade_id).0,
+            LogisticsError::InvalidConsolidation
+        );
+        require_keys_eq!(
+            *seller.get_or_insert(trade_account.seller),
+            trade_account.seller,
+            LogisticsError::InvalidConsolidation
+        );
+        require_keys_eq!(
+            purchase.token_mint,
+            ctx.accounts.buyer_token_account.mint,
+            LogisticsError::InvalidTokenMint
+        );
+        require_keys_eq!(purchase.buyer, buyer, LogisticsError::NotAuthorized);
+        require_keys_eq!(
+            purchase.chosen_logistics_provider,
+            provider,
+            LogisticsError::NotAuthorized
+        );
+        require!(
+            !purchase.delivered_and_confirmed && !purchase.disputed && !purchase.settled,
+            LogisticsError::AlreadySettled
+        );
+        require!(!purchase.consolidated, LogisticsError::AlreadyConsolidated);
+        require!(
+            purchase.delivered_quantity == 0,
+            LogisticsError::InvalidConsolidation
+        );
+        purchases.push(purchase);
+    }
+
+    let earliest = purchases.iter().map(|p| p.created_at).min().unwrap();
+    let latest = purchases.iter().map(|p| p.created_at).max().unwrap();
+    require!(
+        latest - earliest <= CONSOLIDATION_WINDOW_SECONDS,
+        LogisticsError::ConsolidationWindowExceeded
+    );
+
+    let current_costs: Vec<u64> = purchases.iter().map(|p| p.logistics_cost).collect();
+    let current_total = current_costs
+        .iter()
+        .try_fold(0u64, |total, cost| total.checked_add(*cost))
+        .ok_or(LogisticsError::MathOverflow)?;
+    require!(
+        consolidated_logistics_cost <= current_total,
+        LogisticsError::InvalidConsolidation
+    );
+    let adjusted_costs = prorate_logistics_costs(&current_costs, consolidated_logistics_cost);
+    let refund_amount = current_total - consolidated_logistics_cost;
+
+    let token_mint = ctx.accounts.buyer_token_account.mint;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+
+    let mut refunds = Vec::with_capacity(purchases.len());
+    for ((purchase, adjusted_cost), info) in purchases
+        .iter_mut()
+        .zip(adjusted_costs)
+        .zip(purchase_infos.iter())
+    {
+        let refund = purchase.logistics_cost - adjusted_cost;
+        purchase.logistics_cost = adjusted_cost;
+        purchase.total_amount -= refund;
+        purchase.consolidated = true;
+        refunds.push(refund);
+
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data[..];
+        purchase.try_serialize(&mut writer)?;
+    }
+
+    // Escrow only moves once every purchase records its adjusted cost
+    for ((purchase, refund), escrow_info) in purchases.iter().zip(refunds).zip(escrow_infos.iter())
+    {
+        // Each refund comes out of that purchase's logistics portion
+        let expected_escrow = if purchase.split_escrow {
+            pda::logistics_escrow_pda(purchase.purchase_id).0
+        } else {
+            escrow_vault.key()
+        };
+        require_keys_eq!(
+            escrow_info.key(),
+            expected_escrow,
+            LogisticsError::MissingEscrowBucket
+        );
+        release_escrow(
+            &ctx.accounts.token_program.to_account_info(),
+            escrow_info,
+            &escrow_vault,
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            refund,
+            signer,
+        )?;
+    }
+
+    let shipment_account = &mut ctx.accounts.shipment_account;
+    shipment_account.provider = provider;
+    shipment_account.buyer = buyer;
+    shipment_account.seller = seller.unwrap_or_default();
+    shipment_account.purchase_ids = purchase_ids.clone();
+    shipment_account.logistics_cost = consolidated_logistics_cost;
+    shipment_account.created_at = Clock::get()?.unix_timestamp;
+    shipment_account.version = ACCOUNT_VERSION;
+    shipment_account.bump = ctx.bumps.shipment_account;
+
+    emit!(ShipmentsConsolidated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        shipment: ctx.accounts.shipment_account.key(),
+        provider,
+        buyer,
+        seller: ctx.accounts.shipment_account.seller,
+        purchase_ids,
+        logistics_cost: consolidated_logistics_cost,
+        refund_amount,
+    });
+
+    Ok(())
+}
+
+pub fn mark_shipped(ctx: Context<MarkShipped>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == purchase_account.chosen_logistics_provider,
+        LogisticsError::NotAuthorized
+    );
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(
+        purchase_account.shipped_at == 0,
+        LogisticsError::AlreadyShipped
+    );
+    require!(
+        !purchase_account.provider_declined(),
+        LogisticsError::ProviderDeclined
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.shipped_at = now;
+
+    emit!(PurchaseShipped {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        shipped_by: authority,
+        unix_timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn record_tracking(
+    ctx: Context<RecordTracking>,
+    _purchase_id: u64,
+    carrier_code: u16,
+    tracking_hash: [u8; 32],
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.set_tracking(&TrackingInfo {
+        carrier_code,
+        tracking_hash,
+        recorded_at: now,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.logistics_provider.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(TrackingRecorded {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        logistics_provider: ctx.accounts.logistics_provider.key(),
+        carrier_code,
+        tracking_hash,
+        unix_timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn submit_pod(ctx: Context<SubmitPod>, _purchase_id: u64, pod_hash: [u8; 32]) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.set_proof_of_delivery(&ProofOfDelivery {
+        pod_hash,
+        submitted_at: now,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.logistics_provider.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(ProofOfDeliverySubmitted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        logistics_provider: ctx.accounts.logistics_provider.key(),
+        pod_hash,
+        unix_timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn provider_accept_order(ctx: Context<AnswerAssignment>, _purchase_id: u64) -> Result<()> {
+    answer_assignment(ctx, true)
+}
+
+pub fn provider_decline_order(ctx: Context<AnswerAssignment>, _purchase_id: u64) -> Result<()> {
+    answer_assignment(ctx, false)
+}
+
+fn answer_assignment(ctx: Context<AnswerAssignment>, accepted: bool) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(
+        purchase_account.shipped_at == 0,
+        LogisticsError::AlreadyShipped
+    );
+    require!(
+        purchase_account.provider_assignment().is_none(),
+        LogisticsError::AssignmentAlreadyAnswered
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.set_provider_assignment(&ProviderAssignment {
+        accepted,
+        responded_at: now,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.logistics_provider.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(ProviderAssignmentAnswered {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        logistics_provider: ctx.accounts.logistics_provider.key(),
+        accepted,
+        unix_timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn reassign_logistics(
+    ctx: Context<ReassignLogistics>,
+    _purchase_id: u64,
+    logistics_provider: Pubkey,
+    logistics_provider_index: u8,
+    max_logistics_cost: u64,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(
+        !purchase_account.consolidated,
+        LogisticsError::AlreadyConsolidated
+    );
+    require!(
+        purchase_account.provider_declined(),
+        LogisticsError::ProviderNotDeclined
+    );
+
+    let trade_account = &ctx.accounts.trade_account;
+    let provider_index = trade_account
+        .provider_at(logistics_provider_index, &logistics_provider)
+        .ok_or(LogisticsError::InvalidLogisticsProvider)?;
+    require!(
+        logistics_provider != purchase_account.chosen_logistics_provider,
+        LogisticsError::InvalidLogisticsProvider
+    );
+    require!(
+        trade_account.provider_enabled(provider_index),
+        LogisticsError::ProviderDisabled
+    );
+
+    // Re-price the logistics leg as buy_trade would have, in the purchase's
+    // mint and, for USD-priced trades, at the rate the purchase filled at
+    let (_, logistics_costs) = trade_account
+        .prices_for_mint(&purchase_account.token_mint)
+        .ok_or(LogisticsError::InvalidTokenMint)?;
+    let rate_per_kg = if trade_account.weight_grams > 0 {
+        trade_account.logistics_rates_per_kg[provider_index]
+    } else {
+        0
+    };
+    let mut logistics_cost = compute_logistics_cost(
+        logistics_costs[provider_index],
+        rate_per_kg,
+        trade_account.weight_grams,
+        purchase_account.quantity,
+    )?;
+    if trade_account.price_feed.is_some() && purchase_account.token_mint == trade_account.token_mint
+    {
+        logistics_cost = usd_cents_to_token_amount(
+            logistics_cost,
+            purchase_account.fill_price,
+            purchase_account.fill_expo,
+            ctx.accounts.token_mint.decimals,
+        )?;
+    }
+    require!(
+        logistics_cost <= max_logistics_cost,
+        LogisticsError::LogisticsCostAboveLimit
+    );
+
+    let previous_provider = purchase_account.chosen_logistics_provider;
+    let previous_cost = purchase_account.logistics_cost;
+    purchase_account.total_amount = purchase_account
+        .total_amount
+        .checked_sub(previous_cost)
+        .and_then(|amount| amount.checked_add(logistics_cost))
+        .ok_or(LogisticsError::MathOverflow)?;
+    purchase_account.logistics_cost = logistics_cost;
+    purchase_account.chosen_logistics_provider = logistics_provider;
+    count_provider_purchases_closed(
+        &ctx.accounts.previous_provider_account,
+        &previous_provider,
+        1,
+    )?;
+    count_provider_purchase_opened(&ctx.accounts.provider_account, &logistics_provider)?;
+    // The new provider answers afresh, and the old one's sign-off no longer counts
+    purchase_account.clear_provider_assignment();
+    purchase_account.confirmations &= !ConfirmingParty::LogisticsProvider.mask();
+
+    let logistics_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    if logistics_cost > previous_cost {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: logistics_escrow,
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, logistics_cost - previous_cost)?;
+    } else {
+        let token_mint = purchase_account.token_mint;
+        let seeds = &[
+            pda::ESCROW_SEED,
+            token_mint.as_ref(),
+            &[ctx.bumps.escrow_token_account],
+        ];
+        release_escrow(
+            &ctx.accounts.token_program.to_account_info(),
+            &logistics_escrow,
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            previous_cost - logistics_cost,
+            &[&seeds[..]],
+        )?;
+    }
+
+    // The sub-vault must hold exactly the re-priced leg, neither more nor less
+    if let Some(logistics_escrow) = ctx.accounts.logistics_escrow.as_mut() {
+        logistics_escrow.reload()?;
+        require!(
+            logistics_escrow.amount == purchase_account.logistics_cost,
+            LogisticsError::EscrowMismatch
+        );
+    }
+
+    emit!(LogisticsReassigned {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        previous_provider,
+        logistics_provider,
+        previous_cost,
+        logistics_cost,
+    });
+    Ok(())
+}
+
+pub fn sign_confirmation(ctx: Context<SignConfirmation>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+
+    let signer = ctx.accounts.authority.key();
+    let party = confirming_party(
+        &signer,
+        &purchase_account.buyer,
+        &ctx.accounts.trade_account.seller,
+        &purchase_account.chosen_logistics_provider,
+    );
+    require!(
+        purchase_account.record_confirmation(party),
+        LogisticsError::ConfirmationAlreadySigned
+    );
+
+    emit!(ConfirmationSigned {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        signer,
+        party,
+        confirmations: purchase_account.confirmations,
+    });
+    Ok(())
+}
+
+pub fn seller_cancel_purchase(
+    ctx: Context<SellerCancelPurchase>,
+    _purchase_id: u64,
+    reason: CancelReason,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let trade_account = &mut ctx.accounts.trade_account;
+
+    require!(
+        reason != CancelReason::None,
+        LogisticsError::InvalidCancelReason
+    );
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(
+        purchase_account.shipped_at == 0 && purchase_account.delivered_quantity == 0,
+        LogisticsError::AlreadyShipped
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+    purchase_account.cancelled = true;
+    purchase_account.cancel_reason = reason;
+    count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+    trade_account.record_refund(purchase_account.quantity);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_account.purchase_id,
+    );
+
+    // Only registered sellers have a counter; the PDA is always passed so
+    // the seller cannot skip it.
+    let seller_info = ctx.accounts.seller_account.to_account_info();
+    let mut cancellations = 0;
+    if let Some(mut seller_account) = load_seller_account(&seller_info)? {
+        seller_account.cancellations = seller_account.cancellations.saturating_add(1);
+        cancellations = seller_account.cancellations;
+        store_seller_account(&seller_info, &seller_account)?;
+    }
+
+    // The premium is refunded along with the escrow
+    let refund_premium = purchase_account.insurance_status == InsuranceStatus::Active;
+    if refund_premium {
+        purchase_account.insurance_status = InsuranceStatus::None;
+    }
+    commit_settlement(purchase_account)?;
+
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    refund_escrow(
+        &ctx.accounts.token_program.to_account_info(),
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.buyer_token_account.to_account_info(),
+        0,
+        signer,
+    )?;
+    let mut refund_amount = purchase_account.total_amount;
+
+    if refund_premium {
+        let Some(insurance_pool) = ctx.accounts.insurance_pool.as_ref() else {
+            return err!(LogisticsError::MissingInsurancePool);
+        };
+        let pool_bump = ctx.bumps.insurance_pool;
+        let seeds = &[pda::INSURANCE_POOL_SEED, token_mint.as_ref(), &[pool_bump]];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: insurance_pool.to_account_info(),
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: insurance_pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, purchase_account.insurance_premium)?;
+        refund_amount += purchase_account.insurance_premium;
+    }
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.purchases_cancelled += 1;
+    }
+
+    emit!(PurchaseCancelledBySeller {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        seller: trade_account.seller,
+        buyer: purchase_account.buyer,
+        token_mint,
+        refund_amount,
+        cancellations,
+        reason,
+    });
+    Ok(())
+}
+
+pub fn cancel_purchase(
+    ctx: Context<CancelPurchase>,
+    _purchase_id: u64,
+    reason: CancelReason,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let trade_account = &mut ctx.accounts.trade_account;
+
+    require!(
+        reason != CancelReason::None,
+        LogisticsError::InvalidCancelReason
+    );
+    require!(
+        ctx.accounts.buyer.key() == purchase_account.buyer,
+        LogisticsError::NotAuthorized
+    );
+    require!(
+        !purchase_account.delivered_and_confirmed,
+        LogisticsError::AlreadyConfirmed
+    );
+    require!(!purchase_account.disputed, LogisticsError::Disputed);
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+    require!(
+        purchase_account.delivered_quantity == 0,
+        LogisticsError::PartiallyDelivered
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    purchase_account.delivered_and_confirmed = true;
+    purchase_account.mark_settled(now);
+    purchase_account.cancelled = true;
+    purchase_account.cancel_reason = reason;
+    count_provider_purchases_closed(
+        &ctx.accounts.provider_account,
+        &purchase_account.chosen_logistics_provider,
+        1,
+    )?;
+    trade_account.record_refund(purchase_account.quantity);
+
+    mark_order_settled(
+        ctx.accounts.buyer_order_index.as_mut(),
+        ctx.accounts.seller_order_index.as_mut(),
+        purchase_account.purchase_id,
+    );
+    commit_settlement(purchase_account)?;
+
+    // Refund buyer
+    let escrow_bump = ctx.bumps.escrow_token_account;
+
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[escrow_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let global_state = &ctx.accounts.global_state;
+    let within_cooling_off =
+        now <= purchase_account.timers_start() + global_state.cancellation_window_seconds;
+    let restocking_fee = if within_cooling_off || ctx.accounts.seller.is_some() {
+        0
+    } else {
+        let product_amount = purchase_account
+            .unit_price
+            .checked_mul(purchase_account.quantity)
+            .ok_or(LogisticsError::MathOverflow)?;
+        compute_restocking_fee(product_amount, global_state.restocking_fee_bps)
+    };
+    let refund_amount = purchase_account
+        .total_amount
+        .checked_sub(restocking_fee)
+        .ok_or(LogisticsError::MathOverflow)?;
+    let fee_reversed = compute_fee_reversal(purchase_account)?;
+
+    // The restocking fee comes out of the product portion
+    if restocking_fee > 0 {
+        let seller_token_account = ctx
+            .accounts
+            .seller_token_account
+            .as_ref()
+            .ok_or(LogisticsError::MissingPayoutAccounts)?;
+        let product_escrow = escrow_bucket(
+            purchase_account,
+            ctx.accounts.product_escrow.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?;
+        release_escrow(
+            &ctx.accounts.token_program.to_account_info(),
+            &product_escrow,
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &seller_token_account.to_account_info(),
+            restocking_fee,
+            signer,
+        )?;
+    }
+
+    refund_escrow(
+        &ctx.accounts.token_program.to_account_info(),
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+        &ctx.accounts.buyer_token_account.to_account_info(),
+        restocking_fee,
+        signer,
+    )?;
+    purchase_account.unlock_settlement();
+
+    if let Some(stats) = ctx.accounts.stats.as_mut() {
+        stats.purchases_cancelled += 1;
+        stats.record_fee_reversal(purchase_account.token_mint, fee_reversed);
+    }
+
+    if fee_reversed > 0 {
+        emit!(FeeReversed {
+            event_seq: ctx.accounts.global_state.next_event_seq(),
+            purchase_id: purchase_account.purchase_id,
+            buyer: purchase_account.buyer,
+            token_mint: purchase_account.token_mint,
+            fee_amount: fee_reversed,
+            unix_timestamp: now,
+        });
+    }
+
+    let event = PurchaseCancelled {
+        schema_version: EVENT_SCHEMA_VERSION,
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        token_mint: purchase_account.token_mint,
+        refund_amount,
+        restocking_fee,
+        reason,
+        unix_timestamp: now,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn release_seller_stream(ctx: Context<ReleaseSellerStream>, _purchase_id: u64) -> Result<()> {
+    ctx.accounts.global_state.check_not_paused()?;
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut stream = purchase_account
+        .seller_stream()
+        .ok_or(LogisticsError::StreamNotRunning)?;
+    let amount = stream.releasable(Clock::get()?.unix_timestamp);
+    require!(amount > 0, LogisticsError::NothingToClaim);
+    stream.released += amount;
+    purchase_account.set_seller_stream(&stream)?;
+
+    let destination = seller_payout_account(
+        ctx.accounts.seller_account.as_deref(),
+        ctx.accounts.seller_token_account.as_ref(),
+        ctx.accounts.seller_payout_token_account.as_ref(),
+        purchase_account.token_mint,
+    )?
+    .ok_or(LogisticsError::MissingPayoutAccounts)?;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        purchase_account.token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    release_escrow(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.product_escrow.to_account_info(),
+        &ctx.accounts.escrow_token_account.to_account_info(),
+        &destination.to_account_info(),
+        amount,
+        &[&seeds[..]],
+    )?;
+
+    emit!(SellerStreamReleased {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        seller: ctx.accounts.trade_account.seller,
+        amount,
+        released: stream.released,
+    });
+    Ok(())
+}
+
+pub fn open_claimable_balance(ctx: Context<OpenClaimableBalance>) -> Result<()> {
+    let claimable = &mut ctx.accounts.claimable_balance;
+    claimable.recipient = ctx.accounts.recipient.key();
+    claimable.token_mint = ctx.accounts.token_mint.key();
+    claimable.version = ACCOUNT_VERSION;
+    claimable.bump = ctx.bumps.claimable_balance;
+    Ok(())
+}
+
+pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+    let claimable = &mut ctx.accounts.claimable_balance;
+    let amount = claimable.amount;
+    require!(amount > 0, LogisticsError::NothingToClaim);
+    claimable.amount = 0;
+
+    let token_mint = claimable.token_mint;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow_token_account.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.escrow_token_account.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(PayoutClaimed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        recipient: ctx.accounts.recipient.key(),
+        token_mint,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn claim_insurance(ctx: Context<ClaimInsurance>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        purchase_account.insurance_status == InsuranceStatus::Claimable,
+        LogisticsError::InsuranceNotClaimable
+    );
+
+    let amount = purchase_account
+        .unit_price
+        .checked_mul(purchase_account.quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    require!(
+        ctx.accounts.insurance_pool.amount >= amount,
+        LogisticsError::InsufficientInsurancePool
+    );
+    purchase_account.insurance_status = InsuranceStatus::Claimed;
+
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[
+        pda::INSURANCE_POOL_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.insurance_pool],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.insurance_pool.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.insurance_pool.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(InsuranceClaimed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        seller: ctx.accounts.seller.key(),
+        token_mint,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn close_purchase(ctx: Context<ClosePurchase>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &ctx.accounts.purchase_account;
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == purchase_account.buyer || authority == ctx.accounts.trade_account.seller,
+        LogisticsError::NotAuthorized
+    );
+    require!(
+        purchase_account.settled && purchase_account.delivered_and_confirmed,
+        LogisticsError::NotSettled
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= purchase_account.settled_at + PURCHASE_RETENTION_SECONDS,
+        LogisticsError::RetentionWindowActive
+    );
+    require!(
+        purchase_account
+            .return_request()
+            .is_none_or(|request| request.status == ReturnStatus::Completed),
+        LogisticsError::InvalidReturnState
+    );
+    require!(
+        purchase_account
+            .seller_stream()
+            .is_none_or(|stream| stream.is_finished()),
+        LogisticsError::StreamActive
+    );
+    require!(
+        purchase_account
+            .dispute_resolution()
+            .is_none_or(|resolution| resolution.is_final_at(now)),
+        LogisticsError::ResolutionNotFinal
+    );
+
+    // Both sub-vaults are empty once the purchase settles; anything sent to
+    // them since is swept into the mint vault so they can be closed.
+    if purchase_account.split_escrow {
+        let (Some(product_escrow), Some(logistics_escrow)) = (
+            ctx.accounts.product_escrow.as_ref(),
+            ctx.accounts.logistics_escrow.as_ref(),
+        ) else {
+            return err!(LogisticsError::MissingEscrowBucket);
+        };
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+        let seeds = &[
+            pda::ESCROW_SEED,
+            purchase_account.token_mint.as_ref(),
+            &[ctx.bumps.escrow_token_account],
+        ];
+        let signer = &[&seeds[..]];
+        for bucket in [product_escrow, logistics_escrow] {
+            sweep_to_vault(
+                &token_program,
+                &bucket.to_account_info(),
+                &escrow_vault,
+                bucket.amount,
+                signer,
+            )?;
+            let close_ctx = CpiContext::new_with_signer(
+                token_program.clone(),
+                CloseAccount {
+                    account: bucket.to_account_info(),
+                    destination: ctx.accounts.payer.to_account_info(),
+                    authority: escrow_vault.clone(),
+                },
+                signer,
+            );
+            token::close_account(close_ctx)?;
+        }
+    }
+
+    emit!(PurchaseClosed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        rent_recipient: purchase_account.payer,
+    });
+    Ok(())
+}
+
+pub fn open_shipping_info(ctx: Context<OpenShippingInfo>, purchase_id: u64) -> Result<()> {
+    let shipping_info = &mut ctx.accounts.shipping_info;
+    shipping_info.version = ACCOUNT_VERSION;
+    shipping_info.purchase_id = purchase_id;
+    shipping_info.buyer = ctx.accounts.buyer.key();
+    shipping_info.bump = ctx.bumps.shipping_info;
+    Ok(())
+}
+
+pub fn post_shipping_info(
+    ctx: Context<PostShippingInfo>,
+    purchase_id: u64,
+    ephemeral_pubkey: [u8; 32],
+    seller_ciphertext: Vec<u8>,
+    provider_ciphertext: Vec<u8>,
+) -> Result<()> {
+    require!(
+        seller_ciphertext.len() <= MAX_SHIPPING_CIPHERTEXT_LEN
+            && provider_ciphertext.len() <= MAX_SHIPPING_CIPHERTEXT_LEN,
+        LogisticsError::ShippingInfoTooLong
+    );
+    let purchase_account = &ctx.accounts.purchase_account;
+    require!(!purchase_account.settled, LogisticsError::AlreadySettled);
+
+    // Reposting replaces the ciphertexts, e.g. after a change of provider
+    let shipping_info = &mut ctx.accounts.shipping_info;
+    shipping_info.ephemeral_pubkey = ephemeral_pubkey;
+    shipping_info.seller_ciphertext = seller_ciphertext;
+    shipping_info.provider_ciphertext = provider_ciphertext;
+    shipping_info.posted_at = Clock::get()?.unix_timestamp;
+
+    emit!(ShippingInfoPosted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+        buyer: purchase_account.buyer,
+        logistics_provider: purchase_account.chosen_logistics_provider,
+    });
+    Ok(())
+}
+
+pub fn close_shipping_info(ctx: Context<CloseShippingInfo>, purchase_id: u64) -> Result<()> {
+    require!(
+        ctx.accounts.purchase_account.settled,
+        LogisticsError::NotSettled
+    );
+
+    emit!(ShippingInfoClosed {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id,
+    });
+    Ok(())
+}
+
+pub fn get_purchase_status(
+    ctx: Context<GetPurchaseStatus>,
+    _purchase_id: u64,
+) -> Result<PurchaseStatus> {
+    let purchase_account = &ctx.accounts.purchase_account;
+    let escrow_fee_bps = purchase_account.escrow_fee_bps();
+    let amounts = purchase_account.settlement_payouts()?;
+
+    Ok(PurchaseStatus {
+        purchase_id: purchase_account.purchase_id,
+        trade_id: purchase_account.trade_id,
+        buyer: purchase_account.buyer,
+        token_mint: purchase_account.token_mint,
+        stage: purchase_account.stage(),
+        quantity: purchase_account.quantity,
+        delivered_quantity: purchase_account.delivered_quantity,
+        total_amount: purchase_account.total_amount,
+        escrow_fee_bps,
+        seller_amount: amounts.seller_amount,
+        logistics_amount: amounts.logistics_amount,
+        fee_amount: amounts.fee_amount,
+    })
+}
+
+pub fn audit_escrow<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuditEscrow<'info>>,
+    trade_id: u64,
+) -> Result<()> {
+    // Each audited purchase is passed as (purchase, product sub-vault, logistics sub-vault)
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(3),
+        LogisticsError::InvalidPurchaseAccount
+    );
+
+    let mut audited = 0u32;
+    let mut total_expected = 0u64;
+    let mut total_held = 0u64;
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let (purchase_info, product_info, logistics_info) =
+            (&accounts[0], &accounts[1], &accounts[2]);
+        require_keys_eq!(
+            *purchase_info.owner,
+            crate::ID,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        let purchase =
+            PurchaseAccount::try_deserialize(&mut &purchase_info.try_borrow_data()?[..])?;
+        require_keys_eq!(
+            purchase_info.key(),
+            pda::purchase_pda(purchase.purchase_id).0,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        require!(
+            purchase.trade_id == trade_id,
+            LogisticsError::InvalidPurchaseAccount
+        );
+        require_keys_eq!(
+            product_info.key(),
+            pda::product_escrow_pda(purchase.purchase_id).0,
+            LogisticsError::MissingEscrowBucket
+        );
+        require_keys_eq!(
+            logistics_info.key(),
+            pda::logistics_escrow_pda(purchase.purchase_id).0,
+            LogisticsError::MissingEscrowBucket
+        );
+
+        let Some(expected) = purchase.expected_escrow() else {
+            continue;
+        };
+        let held = load_token_account(product_info)?
+            .amount
+            .checked_add(load_token_account(logistics_info)?.amount)
+            .ok_or(LogisticsError::MathOverflow)?;
+        if held != expected {
+            emit!(EscrowInvariantViolated {
+                event_seq: ctx.accounts.global_state.next_event_seq(),
+                trade_id,
+                purchase_id: purchase.purchase_id,
+                expected,
+                held,
+            });
+        }
+
+        audited += 1;
+        total_expected = total_expected
+            .checked_add(expected)
+            .ok_or(LogisticsError::MathOverflow)?;
+        total_held = total_held
+            .checked_add(held)
+            .ok_or(LogisticsError::MathOverflow)?;
+    }
+
+    emit!(EscrowAudited {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        purchases: audited,
+        expected: total_expected,
+        held: total_held,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, shard_no: u32)]
+pub struct CreateOrderIndexShard<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OrderIndex::INIT_SPACE,
+        seeds = [pda::ORDER_INDEX_SEED, user.as_ref(), shard_no.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_index: Account<'info, OrderIndex>,
+    #[account(
+        seeds = [pda::ORDER_INDEX_SEED, user.as_ref(), shard_no.saturating_sub(1).to_le_bytes().as_ref()],
+        bump = previous_shard.bump
+    )]
+    pub previous_shard: Option<Account<'info, OrderIndex>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, shard_no: u32)]
+pub struct AddPurchaseIndexPage<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, user.as_ref(), shard_no.to_le_bytes().as_ref()],
+        bump = order_index.bump,
+        realloc = order_index.to_account_info().data_len() + ORDER_INDEX_PAGE_BYTES,
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub order_index: Account<'info, OrderIndex>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct OpenBuyerQuota<'info> {
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + BuyerQuota::INIT_SPACE,
+        seeds = [pda::BUYER_QUOTA_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_quota: Account<'info, BuyerQuota>,
+    /// CHECK: Wallet the quota counts purchases for
+    pub buyer: UncheckedAccount<'info>,
+    /// Anyone may open a buyer's quota, typically the buyer or their relayer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct BuyTrade<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::BUYER_SEED, buyer.key().as_ref()],
+        bump = buyer_account.bump,
+        constraint = buyer_account.is_registered @ LogisticsError::BuyerNotRegistered
+    )]
+    pub buyer_account: Account<'info, BuyerAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Account<'info, TokenAccount>,
+    #[account(
+        constraint = trade_account.prices_for_mint(&token_mint.key()).is_some() @ LogisticsError::InvalidTokenMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump = allowed_mint.bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    /// CHECK: Pyth price account, must be the feed configured on the trade; parsed by the handler
+    #[account(constraint = trade_account.price_feed == Some(price_feed.key()) @ LogisticsError::InvalidPriceFeed)]
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [pda::INSURANCE_POOL_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub insurance_pool: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, buyer.key().as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::RESERVATION_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = reservation.bump,
+        close = buyer
+    )]
+    pub reservation: Option<Account<'info, ReservationAccount>>,
+    /// Required when the trade sets `max_per_buyer` or a purchase cooldown
+    #[account(
+        mut,
+        seeds = [pda::BUYER_QUOTA_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump = buyer_quota.bump
+    )]
+    pub buyer_quota: Option<Account<'info, BuyerQuota>>,
+    /// Buyer's token account for the gate mint of a token-gated trade
+    #[account(constraint = gate_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner)]
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// Buyer's attestation from the verifier of an attestation-gated trade
+    #[account(constraint = buyer_attestation.subject == buyer.key() @ LogisticsError::BuyerGateNotSatisfied)]
+    pub buyer_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// Buyer's attestation from the region registrar, once one is configured
+    #[account(constraint = region_attestation.subject == buyer.key() @ LogisticsError::RegionNotVerified)]
+    pub region_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// Storefront routing the order; must be registered in `GlobalState`
+    pub platform: Option<Signer<'info>>,
+    /// Buyer's loyalty account; any redeemed discount applies to this purchase
+    #[account(
+        mut,
+        seeds = [pda::LOYALTY_SEED, buyer.key().as_ref()],
+        bump = loyalty_account.bump
+    )]
+    pub loyalty_account: Option<Account<'info, LoyaltyAccount>>,
+    /// Required when a session key signs instead of the buyer
+    #[account(
+        mut,
+        seeds = [pda::SESSION_SEED, buyer.key().as_ref(), authority.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    /// CHECK: The buyer's wallet; signs as `authority` or through `session`
+    #[account(
+        mut,
+        constraint = buyer.key() == authority.key() || session.is_some() @ LogisticsError::NotAuthorized
+    )]
+    pub buyer: UncheckedAccount<'info>,
+    /// The buyer, or a session key they authorised; pays the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRelayNonce<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RelayNonce::INIT_SPACE,
+        seeds = [pda::RELAY_NONCE_SEED, buyer.key().as_ref()],
+        bump
+    )]
+    pub relay_nonce: Account<'info, RelayNonce>,
+    /// CHECK: Wallet whose relayed orders the nonce sequences
+    pub buyer: UncheckedAccount<'info>,
+    /// Anyone may open the nonce, typically the relayer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(order: RelayedOrder)]
+pub struct BuyTradeRelayed<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, order.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::BUYER_SEED, order.buyer.as_ref()],
+        bump = buyer_account.bump,
+        constraint = buyer_account.is_registered @ LogisticsError::BuyerNotRegistered
+    )]
+    pub buyer_account: Account<'info, BuyerAccount>,
+    #[account(
+        mut,
+        seeds = [pda::RELAY_NONCE_SEED, order.buyer.as_ref()],
+        bump = relay_nonce.bump
+    )]
+    pub relay_nonce: Account<'info, RelayNonce>,
+    /// CHECK: Delegate the buyer approved on their token account; signs by seeds
+    #[account(seeds = [pda::RELAY_AUTHORITY_SEED], bump)]
+    pub relay_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == order.buyer @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = relayer,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = relayer,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Account<'info, TokenAccount>,
+    #[account(address = trade_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    /// Required when the order pays a relayer fee
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint
+    )]
+    pub relayer_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, order.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    /// Required when the trade sets `max_per_buyer` or a purchase cooldown
+    #[account(
+        mut,
+        seeds = [pda::BUYER_QUOTA_SEED, order.trade_id.to_le_bytes().as_ref(), order.buyer.as_ref()],
+        bump = buyer_quota.bump
+    )]
+    pub buyer_quota: Option<Account<'info, BuyerQuota>>,
+    /// Buyer's token account for the gate mint of a token-gated trade
+    #[account(constraint = gate_token_account.owner == order.buyer @ LogisticsError::InvalidTokenOwner)]
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// Buyer's attestation from the verifier of an attestation-gated trade
+    #[account(constraint = buyer_attestation.subject == order.buyer @ LogisticsError::BuyerGateNotSatisfied)]
+    pub buyer_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// Buyer's attestation from the region registrar, once one is configured
+    #[account(constraint = region_attestation.subject == order.buyer @ LogisticsError::RegionNotVerified)]
+    pub region_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// CHECK: Instructions sysvar, read to find the buyer's Ed25519 signature
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// Submits the order and pays the fees and rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct BuyTradeWithPermit<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = delegate,
+        space = 8 + PurchaseAccount::INIT_SPACE,
+        seeds = [pda::PURCHASE_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::BUYER_SEED, spending_permit.buyer.as_ref()],
+        bump = buyer_account.bump,
+        constraint = buyer_account.is_registered @ LogisticsError::BuyerNotRegistered
+    )]
+    pub buyer_account: Account<'info, BuyerAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SPENDING_PERMIT_SEED, spending_permit.buyer.as_ref(), delegate.key().as_ref()],
+        bump = spending_permit.bump,
+        has_one = delegate @ LogisticsError::NotAuthorized,
+        has_one = buyer_token_account @ LogisticsError::InvalidTokenOwner
+    )]
+    pub spending_permit: Account<'info, SpendingPermit>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = delegate,
+        seeds = [pda::PRODUCT_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = delegate,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, global_state.purchase_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_token_account
+    )]
+    pub logistics_escrow: Account<'info, TokenAccount>,
+    #[account(address = trade_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, spending_permit.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    /// Required when the trade sets `max_per_buyer` or a purchase cooldown
+    #[account(
+        mut,
+        seeds = [pda::BUYER_QUOTA_SEED, trade_id.to_le_bytes().as_ref(), spending_permit.buyer.as_ref()],
+        bump = buyer_quota.bump
+    )]
+    pub buyer_quota: Option<Account<'info, BuyerQuota>>,
+    /// Buyer's token account for the gate mint of a token-gated trade
+    #[account(constraint = gate_token_account.owner == spending_permit.buyer @ LogisticsError::InvalidTokenOwner)]
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    /// Buyer's attestation from the verifier of an attestation-gated trade
+    #[account(constraint = buyer_attestation.subject == spending_permit.buyer @ LogisticsError::BuyerGateNotSatisfied)]
+    pub buyer_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// Buyer's attestation from the region registrar, once one is configured
+    #[account(constraint = region_attestation.subject == spending_permit.buyer @ LogisticsError::RegionNotVerified)]
+    pub region_attestation: Option<Account<'info, BuyerAttestation>>,
+    /// Session key or backend named in the permit; pays the fees and rent
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ConfirmDeliveryAndPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Seller wallet, only used as the authority of its associated token account
+    #[account(address = trade_account.seller @ LogisticsError::InvalidTokenOwner)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Logistics provider wallet, only used as the authority of its associated token account
+    #[account(address = purchase_account.chosen_logistics_provider @ LogisticsError::InvalidTokenOwner)]
+    pub logistics_provider: UncheckedAccount<'info>,
+    #[account(address = purchase_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `seller_account`, which replaces
+    /// `seller_token_account` when set
+    #[account(mut)]
+    pub seller_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = logistics_provider
+    )]
+    pub logistics_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `provider_account`, which replaces
+    /// `logistics_token_account` when set
+    #[account(mut)]
+    pub logistics_payout_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, trade_account.seller.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = seller_claimable.bump
+    )]
+    pub seller_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, purchase_account.chosen_logistics_provider.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = logistics_claimable.bump
+    )]
+    pub logistics_claimable: Option<Account<'info, ClaimableBalance>>,
+    /// Token account the protocol's share of escrow fees is collected in
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// Required when the purchase was routed by a platform
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, purchase_account.referring_platform().as_ref(), purchase_account.token_mint.as_ref()],
+        bump = platform_claimable.bump
+    )]
+    pub platform_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::SELLER_SEED, trade_account.seller.as_ref()],
+        bump = seller_account.bump
+    )]
+    pub seller_account: Option<Account<'info, SellerAccount>>,
+    /// CHECK: AMM program configured by the admin; only invoked with a seller-approved route
+    #[account(
+        executable,
+        constraint = global_state.swap_program != Pubkey::default() @ LogisticsError::InvalidSwapRoute,
+        address = global_state.swap_program @ LogisticsError::InvalidSwapRoute
+    )]
+    pub swap_program: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        token::authority = seller
+    )]
+    pub seller_swap_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    /// Required when a confirmation delegate signs instead of the buyer
+    #[account(
+        seeds = [pda::BUYER_SEED, purchase_account.buyer.as_ref()],
+        bump = buyer_account.bump
+    )]
+    pub buyer_account: Option<Account<'info, BuyerAccount>>,
+    /// Required when a session key of the buyer signs instead of the buyer
+    #[account(
+        seeds = [pda::SESSION_SEED, purchase_account.buyer.as_ref(), buyer.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionAccount>>,
+    /// The purchase's buyer, their session key, or the delegate recorded on
+    /// their buyer account. On two-of-three trades the seller or logistics
+    /// provider may also sign.
+    #[account(
+        mut,
+        constraint = buyer.key() == purchase_account.buyer
+            || session.is_some()
+            || buyer_account
+                .as_ref()
+                .is_some_and(|account| account.is_confirmation_delegate(&buyer.key()))
+            || (trade_account.confirmation_mode == ConfirmationMode::TwoOfThree
+                && (buyer.key() == trade_account.seller
+                    || buyer.key() == purchase_account.chosen_logistics_provider))
+            @ LogisticsError::NotAuthorized
+    )]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct ConfirmDeliveriesBatch<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Seller wallet, only used as the authority of its associated token account
+    #[account(address = trade_account.seller @ LogisticsError::InvalidTokenOwner)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Logistics provider of every purchase in the batch, checked
+    /// against each purchase
+    pub logistics_provider: UncheckedAccount<'info>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `seller_account`, which replaces
+    /// `seller_token_account` when set
+    #[account(mut)]
+    pub seller_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = logistics_provider
+    )]
+    pub logistics_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `provider_account`, which replaces
+    /// `logistics_token_account` when set
+    #[account(mut)]
+    pub logistics_payout_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, trade_account.seller.as_ref(), token_mint.key().as_ref()],
+        bump = seller_claimable.bump
+    )]
+    pub seller_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, logistics_provider.key().as_ref(), token_mint.key().as_ref()],
+        bump = logistics_claimable.bump
+    )]
+    pub logistics_claimable: Option<Account<'info, ClaimableBalance>>,
+    /// Token account the protocol's share of escrow fees is collected in
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, buyer.key().as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        seeds = [pda::SELLER_SEED, trade_account.seller.as_ref()],
+        bump = seller_account.bump
+    )]
+    pub seller_account: Option<Account<'info, SellerAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct AutoSettleExpired<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Seller wallet, only used as the authority of its associated token account
+    #[account(address = trade_account.seller @ LogisticsError::InvalidTokenOwner)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Logistics provider wallet, only used as the authority of its associated token account
+    #[account(address = purchase_account.chosen_logistics_provider @ LogisticsError::InvalidTokenOwner)]
+    pub logistics_provider: UncheckedAccount<'info>,
+    #[account(address = purchase_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `seller_account`, which replaces
+    /// `seller_token_account` when set
+    #[account(mut)]
+    pub seller_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [pda::SELLER_SEED, trade_account.seller.as_ref()],
+        bump = seller_account.bump
+    )]
+    pub seller_account: Option<Account<'info, SellerAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = logistics_provider
+    )]
+    pub logistics_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `provider_account`, which replaces
+    /// `logistics_token_account` when set
+    #[account(mut)]
+    pub logistics_payout_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, trade_account.seller.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = seller_claimable.bump
+    )]
+    pub seller_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, purchase_account.chosen_logistics_provider.as_ref(), purchase_account.token_mint.as_ref()],
+        bump = logistics_claimable.bump
+    )]
+    pub logistics_claimable: Option<Account<'info, ClaimableBalance>>,
+    /// Token account the protocol's share of escrow fees is collected in
+    #[account(
+        mut,
+        seeds = [pda::FEE_VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+    /// Required when the purchase was routed by a platform
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, purchase_account.referring_platform().as_ref(), purchase_account.token_mint.as_ref()],
+        bump = platform_claimable.bump
+    )]
+    pub platform_claimable: Option<Account<'info, ClaimableBalance>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = cranker_token_account.owner == cranker.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub cranker_token_account: Option<Account<'info, TokenAccount>>,
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct AutoRefundTimeouts<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::INSURANCE_POOL_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub insurance_pool: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = cranker_token_account.owner == cranker.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub cranker_token_account: Option<Account<'info, TokenAccount>>,
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(shipment_id: u64)]
+pub struct ConsolidateShipments<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = provider,
+        space = 8 + ShipmentAccount::INIT_SPACE,
+        seeds = [pda::SHIPMENT_SEED, provider.key().as_ref(), shipment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub shipment_account: Account<'info, ShipmentAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, buyer_token_account.mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer: Signer<'info>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ClaimInsurance<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::INSURANCE_POOL_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub insurance_pool: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct MarkShipped<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct RecordTracking<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        address = purchase_account.chosen_logistics_provider @ LogisticsError::NotAuthorized
+    )]
+    pub logistics_provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct AnswerAssignment<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        address = purchase_account.chosen_logistics_provider @ LogisticsError::NotAuthorized
+    )]
+    pub logistics_provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ReassignLogistics<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(address = purchase_account.token_mint @ LogisticsError::InvalidTokenMint)]
+    pub token_mint: Account<'info, Mint>,
+    /// CHECK: Account PDA of the provider being replaced, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub previous_provider_account: UncheckedAccount<'info>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct SignConfirmation<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// Required when a confirmation delegate signs instead of the buyer
+    #[account(
+        seeds = [pda::BUYER_SEED, purchase_account.buyer.as_ref()],
+        bump = buyer_account.bump
+    )]
+    pub buyer_account: Option<Account<'info, BuyerAccount>>,
+    /// The buyer or their delegate, the seller, or the logistics provider
+    #[account(
+        constraint = authority.key() == purchase_account.buyer
+            || authority.key() == trade_account.seller
+            || authority.key() == purchase_account.chosen_logistics_provider
+            || buyer_account
+                .as_ref()
+                .is_some_and(|account| account.is_confirmation_delegate(&authority.key()))
+            @ LogisticsError::NotAuthorized
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct SubmitPod<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        address = purchase_account.chosen_logistics_provider @ LogisticsError::NotAuthorized
+    )]
+    pub logistics_provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct SellerCancelPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// CHECK: Seller account PDA, which may not exist; parsed by the handler when initialized
+    #[account(
+        mut,
+        seeds = [pda::SELLER_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub seller_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::INSURANCE_POOL_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub insurance_pool: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, seller.key().as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct CancelPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Logistics provider account PDA, which may not exist; checked and counted by the handler
+    #[account(mut)]
+    pub provider_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, purchase_account.buyer.as_ref(), buyer_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = buyer_order_index.bump
+    )]
+    pub buyer_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::ORDER_INDEX_SEED, trade_account.seller.as_ref(), seller_order_index.shard_no.to_le_bytes().as_ref()],
+        bump = seller_order_index.bump
+    )]
+    pub seller_order_index: Option<Account<'info, OrderIndex>>,
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// Seller co-signature waiving the restocking fee
+    #[account(address = trade_account.seller @ LogisticsError::NotAuthorized)]
+    pub seller: Option<Signer<'info>>,
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = seller_token_account.owner == trade_account.seller.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [pda::STATS_SEED],
+        bump = stats.bump
+    )]
+    pub stats: Option<Account<'info, Stats>>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ReleaseSellerStream<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Account<'info, TokenAccount>,
+    /// CHECK: Seller wallet, only used as the authority of its associated token account
+    #[account(address = trade_account.seller @ LogisticsError::InvalidTokenOwner)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = purchase_account.token_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    /// Payout account registered on `seller_account`, which replaces
+    /// `seller_token_account` when set
+    #[account(mut)]
+    pub seller_payout_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [pda::SELLER_SEED, trade_account.seller.as_ref()],
+        bump = seller_account.bump
+    )]
+    pub seller_account: Option<Account<'info, SellerAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct OpenClaimableBalance<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimableBalance::INIT_SPACE,
+        seeds = [pda::CLAIMABLE_SEED, recipient.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub claimable_balance: Account<'info, ClaimableBalance>,
+    /// CHECK: Wallet the deferred payouts are owed to
+    pub recipient: UncheckedAccount<'info>,
+    pub token_mint: Account<'info, Mint>,
+    /// Anyone may open a recipient's balance, typically whoever settles
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPayout<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::CLAIMABLE_SEED, recipient.key().as_ref(), claimable_balance.token_mint.as_ref()],
+        bump = claimable_balance.bump,
+        has_one = recipient
+    )]
+    pub claimable_balance: Account<'info, ClaimableBalance>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, claimable_balance.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = claimable_balance.token_mint,
+        token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub recipient: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ClosePurchase<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// CHECK: Original rent payer recorded on the purchase, receives the lamports
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct OpenShippingInfo<'info> {
+    #[account(
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + ShippingInfo::INIT_SPACE,
+        seeds = [pda::SHIPPING_INFO_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub shipping_info: Account<'info, ShippingInfo>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct PostShippingInfo<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SHIPPING_INFO_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = shipping_info.bump
+    )]
+    pub shipping_info: Account<'info, ShippingInfo>,
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct CloseShippingInfo<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        mut,
+        seeds = [pda::SHIPPING_INFO_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = shipping_info.bump,
+        close = buyer
+    )]
+    pub shipping_info: Account<'info, ShippingInfo>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct GetPurchaseStatus<'info> {
+    #[account(
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct AuditEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+}
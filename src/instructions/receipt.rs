@@ -0,0 +1,119 @@
+//! Compressed NFT receipts minted to buyers of settled purchases.
+
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::MintV1CpiBuilder;
+use mpl_bubblegum::programs::{SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use mpl_bubblegum::types::{MetadataArgs, TokenProgramVersion, TokenStandard};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn mint_purchase_receipt(ctx: Context<MintPurchaseReceipt>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        purchase_account.settled && purchase_account.delivered_and_confirmed,
+        LogisticsError::NotSettled
+    );
+    require!(
+        purchase_account.receipt().is_none(),
+        LogisticsError::ReceiptAlreadyMinted
+    );
+
+    let merkle_tree = ctx.accounts.receipt_config.merkle_tree;
+    purchase_account.set_receipt(&PurchaseReceipt {
+        merkle_tree,
+        minted_at: Clock::get()?.unix_timestamp,
+    })?;
+    let metadata = MetadataArgs {
+        name: format!("Receipt #{}", purchase_account.purchase_id),
+        symbol: RECEIPT_SYMBOL.to_string(),
+        uri: ctx.accounts.receipt_config.receipt_uri(purchase_account),
+        seller_fee_basis_points: 0,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: Vec::new(),
+    };
+
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    let seeds = &[pda::RECEIPT_AUTHORITY_SEED, &[ctx.bumps.receipt_authority]];
+    let buyer = ctx.accounts.buyer.to_account_info();
+    MintV1CpiBuilder::new(&ctx.accounts.bubblegum_program)
+        .tree_config(&ctx.accounts.tree_config)
+        .leaf_owner(&buyer)
+        .leaf_delegate(&buyer)
+        .merkle_tree(&ctx.accounts.merkle_tree)
+        .payer(&buyer)
+        .tree_creator_or_delegate(&ctx.accounts.receipt_authority)
+        .log_wrapper(&ctx.accounts.log_wrapper)
+        .compression_program(&ctx.accounts.compression_program)
+        .system_program(&ctx.accounts.system_program)
+        .metadata(metadata)
+        .invoke_signed(&[&seeds[..]])?;
+
+    emit!(PurchaseReceiptMinted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        merkle_tree,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct MintPurchaseReceipt<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::RECEIPT_CONFIG_SEED],
+        bump = receipt_config.bump
+    )]
+    pub receipt_config: Account<'info, ReceiptConfig>,
+    /// CHECK: Bubblegum tree config of the receipt tree, validated by Bubblegum
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: Receipt tree configured by the admin
+    #[account(mut, address = receipt_config.merkle_tree @ LogisticsError::InvalidReceiptTree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: PDA delegated the receipt tree, signs the mint
+    #[account(seeds = [pda::RECEIPT_AUTHORITY_SEED], bump)]
+    pub receipt_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: SPL noop program used by Bubblegum as a log wrapper
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: SPL account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,481 @@
+//! Post-delivery returns funded from seller reserves.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::state::*;
+
+pub fn open_returns_reserve(_ctx: Context<OpenReturnsReserve>) -> Result<()> {
+    Ok(())
+}
+
+pub fn fund_returns_reserve(ctx: Context<FundReturnsReserve>, amount: u64) -> Result<()> {
+    require!(amount > 0, LogisticsError::InvalidAmount);
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.seller_token_account.to_account_info(),
+            to: ctx.accounts.returns_reserve.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(ReturnsReserveFunded {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: ctx.accounts.seller.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        amount,
+    });
+    Ok(())
+}
+
+pub fn withdraw_returns_reserve(ctx: Context<WithdrawReturnsReserve>, amount: u64) -> Result<()> {
+    require!(amount > 0, LogisticsError::InvalidAmount);
+
+    let seller = ctx.accounts.seller.key();
+    let token_mint = ctx.accounts.returns_reserve.mint;
+    let seeds = &[
+        pda::RETURNS_RESERVE_SEED,
+        seller.as_ref(),
+        token_mint.as_ref(),
+        &[ctx.bumps.returns_reserve],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.returns_reserve.to_account_info(),
+            to: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.returns_reserve.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    emit!(ReturnsReserveWithdrawn {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller,
+        token_mint,
+        amount,
+    });
+    Ok(())
+}
+
+pub fn request_return(
+    ctx: Context<RequestReturn>,
+    _purchase_id: u64,
+    logistics_provider: Pubkey,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    require!(
+        purchase_account.settled
+            && purchase_account.delivered_and_confirmed
+            && !purchase_account.disputed
+            && !purchase_account.cancelled,
+        LogisticsError::NotReturnable
+    );
+    require!(
+        purchase_account.return_request().is_none(),
+        LogisticsError::ReturnAlreadyRequested
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now <= purchase_account.settled_at + RETURN_WINDOW_SECONDS,
+        LogisticsError::ReturnWindowClosed
+    );
+    require!(
+        ctx.accounts
+            .trade_account
+            .logistics_providers
+            .contains(&logistics_provider),
+        LogisticsError::InvalidLogisticsProvider
+    );
+
+    purchase_account.set_return_request(&ReturnRequest {
+        status: ReturnStatus::Requested,
+        logistics_provider,
+        logistics_cost: 0,
+        refund_amount: 0,
+        requested_at: now,
+    })?;
+    let new_len = 8 + purchase_account.try_to_vec()?.len();
+    grow_account(
+        &purchase_account.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(ReturnRequested {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        logistics_provider,
+    });
+    Ok(())
+}
+
+pub fn approve_return(
+    ctx: Context<ApproveReturn>,
+    _purchase_id: u64,
+    logistics_cost: u64,
+) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut request = purchase_account
+        .return_request()
+        .ok_or(LogisticsError::InvalidReturnState)?;
+    require!(
+        request.status == ReturnStatus::Requested,
+        LogisticsError::InvalidReturnState
+    );
+
+    let returned_quantity = purchase_account.quantity + purchase_account.delivered_quantity;
+    let refund_amount = purchase_account
+        .unit_price
+        .checked_mul(returned_quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+
+    request.status = ReturnStatus::Approved;
+    request.refund_amount = refund_amount;
+    request.logistics_cost = logistics_cost;
+    purchase_account.set_return_request(&request)?;
+    commit_settlement(purchase_account)?;
+
+    let seller = ctx.accounts.seller.key();
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[
+        pda::RETURNS_RESERVE_SEED,
+        seller.as_ref(),
+        token_mint.as_ref(),
+        &[ctx.bumps.returns_reserve],
+    ];
+    let signer = &[&seeds[..]];
+
+    // The refund and the return shipping are each reserved in their own bucket
+    let product_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.product_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    let logistics_escrow = escrow_bucket(
+        purchase_account,
+        ctx.accounts.logistics_escrow.as_ref(),
+        &ctx.accounts.escrow_token_account,
+    )?;
+    for (bucket, amount) in [
+        (product_escrow, refund_amount),
+        (logistics_escrow, logistics_cost),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.returns_reserve.to_account_info(),
+                to: bucket,
+                authority: ctx.accounts.returns_reserve.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+    purchase_account.unlock_settlement();
+
+    emit!(ReturnApproved {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        refund_amount,
+        logistics_cost,
+    });
+    Ok(())
+}
+
+pub fn complete_return(ctx: Context<CompleteReturn>, _purchase_id: u64) -> Result<()> {
+    let purchase_account = &mut ctx.accounts.purchase_account;
+    let mut request = purchase_account
+        .return_request()
+        .ok_or(LogisticsError::InvalidReturnState)?;
+    require!(
+        request.status == ReturnStatus::Approved,
+        LogisticsError::InvalidReturnState
+    );
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller || authority == request.logistics_provider,
+        LogisticsError::NotAuthorized
+    );
+
+    request.status = ReturnStatus::Completed;
+    purchase_account.set_return_request(&request)?;
+    commit_settlement(purchase_account)?;
+
+    let token_mint = purchase_account.token_mint;
+    let seeds = &[
+        pda::ESCROW_SEED,
+        token_mint.as_ref(),
+        &[ctx.bumps.escrow_token_account],
+    ];
+    let signer = &[&seeds[..]];
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let escrow_vault = ctx.accounts.escrow_token_account.to_account_info();
+    release_escrow(
+        &token_program,
+        &escrow_bucket(
+            purchase_account,
+            ctx.accounts.product_escrow.as_ref(),
+            &ctx.accounts.escrow_token_account,
+        )?,
+        &escrow_vault,
+        &ctx.accounts.buyer_token_account.to_account_info(),
+        request.refund_amount,
+        signer,
+    )?;
+
+    if request.logistics_cost > 0 {
+        let logistics_token_account = ctx
+            .accounts
+            .logistics_token_account
+            .as_ref()
+            .ok_or(LogisticsError::MissingPayoutAccounts)?;
+        require_keys_eq!(
+            logistics_token_account.owner,
+            request.logistics_provider,
+            LogisticsError::InvalidTokenOwner
+        );
+        require_keys_eq!(
+            logistics_token_account.mint,
+            token_mint,
+            LogisticsError::InvalidTokenMint
+        );
+
+        release_escrow(
+            &token_program,
+            &escrow_bucket(
+                purchase_account,
+                ctx.accounts.logistics_escrow.as_ref(),
+                &ctx.accounts.escrow_token_account,
+            )?,
+            &escrow_vault,
+            &logistics_token_account.to_account_info(),
+            request.logistics_cost,
+            signer,
+        )?;
+    }
+    purchase_account.unlock_settlement();
+
+    emit!(ReturnCompleted {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        purchase_id: purchase_account.purchase_id,
+        buyer: purchase_account.buyer,
+        refund_amount: request.refund_amount,
+        logistics_provider: request.logistics_provider,
+        logistics_cost: request.logistics_cost,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct OpenReturnsReserve<'info> {
+    #[account(
+        init,
+        payer = seller,
+        seeds = [pda::RETURNS_RESERVE_SEED, seller.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = returns_reserve
+    )]
+    pub returns_reserve: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundReturnsReserve<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::RETURNS_RESERVE_SEED, seller.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub returns_reserve: Account<'info, TokenAccount>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReturnsReserve<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::RETURNS_RESERVE_SEED, seller.key().as_ref(), returns_reserve.mint.as_ref()],
+        bump
+    )]
+    pub returns_reserve: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == returns_reserve.mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = seller_token_account.owner == seller.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct RequestReturn<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump,
+        has_one = buyer @ LogisticsError::NotAuthorized
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct ApproveReturn<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::RETURNS_RESERVE_SEED, seller.key().as_ref(), purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub returns_reserve: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    pub seller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_id: u64)]
+pub struct CompleteReturn<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        bump = purchase_account.bump
+    )]
+    pub purchase_account: Account<'info, PurchaseAccount>,
+    #[account(
+        seeds = [pda::TRADE_SEED, purchase_account.trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::ESCROW_SEED, purchase_account.token_mint.as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    /// Product sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::PRODUCT_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub product_escrow: Option<Account<'info, TokenAccount>>,
+    /// Logistics sub-vault, required for purchases with split escrow
+    #[account(
+        mut,
+        seeds = [pda::LOGISTICS_ESCROW_SEED, purchase_account.purchase_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub logistics_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == purchase_account.token_mint.key() @ LogisticsError::InvalidTokenMint,
+        constraint = buyer_token_account.owner == purchase_account.buyer.key() @ LogisticsError::InvalidTokenOwner
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub logistics_token_account: Option<Account<'info, TokenAccount>>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,1326 @@
+//! Listings, their metadata and checkout reservations.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::events::*;
+use crate::helpers::*;
+use crate::pda;
+use crate::settlement;
+use crate::state::*;
+
+pub fn create_category_index_shard(
+    ctx: Context<CreateCategoryIndexShard>,
+    category: u16,
+    shard_no: u32,
+) -> Result<()> {
+    if shard_no > 0 {
+        let previous_shard = ctx
+            .accounts
+            .previous_shard
+            .as_ref()
+            .ok_or(LogisticsError::PreviousShardNotFull)?;
+        require!(
+            previous_shard.is_full(),
+            LogisticsError::PreviousShardNotFull
+        );
+    }
+
+    let category_index = &mut ctx.accounts.category_index;
+    category_index.category = category;
+    category_index.shard_no = shard_no;
+    category_index.trade_ids = Vec::new();
+    category_index.version = ACCOUNT_VERSION;
+    category_index.bump = ctx.bumps.category_index;
+
+    emit!(CategoryIndexShardCreated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        category,
+        shard_no,
+    });
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_trade(
+    ctx: Context<CreateTrade>,
+    product_cost: u64,
+    logistics_providers: Vec<Pubkey>,
+    logistics_costs: Vec<u64>,
+    total_quantity: u64,
+    payout_splits: Vec<PayoutSplit>,
+    weight_grams: u64,
+    logistics_rates_per_kg: Vec<u64>,
+    category: u16,
+    tag_hashes: Vec<[u8; 32]>,
+    price_feed: Option<Pubkey>,
+    accepted_mints: Vec<AcceptedMint>,
+    insurance_bps: u16,
+    max_per_buyer: Option<u64>,
+    ship_to_regions: u16,
+    requires_proof_of_delivery: bool,
+    confirmation_mode: ConfirmationMode,
+) -> Result<()> {
+    // A storefront's default providers stand in when the listing names none
+    let logistics_providers = match ctx.accounts.storefront.as_ref() {
+        Some(storefront) if logistics_providers.is_empty() => {
+            storefront.default_logistics_providers.clone()
+        }
+        _ => logistics_providers,
+    };
+    require!(
+        logistics_providers.len() == logistics_costs.len(),
+        LogisticsError::MismatchedArrays
+    );
+    require!(
+        !logistics_providers.is_empty(),
+        LogisticsError::NoLogisticsProviders
+    );
+    require!(
+        logistics_providers.len() <= MAX_LOGISTICS_PROVIDERS,
+        LogisticsError::TooManyProviders
+    );
+    check_unique_providers(&logistics_providers)?;
+    require!(total_quantity > 0, LogisticsError::InvalidQuantity);
+    require!(
+        payout_splits.len() <= MAX_PAYOUT_SPLITS,
+        LogisticsError::TooManyPayoutSplits
+    );
+    if !payout_splits.is_empty() {
+        let total_bps: u64 = payout_splits.iter().map(|split| split.bps as u64).sum();
+        require!(
+            total_bps == BASIS_POINTS && payout_splits.iter().all(|split| split.bps > 0),
+            LogisticsError::InvalidPayoutSplits
+        );
+    }
+    require!(
+        tag_hashes.len() <= MAX_TRADE_TAGS,
+        LogisticsError::TooManyTags
+    );
+    require!(
+        insurance_bps <= MAX_INSURANCE_BPS,
+        LogisticsError::InvalidInsuranceBps
+    );
+    require!(
+        accepted_mints.len() <= MAX_ACCEPTED_MINTS,
+        LogisticsError::InvalidAcceptedMints
+    );
+    require!(max_per_buyer != Some(0), LogisticsError::InvalidQuantity);
+    require!(
+        ship_to_regions & !DeliveryRegion::ALL_MASK == 0,
+        LogisticsError::InvalidRegions
+    );
+    // Per-kg rates are quoted in the primary mint only
+    require!(
+        weight_grams == 0 || accepted_mints.is_empty(),
+        LogisticsError::InvalidAcceptedMints
+    );
+    let token_mint = ctx.accounts.token_mint.key();
+    for (i, accepted) in accepted_mints.iter().enumerate() {
+        require!(
+            accepted.mint != token_mint
+                && accepted_mints[..i]
+                    .iter()
+                    .all(|other| other.mint != accepted.mint)
+                && accepted.logistics_costs.len() == logistics_providers.len(),
+            LogisticsError::InvalidAcceptedMints
+        );
+    }
+    if weight_grams == 0 {
+        require!(
+            logistics_rates_per_kg.is_empty(),
+            LogisticsError::MismatchedArrays
+        );
+    } else {
+        require!(
+            logistics_rates_per_kg.len() == logistics_providers.len(),
+            LogisticsError::MismatchedArrays
+        );
+    }
+
+    // Each provider's account PDA follows in the remaining accounts, in list
+    // order. A provider with a published profile must serve one of the
+    // listing's regions, and a zero cost or rate takes its published one
+    require!(
+        ctx.remaining_accounts.len() == logistics_providers.len(),
+        LogisticsError::MismatchedArrays
+    );
+    let mut logistics_costs = logistics_costs;
+    let mut logistics_rates_per_kg = logistics_rates_per_kg;
+    for (i, (provider, info)) in logistics_providers
+        .iter()
+        .zip(ctx.remaining_accounts)
+        .enumerate()
+    {
+        let provider_account = load_provider_account(info, provider)?
+            .filter(|account| account.is_registered)
+            .ok_or(LogisticsError::ProviderNotRegistered)?;
+        if provider_account.regions == 0 {
+            continue;
+        }
+        require!(
+            ship_to_regions == 0 || provider_account.regions & ship_to_regions != 0,
+            LogisticsError::RegionNotServed
+        );
+        take_published_rate(&mut logistics_costs[i], provider_account.base_price)?;
+        if weight_grams > 0 {
+            take_published_rate(
+                &mut logistics_rates_per_kg[i],
+                provider_account.price_per_kg,
+            )?;
+        }
+    }
+
+    let global_state = &mut ctx.accounts.global_state;
+    global_state.trade_counter += 1;
+    let trade_id = global_state.trade_counter;
+
+    let product_escrow_fee = product_cost
+        .checked_mul(global_state.fee_bps as u64)
+        .ok_or(LogisticsError::MathOverflow)?
+        / BASIS_POINTS;
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.trade_id = trade_id;
+    trade_account.seller = ctx.accounts.seller.key();
+    trade_account.logistics_providers = logistics_providers.clone();
+    trade_account.logistics_costs = logistics_costs;
+    trade_account.product_cost = product_cost;
+    trade_account.escrow_fee = product_escrow_fee;
+    trade_account.total_quantity = total_quantity;
+    trade_account.remaining_quantity = total_quantity;
+    trade_account.active = true;
+    trade_account.purchase_ids = Vec::new();
+    trade_account.token_mint = ctx.accounts.token_mint.key();
+    trade_account.payout_splits = payout_splits;
+    trade_account.weight_grams = weight_grams;
+    trade_account.logistics_rates_per_kg = logistics_rates_per_kg;
+    trade_account.category = category;
+    trade_account.tag_hashes = tag_hashes;
+    trade_account.price_feed = price_feed;
+    trade_account.accepted_mints = accepted_mints;
+    trade_account.insurance_bps = insurance_bps;
+    trade_account.max_per_buyer = max_per_buyer;
+    trade_account.ship_to_regions = ship_to_regions;
+    trade_account.requires_proof_of_delivery = requires_proof_of_delivery;
+    trade_account.confirmation_mode = confirmation_mode;
+    trade_account.unlimited = false;
+    trade_account.sold_quantity = 0;
+    trade_account.preorder = false;
+    trade_account.fulfillment_start = 0;
+    trade_account.extensions = Vec::new();
+    trade_account.version = ACCOUNT_VERSION;
+    trade_account.bump = ctx.bumps.trade_account;
+
+    if let Some(category_index) = ctx.accounts.category_index.as_mut() {
+        require!(
+            category_index.category == category,
+            LogisticsError::CategoryMismatch
+        );
+        category_index.append(trade_id)?;
+    }
+
+    if let Some(storefront) = ctx.accounts.storefront.as_mut() {
+        let trade_account = &mut ctx.accounts.trade_account;
+        trade_account.set_storefront_listing(true)?;
+        let new_len = 8 + trade_account.try_to_vec()?.len();
+        grow_account(
+            &trade_account.to_account_info(),
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        storefront.trade_count += 1;
+        storefront.active_trade_count += 1;
+        storefront.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(StorefrontUpdated {
+            event_seq: global_state.next_event_seq(),
+            seller: storefront.seller,
+            metadata_hash: storefront.metadata_hash,
+            trade_id: Some(trade_id),
+            trade_count: storefront.trade_count,
+            active_trade_count: storefront.active_trade_count,
+        });
+    }
+
+    let event = TradeCreated {
+        event_seq: global_state.next_event_seq(),
+        trade_id,
+        seller: ctx.accounts.seller.key(),
+        product_cost,
+        total_quantity,
+        token_address: ctx.accounts.token_mint.key(),
+        category,
+        metadata: pda::trade_metadata_pda(trade_id).0,
+    };
+    #[cfg(feature = "cpi-events")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "cpi-events"))]
+    emit!(event);
+
+    Ok(())
+}
+
+pub fn open_storefront(ctx: Context<OpenStorefront>) -> Result<()> {
+    let storefront = &mut ctx.accounts.storefront;
+    storefront.seller = ctx.accounts.seller.key();
+    storefront.created_at = Clock::get()?.unix_timestamp;
+    storefront.version = ACCOUNT_VERSION;
+    storefront.bump = ctx.bumps.storefront;
+    Ok(())
+}
+
+pub fn set_storefront(
+    ctx: Context<SetStorefront>,
+    metadata_hash: [u8; 32],
+    default_logistics_providers: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        default_logistics_providers.len() <= MAX_LOGISTICS_PROVIDERS,
+        LogisticsError::TooManyProviders
+    );
+    check_unique_providers(&default_logistics_providers)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let storefront = &mut ctx.accounts.storefront;
+    storefront.metadata_hash = metadata_hash;
+    storefront.default_logistics_providers = default_logistics_providers;
+    storefront.updated_at = now;
+
+    emit!(StorefrontUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: storefront.seller,
+        metadata_hash,
+        trade_id: None,
+        trade_count: storefront.trade_count,
+        active_trade_count: storefront.active_trade_count,
+    });
+    Ok(())
+}
+
+pub fn sync_storefront_listing(ctx: Context<SyncStorefrontListing>, trade_id: u64) -> Result<()> {
+    let trade_account = &mut ctx.accounts.trade_account;
+    let counted_active = trade_account
+        .storefront_listing()
+        .ok_or(LogisticsError::TradeNotInStorefront)?;
+    if counted_active == trade_account.active {
+        return Ok(());
+    }
+
+    let storefront = &mut ctx.accounts.storefront;
+    if trade_account.active {
+        storefront.active_trade_count += 1;
+    } else {
+        storefront.active_trade_count = storefront.active_trade_count.saturating_sub(1);
+    }
+    storefront.updated_at = Clock::get()?.unix_timestamp;
+    let active = trade_account.active;
+    trade_account.set_storefront_listing(active)?;
+
+    emit!(StorefrontUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        seller: storefront.seller,
+        metadata_hash: storefront.metadata_hash,
+        trade_id: Some(trade_id),
+        trade_count: storefront.trade_count,
+        active_trade_count: storefront.active_trade_count,
+    });
+    Ok(())
+}
+
+pub fn get_trade_summary(ctx: Context<GetTradeSummary>, trade_id: u64) -> Result<TradeSummary> {
+    let trade_account = &ctx.accounts.trade_account;
+    let fee_bps = ctx.accounts.global_state.fee_bps as u64;
+    let per_unit = settlement::compute_payouts(trade_account.product_cost, 0, 1, fee_bps)?;
+
+    Ok(TradeSummary {
+        trade_id,
+        seller: trade_account.seller,
+        token_mint: trade_account.token_mint,
+        active: trade_account.active,
+        total_quantity: trade_account.total_quantity,
+        remaining_quantity: trade_account.remaining_quantity,
+        unlimited: trade_account.unlimited,
+        sold_quantity: trade_account.sold_quantity,
+        fulfillment_start: trade_account.preorder_start(),
+        product_cost: trade_account.product_cost,
+        priced_in_usd: trade_account.price_feed.is_some(),
+        escrow_fee_bps: fee_bps,
+        seller_amount_per_unit: per_unit.seller_amount,
+        fee_per_unit: per_unit.fee_amount,
+    })
+}
+
+pub fn create_trade_metadata(ctx: Context<CreateTradeMetadata>, trade_id: u64) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_metadata = &mut ctx.accounts.trade_metadata;
+    trade_metadata.trade_id = trade_id;
+    trade_metadata.version = ACCOUNT_VERSION;
+    trade_metadata.bump = ctx.bumps.trade_metadata;
+    Ok(())
+}
+
+pub fn set_trade_metadata(
+    ctx: Context<SetTradeMetadata>,
+    trade_id: u64,
+    content_hash: [u8; 32],
+    uri: String,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+    require!(
+        uri.len() <= MAX_METADATA_URI_LEN,
+        LogisticsError::MetadataUriTooLong
+    );
+
+    let trade_metadata = &mut ctx.accounts.trade_metadata;
+    trade_metadata.content_hash = content_hash;
+    trade_metadata.uri = uri.clone();
+    trade_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(TradeMetadataUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        content_hash,
+        uri,
+    });
+    Ok(())
+}
+
+pub fn restock_trade(
+    ctx: Context<RestockTrade>,
+    trade_id: u64,
+    additional_quantity: u64,
+) -> Result<()> {
+    require!(additional_quantity > 0, LogisticsError::InvalidQuantity);
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.total_quantity = trade_account
+        .total_quantity
+        .checked_add(additional_quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    trade_account.remaining_quantity = trade_account
+        .remaining_quantity
+        .checked_add(additional_quantity)
+        .ok_or(LogisticsError::MathOverflow)?;
+    // Trades only go inactive by selling out
+    let reactivated = !trade_account.active;
+    trade_account.active = true;
+
+    emit!(TradeRestocked {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        seller: trade_account.seller,
+        additional_quantity,
+        remaining_quantity: trade_account.remaining_quantity,
+        reactivated,
+    });
+    Ok(())
+}
+
+pub fn set_unlimited_quantity(
+    ctx: Context<SetUnlimitedQuantity>,
+    trade_id: u64,
+    unlimited: bool,
+) -> Result<()> {
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.unlimited = unlimited;
+    // Leaving made-to-order falls back to whatever stock is still recorded
+    trade_account.active = unlimited || trade_account.remaining_quantity > 0;
+
+    emit!(TradeQuantityModeSet {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        seller: trade_account.seller,
+        unlimited,
+        active: trade_account.active,
+    });
+    Ok(())
+}
+
+pub fn set_preorder(
+    ctx: Context<SetPreorder>,
+    trade_id: u64,
+    preorder: bool,
+    fulfillment_start: i64,
+) -> Result<()> {
+    if preorder {
+        require!(
+            fulfillment_start > Clock::get()?.unix_timestamp,
+            LogisticsError::InvalidFulfillmentStart
+        );
+    }
+
+    // Existing purchases keep the fulfillment date they were bought with
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.preorder = preorder;
+    trade_account.fulfillment_start = if preorder { fulfillment_start } else { 0 };
+
+    emit!(TradePreorderSet {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        seller: trade_account.seller,
+        preorder,
+        fulfillment_start: trade_account.fulfillment_start,
+    });
+    Ok(())
+}
+
+pub fn set_trade_order_limits(
+    ctx: Context<SetTradeOrderLimits>,
+    trade_id: u64,
+    limits: Option<OrderLimits>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match limits.as_ref() {
+        Some(limits) => {
+            require!(limits.is_valid(), LogisticsError::InvalidOrderLimits);
+            trade_account.set_order_limits(limits)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(&mut trade_account.extensions, ExtensionType::OrderLimits);
+        }
+    }
+
+    emit!(TradeOrderLimitsUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        limits,
+    });
+    Ok(())
+}
+
+pub fn set_purchase_cooldown(
+    ctx: Context<SetPurchaseCooldown>,
+    trade_id: u64,
+    cooldown_slots: Option<u64>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+    require!(
+        cooldown_slots != Some(0),
+        LogisticsError::InvalidPurchaseCooldown
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match cooldown_slots {
+        Some(cooldown_slots) => {
+            trade_account.set_purchase_cooldown_slots(cooldown_slots)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(
+                &mut trade_account.extensions,
+                ExtensionType::PurchaseCooldown,
+            );
+        }
+    }
+
+    emit!(PurchaseCooldownUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        cooldown_slots,
+    });
+    Ok(())
+}
+
+pub fn set_presale_allowlist(
+    ctx: Context<SetPresaleAllowlist>,
+    trade_id: u64,
+    presale: Option<PresaleAllowlist>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match presale {
+        Some(presale) => {
+            require!(
+                presale.merkle_root != [0u8; 32],
+                LogisticsError::InvalidPresale
+            );
+            trade_account.set_presale_allowlist(&presale)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(
+                &mut trade_account.extensions,
+                ExtensionType::PresaleAllowlist,
+            );
+        }
+    }
+
+    emit!(PresaleAllowlistUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        presale,
+    });
+    Ok(())
+}
+
+pub fn set_settlement_stream(
+    ctx: Context<SetSettlementStream>,
+    trade_id: u64,
+    duration_seconds: Option<i64>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match duration_seconds {
+        Some(duration_seconds) => {
+            // Streams pay the seller directly, so split payouts are not supported
+            require!(
+                duration_seconds > 0 && trade_account.payout_splits.is_empty(),
+                LogisticsError::InvalidSettlementStream
+            );
+            trade_account.set_settlement_stream_seconds(duration_seconds)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(
+                &mut trade_account.extensions,
+                ExtensionType::SettlementStream,
+            );
+        }
+    }
+
+    emit!(SettlementStreamUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        duration_seconds,
+    });
+    Ok(())
+}
+
+pub fn set_trade_milestones(
+    ctx: Context<SetTradeMilestones>,
+    trade_id: u64,
+    milestones: Vec<Milestone>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    // Buyers paid under the existing terms
+    require!(
+        trade_account.purchase_ids.is_empty(),
+        LogisticsError::MilestonesLocked
+    );
+    if milestones.is_empty() {
+        remove_extension(&mut trade_account.extensions, ExtensionType::Milestones);
+    } else {
+        let total_bps: u64 = milestones
+            .iter()
+            .map(|milestone| milestone.bps as u64)
+            .sum();
+        require!(
+            milestones.len() <= MAX_MILESTONES
+                && total_bps == BASIS_POINTS
+                && milestones.iter().all(|milestone| milestone.bps > 0),
+            LogisticsError::InvalidMilestones
+        );
+        trade_account.set_milestones(&milestones)?;
+        let new_len = 8 + trade_account.try_to_vec()?.len();
+        grow_account(
+            &trade_account.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+    }
+
+    emit!(TradeMilestonesUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        milestones,
+    });
+    Ok(())
+}
+
+pub fn set_low_stock_threshold(
+    ctx: Context<SetLowStockThreshold>,
+    trade_id: u64,
+    threshold: Option<u64>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match threshold {
+        Some(threshold) => {
+            trade_account.set_low_stock_threshold(threshold)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(
+                &mut trade_account.extensions,
+                ExtensionType::LowStockThreshold,
+            );
+        }
+    }
+
+    emit!(LowStockThresholdUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        threshold,
+    });
+    Ok(())
+}
+
+pub fn set_trade_buyer_gate(
+    ctx: Context<SetTradeBuyerGate>,
+    trade_id: u64,
+    gate: Option<BuyerGate>,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.trade_account.seller
+            || authority == ctx.accounts.global_state.admin,
+        LogisticsError::NotAuthorized
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    match gate.as_ref() {
+        Some(gate) => {
+            trade_account.set_buyer_gate(gate)?;
+            let new_len = 8 + trade_account.try_to_vec()?.len();
+            grow_account(
+                &trade_account.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                new_len,
+            )?;
+        }
+        None => {
+            remove_extension(&mut trade_account.extensions, ExtensionType::BuyerGate);
+        }
+    }
+
+    emit!(TradeBuyerGateUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        gate,
+    });
+    Ok(())
+}
+
+pub fn set_provider_status(
+    ctx: Context<SetProviderStatus>,
+    trade_id: u64,
+    index: u8,
+    enabled: bool,
+) -> Result<()> {
+    let trade_account = &mut ctx.accounts.trade_account;
+    require!(
+        ctx.accounts.seller.key() == trade_account.seller,
+        LogisticsError::NotAuthorized
+    );
+    let logistics_provider = *trade_account
+        .logistics_providers
+        .get(index as usize)
+        .ok_or(LogisticsError::InvalidLogisticsProvider)?;
+
+    trade_account.set_provider_enabled(index as usize, enabled)?;
+    let new_len = 8 + trade_account.try_to_vec()?.len();
+    grow_account(
+        &trade_account.to_account_info(),
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    emit!(ProviderStatusUpdated {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        logistics_provider,
+        index,
+        enabled,
+    });
+    Ok(())
+}
+
+pub fn reserve_quantity(ctx: Context<ReserveQuantity>, trade_id: u64, quantity: u64) -> Result<()> {
+    require!(quantity > 0, LogisticsError::InvalidQuantity);
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    require!(trade_account.active, LogisticsError::TradeInactive);
+    trade_account.check_stock(quantity)?;
+    require!(
+        ctx.accounts.buyer.key() != trade_account.seller,
+        LogisticsError::BuyerIsSeller
+    );
+
+    trade_account.take_stock(quantity)?;
+
+    let expires_at = Clock::get()?.unix_timestamp + RESERVATION_TTL_SECONDS;
+    let reservation = &mut ctx.accounts.reservation;
+    reservation.trade_id = trade_id;
+    reservation.buyer = ctx.accounts.buyer.key();
+    reservation.quantity = quantity;
+    reservation.expires_at = expires_at;
+    reservation.version = ACCOUNT_VERSION;
+    reservation.bump = ctx.bumps.reservation;
+
+    emit!(QuantityReserved {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id,
+        buyer: reservation.buyer,
+        quantity,
+        expires_at,
+    });
+    Ok(())
+}
+
+pub fn release_reservation(ctx: Context<ReleaseReservation>, _trade_id: u64) -> Result<()> {
+    let reservation = &ctx.accounts.reservation;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.authority.key() == reservation.buyer || now >= reservation.expires_at,
+        LogisticsError::ReservationActive
+    );
+
+    let trade_account = &mut ctx.accounts.trade_account;
+    trade_account.return_stock(reservation.quantity);
+
+    emit!(ReservationReleased {
+        event_seq: ctx.accounts.global_state.next_event_seq(),
+        trade_id: reservation.trade_id,
+        buyer: reservation.buyer,
+        quantity: reservation.quantity,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(category: u16, shard_no: u32)]
+pub struct CreateCategoryIndexShard<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CategoryIndex::INIT_SPACE,
+        seeds = [pda::CATEGORY_INDEX_SEED, category.to_le_bytes().as_ref(), shard_no.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+    #[account(
+        seeds = [pda::CATEGORY_INDEX_SEED, category.to_le_bytes().as_ref(), shard_no.saturating_sub(1).to_le_bytes().as_ref()],
+        bump = previous_shard.bump
+    )]
+    pub previous_shard: Option<Account<'info, CategoryIndex>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "cpi-events", event_cpi)]
+#[derive(Accounts)]
+pub struct CreateTrade<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump,
+        has_one = admin
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TradeAccount::INIT_SPACE,
+        seeds = [pda::TRADE_SEED, global_state.trade_counter.saturating_add(1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    /// CHECK: This is the seller for the trade
+    pub seller: UncheckedAccount<'info>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        seeds = [pda::ALLOWED_MINT_SEED, token_mint.key().as_ref()],
+        bump = allowed_mint.bump
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    #[account(
+        mut,
+        seeds = [pda::CATEGORY_INDEX_SEED, category_index.category.to_le_bytes().as_ref(), category_index.shard_no.to_le_bytes().as_ref()],
+        bump = category_index.bump
+    )]
+    pub category_index: Option<Account<'info, CategoryIndex>>,
+    /// Seller's storefront to list the trade under
+    #[account(
+        mut,
+        seeds = [pda::STOREFRONT_SEED, seller.key().as_ref()],
+        bump = storefront.bump
+    )]
+    pub storefront: Option<Account<'info, StorefrontAccount>>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenStorefront<'info> {
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + StorefrontAccount::INIT_SPACE,
+        seeds = [pda::STOREFRONT_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub storefront: Account<'info, StorefrontAccount>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStorefront<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::STOREFRONT_SEED, seller.key().as_ref()],
+        bump = storefront.bump
+    )]
+    pub storefront: Account<'info, StorefrontAccount>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SyncStorefrontListing<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::STOREFRONT_SEED, trade_account.seller.as_ref()],
+        bump = storefront.bump
+    )]
+    pub storefront: Account<'info, StorefrontAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct CreateTradeMetadata<'info> {
+    #[account(
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TradeMetadata::INIT_SPACE,
+        seeds = [pda::TRADE_METADATA_SEED, trade_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub trade_metadata: Account<'info, TradeMetadata>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetTradeMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_METADATA_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_metadata.bump
+    )]
+    pub trade_metadata: Account<'info, TradeMetadata>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct GetTradeSummary<'info> {
+    #[account(seeds = [pda::GLOBAL_STATE_SEED], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct RestockTrade<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetUnlimitedQuantity<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetPreorder<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump,
+        has_one = seller @ LogisticsError::NotAuthorized
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetTradeOrderLimits<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetPurchaseCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetPresaleAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetSettlementStream<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetTradeMilestones<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetLowStockThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetTradeBuyerGate<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct SetProviderStatus<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct ReserveQuantity<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + ReservationAccount::INIT_SPACE,
+        seeds = [pda::RESERVATION_SEED, trade_id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub reservation: Account<'info, ReservationAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(trade_id: u64)]
+pub struct ReleaseReservation<'info> {
+    #[account(
+        mut,
+        seeds = [pda::GLOBAL_STATE_SEED],
+        bump = global_state.bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [pda::TRADE_SEED, trade_id.to_le_bytes().as_ref()],
+        bump = trade_account.bump
+    )]
+    pub trade_account: Account<'info, TradeAccount>,
+    #[account(
+        mut,
+        seeds = [pda::RESERVATION_SEED, trade_id.to_le_bytes().as_ref(), reservation.buyer.as_ref()],
+        bump = reservation.bump,
+        has_one = buyer,
+        close = buyer
+    )]
+    pub reservation: Account<'info, ReservationAccount>,
+    /// CHECK: Buyer who paid for the reservation and receives its rent back
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,184 @@
+//! Layouts written before accounts carried a version byte, and the helpers
+//! `migrate_trade` and `migrate_purchase` use to upgrade them in place.
+//!
+//! Legacy trade and purchase accounts start with the zeroed `discriminator`
+//! field, so their first byte after the Anchor discriminator reads as
+//! version 0.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::constants::{ACCOUNT_VERSION, ESCROW_FEE_PERCENT};
+use crate::errors::LogisticsError;
+use crate::state::{
+    grow_account, AcceptedMint, CancelReason, ConfirmationMode, DisputeReason, InsuranceStatus,
+    PayoutSplit, PurchaseAccount, TradeAccount,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyTradeAccount {
+    pub discriminator: [u8; 8],
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub logistics_providers: Vec<Pubkey>,
+    pub logistics_costs: Vec<u64>,
+    pub product_cost: u64,
+    pub escrow_fee: u64,
+    pub total_quantity: u64,
+    pub remaining_quantity: u64,
+    pub active: bool,
+    pub purchase_ids: Vec<u64>,
+    pub token_mint: Pubkey,
+    pub payout_splits: Vec<PayoutSplit>,
+    pub weight_grams: u64,
+    pub logistics_rates_per_kg: Vec<u64>,
+    pub category: u16,
+    pub tag_hashes: Vec<[u8; 32]>,
+    pub price_feed: Option<Pubkey>,
+    pub accepted_mints: Vec<AcceptedMint>,
+    pub insurance_bps: u16,
+    pub extensions: Vec<u8>,
+    pub bump: u8,
+}
+
+impl From<LegacyTradeAccount> for TradeAccount {
+    fn from(legacy: LegacyTradeAccount) -> Self {
+        TradeAccount {
+            version: ACCOUNT_VERSION,
+            discriminator: legacy.discriminator,
+            trade_id: legacy.trade_id,
+            seller: legacy.seller,
+            logistics_providers: legacy.logistics_providers,
+            logistics_costs: legacy.logistics_costs,
+            product_cost: legacy.product_cost,
+            escrow_fee: legacy.escrow_fee,
+            total_quantity: legacy.total_quantity,
+            remaining_quantity: legacy.remaining_quantity,
+            active: legacy.active,
+            purchase_ids: legacy.purchase_ids,
+            token_mint: legacy.token_mint,
+            payout_splits: legacy.payout_splits,
+            weight_grams: legacy.weight_grams,
+            logistics_rates_per_kg: legacy.logistics_rates_per_kg,
+            category: legacy.category,
+            tag_hashes: legacy.tag_hashes,
+            price_feed: legacy.price_feed,
+            accepted_mints: legacy.accepted_mints,
+            insurance_bps: legacy.insurance_bps,
+            max_per_buyer: None,
+            ship_to_regions: 0,
+            requires_proof_of_delivery: false,
+            confirmation_mode: ConfirmationMode::BuyerConfirms,
+            unlimited: false,
+            sold_quantity: legacy.total_quantity - legacy.remaining_quantity,
+            preorder: false,
+            fulfillment_start: 0,
+            extensions: legacy.extensions,
+            bump: legacy.bump,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyPurchaseAccount {
+    pub discriminator: [u8; 8],
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub total_amount: u64,
+    pub delivered_and_confirmed: bool,
+    pub disputed: bool,
+    pub chosen_logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+    pub settled: bool,
+    pub created_at: i64,
+    pub settled_at: i64,
+    pub payer: Pubkey,
+    pub consolidated: bool,
+    pub cancelled: bool,
+    pub delivered_quantity: u64,
+    pub shipped_at: i64,
+    pub token_mint: Pubkey,
+    pub unit_price: u64,
+    pub fill_price: i64,
+    pub fill_expo: i32,
+    pub insurance_premium: u64,
+    pub insurance_status: InsuranceStatus,
+    pub dispute_reason: DisputeReason,
+    pub cancel_reason: CancelReason,
+    pub extensions: Vec<u8>,
+    pub bump: u8,
+}
+
+impl From<LegacyPurchaseAccount> for PurchaseAccount {
+    fn from(legacy: LegacyPurchaseAccount) -> Self {
+        PurchaseAccount {
+            version: ACCOUNT_VERSION,
+            discriminator: legacy.discriminator,
+            purchase_id: legacy.purchase_id,
+            trade_id: legacy.trade_id,
+            buyer: legacy.buyer,
+            quantity: legacy.quantity,
+            total_amount: legacy.total_amount,
+            delivered_and_confirmed: legacy.delivered_and_confirmed,
+            disputed: legacy.disputed,
+            chosen_logistics_provider: legacy.chosen_logistics_provider,
+            logistics_cost: legacy.logistics_cost,
+            settled: legacy.settled,
+            created_at: legacy.created_at,
+            settled_at: legacy.settled_at,
+            payer: legacy.payer,
+            consolidated: legacy.consolidated,
+            cancelled: legacy.cancelled,
+            delivered_quantity: legacy.delivered_quantity,
+            shipped_at: legacy.shipped_at,
+            token_mint: legacy.token_mint,
+            unit_price: legacy.unit_price,
+            fill_price: legacy.fill_price,
+            fill_expo: legacy.fill_expo,
+            insurance_premium: legacy.insurance_premium,
+            insurance_status: legacy.insurance_status,
+            dispute_reason: legacy.dispute_reason,
+            cancel_reason: legacy.cancel_reason,
+            confirmations: 0,
+            split_escrow: false,
+            settlement_locked: false,
+            fee_bps: ESCROW_FEE_PERCENT as u16,
+            memo_hash: None,
+            fulfillment_start: 0,
+            extensions: legacy.extensions,
+            bump: legacy.bump,
+        }
+    }
+}
+
+/// Decodes a version-0 account of type `A` into its legacy layout `L`.
+pub fn load_legacy<A: Discriminator, L: AnchorDeserialize>(account: &AccountInfo) -> Result<L> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == A::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require!(data[8] == 0, LogisticsError::AlreadyMigrated);
+    L::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+}
+
+/// Writes `upgraded` over `account`, reallocating it first if the new layout
+/// is larger.
+pub fn store_migrated<'info, A: AccountSerialize + AnchorSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    upgraded: &A,
+) -> Result<()> {
+    grow_account(
+        account,
+        payer,
+        system_program,
+        8 + upgraded.try_to_vec()?.len(),
+    )?;
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    upgraded.try_serialize(&mut writer)
+}
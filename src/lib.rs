@@ -0,0 +1,1581 @@
+//! DezenMart logistics escrow program.
+//!
+//! Other Anchor programs can depend on this crate with the `cpi` feature and
+//! call its instructions through the generated `cpi` module, passing the
+//! account structs from `cpi::accounts`. Account types, events and errors are
+//! re-exported at the crate root so callers can read program accounts.
+
+use anchor_lang::prelude::*;
+
+pub mod constants;
+pub mod errors;
+pub mod events;
+pub mod helpers;
+pub mod instructions;
+pub mod legacy;
+pub mod pda;
+pub mod settlement;
+pub mod state;
+
+pub use errors::*;
+pub use events::*;
+pub use helpers::*;
+// `#[program]` re-exports the entrypoints under the handlers' names
+#[allow(ambiguous_glob_reexports)]
+pub use instructions::*;
+pub use state::*;
+
+declare_id!("FZVgE9vrdTHufoy197xMms8iT61q2xeeqLCAWXnUtC2C");
+
+#[program]
+pub mod dezenmart_logistics {
+    use super::*;
+
+    pub use crate::constants::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        instructions::admin::initialize(ctx)
+    }
+
+    /// Hands fee, risk and arbitrator management (the escrow fee, the pause
+    /// switch, fee share, fee split, crank, cancellation, escalation, stake, seller bond, order limit, swap,
+    /// lending and region settings, the mint whitelist, fee withdrawals, and
+    /// arbitrator registration) to `governance_authority`, typically an SPL Governance realm's governance
+    /// PDA. Signed by whoever currently holds those powers; the default key
+    /// returns them to the admin.
+    pub fn set_governance_authority(
+        ctx: Context<SetGovernanceAuthority>,
+        governance_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::set_governance_authority(ctx, governance_authority)
+    }
+
+    pub fn set_stake_config(
+        ctx: Context<SetStakeConfig>,
+        provider_stake_amount: u64,
+        slash_bps: u16,
+        stake_cooldown_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::set_stake_config(
+            ctx,
+            provider_stake_amount,
+            slash_bps,
+            stake_cooldown_seconds,
+        )
+    }
+
+    pub fn register_logistics_provider(ctx: Context<RegisterLogisticsProvider>) -> Result<()> {
+        instructions::participant::register_logistics_provider(ctx)
+    }
+
+    /// Publishes the `DeliveryRegion` bitmask the provider serves and its rate,
+    /// `base_price` plus `price_per_kg` of shipment weight. Listings naming the
+    /// provider must ship to one of those regions and charge that rate.
+    pub fn set_provider_profile(
+        ctx: Context<SetProviderProfile>,
+        regions: u16,
+        base_price: u64,
+        price_per_kg: u64,
+    ) -> Result<()> {
+        instructions::participant::set_provider_profile(ctx, regions, base_price, price_per_kg)
+    }
+
+    /// Routes the provider's settlement payouts to `payout_token_account`
+    /// instead of their associated token account. Omit the account to go back
+    /// to the associated token account.
+    pub fn update_provider_payout(ctx: Context<UpdateProviderPayout>) -> Result<()> {
+        instructions::participant::update_provider_payout(ctx)
+    }
+
+    pub fn deregister_logistics_provider(ctx: Context<DeregisterLogisticsProvider>) -> Result<()> {
+        instructions::participant::deregister_logistics_provider(ctx)
+    }
+
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        instructions::participant::withdraw_stake(ctx)
+    }
+
+    pub fn set_seller_bond_config(
+        ctx: Context<SetSellerBondConfig>,
+        seller_loss_threshold: u32,
+        seller_bond_slash_bps: u16,
+        seller_probation_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::set_seller_bond_config(
+            ctx,
+            seller_loss_threshold,
+            seller_bond_slash_bps,
+            seller_probation_seconds,
+        )
+    }
+
+    /// Deadlines enforced by the permissionless cranks: shipped purchases are
+    /// settled `auto_confirm_seconds` after shipping, unshipped ones refunded
+    /// `ship_timeout_seconds` after purchase. Crank callers earn
+    /// `crank_reward_bps` of the escrow fee.
+    pub fn set_crank_config(
+        ctx: Context<SetCrankConfig>,
+        auto_confirm_seconds: i64,
+        ship_timeout_seconds: i64,
+        crank_reward_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_crank_config(
+            ctx,
+            auto_confirm_seconds,
+            ship_timeout_seconds,
+            crank_reward_bps,
+        )
+    }
+
+    /// Registers a storefront program that may route orders through
+    /// `buy_trade`, earning `fee_share_bps` of the escrow fee on every purchase
+    /// it refers. Updating an entry only affects later purchases; a zero share
+    /// removes the platform.
+    pub fn set_platform_fee_share(
+        ctx: Context<SetPlatformFeeShare>,
+        platform: Pubkey,
+        fee_share_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_platform_fee_share(ctx, platform, fee_share_bps)
+    }
+
+    /// Platform-wide bounds on a purchase's total amount, in base units of the
+    /// payment mint, rejecting dust and fat-finger orders in `buy_trade`. A
+    /// zero `max_order_amount` disables the upper bound.
+    pub fn set_order_limits(
+        ctx: Context<SetOrderLimits>,
+        min_order_amount: u64,
+        max_order_amount: u64,
+    ) -> Result<()> {
+        instructions::admin::set_order_limits(ctx, min_order_amount, max_order_amount)
+    }
+
+    /// Overrides the platform-wide order limits for one trade, or with `None`
+    /// restores them. Callable by the seller or the admin.
+    pub fn set_trade_order_limits(
+        ctx: Context<SetTradeOrderLimits>,
+        trade_id: u64,
+        limits: Option<OrderLimits>,
+    ) -> Result<()> {
+        instructions::trade::set_trade_order_limits(ctx, trade_id, limits)
+    }
+
+    /// Buyers may cancel for free within `cancellation_window_seconds` of
+    /// purchase. Later cancellations pay `restocking_fee_bps` of the product
+    /// cost to the seller unless the seller co-signs.
+    pub fn set_cancellation_policy(
+        ctx: Context<SetCancellationPolicy>,
+        cancellation_window_seconds: i64,
+        restocking_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_cancellation_policy(
+            ctx,
+            cancellation_window_seconds,
+            restocking_fee_bps,
+        )
+    }
+
+    /// Sets the escrow fee, in basis points, that new purchases lock in.
+    /// Existing purchases keep the rate they were opened at.
+    pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        instructions::admin::set_fee(ctx, fee_bps)
+    }
+
+    /// Pauses or resumes purchases and seller payouts. While paused, buyers
+    /// can still cancel, dispute and take refunds.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::admin::set_paused(ctx, paused)
+    }
+
+    /// Sets the AMM program that settlement may route seller proceeds through.
+    /// `Pubkey::default()` disables the swap hook.
+    pub fn set_swap_program(ctx: Context<SetSwapProgram>, swap_program: Pubkey) -> Result<()> {
+        instructions::admin::set_swap_program(ctx, swap_program)
+    }
+
+    /// Sets the lending program buyers may deposit pending escrow into and
+    /// the share of the yield paid back to them; the platform keeps the rest.
+    /// `Pubkey::default()` disables new deposits.
+    pub fn set_lending_config(
+        ctx: Context<SetLendingConfig>,
+        lending_program: Pubkey,
+        yield_buyer_share_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_lending_config(ctx, lending_program, yield_buyer_share_bps)
+    }
+
+    /// Sets the verifier whose attestations certify buyer regions for trades
+    /// with ship-to restrictions. `Pubkey::default()` trusts claimed regions.
+    pub fn set_region_registrar(
+        ctx: Context<SetRegionRegistrar>,
+        region_registrar: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::set_region_registrar(ctx, region_registrar)
+    }
+
+    /// Opens shard `shard_no` of `user`'s order index. Shard 0 can always be
+    /// created; later shards require the previous shard to be full. Anyone may
+    /// pay for a shard.
+    pub fn create_order_index_shard(
+        ctx: Context<CreateOrderIndexShard>,
+        user: Pubkey,
+        shard_no: u32,
+    ) -> Result<()> {
+        instructions::purchase::create_order_index_shard(ctx, user, shard_no)
+    }
+
+    /// Grows an order index shard by one 10KB page, paid by the caller, so
+    /// busy users can keep appending to a shard instead of opening new ones.
+    pub fn add_purchase_index_page(
+        ctx: Context<AddPurchaseIndexPage>,
+        user: Pubkey,
+        shard_no: u32,
+    ) -> Result<()> {
+        instructions::purchase::add_purchase_index_page(ctx, user, shard_no)
+    }
+
+    /// Opens shard `shard_no` of the trade index for `category`, following the
+    /// same rules as order index shards.
+    pub fn create_category_index_shard(
+        ctx: Context<CreateCategoryIndexShard>,
+        category: u16,
+        shard_no: u32,
+    ) -> Result<()> {
+        instructions::trade::create_category_index_shard(ctx, category, shard_no)
+    }
+
+    /// A first-tier ruling becomes final `escalation_window_seconds` after it
+    /// is made unless the losing side escalates by escrowing
+    /// `escalation_fee_bps` of the purchase total.
+    pub fn set_escalation_policy(
+        ctx: Context<SetEscalationPolicy>,
+        escalation_window_seconds: i64,
+        escalation_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_escalation_policy(
+            ctx,
+            escalation_window_seconds,
+            escalation_fee_bps,
+        )
+    }
+
+    /// Creates the program version account read by clients, before the first
+    /// `set_program_version`.
+    pub fn initialize_program_version(ctx: Context<InitializeProgramVersion>) -> Result<()> {
+        instructions::admin::initialize_program_version(ctx)
+    }
+
+    /// Records the version of a new deploy and the oldest client version that
+    /// can still build instructions for it. Called by the admin after every
+    /// upgrade; versions may only move forward.
+    pub fn set_program_version(
+        ctx: Context<SetProgramVersion>,
+        program_version: SemVer,
+        min_client_version: SemVer,
+    ) -> Result<()> {
+        instructions::admin::set_program_version(ctx, program_version, min_client_version)
+    }
+
+    /// Sets how many loyalty points settled volume earns and what they redeem
+    /// for. Governed like the other fee parameters.
+    pub fn set_loyalty_config(ctx: Context<SetLoyaltyConfig>, config: LoyaltyConfig) -> Result<()> {
+        instructions::admin::set_loyalty_config(ctx, config)
+    }
+
+    /// Creates the receipt config account, before the first
+    /// `set_receipt_config`.
+    pub fn initialize_receipt_config(ctx: Context<InitializeReceiptConfig>) -> Result<()> {
+        instructions::admin::initialize_receipt_config(ctx)
+    }
+
+    /// Points purchase receipts at a Bubblegum merkle tree and sets the
+    /// metadata URI prefix the receipt details are appended to. The tree must
+    /// delegate minting to the receipt authority PDA.
+    pub fn set_receipt_config(
+        ctx: Context<SetReceiptConfig>,
+        merkle_tree: Pubkey,
+        uri_prefix: String,
+    ) -> Result<()> {
+        instructions::admin::set_receipt_config(ctx, merkle_tree, uri_prefix)
+    }
+
+    pub fn initialize_stats(ctx: Context<InitializeStats>) -> Result<()> {
+        instructions::admin::initialize_stats(ctx)
+    }
+
+    pub fn register_arbitrator(ctx: Context<RegisterArbitrator>) -> Result<()> {
+        instructions::admin::register_arbitrator(ctx)
+    }
+
+    pub fn remove_arbitrator(ctx: Context<RemoveArbitrator>) -> Result<()> {
+        instructions::admin::remove_arbitrator(ctx)
+    }
+
+    /// Creates the jury pool and its stake vault in the global stake mint.
+    /// Callable by the governing authority.
+    pub fn init_jury_pool(ctx: Context<InitJuryPool>, config: JuryConfig) -> Result<()> {
+        instructions::admin::init_jury_pool(ctx, config)
+    }
+
+    /// Updates the jury stake, size, voting window and minority slash. Juries
+    /// already drawn keep their voting deadline.
+    pub fn set_jury_config(ctx: Context<SetJuryConfig>, config: JuryConfig) -> Result<()> {
+        instructions::admin::set_jury_config(ctx, config)
+    }
+
+    /// Whitelists a mint for listing trades and paying for purchases.
+    pub fn add_allowed_mint(ctx: Context<AddAllowedMint>) -> Result<()> {
+        instructions::admin::add_allowed_mint(ctx)
+    }
+
+    /// Removes a mint from the whitelist. Existing purchases still settle,
+    /// but no new trades or purchases can use it.
+    pub fn remove_allowed_mint(ctx: Context<RemoveAllowedMint>) -> Result<()> {
+        instructions::admin::remove_allowed_mint(ctx)
+    }
+
+    /// Opens the escrow vault of a whitelisted mint. Purchases, offers and
+    /// bids in the mint all require it to exist; anyone may pay for it.
+    pub fn create_escrow_vault(ctx: Context<CreateEscrowVault>) -> Result<()> {
+        instructions::admin::create_escrow_vault(ctx)
+    }
+
+    /// Opens the vault the protocol's share of escrow fees is collected in for
+    /// a whitelisted mint. Settlement in the mint requires it to exist; anyone
+    /// may pay for it.
+    pub fn create_fee_vault(ctx: Context<CreateFeeVault>) -> Result<()> {
+        instructions::admin::create_fee_vault(ctx)
+    }
+
+    /// Opens the insurance pool of a whitelisted mint, required before an
+    /// insured purchase in the mint; anyone may pay for it.
+    pub fn create_insurance_pool(ctx: Context<CreateInsurancePool>) -> Result<()> {
+        instructions::admin::create_insurance_pool(ctx)
+    }
+
+    pub fn register_seller(ctx: Context<RegisterSeller>) -> Result<()> {
+        instructions::participant::register_seller(ctx)
+    }
+
+    /// Lets a seller ask for their proceeds in a different mint. When set,
+    /// `confirm_delivery_and_purchase` may swap the seller's share through the
+    /// configured AMM, rejecting routes that return less than the quote minus
+    /// `max_slippage_bps`. Passing `None` turns the preference off.
+    pub fn set_payout_preference(
+        ctx: Context<SetPayoutPreference>,
+        preferred_mint: Option<Pubkey>,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::participant::set_payout_preference(ctx, preferred_mint, max_slippage_bps)
+    }
+
+    /// Routes the seller's settlement proceeds to `payout_token_account`, for
+    /// example a treasury, instead of their associated token account. Omit the
+    /// account to go back to the associated token account. A payout account in
+    /// a different mint than a purchase is skipped for that purchase.
+    pub fn set_seller_payout_account(ctx: Context<SetSellerPayoutAccount>) -> Result<()> {
+        instructions::participant::set_seller_payout_account(ctx)
+    }
+
+    pub fn register_seller_with_bond(
+        ctx: Context<RegisterSellerWithBond>,
+        bond_amount: u64,
+    ) -> Result<()> {
+        instructions::participant::register_seller_with_bond(ctx, bond_amount)
+    }
+
+    pub fn release_bond(ctx: Context<ReleaseBond>) -> Result<()> {
+        instructions::participant::release_bond(ctx)
+    }
+
+    /// Upgrades a trade written before account versioning to the current
+    /// layout in place. Anyone may migrate; the payer funds any extra rent.
+    pub fn migrate_trade(ctx: Context<MigrateTrade>, trade_id: u64) -> Result<()> {
+        instructions::admin::migrate_trade(ctx, trade_id)
+    }
+
+    /// Upgrades a purchase written before account versioning to the current
+    /// layout in place. Anyone may migrate; the payer funds any extra rent.
+    pub fn migrate_purchase(ctx: Context<MigratePurchase>, purchase_id: u64) -> Result<()> {
+        instructions::admin::migrate_purchase(ctx, purchase_id)
+    }
+
+    /// Lets `delegate` confirm delivery of the buyer's purchases, e.g. after
+    /// the buyer loses access to the purchasing wallet. `None` clears it.
+    pub fn set_confirmation_delegate(
+        ctx: Context<SetConfirmationDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::participant::set_confirmation_delegate(ctx, delegate)
+    }
+
+    /// Issues `subject` a credential signed by the calling verifier, e.g.
+    /// after an off-chain KYC check. Trades gated on that verifier accept the
+    /// subject's purchases until `expires_at`, or indefinitely when it is 0.
+    /// Issued by the region registrar, `region` certifies where the subject
+    /// resides.
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        subject: Pubkey,
+        expires_at: i64,
+        region: Option<DeliveryRegion>,
+    ) -> Result<()> {
+        instructions::participant::issue_attestation(ctx, subject, expires_at, region)
+    }
+
+    /// Closes an attestation; only the verifier that issued it may revoke it.
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        instructions::participant::revoke_attestation(ctx)
+    }
+
+    /// Lets `delegate`, e.g. a session key or the marketplace backend, place
+    /// orders for the buyer with `buy_trade_with_permit` until `expires_at`.
+    /// The permit PDA is approved as delegate on `buyer_token_account` for
+    /// `max_total_amount`, and no single order may exceed `max_order_amount`.
+    /// A token account holds one delegate, so this replaces any earlier
+    /// subscription, relay or permit approval on it.
+    pub fn create_spending_permit(
+        ctx: Context<CreateSpendingPermit>,
+        delegate: Pubkey,
+        max_total_amount: u64,
+        max_order_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::participant::create_spending_permit(
+            ctx,
+            delegate,
+            max_total_amount,
+            max_order_amount,
+            expires_at,
+        )
+    }
+
+    /// Closes a spending permit and revokes the token account's delegation.
+    pub fn revoke_spending_permit(ctx: Context<RevokeSpendingPermit>) -> Result<()> {
+        instructions::participant::revoke_spending_permit(ctx)
+    }
+
+    /// Authorises `session_key` to sign `buy_trade` and delivery confirmations
+    /// for the buyer until `expires_at_slot`, at most `MAX_SESSION_SLOTS`
+    /// ahead. A non-zero `spend_cap` approves the session PDA as delegate on
+    /// `buyer_token_account` for that amount, replacing any earlier delegate.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at_slot: u64,
+        spend_cap: u64,
+    ) -> Result<()> {
+        instructions::participant::create_session(ctx, session_key, expires_at_slot, spend_cap)
+    }
+
+    /// Closes a session, revoking its delegation if it still holds one.
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        instructions::participant::revoke_session(ctx)
+    }
+
+    pub fn register_buyer(ctx: Context<RegisterBuyer>) -> Result<()> {
+        instructions::participant::register_buyer(ctx)
+    }
+
+    /// Lists a trade. The account PDA of each logistics provider follows in
+    /// the remaining accounts, in list order, and every provider must be
+    /// registered.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_trade(
+        ctx: Context<CreateTrade>,
+        product_cost: u64,
+        logistics_providers: Vec<Pubkey>,
+        logistics_costs: Vec<u64>,
+        total_quantity: u64,
+        payout_splits: Vec<PayoutSplit>,
+        weight_grams: u64,
+        logistics_rates_per_kg: Vec<u64>,
+        category: u16,
+        tag_hashes: Vec<[u8; 32]>,
+        price_feed: Option<Pubkey>,
+        accepted_mints: Vec<AcceptedMint>,
+        insurance_bps: u16,
+        max_per_buyer: Option<u64>,
+        ship_to_regions: u16,
+        requires_proof_of_delivery: bool,
+        confirmation_mode: ConfirmationMode,
+    ) -> Result<()> {
+        instructions::trade::create_trade(
+            ctx,
+            product_cost,
+            logistics_providers,
+            logistics_costs,
+            total_quantity,
+            payout_splits,
+            weight_grams,
+            logistics_rates_per_kg,
+            category,
+            tag_hashes,
+            price_feed,
+            accepted_mints,
+            insurance_bps,
+            max_per_buyer,
+            ship_to_regions,
+            requires_proof_of_delivery,
+            confirmation_mode,
+        )
+    }
+
+    /// Sets the minimum number of slots between two purchases of the trade by
+    /// the same buyer, or clears it with `None`. Rapid-fire buys fail with
+    /// `RateLimited`. Callable by the seller or the admin.
+    pub fn set_purchase_cooldown(
+        ctx: Context<SetPurchaseCooldown>,
+        trade_id: u64,
+        cooldown_slots: Option<u64>,
+    ) -> Result<()> {
+        instructions::trade::set_purchase_cooldown(ctx, trade_id, cooldown_slots)
+    }
+
+    /// Starts a pre-sale on the trade: until `public_sale_start` only buyers
+    /// in the allowlist with root `merkle_root` may purchase, through
+    /// `buy_trade_allowlisted`. `None` ends the pre-sale. Callable by the
+    /// seller or the admin.
+    pub fn set_presale_allowlist(
+        ctx: Context<SetPresaleAllowlist>,
+        trade_id: u64,
+        presale: Option<PresaleAllowlist>,
+    ) -> Result<()> {
+        instructions::trade::set_presale_allowlist(ctx, trade_id, presale)
+    }
+
+    /// Turns the trade into a service listing: once a purchase is confirmed,
+    /// the seller's share stays in escrow and vests linearly over
+    /// `duration_seconds`, released with `release_seller_stream`. Until it has
+    /// fully vested the buyer may halt the stream with `raise_dispute`.
+    /// Logistics and fee legs are paid at confirmation as usual, and other
+    /// settlement paths pay the seller at once. `None` turns streaming off.
+    /// Callable by the seller or the admin, on trades without payout splits.
+    pub fn set_settlement_stream(
+        ctx: Context<SetSettlementStream>,
+        trade_id: u64,
+        duration_seconds: Option<i64>,
+    ) -> Result<()> {
+        instructions::trade::set_settlement_stream(ctx, trade_id, duration_seconds)
+    }
+
+    /// Splits the trade's escrow release into up to five production
+    /// milestones whose basis points total 10000, each confirmed by the buyer
+    /// with `confirm_milestone`. An empty list removes them. Callable by the
+    /// seller or the admin until the trade's first sale.
+    pub fn set_trade_milestones(
+        ctx: Context<SetTradeMilestones>,
+        trade_id: u64,
+        milestones: Vec<Milestone>,
+    ) -> Result<()> {
+        instructions::trade::set_trade_milestones(ctx, trade_id, milestones)
+    }
+
+    /// Sets the remaining quantity at or below which `buy_trade` emits
+    /// `LowStock`, or clears it with `None`. Callable by the seller or the admin.
+    pub fn set_low_stock_threshold(
+        ctx: Context<SetLowStockThreshold>,
+        trade_id: u64,
+        threshold: Option<u64>,
+    ) -> Result<()> {
+        instructions::trade::set_low_stock_threshold(ctx, trade_id, threshold)
+    }
+
+    /// Simulation-friendly view of a listing, returned through return data:
+    /// stock, price and the per-unit seller payout net of the escrow fee.
+    pub fn get_trade_summary(ctx: Context<GetTradeSummary>, trade_id: u64) -> Result<TradeSummary> {
+        instructions::trade::get_trade_summary(ctx, trade_id)
+    }
+
+    /// Adds stock to a listing, reopening it if it had sold out. Seller only.
+    /// A storefront picks up the reopened listing on `sync_storefront_listing`.
+    pub fn restock_trade(
+        ctx: Context<RestockTrade>,
+        trade_id: u64,
+        additional_quantity: u64,
+    ) -> Result<()> {
+        instructions::trade::restock_trade(ctx, trade_id, additional_quantity)
+    }
+
+    /// Switches a listing to made-to-order, where purchases never draw down
+    /// or sell out `remaining_quantity` but still count towards
+    /// `sold_quantity`. Seller only.
+    pub fn set_unlimited_quantity(
+        ctx: Context<SetUnlimitedQuantity>,
+        trade_id: u64,
+        unlimited: bool,
+    ) -> Result<()> {
+        instructions::trade::set_unlimited_quantity(ctx, trade_id, unlimited)
+    }
+
+    /// Opens a listing for pre-order. Purchases escrow immediately, but the
+    /// ship timeout, auto-confirm and free-cancellation windows only start
+    /// at `fulfillment_start`, so buyers can cancel freely until then.
+    /// Seller only; purchases already made keep their fulfillment date.
+    pub fn set_preorder(
+        ctx: Context<SetPreorder>,
+        trade_id: u64,
+        preorder: bool,
+        fulfillment_start: i64,
+    ) -> Result<()> {
+        instructions::trade::set_preorder(ctx, trade_id, preorder, fulfillment_start)
+    }
+
+    /// Opens the seller's storefront, which `set_storefront` then configures.
+    pub fn open_storefront(ctx: Context<OpenStorefront>) -> Result<()> {
+        instructions::trade::open_storefront(ctx)
+    }
+
+    /// Updates the seller's storefront: a hash of its branding metadata and
+    /// the logistics providers new listings default to. Trades created with
+    /// the storefront are counted under it.
+    pub fn set_storefront(
+        ctx: Context<SetStorefront>,
+        metadata_hash: [u8; 32],
+        default_logistics_providers: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::trade::set_storefront(ctx, metadata_hash, default_logistics_providers)
+    }
+
+    /// Brings a storefront's active trade count in line with a listing that
+    /// has sold out or reopened since it was last counted. Permissionless.
+    pub fn sync_storefront_listing(
+        ctx: Context<SyncStorefrontListing>,
+        trade_id: u64,
+    ) -> Result<()> {
+        instructions::trade::sync_storefront_listing(ctx, trade_id)
+    }
+
+    /// Creates a listing's metadata account, which `set_trade_metadata` then
+    /// fills in. Callable by the seller or the admin.
+    pub fn create_trade_metadata(ctx: Context<CreateTradeMetadata>, trade_id: u64) -> Result<()> {
+        instructions::trade::create_trade_metadata(ctx, trade_id)
+    }
+
+    /// Points a listing at its off-chain content (title, description, images).
+    /// `content_hash` commits to the document behind `uri` so storefronts can
+    /// verify what they fetch. Callable by the seller or the admin.
+    pub fn set_trade_metadata(
+        ctx: Context<SetTradeMetadata>,
+        trade_id: u64,
+        content_hash: [u8; 32],
+        uri: String,
+    ) -> Result<()> {
+        instructions::trade::set_trade_metadata(ctx, trade_id, content_hash, uri)
+    }
+
+    /// Restricts a trade to buyers holding a credential: a token of a given
+    /// mint, or an attestation from a given verifier. `None` lifts the gate.
+    /// Callable by the seller or the admin.
+    pub fn set_trade_buyer_gate(
+        ctx: Context<SetTradeBuyerGate>,
+        trade_id: u64,
+        gate: Option<BuyerGate>,
+    ) -> Result<()> {
+        instructions::trade::set_trade_buyer_gate(ctx, trade_id, gate)
+    }
+
+    /// Pauses (`enabled == false`) or resumes the logistics provider at
+    /// `index` of a trade's provider list. Purchases naming a paused provider
+    /// are rejected. Callable by the seller.
+    pub fn set_provider_status(
+        ctx: Context<SetProviderStatus>,
+        trade_id: u64,
+        index: u8,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::trade::set_provider_status(ctx, trade_id, index, enabled)
+    }
+
+    /// Pre-authorizes `cycles` recurring purchases of `quantity` units, one
+    /// every `interval_seconds` starting now. The subscription account is
+    /// approved as delegate on the buyer's token account for every cycle at
+    /// today's price; a later price above that per-cycle amount stops the
+    /// subscription from processing. A token account holds one delegate, so
+    /// a new approval on it replaces any earlier subscription's allowance.
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        trade_id: u64,
+        quantity: u64,
+        logistics_provider: Pubkey,
+        interval_seconds: i64,
+        cycles: u32,
+    ) -> Result<()> {
+        instructions::market::create_subscription(
+            ctx,
+            trade_id,
+            quantity,
+            logistics_provider,
+            interval_seconds,
+            cycles,
+        )
+    }
+
+    /// Runs one due subscription cycle: pulls the cycle's payment from the
+    /// buyer's delegated token account into escrow and opens a purchase at
+    /// the listing's current price. Anyone may call it; the caller pays the
+    /// purchase account's rent.
+    pub fn process_subscription_cycle(
+        ctx: Context<ProcessSubscriptionCycle>,
+        trade_id: u64,
+    ) -> Result<()> {
+        instructions::market::process_subscription_cycle(ctx, trade_id)
+    }
+
+    /// Ends a subscription, revoking the token delegation and returning the
+    /// account's rent to the buyer.
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>, trade_id: u64) -> Result<()> {
+        instructions::market::cancel_subscription(ctx, trade_id)
+    }
+
+    /// Offers to buy `quantity` units of a token-priced listing at
+    /// `unit_price`, escrowing the offered product cost plus the chosen
+    /// provider's logistics cost until `expires_at`.
+    pub fn make_offer(
+        ctx: Context<MakeOffer>,
+        trade_id: u64,
+        unit_price: u64,
+        quantity: u64,
+        logistics_provider: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::market::make_offer(
+            ctx,
+            trade_id,
+            unit_price,
+            quantity,
+            logistics_provider,
+            expires_at,
+        )
+    }
+
+    /// Seller acceptance of an unexpired offer. The escrowed funds become a
+    /// regular purchase at the offered price; the seller pays its rent.
+    pub fn accept_offer(ctx: Context<AcceptOffer>, trade_id: u64) -> Result<()> {
+        instructions::market::accept_offer(ctx, trade_id)
+    }
+
+    /// Refunds an offer the seller let expire and closes it.
+    pub fn withdraw_offer(ctx: Context<WithdrawOffer>, trade_id: u64) -> Result<()> {
+        instructions::market::withdraw_offer(ctx, trade_id)
+    }
+
+    /// Holds `quantity` units of a listing for the buyer during checkout. The
+    /// units leave `remaining_quantity` until the reservation is consumed by
+    /// `buy_trade` or released, which anyone may do once it has expired.
+    pub fn reserve_quantity(
+        ctx: Context<ReserveQuantity>,
+        trade_id: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        instructions::trade::reserve_quantity(ctx, trade_id, quantity)
+    }
+
+    /// Returns reserved units to the listing and closes the reservation. The
+    /// buyer may release at any time; anyone may release an expired hold.
+    pub fn release_reservation(ctx: Context<ReleaseReservation>, trade_id: u64) -> Result<()> {
+        instructions::trade::release_reservation(ctx, trade_id)
+    }
+
+    /// Buys `quantity` units from a listing, escrowing the product and logistics
+    /// cost. With `insured`, the buyer also pays the trade's insurance premium
+    /// into the mint's insurance pool; the premium is not refundable. Passing
+    /// the buyer's reservation consumes it: its units count towards this
+    /// purchase and the account is closed. Trades with a `max_per_buyer` limit
+    /// need the buyer's quota account, which tracks units bought across
+    /// purchases; cancelled or refunded units still count. `buyer_region` must
+    /// be one the trade ships to; once a region registrar is configured, the
+    /// buyer's attestation from it must certify that region.
+    ///
+    /// Third-party storefronts route orders here through `cpi::buy_trade`,
+    /// signing as the optional `platform` account, usually a PDA of the
+    /// storefront program. A platform registered with `set_platform_fee_share`
+    /// has its share recorded on the purchase and is credited that share of the
+    /// escrow fee, claimable with `claim_payout`, when the purchase settles.
+    ///
+    /// `authority` is the buyer, or a session key passed with its `session`;
+    /// a session pays through its delegation and within its spend cap.
+    ///
+    /// `logistics_provider_index` is the position of `logistics_provider` in
+    /// the trade's provider list; the order fails if the provider is no longer
+    /// there.
+    ///
+    /// `memo_hash` optionally references order notes the buyer keeps
+    /// encrypted off-chain, such as a delivery address; it is stored on the
+    /// purchase and included in `PurchaseCreated`.
+    ///
+    /// The buyer must have run `register_buyer`, and the mint's escrow vault
+    /// must exist (see `create_escrow_vault`). Insured orders also need the
+    /// mint's insurance pool (`create_insurance_pool`), and trades with a
+    /// per-buyer limit or cooldown the buyer's quota (`open_buyer_quota`).
+    /// Relayed and permit orders have the same prerequisites.
+    #[allow(clippy::too_many_arguments)]
+    pub fn buy_trade(
+        ctx: Context<BuyTrade>,
+        trade_id: u64,
+        quantity: u64,
+        logistics_provider: Pubkey,
+        logistics_provider_index: u8,
+        insured: bool,
+        buyer_region: DeliveryRegion,
+        memo_hash: Option<[u8; 64]>,
+    ) -> Result<()> {
+        instructions::purchase::buy_trade(
+            ctx,
+            trade_id,
+            quantity,
+            logistics_provider,
+            logistics_provider_index,
+            insured,
+            buyer_region,
+            memo_hash,
+        )
+    }
+
+    /// Opens the account counting a buyer's purchases from a trade, required
+    /// before buying from a trade that sets `max_per_buyer` or a purchase
+    /// cooldown. Anyone may pay for it.
+    pub fn open_buyer_quota(ctx: Context<OpenBuyerQuota>, trade_id: u64) -> Result<()> {
+        instructions::purchase::open_buyer_quota(ctx, trade_id)
+    }
+
+    /// `buy_trade` during a trade's pre-sale. `proof` holds the sibling hashes
+    /// from the buyer's `allowlist_leaf` up to the allowlist's Merkle root,
+    /// pairs hashed with `hash_allowlist_pair`. Until the public sale opens,
+    /// plain `buy_trade`, relayed and permit orders fail with `PresaleActive`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn buy_trade_allowlisted(
+        ctx: Context<BuyTrade>,
+        trade_id: u64,
+        quantity: u64,
+        logistics_provider: Pubkey,
+        logistics_provider_index: u8,
+        insured: bool,
+        buyer_region: DeliveryRegion,
+        memo_hash: Option<[u8; 64]>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::purchase::buy_trade_allowlisted(
+            ctx,
+            trade_id,
+            quantity,
+            logistics_provider,
+            logistics_provider_index,
+            insured,
+            buyer_region,
+            memo_hash,
+            proof,
+        )
+    }
+
+    /// Opens the buyer's relay nonce, starting at zero. Anyone may pay for it,
+    /// typically the relayer.
+    pub fn open_relay_nonce(ctx: Context<OpenRelayNonce>) -> Result<()> {
+        instructions::purchase::open_relay_nonce(ctx)
+    }
+
+    /// `buy_trade` submitted by a relayer on behalf of a buyer without SOL.
+    /// The buyer signs `order.signing_message()` off-chain, and the relayer
+    /// places an Ed25519 program instruction verifying that signature
+    /// immediately before this one. Funds are pulled through the buyer's
+    /// SPL approval of the relay authority PDA, so the buyer approves it once
+    /// (the relayer may pay that transaction's fee). The relayer pays all
+    /// rent and collects `order.relayer_fee` in the payment token.
+    ///
+    /// Orders are fixed-price, in the trade's primary mint and uninsured.
+    /// Each carries the buyer's next relay nonce and an expiry, so a signed
+    /// order cannot be replayed. The nonce account is opened beforehand with
+    /// `open_relay_nonce`.
+    pub fn buy_trade_relayed(ctx: Context<BuyTradeRelayed>, order: RelayedOrder) -> Result<()> {
+        instructions::purchase::buy_trade_relayed(ctx, order)
+    }
+
+    /// `buy_trade` executed by the delegate of the buyer's spending permit,
+    /// which signs and pays rent in place of the buyer. Funds are pulled
+    /// through the permit's SPL delegation and drawn down from its allowance.
+    /// Orders are fixed-price, in the trade's primary mint and uninsured.
+    pub fn buy_trade_with_permit(
+        ctx: Context<BuyTradeWithPermit>,
+        trade_id: u64,
+        quantity: u64,
+        logistics_provider: Pubkey,
+        buyer_region: DeliveryRegion,
+    ) -> Result<()> {
+        instructions::purchase::buy_trade_with_permit(
+            ctx,
+            trade_id,
+            quantity,
+            logistics_provider,
+            buyer_region,
+        )
+    }
+
+    /// Puts `quantity` units of a token-priced listing up for auction in the
+    /// trade's primary mint. Bids are per-unit prices; the lot leaves
+    /// `remaining_quantity` until an unsold auction is finalized.
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        trade_id: u64,
+        quantity: u64,
+        start_price: u64,
+        min_increment: u64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::market::create_auction(
+            ctx,
+            trade_id,
+            quantity,
+            start_price,
+            min_increment,
+            end_time,
+        )
+    }
+
+    /// Bids `unit_bid` per unit, escrowing the lot price plus the chosen
+    /// provider's logistics cost. The previous leader is refunded in full.
+    pub fn place_bid(
+        ctx: Context<PlaceBid>,
+        trade_id: u64,
+        unit_bid: u64,
+        logistics_provider: Pubkey,
+    ) -> Result<()> {
+        instructions::market::place_bid(ctx, trade_id, unit_bid, logistics_provider)
+    }
+
+    /// Closes an auction after `end_time`. The winning bid becomes a regular
+    /// purchase, already escrowed, that continues through the normal delivery
+    /// flow; an unsold lot returns to the listing. Anyone may finalize, and
+    /// the caller pays the new purchase account's rent.
+    pub fn finalize_auction(ctx: Context<FinalizeAuction>, trade_id: u64) -> Result<()> {
+        instructions::market::finalize_auction(ctx, trade_id)
+    }
+
+    /// Reserves `quantity` units of a token-priced listing for a sealed-bid
+    /// drop at the trade's current price. Buyers commit until `commit_end`
+    /// and reveal until `reveal_end`; no buyer may ask for more than
+    /// `max_quantity_per_commit` units.
+    pub fn create_sealed_drop(
+        ctx: Context<CreateSealedDrop>,
+        trade_id: u64,
+        quantity: u64,
+        max_quantity_per_commit: u64,
+        commit_end: i64,
+        reveal_end: i64,
+    ) -> Result<()> {
+        instructions::market::create_sealed_drop(
+            ctx,
+            trade_id,
+            quantity,
+            max_quantity_per_commit,
+            commit_end,
+            reveal_end,
+        )
+    }
+
+    /// Stores `commitment`, the `sealed_commitment` hash of the buyer, the
+    /// wanted quantity and a secret salt, and escrows the price of
+    /// `max_quantity_per_commit` units plus the chosen provider's logistics
+    /// cost. One commit per buyer and drop.
+    pub fn commit_purchase(
+        ctx: Context<CommitPurchase>,
+        trade_id: u64,
+        commitment: [u8; 32],
+        logistics_provider: Pubkey,
+    ) -> Result<()> {
+        instructions::market::commit_purchase(ctx, trade_id, commitment, logistics_provider)
+    }
+
+    /// Opens a commitment between `commit_end` and `reveal_end`, adding
+    /// `quantity` to the drop's revealed demand.
+    pub fn reveal_purchase(
+        ctx: Context<RevealPurchase>,
+        trade_id: u64,
+        quantity: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        instructions::market::reveal_purchase(ctx, trade_id, quantity, salt)
+    }
+
+    /// Settles a commit after `reveal_end`. When revealed demand exceeds the
+    /// lot, every reveal is cut to its pro rata share, rounded down, so the
+    /// order of reveals and claims does not matter. Allocated units become a
+    /// regular escrowed purchase; the rest of the deposit, all of it for
+    /// unrevealed or unallocated commits, is refunded. Anyone may claim.
+    pub fn claim_sealed_purchase(ctx: Context<ClaimSealedPurchase>, trade_id: u64) -> Result<()> {
+        instructions::market::claim_sealed_purchase(ctx, trade_id)
+    }
+
+    /// Closes a drop once every commit is claimed, returning unallocated units
+    /// to the listing. Anyone may close it.
+    pub fn close_sealed_drop(ctx: Context<CloseSealedDrop>, trade_id: u64) -> Result<()> {
+        instructions::market::close_sealed_drop(ctx, trade_id)
+    }
+
+    /// Releases escrow to the seller and logistics provider. If the seller has a
+    /// preferred payout mint, the caller may pass a `swap_route` (instruction
+    /// data from an off-chain quote) to swap the seller's share through the
+    /// configured AMM first. The seller must co-sign a swapped settlement so
+    /// nobody else can pick the quote. The route's accounts are passed as
+    /// remaining accounts, so swaps are only offered on trades without payout
+    /// splits. An active session key of the buyer may confirm in their place.
+    pub fn confirm_delivery_and_purchase<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+        purchase_id: u64,
+        swap_route: Option<SwapRoute>,
+    ) -> Result<()> {
+        instructions::purchase::confirm_delivery_and_purchase(ctx, purchase_id, swap_route)
+    }
+
+    /// Confirms and settles up to `MAX_BATCH_CONFIRMATIONS` of the buyer's
+    /// purchases at once, paying out as `confirm_delivery_and_purchase` would.
+    /// The purchases must share the trade, mint and logistics provider. Each
+    /// is passed in remaining accounts as its purchase account followed by its
+    /// product and logistics sub-vaults, and the trade's payout split token
+    /// accounts come last. Purchases routed by a platform and streamed trades
+    /// are settled one at a time.
+    pub fn confirm_deliveries_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConfirmDeliveriesBatch<'info>>,
+        trade_id: u64,
+        purchase_count: u8,
+    ) -> Result<()> {
+        instructions::purchase::confirm_deliveries_batch(ctx, trade_id, purchase_count)
+    }
+
+    /// Permissionless crank settling a shipped purchase the buyer neither
+    /// confirmed nor disputed within `auto_confirm_seconds`, exactly as a
+    /// buyer confirmation would. Already-settled purchases are a no-op so
+    /// automation threads can retry freely.
+    pub fn auto_settle_expired<'info>(
+        ctx: Context<'_, '_, '_, 'info, AutoSettleExpired<'info>>,
+        purchase_id: u64,
+    ) -> Result<()> {
+        instructions::purchase::auto_settle_expired(ctx, purchase_id)
+    }
+
+    /// Permissionless crank refunding a purchase the seller did not ship
+    /// within `ship_timeout_seconds`, including any insurance premium. No fee
+    /// is earned on a refund, so the crank reward comes out of the fees
+    /// already accumulated in the mint's escrow vault. Already-settled
+    /// purchases are a no-op.
+    pub fn auto_refund_timeouts(ctx: Context<AutoRefundTimeouts>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::auto_refund_timeouts(ctx, purchase_id)
+    }
+
+    /// Settles the units a buyer has received out of a larger order. The seller
+    /// and logistics provider are paid pro rata, and the purchase is reduced to
+    /// the undelivered remainder, which stays escrowed until it is confirmed,
+    /// disputed or refunded. Takes the same accounts as
+    /// `confirm_delivery_and_purchase`; partial payouts are never swapped.
+    pub fn confirm_partial_delivery<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+        purchase_id: u64,
+        quantity_received: u64,
+    ) -> Result<()> {
+        instructions::purchase::confirm_partial_delivery(ctx, purchase_id, quantity_received)
+    }
+
+    /// Buyer sign-off on one production stage of a made-to-order trade. Pays
+    /// the milestone's share of the seller's proceeds out of the product
+    /// escrow; logistics and the escrow fee wait for the final confirmation,
+    /// which pays the seller what is left. Takes the same accounts as
+    /// `confirm_delivery_and_purchase`. Once a milestone is released the order
+    /// can no longer be confirmed partially.
+    pub fn confirm_milestone<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfirmDeliveryAndPurchase<'info>>,
+        purchase_id: u64,
+        index: u8,
+    ) -> Result<()> {
+        instructions::purchase::confirm_milestone(ctx, purchase_id, index)
+    }
+
+    /// Merges several open purchases by the same buyer from the same seller,
+    /// shipped by the same provider, into one shipment. The provider proposes
+    /// the combined logistics cost, the buyer co-signs to approve it, and the
+    /// difference is refunded to the buyer pro-rata across the merged
+    /// purchases, each share from that purchase's logistics escrow. Purchase
+    /// accounts are passed as writable remaining accounts in the same order as
+    /// `purchase_ids`, followed by each purchase's trade account, then by their
+    /// logistics sub-vaults (or the mint's escrow vault for purchases opened
+    /// before escrow was split), both in the same order. `shipment_id` keys the
+    /// shipment account and must be the first of `purchase_ids`.
+    pub fn consolidate_shipments<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConsolidateShipments<'info>>,
+        shipment_id: u64,
+        purchase_ids: Vec<u64>,
+        consolidated_logistics_cost: u64,
+    ) -> Result<()> {
+        instructions::purchase::consolidate_shipments(
+            ctx,
+            shipment_id,
+            purchase_ids,
+            consolidated_logistics_cost,
+        )
+    }
+
+    pub fn raise_dispute(
+        ctx: Context<RaiseDispute>,
+        purchase_id: u64,
+        reason: DisputeReason,
+    ) -> Result<()> {
+        instructions::dispute::raise_dispute(ctx, purchase_id, reason)
+    }
+
+    /// First-tier ruling by a registered arbitrator. Funds stay in escrow
+    /// until `resolve_dispute` executes the ruling once the escalation window
+    /// has passed, or the admin re-adjudicates an escalated dispute.
+    pub fn arbitrate_dispute(
+        ctx: Context<ArbitrateDispute>,
+        purchase_id: u64,
+        winner: Pubkey,
+        slash_provider: bool,
+    ) -> Result<()> {
+        instructions::dispute::arbitrate_dispute(ctx, purchase_id, winner, slash_provider)
+    }
+
+    /// `arbitrate_dispute` for up to `MAX_BATCH_RULINGS` disputes in
+    /// `token_mint`, e.g. to clear a backlog after an incident. Each ruling's
+    /// purchase and trade accounts are passed in that order as remaining
+    /// accounts. A dispute that cannot be ruled is skipped with a
+    /// `DisputeRulingSkipped` event carrying the error it would have failed
+    /// with, and the others are still ruled.
+    pub fn resolve_disputes_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDisputesBatch<'info>>,
+        token_mint: Pubkey,
+        rulings: Vec<BatchRuling>,
+    ) -> Result<()> {
+        instructions::dispute::resolve_disputes_batch(ctx, token_mint, rulings)
+    }
+
+    /// Appeals a first-tier ruling to the admin. The escalation fee is held in
+    /// escrow and paid to whoever wins the re-adjudication.
+    pub fn escalate_dispute(ctx: Context<EscalateDispute>, purchase_id: u64) -> Result<()> {
+        instructions::dispute::escalate_dispute(ctx, purchase_id)
+    }
+
+    /// Stakes the configured juror stake and joins the pool juries are drawn
+    /// from.
+    pub fn join_jury_pool(ctx: Context<JoinJuryPool>) -> Result<()> {
+        instructions::dispute::join_jury_pool(ctx)
+    }
+
+    /// Leaves the jury pool and withdraws the juror's stake, less anything
+    /// slashed. Not possible while the juror sits on an undecided dispute.
+    pub fn leave_jury_pool(ctx: Context<LeaveJuryPool>) -> Result<()> {
+        instructions::dispute::leave_jury_pool(ctx)
+    }
+
+    /// Opts an unruled dispute into jury arbitration instead of a single
+    /// arbitrator. Any party may summon a jury; `jury_size` staked jurors who
+    /// are not parties are drawn pseudo-randomly, seeded by the latest slot
+    /// hash, and vote within the voting window.
+    pub fn summon_jury(ctx: Context<SummonJury>, purchase_id: u64) -> Result<()> {
+        instructions::dispute::summon_jury(ctx, purchase_id)
+    }
+
+    /// A drawn juror's vote for the buyer, seller or logistics provider.
+    pub fn cast_jury_vote(
+        ctx: Context<CastJuryVote>,
+        purchase_id: u64,
+        winner: Pubkey,
+    ) -> Result<()> {
+        instructions::dispute::cast_jury_vote(ctx, purchase_id, winner)
+    }
+
+    /// Tallies a jury once everyone has voted or the window has closed.
+    /// Minority voters lose `minority_slash_bps` of their stake to the
+    /// majority, and a majority verdict becomes the dispute's first-tier
+    /// ruling, escalatable as usual and executed by `resolve_dispute`. Without
+    /// a majority the jury is dismissed and the dispute may be arbitrated or
+    /// sent to a new jury. Permissionless.
+    pub fn finalize_jury_verdict(
+        ctx: Context<FinalizeJuryVerdict>,
+        purchase_id: u64,
+    ) -> Result<()> {
+        instructions::dispute::finalize_jury_verdict(ctx, purchase_id)
+    }
+
+    /// Settles a dispute. The admin may adjudicate any dispute; anyone else
+    /// may only execute an unescalated first-tier ruling after its escalation
+    /// window has passed.
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+        purchase_id: u64,
+        winner: Pubkey,
+        slash_provider: bool,
+    ) -> Result<()> {
+        instructions::dispute::resolve_dispute(ctx, purchase_id, winner, slash_provider)
+    }
+
+    /// Admin ruling on a buyer's dispute of a streamed payout. A buyer win
+    /// refunds the part that had not vested when the stream was halted; a
+    /// seller win resumes the stream on its original schedule.
+    pub fn resolve_stream_dispute(
+        ctx: Context<ResolveStreamDispute>,
+        purchase_id: u64,
+        winner: Pubkey,
+    ) -> Result<()> {
+        instructions::dispute::resolve_stream_dispute(ctx, purchase_id, winner)
+    }
+
+    /// Appeals an executed `resolve_dispute` within the escalation window,
+    /// posting an appeal bond at the escalation fee rate. Only a losing party
+    /// may appeal, once, and not a resolution of an escalated ruling, which
+    /// is final at once. The purchase cannot be closed until the appeal is
+    /// reviewed.
+    pub fn appeal_resolution(ctx: Context<AppealResolution>, purchase_id: u64) -> Result<()> {
+        instructions::dispute::appeal_resolution(ctx, purchase_id)
+    }
+
+    /// Admin re-review of an appealed resolution, which makes it final. The
+    /// bond goes to the winner when the resolution is upheld and back to the
+    /// appellant when it is not; payouts already executed are not reversed.
+    pub fn review_appeal(ctx: Context<ReviewAppeal>, purchase_id: u64, upheld: bool) -> Result<()> {
+        instructions::dispute::review_appeal(ctx, purchase_id, upheld)
+    }
+
+    /// Simulation-only preview of the transfers `resolve_dispute` would execute
+    /// for the given winner. The preview is returned through return data and
+    /// does not mutate any account.
+    pub fn preview_resolution(
+        ctx: Context<PreviewResolution>,
+        purchase_id: u64,
+        winner: Pubkey,
+    ) -> Result<ResolutionPreview> {
+        instructions::dispute::preview_resolution(ctx, purchase_id, winner)
+    }
+
+    /// Records that the goods left the seller. Callable by the seller or the
+    /// chosen logistics provider on pickup; after this the seller can no
+    /// longer cancel the order.
+    pub fn mark_shipped(ctx: Context<MarkShipped>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::mark_shipped(ctx, purchase_id)
+    }
+
+    /// Attaches the carrier and a hash of the tracking number to a purchase so
+    /// buyers and indexers can follow the parcel. Only the chosen logistics
+    /// provider may record it; recording again replaces the previous entry.
+    pub fn record_tracking(
+        ctx: Context<RecordTracking>,
+        purchase_id: u64,
+        carrier_code: u16,
+        tracking_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::purchase::record_tracking(ctx, purchase_id, carrier_code, tracking_hash)
+    }
+
+    /// Posts the digest of a delivery photo or recipient signature. Only the
+    /// chosen logistics provider may submit it. Purchases of trades created
+    /// with `requires_proof_of_delivery` cannot settle without it.
+    pub fn submit_pod(ctx: Context<SubmitPod>, purchase_id: u64, pod_hash: [u8; 32]) -> Result<()> {
+        instructions::purchase::submit_pod(ctx, purchase_id, pod_hash)
+    }
+
+    /// Lets the chosen logistics provider take on a purchase. Either answer
+    /// is final; only a provider that has not declined may mark it shipped.
+    pub fn provider_accept_order(ctx: Context<AnswerAssignment>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::provider_accept_order(ctx, purchase_id)
+    }
+
+    /// Lets the chosen logistics provider turn a purchase down before
+    /// shipping, after which the buyer picks another with `reassign_logistics`.
+    pub fn provider_decline_order(ctx: Context<AnswerAssignment>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::provider_decline_order(ctx, purchase_id)
+    }
+
+    /// Moves a purchase whose provider declined to another of the trade's
+    /// providers, re-pricing the logistics leg. The buyer tops up any increase
+    /// into escrow in the same instruction and is refunded any decrease, so
+    /// the logistics sub-vault always holds exactly the leg's cost.
+    /// `logistics_provider_index` is the new provider's position in the
+    /// trade's provider list; the reassignment fails if the new cost exceeds
+    /// `max_logistics_cost`.
+    pub fn reassign_logistics(
+        ctx: Context<ReassignLogistics>,
+        purchase_id: u64,
+        logistics_provider: Pubkey,
+        logistics_provider_index: u8,
+        max_logistics_cost: u64,
+    ) -> Result<()> {
+        instructions::purchase::reassign_logistics(
+            ctx,
+            purchase_id,
+            logistics_provider,
+            logistics_provider_index,
+            max_logistics_cost,
+        )
+    }
+
+    /// Records the signer's sign-off on delivery. On trades created with
+    /// `ConfirmationMode::TwoOfThree`, escrow is only released once two of
+    /// the buyer, seller and logistics provider have confirmed; the second
+    /// party may confirm through `confirm_delivery_and_purchase` directly.
+    pub fn sign_confirmation(ctx: Context<SignConfirmation>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::sign_confirmation(ctx, purchase_id)
+    }
+
+    /// Lends a pending purchase's product escrow to the configured lending
+    /// program. `route_data` is the program's deposit instruction, invoked
+    /// with the remaining accounts and the mint's escrow vault as signer; it
+    /// must move the whole product sub-vault into the purchase's lending
+    /// receipt account. Only the buyer may opt in.
+    pub fn deposit_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositEscrow<'info>>,
+        purchase_id: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::lending::deposit_escrow(ctx, purchase_id, route_data)
+    }
+
+    /// Redeems a lent purchase's receipt tokens back into its product
+    /// sub-vault and splits any yield between the buyer and the platform. A
+    /// lent purchase cannot settle, refund or be disputed to a ruling until
+    /// the buyer runs this, so clients place it ahead of the settling
+    /// instruction. Redeeming less than the principal fails.
+    pub fn withdraw_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawEscrow<'info>>,
+        purchase_id: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::lending::withdraw_escrow(ctx, purchase_id, route_data)
+    }
+
+    /// Opens a buyer's loyalty account. Anyone may pay for it.
+    pub fn open_loyalty_account(ctx: Context<OpenLoyaltyAccount>) -> Result<()> {
+        instructions::loyalty::open_loyalty_account(ctx)
+    }
+
+    /// Credits the buyer loyalty points for a delivered and settled purchase,
+    /// in proportion to its product volume. Permissionless and once per
+    /// purchase; the buyer's loyalty account must be open.
+    pub fn accrue_loyalty_points(
+        ctx: Context<AccrueLoyaltyPoints>,
+        purchase_id: u64,
+    ) -> Result<()> {
+        instructions::loyalty::accrue_loyalty_points(ctx, purchase_id)
+    }
+
+    /// Converts loyalty points into a discount on the escrow fee of the
+    /// buyer's next `buy_trade`, up to the configured maximum.
+    pub fn redeem_loyalty_points(ctx: Context<RedeemLoyaltyPoints>, points: u64) -> Result<()> {
+        instructions::loyalty::redeem_loyalty_points(ctx, points)
+    }
+
+    /// Mints the buyer a compressed NFT receipt for a delivered and settled
+    /// purchase, encoding its trade, quantity and amount so it can back
+    /// warranty claims and loyalty programs. Optional and once per purchase.
+    pub fn mint_purchase_receipt(
+        ctx: Context<MintPurchaseReceipt>,
+        purchase_id: u64,
+    ) -> Result<()> {
+        instructions::receipt::mint_purchase_receipt(ctx, purchase_id)
+    }
+
+    /// Simulation-friendly view of a purchase, returned through return data:
+    /// its lifecycle stage and what settling it would pay the seller, the
+    /// logistics provider and the platform. Does not mutate any account.
+    pub fn get_purchase_status(
+        ctx: Context<GetPurchaseStatus>,
+        purchase_id: u64,
+    ) -> Result<PurchaseStatus> {
+        instructions::purchase::get_purchase_status(ctx, purchase_id)
+    }
+
+    /// Recomputes what the trade's open purchases should hold in escrow and
+    /// compares it with their sub-vault balances, emitting
+    /// `EscrowInvariantViolated` for each purchase that diverges. Permissionless
+    /// and read-only; each purchase is passed in `remaining_accounts` followed by
+    /// its product and logistics sub-vaults.
+    pub fn audit_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuditEscrow<'info>>,
+        trade_id: u64,
+    ) -> Result<()> {
+        instructions::purchase::audit_escrow(ctx, trade_id)
+    }
+
+    /// Lets the seller decline an order they cannot fulfil, as long as it has
+    /// not shipped. The buyer is refunded in full, including any insurance
+    /// premium, and a registered seller's cancellation counter is incremented.
+    pub fn seller_cancel_purchase(
+        ctx: Context<SellerCancelPurchase>,
+        purchase_id: u64,
+        reason: CancelReason,
+    ) -> Result<()> {
+        instructions::purchase::seller_cancel_purchase(ctx, purchase_id, reason)
+    }
+
+    /// Buyer cancellation before delivery. Free inside the cooling-off window
+    /// or with the seller's co-signature; otherwise the restocking fee is paid
+    /// to the seller and the rest refunded.
+    pub fn cancel_purchase(
+        ctx: Context<CancelPurchase>,
+        purchase_id: u64,
+        reason: CancelReason,
+    ) -> Result<()> {
+        instructions::purchase::cancel_purchase(ctx, purchase_id, reason)
+    }
+
+    /// Pays the seller what has vested of a streamed payout since the last
+    /// release. Anyone may release.
+    pub fn release_seller_stream(
+        ctx: Context<ReleaseSellerStream>,
+        purchase_id: u64,
+    ) -> Result<()> {
+        instructions::purchase::release_seller_stream(ctx, purchase_id)
+    }
+
+    /// Opens the balance a recipient's payouts in `token_mint` are credited to
+    /// when settlement is given no token account for them. Anyone may pay
+    /// for it.
+    pub fn open_claimable_balance(ctx: Context<OpenClaimableBalance>) -> Result<()> {
+        instructions::purchase::open_claimable_balance(ctx)
+    }
+
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        instructions::purchase::claim_payout(ctx)
+    }
+
+    /// Pays the seller the value of goods lost or damaged in transit out of the
+    /// mint's insurance pool, once a dispute on an insured purchase has been
+    /// resolved for the buyer. Must be claimed before the purchase is closed.
+    pub fn claim_insurance(ctx: Context<ClaimInsurance>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::claim_insurance(ctx, purchase_id)
+    }
+
+    /// Opens the seller's returns reserve for `token_mint`.
+    pub fn open_returns_reserve(ctx: Context<OpenReturnsReserve>) -> Result<()> {
+        instructions::returns::open_returns_reserve(ctx)
+    }
+
+    /// Tops up the seller's returns reserve for `token_mint`, which funds
+    /// approved return refunds and return shipping.
+    pub fn fund_returns_reserve(ctx: Context<FundReturnsReserve>, amount: u64) -> Result<()> {
+        instructions::returns::fund_returns_reserve(ctx, amount)
+    }
+
+    /// Withdraws from the seller's returns reserve. Refunds for approved
+    /// returns are already held in escrow, so this cannot strand a buyer.
+    pub fn withdraw_returns_reserve(
+        ctx: Context<WithdrawReturnsReserve>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::returns::withdraw_returns_reserve(ctx, amount)
+    }
+
+    /// Opens a return on a delivered purchase within `RETURN_WINDOW_SECONDS`
+    /// of settlement. The buyer picks one of the listing's logistics providers
+    /// for the return leg. The request is stored as a purchase extension.
+    pub fn request_return(
+        ctx: Context<RequestReturn>,
+        purchase_id: u64,
+        logistics_provider: Pubkey,
+    ) -> Result<()> {
+        instructions::returns::request_return(ctx, purchase_id, logistics_provider)
+    }
+
+    /// Seller accepts a return and moves the product refund plus the quoted
+    /// return shipping cost from their returns reserve into escrow.
+    pub fn approve_return(
+        ctx: Context<ApproveReturn>,
+        purchase_id: u64,
+        logistics_cost: u64,
+    ) -> Result<()> {
+        instructions::returns::approve_return(ctx, purchase_id, logistics_cost)
+    }
+
+    /// Finishes an approved return once the goods are back with the seller:
+    /// refunds the buyer and pays the return logistics provider from escrow.
+    /// Callable by the seller or, attesting delivery, the return provider.
+    pub fn complete_return(ctx: Context<CompleteReturn>, purchase_id: u64) -> Result<()> {
+        instructions::returns::complete_return(ctx, purchase_id)
+    }
+
+    /// Closes a settled purchase after the retention window, returning its rent
+    /// to whoever paid for the account. Callable by the buyer or the seller.
+    pub fn close_purchase(ctx: Context<ClosePurchase>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::close_purchase(ctx, purchase_id)
+    }
+
+    /// Opens the account a purchase's shipping info is posted to. Callable by
+    /// the buyer.
+    pub fn open_shipping_info(ctx: Context<OpenShippingInfo>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::open_shipping_info(ctx, purchase_id)
+    }
+
+    /// Posts the buyer's shipping address for an open purchase, usually in the
+    /// same transaction as `buy_trade` and `open_shipping_info`, so it never
+    /// has to be shared off-chain in the clear. The buyer seals the address separately to the seller and
+    /// to the logistics provider; see `ShippingInfo` for the scheme. Posting
+    /// again replaces both ciphertexts, e.g. after `reassign_logistics`.
+    pub fn post_shipping_info(
+        ctx: Context<PostShippingInfo>,
+        purchase_id: u64,
+        ephemeral_pubkey: [u8; 32],
+        seller_ciphertext: Vec<u8>,
+        provider_ciphertext: Vec<u8>,
+    ) -> Result<()> {
+        instructions::purchase::post_shipping_info(
+            ctx,
+            purchase_id,
+            ephemeral_pubkey,
+            seller_ciphertext,
+            provider_ciphertext,
+        )
+    }
+
+    /// Closes a settled purchase's shipping info, returning its rent to the
+    /// buyer. Must run before `close_purchase`, which removes the purchase it
+    /// checks against.
+    pub fn close_shipping_info(ctx: Context<CloseShippingInfo>, purchase_id: u64) -> Result<()> {
+        instructions::purchase::close_shipping_info(ctx, purchase_id)
+    }
+
+    /// Withdraws `amount` of the escrow fees collected in `mint` from the
+    /// mint's fee vault to a token account of the same mint owned by the
+    /// governing authority.
+    pub fn withdraw_escrow_fees(
+        ctx: Context<WithdrawEscrowFees>,
+        mint: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::admin::withdraw_escrow_fees(ctx, mint, amount)
+    }
+
+    /// Proposes rescuing `amount` from the `source` vault in `token_mint` into
+    /// `destination` after a catastrophic bug. The withdrawal can only be
+    /// executed `EMERGENCY_TIMELOCK_SECONDS` (72 hours) later, giving users time
+    /// to react, and only one may be pending per mint.
+    pub fn propose_emergency_withdrawal(
+        ctx: Context<ProposeEmergencyWithdrawal>,
+        source: EmergencyVault,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::admin::propose_emergency_withdrawal(ctx, source, amount)
+    }
+
+    /// Executes a proposed emergency withdrawal once its timelock has elapsed.
+    pub fn execute_emergency_withdrawal(ctx: Context<ExecuteEmergencyWithdrawal>) -> Result<()> {
+        instructions::admin::execute_emergency_withdrawal(ctx)
+    }
+
+    /// Cancels a pending emergency withdrawal.
+    pub fn cancel_emergency_withdrawal(ctx: Context<CancelEmergencyWithdrawal>) -> Result<()> {
+        instructions::admin::cancel_emergency_withdrawal(ctx)
+    }
+
+    /// Sets the stakeholders `distribute_fees` splits collected fees between.
+    /// Splits must sum to 10000 basis points; an empty list disables
+    /// distribution.
+    pub fn set_fee_splits(ctx: Context<SetFeeSplits>, splits: Vec<PayoutSplit>) -> Result<()> {
+        instructions::admin::set_fee_splits(ctx, splits)
+    }
+
+    /// Fans the whole balance of `mint`'s fee vault out to the configured fee
+    /// splits. Callable by anyone; the recipients' token accounts for `mint`
+    /// are passed, in split order, as remaining accounts.
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeFees<'info>>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::distribute_fees(ctx, mint)
+    }
+}
@@ -0,0 +1,324 @@
+//! Seeds and address derivation for every program-derived account. The
+//! account contexts, signer seeds and off-chain clients all go through this
+//! module so the seeds cannot drift apart.
+
+use anchor_lang::prelude::Pubkey;
+
+pub const GLOBAL_STATE_SEED: &[u8] = b"global_state";
+pub const TRADE_SEED: &[u8] = b"trade";
+pub const PURCHASE_SEED: &[u8] = b"purchase";
+pub const BUYER_SEED: &[u8] = b"buyer";
+pub const SELLER_SEED: &[u8] = b"seller";
+pub const LOGISTICS_PROVIDER_SEED: &[u8] = b"logistics_provider";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const PRODUCT_ESCROW_SEED: &[u8] = b"product_escrow";
+pub const LOGISTICS_ESCROW_SEED: &[u8] = b"logistics_escrow";
+pub const LENDING_RECEIPT_SEED: &[u8] = b"lending_receipt";
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+pub const SELLER_BOND_SEED: &[u8] = b"seller_bond";
+pub const CLAIMABLE_SEED: &[u8] = b"claimable";
+pub const ORDER_INDEX_SEED: &[u8] = b"order_index";
+pub const SHIPMENT_SEED: &[u8] = b"shipment";
+pub const CATEGORY_INDEX_SEED: &[u8] = b"category_index";
+pub const TRADE_METADATA_SEED: &[u8] = b"trade_metadata";
+pub const INSURANCE_POOL_SEED: &[u8] = b"insurance_pool";
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+pub const EMERGENCY_WITHDRAWAL_SEED: &[u8] = b"emergency_withdrawal";
+pub const RECEIPT_CONFIG_SEED: &[u8] = b"receipt_config";
+pub const RECEIPT_AUTHORITY_SEED: &[u8] = b"receipt_authority";
+pub const LOYALTY_SEED: &[u8] = b"loyalty";
+pub const STOREFRONT_SEED: &[u8] = b"storefront";
+pub const RETURNS_RESERVE_SEED: &[u8] = b"returns_reserve";
+pub const ARBITRATOR_SEED: &[u8] = b"arbitrator";
+pub const JURY_POOL_SEED: &[u8] = b"jury_pool";
+pub const JURY_VAULT_SEED: &[u8] = b"jury_vault";
+pub const STATS_SEED: &[u8] = b"stats";
+pub const PROGRAM_VERSION_SEED: &[u8] = b"program_version";
+pub const RESERVATION_SEED: &[u8] = b"reservation";
+pub const AUCTION_SEED: &[u8] = b"auction";
+pub const SEALED_DROP_SEED: &[u8] = b"sealed_drop";
+pub const SEALED_COMMIT_SEED: &[u8] = b"sealed_commit";
+pub const OFFER_SEED: &[u8] = b"offer";
+pub const SUBSCRIPTION_SEED: &[u8] = b"subscription";
+pub const ALLOWED_MINT_SEED: &[u8] = b"allowed_mint";
+pub const BUYER_QUOTA_SEED: &[u8] = b"buyer_quota";
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+pub const RELAY_AUTHORITY_SEED: &[u8] = b"relay_authority";
+pub const RELAY_NONCE_SEED: &[u8] = b"relay_nonce";
+pub const SPENDING_PERMIT_SEED: &[u8] = b"spending_permit";
+pub const SESSION_SEED: &[u8] = b"session";
+pub const SHIPPING_INFO_SEED: &[u8] = b"shipping_info";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn global_state_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GLOBAL_STATE_SEED], &crate::ID)
+}
+
+pub fn stats_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED], &crate::ID)
+}
+
+/// Merkle tree and metadata URI used for purchase receipts.
+pub fn receipt_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_CONFIG_SEED], &crate::ID)
+}
+
+/// Tree delegate that signs receipt mints; the receipt tree must delegate to it.
+pub fn receipt_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_AUTHORITY_SEED], &crate::ID)
+}
+
+/// A seller's storefront, grouping the trades listed under it.
+pub fn storefront_pda(seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STOREFRONT_SEED, seller.as_ref()], &crate::ID)
+}
+
+/// A buyer's loyalty points and any redeemed fee discount.
+pub fn loyalty_pda(buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOYALTY_SEED, buyer.as_ref()], &crate::ID)
+}
+
+/// Version of the deployed program and the oldest client it supports.
+pub fn program_version_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_VERSION_SEED], &crate::ID)
+}
+
+pub fn trade_pda(trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TRADE_SEED, trade_id.to_le_bytes().as_ref()], &crate::ID)
+}
+
+pub fn trade_metadata_pda(trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TRADE_METADATA_SEED, trade_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Open auction on a trade's stock.
+pub fn auction_pda(trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUCTION_SEED, trade_id.to_le_bytes().as_ref()], &crate::ID)
+}
+
+/// Sealed-bid drop on a trade's stock.
+pub fn sealed_drop_pda(trade_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SEALED_DROP_SEED, trade_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Sealed commitment by `buyer` on a trade's drop.
+pub fn sealed_commit_pda(trade_id: u64, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SEALED_COMMIT_SEED, trade_id.to_le_bytes().as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Price offer made by `buyer` on a trade.
+pub fn offer_pda(trade_id: u64, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[OFFER_SEED, trade_id.to_le_bytes().as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Recurring purchase authorization by `buyer` on a trade.
+pub fn subscription_pda(trade_id: u64, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SUBSCRIPTION_SEED, trade_id.to_le_bytes().as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Units of a trade bought so far by `buyer`, for per-customer limits.
+pub fn buyer_quota_pda(trade_id: u64, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BUYER_QUOTA_SEED, trade_id.to_le_bytes().as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Checkout hold placed by `buyer` on a trade.
+pub fn reservation_pda(trade_id: u64, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RESERVATION_SEED, trade_id.to_le_bytes().as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn purchase_pda(purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PURCHASE_SEED, purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn buyer_pda(buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUYER_SEED, buyer.as_ref()], &crate::ID)
+}
+
+/// Credential issued by `verifier` to `subject`, checked by gated trades.
+pub fn attestation_pda(verifier: &Pubkey, subject: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ATTESTATION_SEED, verifier.as_ref(), subject.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn seller_pda(seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SELLER_SEED, seller.as_ref()], &crate::ID)
+}
+
+pub fn logistics_provider_pda(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOGISTICS_PROVIDER_SEED, provider.as_ref()], &crate::ID)
+}
+
+/// Escrow token account shared by every trade settled in `token_mint`.
+pub fn escrow_vault_pda(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED, token_mint.as_ref()], &crate::ID)
+}
+
+/// Sub-vault holding the product portion of a purchase's escrow. Owned by the
+/// escrow vault of the purchase mint.
+pub fn product_escrow_pda(purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PRODUCT_ESCROW_SEED, purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Sub-vault holding the logistics portion of a purchase's escrow. Owned by
+/// the escrow vault of the purchase mint.
+pub fn logistics_escrow_pda(purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOGISTICS_ESCROW_SEED, purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Token account holding the lending program's receipt tokens for a purchase's
+/// deposited product escrow. Owned by the escrow vault of the purchase mint.
+pub fn lending_receipt_pda(purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LENDING_RECEIPT_SEED, purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Buyer's encrypted shipping address for a purchase.
+pub fn shipping_info_pda(purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SHIPPING_INFO_SEED, purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Token account holding the escrow fees collected in `token_mint`.
+pub fn fee_vault_pda(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_VAULT_SEED, token_mint.as_ref()], &crate::ID)
+}
+
+/// Pending emergency withdrawal from the escrow vault of `token_mint`.
+pub fn emergency_withdrawal_pda(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[EMERGENCY_WITHDRAWAL_SEED, token_mint.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Token account holding insurance premiums collected in `token_mint`.
+pub fn insurance_pool_pda(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INSURANCE_POOL_SEED, token_mint.as_ref()], &crate::ID)
+}
+
+/// Seller-funded token account that pays for approved returns in `token_mint`.
+pub fn returns_reserve_pda(seller: &Pubkey, token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RETURNS_RESERVE_SEED, seller.as_ref(), token_mint.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Registry entry whitelisting `token_mint` for trades and payments.
+pub fn allowed_mint_pda(token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ALLOWED_MINT_SEED, token_mint.as_ref()], &crate::ID)
+}
+
+pub fn arbitrator_pda(arbitrator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ARBITRATOR_SEED, arbitrator.as_ref()], &crate::ID)
+}
+
+pub fn jury_pool_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[JURY_POOL_SEED], &crate::ID)
+}
+
+/// Token account holding every juror's stake.
+pub fn jury_vault_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[JURY_VAULT_SEED], &crate::ID)
+}
+
+pub fn stake_vault_pda(provider: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_VAULT_SEED, provider.as_ref()], &crate::ID)
+}
+
+pub fn seller_bond_pda(seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SELLER_BOND_SEED, seller.as_ref()], &crate::ID)
+}
+
+pub fn claimable_pda(recipient: &Pubkey, token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLAIMABLE_SEED, recipient.as_ref(), token_mint.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn order_index_pda(user: &Pubkey, shard_no: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ORDER_INDEX_SEED, user.as_ref(), shard_no.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn category_index_pda(category: u16, shard_no: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CATEGORY_INDEX_SEED, category.to_le_bytes().as_ref(), shard_no.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Shipments are keyed by the provider and the first purchase id in the batch.
+pub fn shipment_pda(provider: &Pubkey, first_purchase_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SHIPMENT_SEED, provider.as_ref(), first_purchase_id.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Delegate buyers approve on their token account so relayed orders can debit it.
+pub fn relay_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RELAY_AUTHORITY_SEED], &crate::ID)
+}
+
+pub fn relay_nonce_pda(buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RELAY_NONCE_SEED, buyer.as_ref()], &crate::ID)
+}
+
+pub fn spending_permit_pda(buyer: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SPENDING_PERMIT_SEED, buyer.as_ref(), delegate.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn session_pda(buyer: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SESSION_SEED, buyer.as_ref(), session_key.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Authority used by `emit_cpi!` when the program is built with `cpi-events`.
+pub fn event_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &crate::ID)
+}
@@ -0,0 +1,51 @@
+//! Settlement payout math shared by the program, its client, tests and
+//! indexers, so every consumer rounds identically.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+
+/// How a purchase's escrow is paid out on settlement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payouts {
+    pub seller_amount: u64,
+    pub logistics_amount: u64,
+    pub fee_amount: u64,
+    /// Parts of `fee_amount` taken from the product and logistics portions.
+    pub product_fee: u64,
+    pub logistics_fee: u64,
+}
+
+/// Escrow payout for `quantity` units at `product_cost` each plus a total
+/// `logistics_cost`. `fee_bps` is taken from both the product and logistics
+/// portions; truncation favours the escrow, and the three parts always sum to
+/// the escrowed product plus logistics cost.
+pub fn compute_payouts(
+    product_cost: u64,
+    logistics_cost: u64,
+    quantity: u64,
+    fee_bps: u64,
+) -> Result<Payouts> {
+    require!(fee_bps <= BASIS_POINTS, LogisticsError::MathOverflow);
+    let product_total = product_cost
+        .checked_mul(quantity)
+        .ok_or_else(|| error!(LogisticsError::MathOverflow))?;
+    // No escrow holds more than u64::MAX, which also bounds the fee sum below
+    product_total
+        .checked_add(logistics_cost)
+        .ok_or_else(|| error!(LogisticsError::MathOverflow))?;
+    let product_fee = ((product_total as u128 * fee_bps as u128) / BASIS_POINTS as u128) as u64;
+    let logistics_fee = ((logistics_cost as u128 * fee_bps as u128) / BASIS_POINTS as u128) as u64;
+    let fee_amount = product_fee
+        .checked_add(logistics_fee)
+        .ok_or_else(|| error!(LogisticsError::MathOverflow))?;
+
+    Ok(Payouts {
+        seller_amount: product_total - product_fee,
+        logistics_amount: logistics_cost - logistics_fee,
+        fee_amount,
+        product_fee,
+        logistics_fee,
+    })
+}
@@ -0,0 +1,2530 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::*;
+use crate::errors::LogisticsError;
+use crate::helpers::*;
+use crate::pda;
+use crate::settlement::{self, Payouts};
+
+// Account extensions
+//
+// `TradeAccount` and `PurchaseAccount` carry a TLV-encoded `extensions` region
+// (u16 type, u16 length, value) so new features can attach data without
+// changing the base layout. Accounts are created with an empty region and
+// grown with `grow_account` when an extension is written.
+
+#[repr(u16)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtensionType {
+    Uninitialized = 0,
+    ReturnRequest = 1,
+    DisputeRuling = 2,
+    PlatformReferral = 3,
+    OrderLimits = 4,
+    BuyerGate = 5,
+    Tracking = 6,
+    ProofOfDelivery = 7,
+    LendingPosition = 8,
+    DisabledProviders = 9,
+    ProviderAssignment = 10,
+    Receipt = 11,
+    FeeDiscount = 12,
+    LoyaltyAccrual = 13,
+    StorefrontListing = 14,
+    LowStockThreshold = 15,
+    PurchaseCooldown = 16,
+    PresaleAllowlist = 17,
+    SettlementStream = 18,
+    SellerStream = 19,
+    Milestones = 20,
+    MilestoneProgress = 21,
+    JuryCase = 22,
+    DisputeResolution = 23,
+}
+
+/// Returns the value stored for `extension_type`, if present.
+pub fn get_extension(extensions: &[u8], extension_type: ExtensionType) -> Option<&[u8]> {
+    let header_len = EXTENSION_HEADER_LEN;
+    let mut offset = 0;
+    while offset + header_len <= extensions.len() {
+        let ext_type = u16::from_le_bytes([extensions[offset], extensions[offset + 1]]);
+        let length = u16::from_le_bytes([extensions[offset + 2], extensions[offset + 3]]) as usize;
+        let start = offset + header_len;
+        let end = start.checked_add(length)?;
+        if end > extensions.len() {
+            return None;
+        }
+        if ext_type == extension_type as u16 {
+            return Some(&extensions[start..end]);
+        }
+        offset = end;
+    }
+    None
+}
+
+/// Writes `value` for `extension_type`, replacing any previous entry.
+pub fn set_extension(
+    extensions: &mut Vec<u8>,
+    extension_type: ExtensionType,
+    value: &[u8],
+) -> Result<()> {
+    require!(
+        extension_type != ExtensionType::Uninitialized && value.len() <= u16::MAX as usize,
+        LogisticsError::InvalidExtension
+    );
+    remove_extension(extensions, extension_type);
+    require!(
+        extensions.len() + EXTENSION_HEADER_LEN + value.len() <= MAX_EXTENSION_BYTES,
+        LogisticsError::ExtensionSpaceExhausted
+    );
+
+    extensions.extend_from_slice(&(extension_type as u16).to_le_bytes());
+    extensions.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    extensions.extend_from_slice(value);
+    Ok(())
+}
+
+/// Drops the entry for `extension_type`, returning whether one was present.
+pub fn remove_extension(extensions: &mut Vec<u8>, extension_type: ExtensionType) -> bool {
+    let header_len = EXTENSION_HEADER_LEN;
+    let mut offset = 0;
+    while offset + header_len <= extensions.len() {
+        let ext_type = u16::from_le_bytes([extensions[offset], extensions[offset + 1]]);
+        let length = u16::from_le_bytes([extensions[offset + 2], extensions[offset + 3]]) as usize;
+        let end = (offset + header_len + length).min(extensions.len());
+        if ext_type == extension_type as u16 {
+            extensions.drain(offset..end);
+            return true;
+        }
+        offset = end;
+    }
+    false
+}
+
+/// Reallocates a program account to `new_len` bytes, topping up rent from `payer`.
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if new_len <= account.data_len() {
+        return Ok(());
+    }
+
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let shortfall = required_lamports.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+// Account structures
+#[account]
+#[derive(InitSpace, Default)]
+pub struct GlobalState {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub admin: Pubkey,
+    pub trade_counter: u64,
+    pub purchase_counter: u64,
+    pub stake_mint: Pubkey,
+    pub provider_stake_amount: u64,
+    pub slash_bps: u16,
+    pub stake_cooldown_seconds: i64,
+    pub seller_loss_threshold: u32,
+    pub seller_bond_slash_bps: u16,
+    pub seller_probation_seconds: i64,
+    pub swap_program: Pubkey,
+    pub cancellation_window_seconds: i64,
+    pub restocking_fee_bps: u16,
+    pub escalation_window_seconds: i64,
+    pub escalation_fee_bps: u16,
+    pub auto_confirm_seconds: i64,
+    pub ship_timeout_seconds: i64,
+    pub crank_reward_bps: u16,
+    #[max_len(MAX_PLATFORMS)]
+    pub platforms: Vec<PlatformFeeShare>,
+    /// Smallest purchase total accepted by `buy_trade`; 0 disables the check.
+    pub min_order_amount: u64,
+    /// Largest purchase total accepted by `buy_trade`; 0 disables the check.
+    pub max_order_amount: u64,
+    /// Verifier whose attestations certify buyer regions; the default key
+    /// accepts buyers' claimed regions unverified.
+    pub region_registrar: Pubkey,
+    /// Lending program escrow may be deposited into while a purchase is
+    /// pending; the default key disables deposits.
+    pub lending_program: Pubkey,
+    /// Share of lending yield paid to the buyer; the rest goes to the platform.
+    pub yield_buyer_share_bps: u16,
+    pub fee_split_config: FeeSplitConfig,
+    /// Governance authority, such as an SPL Governance realm's governance PDA,
+    /// that takes over fee, risk and arbitrator management from the admin; the
+    /// default key leaves them with the admin.
+    pub governance_authority: Pubkey,
+    pub loyalty_config: LoyaltyConfig,
+    /// Sequence number of the last emitted event.
+    pub event_seq: u64,
+    /// Escrow fee rate new purchases lock in, in basis points.
+    pub fee_bps: u16,
+    /// Halts new purchases and payouts to sellers; refunds, cancellations and
+    /// disputes stay open so buyers can still recover escrow.
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl GlobalState {
+    /// Stamps the next event. Every event carries a sequence number one above
+    /// the previous, so off-chain consumers can detect and backfill gaps.
+    pub fn next_event_seq(&mut self) -> u64 {
+        self.event_seq += 1;
+        self.event_seq
+    }
+
+    /// Fails while governance has paused purchases and seller payouts.
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.paused, LogisticsError::ProgramPaused);
+        Ok(())
+    }
+
+    /// Signer required for governed parameter changes.
+    pub fn governing_authority(&self) -> Pubkey {
+        if self.governance_authority == Pubkey::default() {
+            self.admin
+        } else {
+            self.governance_authority
+        }
+    }
+
+    /// Share of the escrow fee owed to `platform`, if it is registered.
+    pub fn platform_fee_share(&self, platform: &Pubkey) -> Option<u16> {
+        self.platforms
+            .iter()
+            .find(|entry| entry.platform == *platform)
+            .map(|entry| entry.fee_share_bps)
+    }
+
+    /// Registers, updates or, with `fee_share_bps == 0`, removes a platform.
+    pub fn set_platform_fee_share(&mut self, platform: Pubkey, fee_share_bps: u16) -> Result<()> {
+        let position = self
+            .platforms
+            .iter()
+            .position(|entry| entry.platform == platform);
+        match (position, fee_share_bps) {
+            (Some(i), 0) => {
+                self.platforms.remove(i);
+            }
+            (Some(i), _) => self.platforms[i].fee_share_bps = fee_share_bps,
+            (None, 0) => {}
+            (None, _) => {
+                require!(
+                    self.platforms.len() < MAX_PLATFORMS,
+                    LogisticsError::InvalidPlatformFeeShare
+                );
+                self.platforms.push(PlatformFeeShare {
+                    platform,
+                    fee_share_bps,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Storefront allowed to route orders through `buy_trade`, earning
+/// `fee_share_bps` of the escrow fee on the purchases it refers.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformFeeShare {
+    pub platform: Pubkey,
+    pub fee_share_bps: u16,
+}
+
+/// Emission parameters for buyer loyalty points. Points accrue on settled
+/// product volume and are redeemed for a discount on the escrow fee of the
+/// buyer's next purchase.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct LoyaltyConfig {
+    /// Points credited per token unit of settled volume, in basis points;
+    /// 0 disables accrual.
+    pub points_bps: u16,
+    /// Points redeemed for each basis point of escrow fee waived.
+    pub points_per_discount_bps: u64,
+    /// Largest share of the escrow fee a buyer can have waived, in basis points.
+    pub max_discount_bps: u16,
+}
+
+impl LoyaltyConfig {
+    pub fn is_valid(&self) -> bool {
+        self.max_discount_bps as u64 <= BASIS_POINTS
+            && (self.max_discount_bps == 0 || self.points_per_discount_bps > 0)
+    }
+}
+
+/// Stakeholders the escrow fees in each mint's fee vault are fanned out to by
+/// `distribute_fees`, such as an operations wallet, a DAO treasury and an
+/// insurance pool. With no splits the fees stay for `withdraw_escrow_fees`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplitConfig {
+    #[max_len(MAX_FEE_SPLITS)]
+    pub splits: Vec<PayoutSplit>,
+}
+
+impl FeeSplitConfig {
+    /// Splits must be non-zero and sum to the whole fee, like trade payout
+    /// splits; an empty config is valid and disables distribution.
+    pub fn is_valid(&self) -> bool {
+        if self.splits.len() > MAX_FEE_SPLITS {
+            return false;
+        }
+        if self.splits.is_empty() {
+            return true;
+        }
+        let total_bps: u64 = self.splits.iter().map(|split| split.bps as u64).sum();
+        total_bps == BASIS_POINTS && self.splits.iter().all(|split| split.bps > 0)
+    }
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct TradeAccount {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    #[max_len(MAX_LOGISTICS_PROVIDERS)]
+    pub logistics_providers: Vec<Pubkey>,
+    #[max_len(MAX_LOGISTICS_PROVIDERS)]
+    pub logistics_costs: Vec<u64>,
+    pub product_cost: u64,
+    pub escrow_fee: u64,
+    pub total_quantity: u64,
+    pub remaining_quantity: u64,
+    pub active: bool,
+    #[max_len(MAX_PURCHASE_IDS)]
+    pub purchase_ids: Vec<u64>,
+    pub token_mint: Pubkey,
+    #[max_len(MAX_PAYOUT_SPLITS)]
+    pub payout_splits: Vec<PayoutSplit>,
+    pub weight_grams: u64,
+    #[max_len(MAX_LOGISTICS_PROVIDERS)]
+    pub logistics_rates_per_kg: Vec<u64>,
+    pub category: u16,
+    /// Hashes of normalized search tags, e.g. sha256 of the lowercase tag text.
+    #[max_len(MAX_TRADE_TAGS)]
+    pub tag_hashes: Vec<[u8; 32]>,
+    /// Pyth price account for the mint. When set, `product_cost` and the
+    /// logistics costs are USD cents converted at purchase time.
+    pub price_feed: Option<Pubkey>,
+    /// Additional mints the listing can be paid in, each with its own prices.
+    #[max_len(MAX_ACCEPTED_MINTS)]
+    pub accepted_mints: Vec<AcceptedMint>,
+    /// Premium buyers pay for transit insurance, in basis points of the product cost.
+    pub insurance_bps: u16,
+    /// Most units a single buyer may purchase over the life of the listing.
+    pub max_per_buyer: Option<u64>,
+    /// `DeliveryRegion` bitmask of where the seller ships; 0 ships everywhere.
+    pub ship_to_regions: u16,
+    /// Settlement waits for the logistics provider's proof of delivery.
+    pub requires_proof_of_delivery: bool,
+    /// Who has to sign off before escrow is released.
+    pub confirmation_mode: ConfirmationMode,
+    /// Made to order: sales never draw down `remaining_quantity`, so the
+    /// listing does not sell out.
+    pub unlimited: bool,
+    /// Units sold over the life of the listing, net of refunds.
+    pub sold_quantity: u64,
+    /// Pre-order: purchases escrow right away, but shipping and delivery
+    /// timers only start at `fulfillment_start`.
+    pub preorder: bool,
+    pub fulfillment_start: i64,
+    /// Allocated empty; `grow_account` makes room as extensions are set.
+    #[max_len(0)]
+    pub extensions: Vec<u8>,
+    pub bump: u8,
+}
+
+impl TradeAccount {
+    /// When purchases made now start their timers; 0 unless the listing is
+    /// a pre-order.
+    pub fn preorder_start(&self) -> i64 {
+        if self.preorder {
+            self.fulfillment_start
+        } else {
+            0
+        }
+    }
+
+    /// Whether `quantity` more units can be taken off the listing.
+    pub fn has_stock(&self, quantity: u64) -> bool {
+        self.unlimited || self.remaining_quantity >= quantity
+    }
+
+    /// Fails with `InsufficientQuantity` unless `quantity` more units can be
+    /// taken off the listing.
+    pub fn check_stock(&self, quantity: u64) -> Result<()> {
+        if !self.unlimited {
+            require_gte!(
+                self.remaining_quantity,
+                quantity,
+                LogisticsError::InsufficientQuantity
+            );
+        }
+        Ok(())
+    }
+
+    /// Takes `quantity` units out of stock, deactivating the listing once it
+    /// sells out. Made-to-order listings keep no stock.
+    pub fn take_stock(&mut self, quantity: u64) -> Result<()> {
+        self.check_stock(quantity)?;
+        if self.unlimited {
+            return Ok(());
+        }
+        self.remaining_quantity -= quantity;
+        if self.remaining_quantity == 0 {
+            self.active = false;
+        }
+        Ok(())
+    }
+
+    /// Puts `quantity` units back on sale, reopening a sold-out listing.
+    pub fn return_stock(&mut self, quantity: u64) {
+        if self.unlimited {
+            return;
+        }
+        self.remaining_quantity += quantity;
+        if !self.active && self.remaining_quantity > 0 {
+            self.active = true;
+        }
+    }
+
+    /// Takes a sale of `quantity` units out of stock and counts it as sold.
+    pub fn record_sale(&mut self, quantity: u64) -> Result<()> {
+        self.take_stock(quantity)?;
+        self.sold_quantity = self
+            .sold_quantity
+            .checked_add(quantity)
+            .ok_or(LogisticsError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Reverses a sale of `quantity` units that was refunded or cancelled.
+    pub fn record_refund(&mut self, quantity: u64) {
+        self.return_stock(quantity);
+        self.sold_quantity = self.sold_quantity.saturating_sub(quantity);
+    }
+
+    /// Whether the seller ships to `region`.
+    pub fn ships_to(&self, region: DeliveryRegion) -> bool {
+        self.ship_to_regions == 0 || self.ship_to_regions & region.mask() != 0
+    }
+
+    /// Logistics cost of shipping `quantity` units with `provider`, in the
+    /// primary mint.
+    pub fn primary_logistics_cost(&self, provider: &Pubkey, quantity: u64) -> Result<u64> {
+        let index = self
+            .logistics_providers
+            .iter()
+            .position(|candidate| candidate == provider)
+            .ok_or(LogisticsError::InvalidLogisticsProvider)?;
+        require!(
+            self.provider_enabled(index),
+            LogisticsError::ProviderDisabled
+        );
+        let rate_per_kg = if self.weight_grams > 0 {
+            self.logistics_rates_per_kg[index]
+        } else {
+            0
+        };
+        compute_logistics_cost(
+            self.logistics_costs[index],
+            rate_per_kg,
+            self.weight_grams,
+            quantity,
+        )
+    }
+
+    /// Product and logistics cost of `quantity` units at the listing's
+    /// primary-mint price.
+    pub fn fixed_price_amount(&self, provider: &Pubkey, quantity: u64) -> Result<u64> {
+        let logistics_cost = self.primary_logistics_cost(provider, quantity)?;
+        self.product_cost
+            .checked_mul(quantity)
+            .and_then(|product| product.checked_add(logistics_cost))
+            .ok_or(error!(LogisticsError::MathOverflow))
+    }
+
+    /// Position `index` in `logistics_providers` if it holds `provider`. Buyers
+    /// name the position they were quoted, which also settles which price
+    /// applies when a provider is listed twice.
+    pub fn provider_at(&self, index: u8, provider: &Pubkey) -> Option<usize> {
+        let index = index as usize;
+        (self.logistics_providers.get(index) == Some(provider)).then_some(index)
+    }
+
+    /// Product cost and per-provider logistics costs when paying in `mint`.
+    /// Per-kg rates are quoted in the primary mint, so weight-priced listings
+    /// accept no other mint.
+    pub fn prices_for_mint(&self, mint: &Pubkey) -> Option<(u64, &[u64])> {
+        if *mint == self.token_mint {
+            return Some((self.product_cost, &self.logistics_costs));
+        }
+        if self.weight_grams > 0 {
+            return None;
+        }
+        self.accepted_mints
+            .iter()
+            .find(|accepted| accepted.mint == *mint)
+            .map(|accepted| (accepted.product_cost, accepted.logistics_costs.as_slice()))
+    }
+
+    pub fn order_limits(&self) -> Option<OrderLimits> {
+        get_extension(&self.extensions, ExtensionType::OrderLimits)
+            .and_then(|value| OrderLimits::try_from_slice(value).ok())
+    }
+
+    pub fn set_order_limits(&mut self, limits: &OrderLimits) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::OrderLimits,
+            &limits.try_to_vec()?,
+        )
+    }
+
+    /// Slots a buyer must wait between purchases from this trade.
+    pub fn purchase_cooldown_slots(&self) -> Option<u64> {
+        get_extension(&self.extensions, ExtensionType::PurchaseCooldown)
+            .and_then(|value| u64::try_from_slice(value).ok())
+    }
+
+    pub fn set_purchase_cooldown_slots(&mut self, cooldown_slots: u64) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::PurchaseCooldown,
+            &cooldown_slots.try_to_vec()?,
+        )
+    }
+
+    pub fn presale_allowlist(&self) -> Option<PresaleAllowlist> {
+        get_extension(&self.extensions, ExtensionType::PresaleAllowlist)
+            .and_then(|value| PresaleAllowlist::try_from_slice(value).ok())
+    }
+
+    pub fn set_presale_allowlist(&mut self, presale: &PresaleAllowlist) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::PresaleAllowlist,
+            &presale.try_to_vec()?,
+        )
+    }
+
+    /// Seconds over which the seller's share of a confirmed purchase is
+    /// released, for service listings.
+    pub fn settlement_stream_seconds(&self) -> Option<i64> {
+        get_extension(&self.extensions, ExtensionType::SettlementStream)
+            .and_then(|value| i64::try_from_slice(value).ok())
+    }
+
+    pub fn set_settlement_stream_seconds(&mut self, duration_seconds: i64) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::SettlementStream,
+            &duration_seconds.try_to_vec()?,
+        )
+    }
+
+    /// Production stages a purchase's product escrow is released over.
+    pub fn milestones(&self) -> Vec<Milestone> {
+        get_extension(&self.extensions, ExtensionType::Milestones)
+            .and_then(|value| Vec::<Milestone>::try_from_slice(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_milestones(&mut self, milestones: &[Milestone]) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::Milestones,
+            &milestones.to_vec().try_to_vec()?,
+        )
+    }
+
+    /// Remaining quantity at or below which `buy_trade` emits `LowStock`.
+    pub fn low_stock_threshold(&self) -> Option<u64> {
+        get_extension(&self.extensions, ExtensionType::LowStockThreshold)
+            .and_then(|value| u64::try_from_slice(value).ok())
+    }
+
+    pub fn set_low_stock_threshold(&mut self, threshold: u64) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::LowStockThreshold,
+            &threshold.try_to_vec()?,
+        )
+    }
+
+    /// Whether the remaining stock has fallen to the seller's threshold.
+    pub fn is_low_stock(&self) -> bool {
+        !self.unlimited
+            && self
+                .low_stock_threshold()
+                .is_some_and(|threshold| self.remaining_quantity <= threshold)
+    }
+
+    /// Whether the trade is listed under its seller's storefront, and if so
+    /// whether the storefront currently counts it as active.
+    pub fn storefront_listing(&self) -> Option<bool> {
+        get_extension(&self.extensions, ExtensionType::StorefrontListing)
+            .and_then(|value| bool::try_from_slice(value).ok())
+    }
+
+    pub fn set_storefront_listing(&mut self, counted_active: bool) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::StorefrontListing,
+            &counted_active.try_to_vec()?,
+        )
+    }
+
+    pub fn buyer_gate(&self) -> Option<BuyerGate> {
+        get_extension(&self.extensions, ExtensionType::BuyerGate)
+            .and_then(|value| BuyerGate::try_from_slice(value).ok())
+    }
+
+    pub fn set_buyer_gate(&mut self, gate: &BuyerGate) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::BuyerGate,
+            &gate.try_to_vec()?,
+        )
+    }
+
+    /// Bitmask, by position in `logistics_providers`, of providers the seller
+    /// has paused.
+    pub fn disabled_providers(&self) -> u16 {
+        get_extension(&self.extensions, ExtensionType::DisabledProviders)
+            .and_then(|value| u16::try_from_slice(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn provider_enabled(&self, index: usize) -> bool {
+        self.disabled_providers() & (1 << index) == 0
+    }
+
+    /// Pauses or resumes the provider at `index`, dropping the extension once
+    /// every provider is enabled again.
+    pub fn set_provider_enabled(&mut self, index: usize, enabled: bool) -> Result<()> {
+        let mut disabled = self.disabled_providers();
+        if enabled {
+            disabled &= !(1 << index);
+        } else {
+            disabled |= 1 << index;
+        }
+        if disabled == 0 {
+            remove_extension(&mut self.extensions, ExtensionType::DisabledProviders);
+            return Ok(());
+        }
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::DisabledProviders,
+            &disabled.to_le_bytes(),
+        )
+    }
+
+    /// Limits enforced on purchases of this trade: its own override if set,
+    /// otherwise the platform-wide limits.
+    pub fn effective_order_limits(&self, global_state: &GlobalState) -> OrderLimits {
+        self.order_limits().unwrap_or(OrderLimits {
+            min_order_amount: global_state.min_order_amount,
+            max_order_amount: global_state.max_order_amount,
+        })
+    }
+}
+
+/// Credential a buyer must present to purchase from a gated trade, stored in
+/// the trade's `ExtensionType::BuyerGate` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuyerGate {
+    /// Buyer must hold at least one token of `mint`, e.g. a KYC NFT or
+    /// soulbound token.
+    TokenHolder { mint: Pubkey },
+    /// Buyer must hold an unexpired attestation issued by `verifier`.
+    Attestation { verifier: Pubkey },
+}
+
+/// Bounds on a purchase's total amount, in base units of the payment mint. A
+/// zero `max_order_amount` means no upper bound. Per-trade overrides are
+/// stored in the trade's `ExtensionType::OrderLimits` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderLimits {
+    pub min_order_amount: u64,
+    pub max_order_amount: u64,
+}
+
+impl OrderLimits {
+    pub fn is_valid(&self) -> bool {
+        self.max_order_amount == 0 || self.min_order_amount <= self.max_order_amount
+    }
+
+    /// Rejects dust orders below the minimum and orders above the maximum. The
+    /// minimum is a floor, so its error logs the order amount as `Left`.
+    pub fn check(&self, amount: u64) -> Result<()> {
+        require_gte!(
+            amount,
+            self.min_order_amount,
+            LogisticsError::OrderBelowMinimum
+        );
+        if self.max_order_amount > 0 {
+            require_gte!(
+                self.max_order_amount,
+                amount,
+                LogisticsError::OrderAboveMaximum
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Pre-sale phase of a trade, stored in its `ExtensionType::PresaleAllowlist`
+/// extension. Until `public_sale_start` only buyers in the Merkle tree with
+/// root `merkle_root` may purchase, through `buy_trade_allowlisted`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresaleAllowlist {
+    pub merkle_root: [u8; 32],
+    pub public_sale_start: i64,
+}
+
+impl PresaleAllowlist {
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.public_sale_start
+    }
+
+    /// Whether `proof` places `buyer` in the allowlist.
+    pub fn includes(&self, buyer: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        let root = proof.iter().fold(allowlist_leaf(buyer), |node, sibling| {
+            hash_allowlist_pair(&node, sibling)
+        });
+        root == self.merkle_root
+    }
+}
+
+/// Swap instruction data quoted off-chain for the configured AMM, plus the
+/// output amount the quote promised.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapRoute {
+    pub data: Vec<u8>,
+    pub quoted_out_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    pub product_cost: u64,
+    #[max_len(MAX_LOGISTICS_PROVIDERS)]
+    pub logistics_costs: Vec<u64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlannedTransfer {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Listing state returned by `get_trade_summary`, with the per-unit payout
+/// already net of the escrow fee.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TradeSummary {
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub active: bool,
+    pub total_quantity: u64,
+    pub remaining_quantity: u64,
+    /// Made to order; `remaining_quantity` is not drawn down.
+    pub unlimited: bool,
+    pub sold_quantity: u64,
+    /// Pre-order fulfillment date; 0 when not a pre-order.
+    pub fulfillment_start: i64,
+    /// Unit price; in USD cents when `priced_in_usd`.
+    pub product_cost: u64,
+    pub priced_in_usd: bool,
+    pub escrow_fee_bps: u64,
+    pub seller_amount_per_unit: u64,
+    pub fee_per_unit: u64,
+}
+
+/// Where a purchase is in its lifecycle.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PurchaseStage {
+    Pending,
+    Shipped,
+    Disputed,
+    Settled,
+    Cancelled,
+}
+
+/// Purchase state returned by `get_purchase_status`. The amounts are what
+/// settling the open remainder would pay out, after any fee discount.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PurchaseStatus {
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub token_mint: Pubkey,
+    pub stage: PurchaseStage,
+    pub quantity: u64,
+    pub delivered_quantity: u64,
+    pub total_amount: u64,
+    pub escrow_fee_bps: u64,
+    pub seller_amount: u64,
+    pub logistics_amount: u64,
+    pub fee_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionPreview {
+    pub purchase_id: u64,
+    pub winner: Pubkey,
+    pub token_mint: Pubkey,
+    pub transfers: Vec<PlannedTransfer>,
+    pub fee_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PayoutSplit {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+/// Production stage of a made-to-order trade. Confirming it releases `bps`
+/// of the seller's share of the product escrow.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Milestone {
+    pub bps: u16,
+    pub description_hash: [u8; 32],
+}
+
+/// Milestones the buyer has confirmed on a purchase, stored in the purchase's
+/// `ExtensionType::MilestoneProgress` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MilestoneProgress {
+    /// Bit `i` is set once milestone `i` is confirmed.
+    pub confirmed: u8,
+    pub seller_released: u64,
+}
+
+impl MilestoneProgress {
+    pub fn is_confirmed(&self, index: u8) -> bool {
+        self.confirmed & (1 << index) != 0
+    }
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct PurchaseAccount {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub total_amount: u64,
+    pub delivered_and_confirmed: bool,
+    pub disputed: bool,
+    pub chosen_logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+    pub settled: bool,
+    pub created_at: i64,
+    pub settled_at: i64,
+    pub payer: Pubkey,
+    pub consolidated: bool,
+    /// Refunded through `cancel_purchase` rather than delivered.
+    pub cancelled: bool,
+    /// Units already settled through `confirm_partial_delivery`. `quantity`,
+    /// `logistics_cost` and `total_amount` cover only the open remainder.
+    pub delivered_quantity: u64,
+    /// When the goods were handed to logistics, zero until `mark_shipped`.
+    pub shipped_at: i64,
+    /// Mint the purchase was paid in; settlement pays out in the same mint.
+    pub token_mint: Pubkey,
+    /// Token units charged per item, after any oracle conversion.
+    pub unit_price: u64,
+    /// Oracle price used for the conversion, zero for token-priced trades.
+    pub fill_price: i64,
+    pub fill_expo: i32,
+    pub insurance_premium: u64,
+    pub insurance_status: InsuranceStatus,
+    pub dispute_reason: DisputeReason,
+    pub cancel_reason: CancelReason,
+    /// `ConfirmingParty` bitmask of who has signed off on delivery.
+    pub confirmations: u8,
+    /// Escrow is held in the purchase's product and logistics sub-vaults.
+    /// Purchases opened before the split keep it in the mint's escrow vault.
+    pub split_escrow: bool,
+    /// Held while a settlement path moves this purchase's escrow; see
+    /// `lock_settlement`.
+    pub settlement_locked: bool,
+    /// Escrow fee rate locked in at purchase, in basis points, before any
+    /// loyalty discount.
+    pub fee_bps: u16,
+    /// Hash of the buyer's encrypted off-chain order notes, such as a
+    /// delivery address.
+    pub memo_hash: Option<[u8; 64]>,
+    /// Pre-order fulfillment date; 0 for regular purchases.
+    pub fulfillment_start: i64,
+    /// Allocated empty; `grow_account` makes room as extensions are set.
+    #[max_len(0)]
+    pub extensions: Vec<u8>,
+    pub bump: u8,
+}
+
+/// Commercial terms of a new purchase, shared by every path that opens one.
+pub struct PurchaseTerms {
+    pub purchase_id: u64,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub payer: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    pub unit_price: u64,
+    pub logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+    pub fee_bps: u16,
+    pub memo_hash: Option<[u8; 64]>,
+    pub fill_price: i64,
+    pub fill_expo: i32,
+    pub insurance_premium: u64,
+    pub insured: bool,
+    pub created_at: i64,
+    pub fulfillment_start: i64,
+    pub bump: u8,
+}
+
+impl PurchaseAccount {
+    /// When the ship timeout and cancellation window start counting: the
+    /// purchase time, or the fulfillment date of a pre-order.
+    pub fn timers_start(&self) -> i64 {
+        self.created_at.max(self.fulfillment_start)
+    }
+
+    /// When the auto-confirm window starts counting. A pre-order shipped
+    /// early still waits for its fulfillment date.
+    pub fn auto_confirm_start(&self) -> i64 {
+        self.shipped_at.max(self.fulfillment_start)
+    }
+
+    /// Initializes a freshly created purchase account from `terms`.
+    pub fn open(&mut self, terms: PurchaseTerms) -> Result<()> {
+        self.purchase_id = terms.purchase_id;
+        self.trade_id = terms.trade_id;
+        self.buyer = terms.buyer;
+        self.quantity = terms.quantity;
+        self.total_amount = terms
+            .unit_price
+            .checked_mul(terms.quantity)
+            .and_then(|product| product.checked_add(terms.logistics_cost))
+            .ok_or(LogisticsError::MathOverflow)?;
+        self.delivered_and_confirmed = false;
+        self.disputed = false;
+        self.chosen_logistics_provider = terms.logistics_provider;
+        self.logistics_cost = terms.logistics_cost;
+        self.settled = false;
+        self.token_mint = terms.token_mint;
+        self.unit_price = terms.unit_price;
+        self.fill_price = terms.fill_price;
+        self.fill_expo = terms.fill_expo;
+        self.created_at = terms.created_at;
+        self.settled_at = 0;
+        self.payer = terms.payer;
+        self.consolidated = false;
+        self.cancelled = false;
+        self.delivered_quantity = 0;
+        self.shipped_at = 0;
+        self.insurance_premium = terms.insurance_premium;
+        self.insurance_status = if terms.insured {
+            InsuranceStatus::Active
+        } else {
+            InsuranceStatus::None
+        };
+        self.dispute_reason = DisputeReason::None;
+        self.cancel_reason = CancelReason::None;
+        self.confirmations = 0;
+        self.split_escrow = true;
+        self.settlement_locked = false;
+        self.fee_bps = terms.fee_bps;
+        self.memo_hash = terms.memo_hash;
+        self.fulfillment_start = terms.fulfillment_start;
+        self.extensions = Vec::new();
+        self.version = ACCOUNT_VERSION;
+        self.bump = terms.bump;
+        Ok(())
+    }
+
+    /// Marks the purchase finally settled at `now`.
+    pub fn mark_settled(&mut self, now: i64) {
+        self.settled = true;
+        self.settled_at = now;
+    }
+
+    pub fn stage(&self) -> PurchaseStage {
+        if self.cancelled {
+            PurchaseStage::Cancelled
+        } else if self.settled {
+            PurchaseStage::Settled
+        } else if self.disputed {
+            PurchaseStage::Disputed
+        } else if self.shipped_at > 0 {
+            PurchaseStage::Shipped
+        } else {
+            PurchaseStage::Pending
+        }
+    }
+
+    /// Balance the purchase's product and logistics sub-vaults should hold
+    /// between them, or `None` when the escrow is not auditable: settled,
+    /// cancelled, mid-settlement, lent out, or held in the shared vault.
+    pub fn expected_escrow(&self) -> Option<u64> {
+        let auditable = self.split_escrow
+            && !self.settled
+            && !self.cancelled
+            && !self.settlement_locked
+            && self.lending_position().is_none();
+        auditable.then_some(self.total_amount)
+    }
+
+    /// Claims the purchase for one settlement path. Paths make all their
+    /// state changes first and take the lock before moving any escrow, so a
+    /// program the path invokes, such as a swap route or a transfer hook,
+    /// cannot re-enter another path and pay out the same escrow twice. The
+    /// lock only lives inside one transaction: if a later transfer fails, the
+    /// runtime rolls back the lock with everything else.
+    pub fn lock_settlement(&mut self) -> Result<()> {
+        require!(
+            !self.settlement_locked,
+            LogisticsError::SettlementInProgress
+        );
+        self.settlement_locked = true;
+        Ok(())
+    }
+
+    /// Releases the lock once the path's last transfer has gone through.
+    pub fn unlock_settlement(&mut self) {
+        self.settlement_locked = false;
+    }
+
+    /// Records `party`'s sign-off, returning false if it had already signed.
+    pub fn record_confirmation(&mut self, party: ConfirmingParty) -> bool {
+        let already_signed = self.confirmations & party.mask() != 0;
+        self.confirmations |= party.mask();
+        !already_signed
+    }
+
+    pub fn confirmation_count(&self) -> u32 {
+        self.confirmations.count_ones()
+    }
+
+    /// Whether enough parties have signed off for escrow to be released.
+    pub fn has_confirmation_quorum(&self, mode: ConfirmationMode) -> bool {
+        match mode {
+            ConfirmationMode::BuyerConfirms => true,
+            ConfirmationMode::TwoOfThree => self.confirmation_count() >= 2,
+        }
+    }
+
+    pub fn return_request(&self) -> Option<ReturnRequest> {
+        get_extension(&self.extensions, ExtensionType::ReturnRequest)
+            .and_then(|value| ReturnRequest::try_from_slice(value).ok())
+    }
+
+    pub fn dispute_ruling(&self) -> Option<DisputeRuling> {
+        get_extension(&self.extensions, ExtensionType::DisputeRuling)
+            .and_then(|value| DisputeRuling::try_from_slice(value).ok())
+    }
+
+    pub fn set_dispute_ruling(&mut self, ruling: &DisputeRuling) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::DisputeRuling,
+            &ruling.try_to_vec()?,
+        )
+    }
+
+    /// Outcome recorded by `resolve_dispute` and its appeal.
+    pub fn dispute_resolution(&self) -> Option<DisputeResolution> {
+        get_extension(&self.extensions, ExtensionType::DisputeResolution)
+            .and_then(|value| DisputeResolution::try_from_slice(value).ok())
+    }
+
+    pub fn set_dispute_resolution(&mut self, resolution: &DisputeResolution) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::DisputeResolution,
+            &resolution.try_to_vec()?,
+        )
+    }
+
+    /// Jury drawn for this dispute with `summon_jury`, until it rules or
+    /// deadlocks.
+    pub fn jury_case(&self) -> Option<JuryCase> {
+        get_extension(&self.extensions, ExtensionType::JuryCase)
+            .and_then(|value| JuryCase::try_from_slice(value).ok())
+    }
+
+    pub fn set_jury_case(&mut self, case: &JuryCase) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::JuryCase,
+            &case.try_to_vec()?,
+        )
+    }
+
+    /// Why an arbitrator may not rule `winner` on this purchase, if anything.
+    /// `seller` is the seller of the purchase's trade.
+    pub fn ruling_error(
+        &self,
+        seller: &Pubkey,
+        winner: &Pubkey,
+        slash_provider: bool,
+    ) -> Option<LogisticsError> {
+        if !self.disputed {
+            return Some(LogisticsError::NotDisputed);
+        }
+        if self.settled {
+            return Some(LogisticsError::AlreadySettled);
+        }
+        if self.dispute_ruling().is_some() {
+            return Some(LogisticsError::AlreadyRuled);
+        }
+        if self.jury_case().is_some() {
+            return Some(LogisticsError::JuryActive);
+        }
+        let valid_winner =
+            *winner == self.buyer || winner == seller || *winner == self.chosen_logistics_provider;
+        if !valid_winner || (slash_provider && *winner == self.chosen_logistics_provider) {
+            return Some(LogisticsError::InvalidWinner);
+        }
+        None
+    }
+
+    pub fn set_return_request(&mut self, request: &ReturnRequest) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::ReturnRequest,
+            &request.try_to_vec()?,
+        )
+    }
+
+    pub fn platform_referral(&self) -> Option<PlatformReferral> {
+        get_extension(&self.extensions, ExtensionType::PlatformReferral)
+            .and_then(|value| PlatformReferral::try_from_slice(value).ok())
+    }
+
+    pub fn set_platform_referral(&mut self, referral: &PlatformReferral) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::PlatformReferral,
+            &referral.try_to_vec()?,
+        )
+    }
+
+    pub fn tracking(&self) -> Option<TrackingInfo> {
+        get_extension(&self.extensions, ExtensionType::Tracking)
+            .and_then(|value| TrackingInfo::try_from_slice(value).ok())
+    }
+
+    pub fn set_tracking(&mut self, tracking: &TrackingInfo) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::Tracking,
+            &tracking.try_to_vec()?,
+        )
+    }
+
+    pub fn proof_of_delivery(&self) -> Option<ProofOfDelivery> {
+        get_extension(&self.extensions, ExtensionType::ProofOfDelivery)
+            .and_then(|value| ProofOfDelivery::try_from_slice(value).ok())
+    }
+
+    pub fn set_proof_of_delivery(&mut self, pod: &ProofOfDelivery) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::ProofOfDelivery,
+            &pod.try_to_vec()?,
+        )
+    }
+
+    pub fn provider_assignment(&self) -> Option<ProviderAssignment> {
+        get_extension(&self.extensions, ExtensionType::ProviderAssignment)
+            .and_then(|value| ProviderAssignment::try_from_slice(value).ok())
+    }
+
+    pub fn set_provider_assignment(&mut self, assignment: &ProviderAssignment) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::ProviderAssignment,
+            &assignment.try_to_vec()?,
+        )
+    }
+
+    pub fn clear_provider_assignment(&mut self) -> bool {
+        remove_extension(&mut self.extensions, ExtensionType::ProviderAssignment)
+    }
+
+    /// Whether the chosen logistics provider turned the order down.
+    pub fn provider_declined(&self) -> bool {
+        self.provider_assignment()
+            .is_some_and(|assignment| !assignment.accepted)
+    }
+
+    pub fn lending_position(&self) -> Option<LendingPosition> {
+        get_extension(&self.extensions, ExtensionType::LendingPosition)
+            .and_then(|value| LendingPosition::try_from_slice(value).ok())
+    }
+
+    pub fn set_lending_position(&mut self, position: &LendingPosition) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::LendingPosition,
+            &position.try_to_vec()?,
+        )
+    }
+
+    pub fn clear_lending_position(&mut self) -> bool {
+        remove_extension(&mut self.extensions, ExtensionType::LendingPosition)
+    }
+
+    /// Share of the escrow fee waived by a loyalty redemption, in basis points.
+    pub fn fee_discount_bps(&self) -> u16 {
+        get_extension(&self.extensions, ExtensionType::FeeDiscount)
+            .and_then(|value| u16::try_from_slice(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_fee_discount_bps(&mut self, discount_bps: u16) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::FeeDiscount,
+            &discount_bps.try_to_vec()?,
+        )
+    }
+
+    /// Escrow fee charged when this purchase settles, net of any discount.
+    pub fn escrow_fee_bps(&self) -> u64 {
+        let discount_bps = (self.fee_discount_bps() as u64).min(BASIS_POINTS);
+        self.fee_bps as u64 * (BASIS_POINTS - discount_bps) / BASIS_POINTS
+    }
+
+    /// Payouts still owed when the purchase settles in full. Milestone slices
+    /// already paid to the seller come out of the seller's share; the escrow
+    /// fee is collected on the whole purchase at settlement.
+    pub fn settlement_payouts(&self) -> Result<Payouts> {
+        let mut payouts = settlement::compute_payouts(
+            self.unit_price,
+            self.logistics_cost,
+            self.quantity,
+            self.escrow_fee_bps(),
+        )?;
+        if let Some(progress) = self.milestone_progress() {
+            payouts.seller_amount = payouts
+                .seller_amount
+                .checked_sub(progress.seller_released)
+                .ok_or(LogisticsError::MathOverflow)?;
+        }
+        Ok(payouts)
+    }
+
+    pub fn milestone_progress(&self) -> Option<MilestoneProgress> {
+        get_extension(&self.extensions, ExtensionType::MilestoneProgress)
+            .and_then(|value| MilestoneProgress::try_from_slice(value).ok())
+    }
+
+    pub fn set_milestone_progress(&mut self, progress: &MilestoneProgress) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::MilestoneProgress,
+            &progress.try_to_vec()?,
+        )
+    }
+
+    /// Loyalty points credited for this purchase, once it has accrued.
+    pub fn loyalty_points_accrued(&self) -> Option<u64> {
+        get_extension(&self.extensions, ExtensionType::LoyaltyAccrual)
+            .and_then(|value| u64::try_from_slice(value).ok())
+    }
+
+    pub fn set_loyalty_points_accrued(&mut self, points: u64) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::LoyaltyAccrual,
+            &points.try_to_vec()?,
+        )
+    }
+
+    pub fn receipt(&self) -> Option<PurchaseReceipt> {
+        get_extension(&self.extensions, ExtensionType::Receipt)
+            .and_then(|value| PurchaseReceipt::try_from_slice(value).ok())
+    }
+
+    pub fn set_receipt(&mut self, receipt: &PurchaseReceipt) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::Receipt,
+            &receipt.try_to_vec()?,
+        )
+    }
+
+    pub fn seller_stream(&self) -> Option<SellerStream> {
+        get_extension(&self.extensions, ExtensionType::SellerStream)
+            .and_then(|value| SellerStream::try_from_slice(value).ok())
+    }
+
+    pub fn set_seller_stream(&mut self, stream: &SellerStream) -> Result<()> {
+        set_extension(
+            &mut self.extensions,
+            ExtensionType::SellerStream,
+            &stream.try_to_vec()?,
+        )
+    }
+
+    /// Platform that routed this purchase, or the default key for direct sales.
+    pub fn referring_platform(&self) -> Pubkey {
+        self.platform_referral()
+            .map(|referral| referral.platform)
+            .unwrap_or_default()
+    }
+}
+
+/// Carrier tracking reference recorded by the logistics provider, stored in
+/// the purchase's `ExtensionType::Tracking` extension. `tracking_hash` is the
+/// sha256 of the carrier's tracking number so it is not published in clear.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackingInfo {
+    pub carrier_code: u16,
+    pub tracking_hash: [u8; 32],
+    pub recorded_at: i64,
+}
+
+/// Digest of the delivery photo or recipient signature posted by the
+/// logistics provider, stored in the purchase's
+/// `ExtensionType::ProofOfDelivery` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofOfDelivery {
+    pub pod_hash: [u8; 32],
+    pub submitted_at: i64,
+}
+
+/// The chosen logistics provider's answer to being assigned a purchase, stored
+/// in the purchase's `ExtensionType::ProviderAssignment` extension. Purchases
+/// without one are still awaiting the provider.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProviderAssignment {
+    pub accepted: bool,
+    pub responded_at: i64,
+}
+
+/// Compressed NFT receipt minted to the buyer, stored in the purchase's
+/// `ExtensionType::Receipt` extension so each purchase is receipted once.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PurchaseReceipt {
+    pub merkle_tree: Pubkey,
+    pub minted_at: i64,
+}
+
+/// Seller's share of a confirmed service purchase, released linearly out of
+/// the product escrow, stored in the purchase's `ExtensionType::SellerStream`
+/// extension. A buyer dispute halts vesting; the amount still unvested then is
+/// refunded to the buyer or resumes streaming, depending on the ruling.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SellerStream {
+    pub amount: u64,
+    pub released: u64,
+    pub refunded: u64,
+    pub started_at: i64,
+    pub duration_seconds: i64,
+    /// When the buyer disputed the remainder; zero while streaming.
+    pub halted_at: i64,
+}
+
+impl SellerStream {
+    /// Amount vested by `now`, frozen at `halted_at` while halted.
+    pub fn vested(&self, now: i64) -> u64 {
+        let end = if self.halted_at > 0 {
+            now.min(self.halted_at)
+        } else {
+            now
+        };
+        let elapsed = (end - self.started_at).clamp(0, self.duration_seconds);
+        if elapsed == self.duration_seconds {
+            return self.amount;
+        }
+        ((self.amount as u128 * elapsed as u128) / self.duration_seconds as u128) as u64
+    }
+
+    /// Vested amount not yet paid to the seller.
+    pub fn releasable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.released)
+    }
+
+    /// Whether the buyer may still halt the stream.
+    pub fn is_running(&self, now: i64) -> bool {
+        self.halted_at == 0 && self.vested(now) < self.amount
+    }
+
+    /// Whether the stream has paid out or refunded its whole amount, leaving
+    /// nothing in the product escrow.
+    pub fn is_finished(&self) -> bool {
+        self.released + self.refunded == self.amount
+    }
+}
+
+/// Product escrow deposited into a lending program, stored in the purchase's
+/// `ExtensionType::LendingPosition` extension. The receipt tokens sit in the
+/// purchase's lending receipt account until withdrawn.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LendingPosition {
+    pub principal: u64,
+    /// Program deposited into, so a later config change cannot strand the deposit.
+    pub lending_program: Pubkey,
+    pub deposited_at: i64,
+}
+
+/// Storefront that routed a purchase and the fee share it was promised,
+/// stored in the purchase's `ExtensionType::PlatformReferral` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformReferral {
+    pub platform: Pubkey,
+    pub fee_share_bps: u16,
+}
+
+/// Return of a delivered purchase, stored in the purchase's
+/// `ExtensionType::ReturnRequest` extension.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReturnRequest {
+    pub status: ReturnStatus,
+    /// Provider carrying the goods back to the seller.
+    pub logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+    pub refund_amount: u64,
+    pub requested_at: i64,
+}
+
+/// One ruling in a `resolve_disputes_batch` call.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchRuling {
+    pub purchase_id: u64,
+    pub winner: Pubkey,
+    pub slash_provider: bool,
+}
+
+/// First-tier arbitration outcome, stored in the purchase's
+/// `ExtensionType::DisputeRuling` extension. `escalated_by` is the default
+/// key until a party appeals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisputeRuling {
+    pub arbitrator: Pubkey,
+    pub winner: Pubkey,
+    pub slash_provider: bool,
+    pub ruled_at: i64,
+    pub escalated_by: Pubkey,
+    pub escalation_fee: u64,
+}
+
+/// Executed dispute resolution, stored in the purchase's
+/// `ExtensionType::DisputeResolution` extension. A losing party may appeal it
+/// once, posting a bond, until `appeal_deadline`; an unappealed resolution is
+/// final after that, and an appealed one once the admin has reviewed it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisputeResolution {
+    pub winner: Pubkey,
+    pub appeal_deadline: i64,
+    pub appealed_by: Pubkey,
+    pub appeal_bond: u64,
+    pub is_final: bool,
+}
+
+impl DisputeResolution {
+    pub fn is_appealed(&self) -> bool {
+        self.appealed_by != Pubkey::default()
+    }
+
+    pub fn is_final_at(&self, now: i64) -> bool {
+        self.is_final || (!self.is_appealed() && now > self.appeal_deadline)
+    }
+}
+
+/// Jurors drawn for a dispute and their votes, stored in the purchase's
+/// `ExtensionType::JuryCase` extension. `votes[i]` is juror `i`'s choice of
+/// winner, the default key until they vote, so voting never grows the account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct JuryCase {
+    pub jurors: Vec<Pubkey>,
+    pub votes: Vec<Pubkey>,
+    pub voting_ends_at: i64,
+}
+
+impl JuryCase {
+    pub fn all_voted(&self) -> bool {
+        self.votes.iter().all(|vote| *vote != Pubkey::default())
+    }
+
+    pub fn votes_cast(&self) -> Vec<Pubkey> {
+        self.votes
+            .iter()
+            .filter(|vote| **vote != Pubkey::default())
+            .copied()
+            .collect()
+    }
+
+    /// Winner backed by more than half of the votes cast, with its vote count.
+    pub fn majority(&self) -> Option<(Pubkey, usize)> {
+        let cast = self.votes_cast();
+        cast.iter()
+            .map(|winner| {
+                let count = cast.iter().filter(|vote| *vote == winner).count();
+                (*winner, count)
+            })
+            .find(|(_, count)| count * 2 > cast.len())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReturnStatus {
+    Requested,
+    Approved,
+    Completed,
+}
+
+/// One page of a user's order history. Shards are addressed by
+/// `[b"order_index", user, shard_no]` so clients can walk them in order.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct OrderIndex {
+    pub version: u8,
+    pub user: Pubkey,
+    pub shard_no: u32,
+    #[max_len(MAX_ORDER_INDEX_ENTRIES)]
+    pub entries: Vec<OrderIndexEntry>,
+    /// Entries the account has room for; grows with `add_purchase_index_page`.
+    pub capacity: u32,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderIndexEntry {
+    pub purchase_id: u64,
+    pub settled: bool,
+}
+
+impl OrderIndex {
+    pub fn is_full(&self) -> bool {
+        self.entries.len() >= self.capacity as usize
+    }
+
+    pub fn append(&mut self, purchase_id: u64) -> Result<()> {
+        require!(!self.is_full(), LogisticsError::OrderIndexShardFull);
+        self.entries.push(OrderIndexEntry {
+            purchase_id,
+            settled: false,
+        });
+        Ok(())
+    }
+
+    pub fn mark_settled(&mut self, purchase_id: u64) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.purchase_id == purchase_id)
+        {
+            entry.settled = true;
+        }
+    }
+}
+
+/// One page of the trade ids listed under a category. Shards are addressed by
+/// `[b"category_index", category, shard_no]`.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct CategoryIndex {
+    pub version: u8,
+    pub category: u16,
+    pub shard_no: u32,
+    #[max_len(MAX_CATEGORY_INDEX_ENTRIES)]
+    pub trade_ids: Vec<u64>,
+    pub bump: u8,
+}
+
+impl CategoryIndex {
+    pub fn is_full(&self) -> bool {
+        self.trade_ids.len() >= MAX_CATEGORY_INDEX_ENTRIES
+    }
+
+    pub fn append(&mut self, trade_id: u64) -> Result<()> {
+        require!(!self.is_full(), LogisticsError::CategoryIndexShardFull);
+        self.trade_ids.push(trade_id);
+        Ok(())
+    }
+}
+
+/// Off-chain content pointer for a listing, addressed by `[b"trade_metadata", trade_id]`.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct TradeMetadata {
+    pub version: u8,
+    pub trade_id: u64,
+    pub content_hash: [u8; 32],
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub uri: String,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// A seller's storefront, addressed by `[b"storefront", seller]`. Trades
+/// created with the storefront are counted here; `sync_storefront_listing`
+/// brings `active_trade_count` up to date as listings sell out or reopen.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct StorefrontAccount {
+    pub version: u8,
+    pub seller: Pubkey,
+    /// Hash of the off-chain branding metadata (name, logo, banner).
+    pub metadata_hash: [u8; 32],
+    /// Providers `create_trade` lists when it is given none.
+    #[max_len(MAX_LOGISTICS_PROVIDERS)]
+    pub default_logistics_providers: Vec<Pubkey>,
+    pub trade_count: u64,
+    pub active_trade_count: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ClaimableBalance {
+    pub version: u8,
+    pub recipient: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+/// Auction of part of a listing's stock, one open auction per trade.
+/// `leading_bidder` is the default key until the first bid.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct AuctionAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    pub start_price: u64,
+    pub min_increment: u64,
+    pub end_time: i64,
+    pub leading_bidder: Pubkey,
+    /// Per-unit price of the leading bid.
+    pub leading_bid: u64,
+    pub leading_logistics_provider: Pubkey,
+    pub leading_logistics_cost: u64,
+    pub bump: u8,
+}
+
+impl AuctionAccount {
+    /// Lowest per-unit bid that can take the lead.
+    pub fn min_next_bid(&self) -> Result<u64> {
+        if self.leading_bidder == Pubkey::default() {
+            return Ok(self.start_price);
+        }
+        self.leading_bid
+            .checked_add(self.min_increment)
+            .ok_or(error!(LogisticsError::MathOverflow))
+    }
+
+    /// Total escrowed for the leading bid, logistics included.
+    pub fn leading_amount(&self) -> Result<u64> {
+        self.leading_bid
+            .checked_mul(self.quantity)
+            .and_then(|product| product.checked_add(self.leading_logistics_cost))
+            .ok_or(error!(LogisticsError::MathOverflow))
+    }
+}
+
+/// Sealed-bid drop of a trade's stock. Buyers commit to a hidden quantity
+/// before `commit_end`, reveal it before `reveal_end`, and the lot is then
+/// shared out pro rata over the revealed demand.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SealedDropAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    /// Per-unit price, fixed when the drop is created.
+    pub unit_price: u64,
+    pub max_quantity_per_commit: u64,
+    pub commit_end: i64,
+    pub reveal_end: i64,
+    pub commit_count: u32,
+    pub claimed_count: u32,
+    /// Total quantity revealed across all commits.
+    pub revealed_quantity: u64,
+    /// Units handed out to claimed commits so far.
+    pub allocated_quantity: u64,
+    pub bump: u8,
+}
+
+impl SealedDropAccount {
+    /// Units allocated to a commit that revealed `revealed_quantity`. Every
+    /// reveal is filled when demand fits the lot; otherwise each gets its pro
+    /// rata share rounded down, and the dust returns to the listing.
+    pub fn allocation(&self, revealed_quantity: u64) -> u64 {
+        if self.revealed_quantity <= self.quantity {
+            return revealed_quantity;
+        }
+        ((revealed_quantity as u128 * self.quantity as u128) / self.revealed_quantity as u128)
+            as u64
+    }
+}
+
+/// A buyer's sealed commitment on a drop, holding the escrow for the largest
+/// quantity the drop allows.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SealedCommitAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    /// `sealed_commitment(buyer, quantity, salt)`.
+    pub commitment: [u8; 32],
+    pub logistics_provider: Pubkey,
+    pub escrowed_amount: u64,
+    /// Zero until revealed.
+    pub revealed_quantity: u64,
+    pub bump: u8,
+}
+
+/// Recurring purchase authorization, one per buyer and trade.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SubscriptionAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    /// Token account the subscription is approved to debit.
+    pub buyer_token_account: Pubkey,
+    pub token_mint: Pubkey,
+    pub quantity: u64,
+    pub logistics_provider: Pubkey,
+    /// Per-cycle payment approved at creation; higher prices are refused.
+    pub max_cycle_amount: u64,
+    pub interval_seconds: i64,
+    pub cycles_total: u32,
+    pub cycles_completed: u32,
+    pub next_cycle_at: i64,
+    pub bump: u8,
+}
+
+impl SubscriptionAccount {
+    pub fn is_due(&self, now: i64) -> bool {
+        self.cycles_completed < self.cycles_total && now >= self.next_cycle_at
+    }
+}
+
+/// Escrow-backed price offer on a listing, one per buyer and trade.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct OfferAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub token_mint: Pubkey,
+    pub unit_price: u64,
+    pub quantity: u64,
+    pub logistics_provider: Pubkey,
+    pub logistics_cost: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl OfferAccount {
+    /// Product cost at the offered price plus logistics, held in escrow.
+    pub fn escrowed_amount(&self) -> Result<u64> {
+        self.unit_price
+            .checked_mul(self.quantity)
+            .and_then(|product| product.checked_add(self.logistics_cost))
+            .ok_or(error!(LogisticsError::MathOverflow))
+    }
+}
+
+/// Credential issued by a verifier, e.g. after a KYC check. `expires_at` of
+/// 0 never expires. `region` is set when the verifier certifies where the
+/// subject resides.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct BuyerAttestation {
+    pub version: u8,
+    pub verifier: Pubkey,
+    pub subject: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub region: Option<DeliveryRegion>,
+    pub bump: u8,
+}
+
+impl BuyerAttestation {
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+/// Cumulative units a buyer has purchased from a trade with a per-buyer limit,
+/// and when they last bought from a trade with a purchase cooldown.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct BuyerQuota {
+    pub version: u8,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity_purchased: u64,
+    pub last_purchase_slot: u64,
+    pub bump: u8,
+}
+
+impl BuyerQuota {
+    /// Rejects a purchase within `cooldown_slots` of the buyer's previous one.
+    pub fn check_cooldown(&self, cooldown_slots: u64, slot: u64) -> Result<()> {
+        if self.last_purchase_slot == 0 {
+            return Ok(());
+        }
+        require_gte!(
+            slot,
+            self.last_purchase_slot.saturating_add(cooldown_slots),
+            LogisticsError::RateLimited
+        );
+        Ok(())
+    }
+
+    /// Records `quantity` more units, failing if that would exceed `max_per_buyer`.
+    pub fn consume(&mut self, quantity: u64, max_per_buyer: u64) -> Result<()> {
+        let total = self
+            .quantity_purchased
+            .checked_add(quantity)
+            .ok_or(LogisticsError::MathOverflow)?;
+        require_gte!(max_per_buyer, total, LogisticsError::PurchaseLimitExceeded);
+        self.quantity_purchased = total;
+        Ok(())
+    }
+}
+
+/// Order a buyer signs off-chain so a relayer can submit it through
+/// `buy_trade_relayed` and pay the transaction fees and rent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RelayedOrder {
+    pub buyer: Pubkey,
+    pub trade_id: u64,
+    pub quantity: u64,
+    pub logistics_provider: Pubkey,
+    pub buyer_region: DeliveryRegion,
+    /// Most the buyer agrees to escrow for the order, excluding the relayer fee.
+    pub max_total_amount: u64,
+    /// Paid from the buyer's token account to the relayer for submitting.
+    pub relayer_fee: u64,
+    /// Must equal the buyer's next relay nonce, so each order lands once.
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+impl RelayedOrder {
+    /// Bytes the buyer signs: a domain tag, the program id and the
+    /// Borsh-encoded order.
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut message = RELAYED_ORDER_DOMAIN.to_vec();
+        message.extend_from_slice(crate::ID.as_ref());
+        message.extend_from_slice(self.buyer.as_ref());
+        message.extend_from_slice(&self.trade_id.to_le_bytes());
+        message.extend_from_slice(&self.quantity.to_le_bytes());
+        message.extend_from_slice(self.logistics_provider.as_ref());
+        message.push(self.buyer_region as u8);
+        message.extend_from_slice(&self.max_total_amount.to_le_bytes());
+        message.extend_from_slice(&self.relayer_fee.to_le_bytes());
+        message.extend_from_slice(&self.nonce.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message
+    }
+}
+
+/// Next relayed-order nonce accepted for a buyer.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct RelayNonce {
+    pub version: u8,
+    pub buyer: Pubkey,
+    pub next_nonce: u64,
+    pub bump: u8,
+}
+
+/// Standing approval for a session key or backend to place orders with
+/// `buy_trade_with_permit`, debiting one of the buyer's token accounts through
+/// this PDA's SPL delegation. Spending is capped in total, per order and in time.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SpendingPermit {
+    pub version: u8,
+    pub buyer: Pubkey,
+    pub delegate: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub remaining_amount: u64,
+    pub max_order_amount: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl SpendingPermit {
+    /// Draws `amount` from the permit for one order.
+    pub fn spend(&mut self, amount: u64, now: i64) -> Result<()> {
+        require!(
+            now <= self.expires_at,
+            LogisticsError::SpendingPermitExpired
+        );
+        require!(
+            amount <= self.max_order_amount && amount <= self.remaining_amount,
+            LogisticsError::SpendingPermitExceeded
+        );
+        self.remaining_amount -= amount;
+        Ok(())
+    }
+}
+
+/// Temporary keypair a buyer authorises to sign `buy_trade` and delivery
+/// confirmations for them until `expires_at_slot`, so a mobile checkout needs
+/// no wallet prompt per step. Purchases draw on `spend_cap` through this PDA's
+/// SPL delegation on `buyer_token_account`.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SessionAccount {
+    pub version: u8,
+    pub buyer: Pubkey,
+    pub session_key: Pubkey,
+    pub buyer_token_account: Pubkey,
+    pub expires_at_slot: u64,
+    pub spend_cap: u64,
+    pub spent: u64,
+    pub bump: u8,
+}
+
+impl SessionAccount {
+    pub fn check_active(&self, slot: u64) -> Result<()> {
+        require!(slot <= self.expires_at_slot, LogisticsError::SessionExpired);
+        Ok(())
+    }
+
+    /// Counts `amount` paid from `buyer_token_account` against the cap.
+    pub fn spend(&mut self, amount: u64, slot: u64) -> Result<()> {
+        self.check_active(slot)?;
+        let spent = self
+            .spent
+            .checked_add(amount)
+            .ok_or(LogisticsError::MathOverflow)?;
+        require_gte!(
+            self.spend_cap,
+            spent,
+            LogisticsError::SessionSpendCapExceeded
+        );
+        self.spent = spent;
+        Ok(())
+    }
+}
+
+/// Checkout hold on part of a listing's stock, one per buyer and trade.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ReservationAccount {
+    pub version: u8,
+    pub trade_id: u64,
+    pub buyer: Pubkey,
+    pub quantity: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+/// Buyer's shipping address for one purchase, sealed so only the seller and
+/// the logistics provider can read it. Each ciphertext is encrypted with the
+/// X25519 shared secret between `ephemeral_pubkey` and the recipient's wallet
+/// key converted to X25519.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ShippingInfo {
+    pub version: u8,
+    pub purchase_id: u64,
+    pub buyer: Pubkey,
+    /// X25519 public key the buyer generated for this purchase.
+    pub ephemeral_pubkey: [u8; 32],
+    /// Address sealed to the seller, nonce first.
+    #[max_len(MAX_SHIPPING_CIPHERTEXT_LEN)]
+    pub seller_ciphertext: Vec<u8>,
+    /// Address sealed to the purchase's logistics provider, nonce first.
+    #[max_len(MAX_SHIPPING_CIPHERTEXT_LEN)]
+    pub provider_ciphertext: Vec<u8>,
+    pub posted_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ShipmentAccount {
+    pub version: u8,
+    pub provider: Pubkey,
+    pub buyer: Pubkey,
+    /// Seller of every merged purchase's listing
+    pub seller: Pubkey,
+    #[max_len(MAX_CONSOLIDATED_PURCHASES)]
+    pub purchase_ids: Vec<u64>,
+    pub logistics_cost: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct LogisticsProviderAccount {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub provider: Pubkey,
+    pub is_registered: bool,
+    pub regions: u16,
+    pub base_price: u64,
+    pub price_per_kg: u64,
+    pub staked_amount: u64,
+    pub deregistered_at: i64,
+    /// Token account settlements pay the provider into instead of their
+    /// associated token account
+    pub payout_token_account: Option<Pubkey>,
+    /// Purchases routed to the provider that have not settled or been
+    /// cancelled, disputed ones included. The stake is held while any remain.
+    pub open_purchases: u32,
+    pub stake_vault_bump: u8,
+    pub bump: u8,
+}
+
+/// Why a purchase was disputed. Loss and damage in transit are covered by
+/// purchase insurance.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum DisputeReason {
+    #[default]
+    None,
+    Lost,
+    Damaged,
+    NotAsDescribed,
+    Other,
+}
+
+impl DisputeReason {
+    pub fn is_insured(self) -> bool {
+        matches!(self, DisputeReason::Lost | DisputeReason::Damaged)
+    }
+}
+
+/// Why a purchase was cancelled before delivery, by either party.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum CancelReason {
+    #[default]
+    None,
+    ChangedMind,
+    OrderedByMistake,
+    DeliveryTooSlow,
+    OutOfStock,
+    Other,
+    /// Refunded by the crank after the seller missed the shipping deadline.
+    ShipTimeout,
+}
+
+/// How a trade's purchases are confirmed for settlement.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum ConfirmationMode {
+    /// The buyer, or their confirmation delegate, releases escrow alone.
+    #[default]
+    BuyerConfirms,
+    /// Any two of the buyer, seller and logistics provider must sign off.
+    TwoOfThree,
+}
+
+/// A party whose sign-off counts towards `ConfirmationMode::TwoOfThree`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfirmingParty {
+    Buyer,
+    Seller,
+    LogisticsProvider,
+}
+
+impl ConfirmingParty {
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum InsuranceStatus {
+    #[default]
+    None,
+    Active,
+    Claimable,
+    Claimed,
+}
+
+/// Delivery regions a logistics provider serves, stored as a bitmask.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryRegion {
+    Africa,
+    Asia,
+    Europe,
+    MiddleEast,
+    NorthAmerica,
+    SouthAmerica,
+    Oceania,
+}
+
+impl DeliveryRegion {
+    pub const ALL_MASK: u16 = (1 << 7) - 1;
+
+    pub fn mask(self) -> u16 {
+        1 << (self as u16)
+    }
+}
+
+/// Marketplace-wide counters kept by the settlement instructions so
+/// dashboards can read totals from one account instead of replaying events.
+/// Instructions update it when the optional `stats` account is passed.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct Stats {
+    pub version: u8,
+    pub purchases_settled: u64,
+    pub purchases_cancelled: u64,
+    pub disputes_raised: u64,
+    pub disputes_resolved: u64,
+    pub disputes_won_by_buyer: u64,
+    /// Settled volume and escrow fees per mint, in first-settled order.
+    /// Mints beyond `MAX_STATS_MINTS` are not tracked.
+    #[max_len(MAX_STATS_MINTS)]
+    pub mint_totals: Vec<MintTotals>,
+    pub bump: u8,
+}
+
+impl Stats {
+    pub fn record_volume(&mut self, mint: Pubkey, volume: u64, fees: u64) {
+        if let Some(totals) = self.mint_totals_mut(mint) {
+            totals.volume = totals.volume.saturating_add(volume);
+            totals.fees = totals.fees.saturating_add(fees);
+        }
+    }
+
+    /// Records escrow fees forgone on a refund, kept apart from `fees` so the
+    /// collected total is never netted against fees that were never taken.
+    pub fn record_fee_reversal(&mut self, mint: Pubkey, fees: u64) {
+        if let Some(totals) = self.mint_totals_mut(mint) {
+            totals.fees_reversed = totals.fees_reversed.saturating_add(fees);
+        }
+    }
+
+    fn mint_totals_mut(&mut self, mint: Pubkey) -> Option<&mut MintTotals> {
+        let index = match self
+            .mint_totals
+            .iter()
+            .position(|totals| totals.mint == mint)
+        {
+            Some(index) => index,
+            None if self.mint_totals.len() < MAX_STATS_MINTS => {
+                self.mint_totals.push(MintTotals {
+                    mint,
+                    volume: 0,
+                    fees: 0,
+                    fees_reversed: 0,
+                });
+                self.mint_totals.len() - 1
+            }
+            None => return None,
+        };
+        Some(&mut self.mint_totals[index])
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct MintTotals {
+    pub mint: Pubkey,
+    pub volume: u64,
+    pub fees: u64,
+    /// Escrow fees forgone on purchases refunded to the buyer.
+    pub fees_reversed: u64,
+}
+
+/// Registered first-tier dispute arbitrator.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ArbitratorAccount {
+    pub version: u8,
+    pub arbitrator: Pubkey,
+    pub bump: u8,
+}
+
+/// Parameters of jury arbitration, set by the governing authority.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct JuryConfig {
+    /// Stake, in the global stake mint, a juror deposits to join the pool and
+    /// must keep to be drawn.
+    pub juror_stake: u64,
+    /// Jurors drawn per dispute; odd, up to `MAX_JURY_SIZE`.
+    pub jury_size: u8,
+    pub voting_window_seconds: i64,
+    /// Share of a minority voter's stake handed to the majority.
+    pub minority_slash_bps: u16,
+}
+
+impl JuryConfig {
+    pub fn is_valid(&self) -> bool {
+        self.juror_stake > 0
+            && self.jury_size % 2 == 1
+            && self.jury_size <= MAX_JURY_SIZE
+            && self.voting_window_seconds > 0
+            && self.minority_slash_bps as u64 <= BASIS_POINTS
+    }
+}
+
+/// A staked juror. `active_cases` counts undecided disputes they sit on and
+/// keeps their stake locked.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JurorEntry {
+    pub juror: Pubkey,
+    pub stake: u64,
+    pub active_cases: u16,
+}
+
+/// Pool of staked jurors that juries are drawn from. Stakes are held together
+/// in the jury vault; slashing only moves them between entries.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct JuryPool {
+    pub version: u8,
+    pub config: JuryConfig,
+    #[max_len(MAX_JURY_POOL_SIZE)]
+    pub jurors: Vec<JurorEntry>,
+    pub vault_bump: u8,
+    pub bump: u8,
+}
+
+impl JuryPool {
+    pub fn position(&self, juror: &Pubkey) -> Option<usize> {
+        self.jurors.iter().position(|entry| entry.juror == *juror)
+    }
+
+    /// Jurors staked enough to be drawn, other than `excluded`.
+    pub fn eligible_jurors(&self, excluded: &[Pubkey]) -> Vec<Pubkey> {
+        self.jurors
+            .iter()
+            .filter(|entry| {
+                entry.stake >= self.config.juror_stake && !excluded.contains(&entry.juror)
+            })
+            .map(|entry| entry.juror)
+            .collect()
+    }
+
+    /// Releases every juror of `case` and moves `minority_slash_bps` of each
+    /// minority voter's stake to the jurors who voted for `winner`, split
+    /// evenly with any remainder to the first. Returns the amount slashed.
+    pub fn settle_case(&mut self, case: &JuryCase, winner: Option<Pubkey>) -> u64 {
+        let slash_bps = self.config.minority_slash_bps as u128;
+        let mut slashed = 0u64;
+        let mut majority = Vec::new();
+        for (juror, vote) in case.jurors.iter().zip(case.votes.iter()) {
+            let Some(i) = self.position(juror) else {
+                continue;
+            };
+            let entry = &mut self.jurors[i];
+            entry.active_cases = entry.active_cases.saturating_sub(1);
+            if *vote == Pubkey::default() {
+                continue;
+            }
+            match winner {
+                Some(winner) if *vote == winner => majority.push(i),
+                Some(_) => {
+                    let slash = (entry.stake as u128 * slash_bps / BASIS_POINTS as u128) as u64;
+                    entry.stake -= slash;
+                    slashed += slash;
+                }
+                None => {}
+            }
+        }
+
+        if let Some(&first) = majority.first() {
+            let share = slashed / majority.len() as u64;
+            for &i in &majority {
+                self.jurors[i].stake += share;
+            }
+            self.jurors[first].stake += slashed - share * majority.len() as u64;
+        }
+        slashed
+    }
+}
+
+/// Admin-approved mint that trades may be listed and paid in.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct AllowedMint {
+    pub version: u8,
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+/// A buyer's loyalty balance. `discount_bps` is redeemed but not yet spent and
+/// applies to the escrow fee of the next `buy_trade`.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct LoyaltyAccount {
+    pub version: u8,
+    pub buyer: Pubkey,
+    pub points: u64,
+    pub lifetime_points: u64,
+    pub discount_bps: u16,
+    pub bump: u8,
+}
+
+/// Where purchase receipts are minted: a Bubblegum merkle tree delegated to
+/// the receipt authority PDA, and the URI prefix the receipt details are
+/// appended to.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ReceiptConfig {
+    pub version: u8,
+    pub merkle_tree: Pubkey,
+    #[max_len(MAX_RECEIPT_URI_PREFIX_LEN)]
+    pub uri_prefix: String,
+    pub bump: u8,
+}
+
+impl ReceiptConfig {
+    /// Metadata URI of a receipt, encoding the purchase's trade, quantity and
+    /// amount for warranty and loyalty programs to read.
+    pub fn receipt_uri(&self, purchase: &PurchaseAccount) -> String {
+        format!(
+            "{}?purchase={}&trade={}&quantity={}&amount={}",
+            self.uri_prefix,
+            purchase.purchase_id,
+            purchase.trade_id,
+            purchase.quantity,
+            purchase.total_amount
+        )
+    }
+}
+
+/// Semantic version; fields compare in order, so the derived ordering is the
+/// semver precedence.
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    InitSpace,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub struct SemVer {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+/// Version of the deployed program, bumped by the admin on every deploy, and
+/// the oldest client version that still builds valid instructions for it.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct ProgramVersion {
+    pub version: u8,
+    pub program_version: SemVer,
+    pub min_client_version: SemVer,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl ProgramVersion {
+    /// Whether a client at `client_version` can talk to this program: it must
+    /// be no older than `min_client_version`, and must know the program's
+    /// major version.
+    pub fn supports_client(&self, client_version: SemVer) -> bool {
+        client_version >= self.min_client_version
+            && client_version.major >= self.program_version.major
+    }
+}
+
+/// Emergency withdrawal from one of the program's vaults in `token_mint`,
+/// proposed by the admin. It can only be executed once `executable_at` has
+/// passed, and may be cancelled until then.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct EmergencyWithdrawal {
+    pub version: u8,
+    pub token_mint: Pubkey,
+    /// Vault the withdrawal drains; its address is derived again on execute.
+    pub source: EmergencyVault,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub proposed_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+/// Vault an emergency withdrawal drains, with the seeds that locate it
+/// besides the mint. Draining a stake or bond vault leaves the recorded
+/// stake or bond in place; the admin settles those by hand.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum EmergencyVault {
+    /// The mint's escrow vault, which also holds bids and offers.
+    #[default]
+    Escrow,
+    ProductEscrow {
+        purchase_id: u64,
+    },
+    LogisticsEscrow {
+        purchase_id: u64,
+    },
+    StakeVault {
+        provider: Pubkey,
+    },
+    SellerBond {
+        seller: Pubkey,
+    },
+    InsurancePool,
+    ReturnsReserve {
+        seller: Pubkey,
+    },
+}
+
+impl EmergencyVault {
+    /// PDA seeds of the vault holding `token_mint`.
+    pub fn seeds(&self, token_mint: &Pubkey) -> Vec<Vec<u8>> {
+        match self {
+            EmergencyVault::Escrow => {
+                vec![pda::ESCROW_SEED.to_vec(), token_mint.to_bytes().to_vec()]
+            }
+            EmergencyVault::ProductEscrow { purchase_id } => {
+                vec![
+                    pda::PRODUCT_ESCROW_SEED.to_vec(),
+                    purchase_id.to_le_bytes().to_vec(),
+                ]
+            }
+            EmergencyVault::LogisticsEscrow { purchase_id } => {
+                vec![
+                    pda::LOGISTICS_ESCROW_SEED.to_vec(),
+                    purchase_id.to_le_bytes().to_vec(),
+                ]
+            }
+            EmergencyVault::StakeVault { provider } => {
+                vec![pda::STAKE_VAULT_SEED.to_vec(), provider.to_bytes().to_vec()]
+            }
+            EmergencyVault::SellerBond { seller } => {
+                vec![pda::SELLER_BOND_SEED.to_vec(), seller.to_bytes().to_vec()]
+            }
+            EmergencyVault::InsurancePool => {
+                vec![
+                    pda::INSURANCE_POOL_SEED.to_vec(),
+                    token_mint.to_bytes().to_vec(),
+                ]
+            }
+            EmergencyVault::ReturnsReserve { seller } => vec![
+                pda::RETURNS_RESERVE_SEED.to_vec(),
+                seller.to_bytes().to_vec(),
+                token_mint.to_bytes().to_vec(),
+            ],
+        }
+    }
+
+    /// Address and bump of the vault holding `token_mint`.
+    pub fn address(&self, token_mint: &Pubkey) -> (Pubkey, u8) {
+        let seeds = self.seeds(token_mint);
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        Pubkey::find_program_address(&seeds, &crate::ID)
+    }
+
+    /// Whether the mint's escrow vault, rather than the vault itself, is the
+    /// token authority. Purchase escrows are owned by the escrow vault.
+    pub fn escrow_is_authority(&self) -> bool {
+        matches!(
+            self,
+            EmergencyVault::ProductEscrow { .. } | EmergencyVault::LogisticsEscrow { .. }
+        )
+    }
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SellerAccount {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub seller: Pubkey,
+    pub is_registered: bool,
+    pub bond_mint: Pubkey,
+    pub bond_amount: u64,
+    pub bonded_at: i64,
+    pub disputes_lost: u32,
+    pub cancellations: u32,
+    pub preferred_mint: Option<Pubkey>,
+    pub max_slippage_bps: u16,
+    /// Token account settlement pays the seller's proceeds into instead of
+    /// their associated token account, e.g. a treasury.
+    pub payout_token_account: Option<Pubkey>,
+    pub bond_vault_bump: u8,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct BuyerAccount {
+    pub version: u8,
+    /// Reserved and always zeroed; unrelated to the Anchor discriminator.
+    pub discriminator: [u8; 8],
+    pub buyer: Pubkey,
+    pub is_registered: bool,
+    #[max_len(MAX_PURCHASE_IDS)]
+    pub purchase_ids: Vec<u64>,
+    /// Key allowed to confirm delivery on the buyer's behalf.
+    pub confirmation_delegate: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl BuyerAccount {
+    pub fn is_confirmation_delegate(&self, key: &Pubkey) -> bool {
+        self.confirmation_delegate == Some(*key)
+    }
+}
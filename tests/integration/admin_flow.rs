@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::constants::EMERGENCY_TIMELOCK_SECONDS;
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, pda, EmergencyVault, EmergencyWithdrawal,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+use crate::fixtures::*;
+use crate::purchase_flow::*;
+
+fn propose_instruction(
+    context: &ProgramTestContext,
+    market: &Market,
+    source: EmergencyVault,
+    source_vault: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
+    program_instruction(
+        accounts::ProposeEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&market.mint).0,
+            token_mint: market.mint,
+            source_vault,
+            destination,
+            admin: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::ProposeEmergencyWithdrawal { source, amount },
+    )
+}
+
+fn execute_instruction(
+    context: &ProgramTestContext,
+    market: &Market,
+    source_vault: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    program_instruction(
+        accounts::ExecuteEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&market.mint).0,
+            source_vault,
+            escrow_token_account: Some(pda::escrow_vault_pda(&market.mint).0),
+            destination,
+            admin: context.payer.pubkey(),
+            token_program: token::ID,
+        },
+        instruction::ExecuteEmergencyWithdrawal {},
+    )
+}
+
+#[tokio::test]
+async fn emergency_withdrawal_drains_only_the_named_vault_after_the_timelock() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+    let admin = context.payer.pubkey();
+    let destination = create_token_account(&mut context, &market.mint, &admin).await;
+    let product_escrow = pda::product_escrow_pda(purchase_id).0;
+    let logistics_escrow = pda::logistics_escrow_pda(purchase_id).0;
+    let source = EmergencyVault::ProductEscrow { purchase_id };
+
+    // The proposal must name the vault it passes
+    let propose = propose_instruction(
+        &context,
+        &market,
+        source,
+        logistics_escrow,
+        destination,
+        PRODUCT_COST,
+    );
+    assert!(send(&mut context, &[propose], &[]).await.is_err());
+    let propose = propose_instruction(
+        &context,
+        &market,
+        source,
+        product_escrow,
+        destination,
+        PRODUCT_COST,
+    );
+    send(&mut context, &[propose], &[]).await.unwrap();
+    let proposal: EmergencyWithdrawal =
+        fetch(&mut context, &pda::emergency_withdrawal_pda(&market.mint).0).await;
+    assert_eq!(proposal.source, source);
+    assert_eq!(
+        proposal.executable_at,
+        proposal.proposed_at + EMERGENCY_TIMELOCK_SECONDS
+    );
+
+    let execute = execute_instruction(&context, &market, product_escrow, destination);
+    assert!(send(&mut context, &[execute], &[]).await.is_err());
+
+    // Past the timelock, only the named vault can be drained
+    advance_clock(&mut context, EMERGENCY_TIMELOCK_SECONDS).await;
+    let execute = execute_instruction(&context, &market, logistics_escrow, destination);
+    assert!(send(&mut context, &[execute], &[]).await.is_err());
+    context.get_new_latest_blockhash().await.unwrap();
+    let execute = execute_instruction(&context, &market, product_escrow, destination);
+    send(&mut context, &[execute], &[]).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut context, &destination).await,
+        PRODUCT_COST
+    );
+    assert_eq!(token_balance(&mut context, &product_escrow).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &logistics_escrow).await,
+        LOGISTICS_COST
+    );
+    let closed = context
+        .banks_client
+        .get_account(pda::emergency_withdrawal_pda(&market.mint).0)
+        .await
+        .unwrap();
+    assert!(closed.is_none());
+}
+
+#[tokio::test]
+async fn cancelled_emergency_withdrawal_cannot_execute() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+    let admin = context.payer.pubkey();
+    let destination = create_token_account(&mut context, &market.mint, &admin).await;
+    let logistics_escrow = pda::logistics_escrow_pda(purchase_id).0;
+
+    let propose = propose_instruction(
+        &context,
+        &market,
+        EmergencyVault::LogisticsEscrow { purchase_id },
+        logistics_escrow,
+        destination,
+        LOGISTICS_COST,
+    );
+    send(&mut context, &[propose], &[]).await.unwrap();
+    let cancel = program_instruction(
+        accounts::CancelEmergencyWithdrawal {
+            global_state: pda::global_state_pda().0,
+            emergency_withdrawal: pda::emergency_withdrawal_pda(&market.mint).0,
+            admin,
+        },
+        instruction::CancelEmergencyWithdrawal {},
+    );
+    send(&mut context, &[cancel], &[]).await.unwrap();
+
+    advance_clock(&mut context, EMERGENCY_TIMELOCK_SECONDS).await;
+    let execute = execute_instruction(&context, &market, logistics_escrow, destination);
+    assert!(send(&mut context, &[execute], &[]).await.is_err());
+    assert_eq!(token_balance(&mut context, &destination).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &logistics_escrow).await,
+        LOGISTICS_COST
+    );
+}
@@ -0,0 +1,213 @@
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::helpers::compute_insurance_premium;
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, pda, CancelReason, ConfirmationMode, GlobalState, PurchaseAccount,
+    SellerAccount, TradeAccount,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+use crate::fixtures::*;
+use crate::purchase_flow::*;
+
+const INSURANCE_BPS: u16 = 300;
+
+/// Cancels `purchase_id` as the seller, refunding the premium from the
+/// insurance pool when it was `insured`.
+fn seller_cancel_instruction(market: &Market, purchase_id: u64, insured: bool) -> Instruction {
+    let seller = market.seller.pubkey();
+    program_instruction(
+        accounts::SellerCancelPurchase {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            seller_account: pda::seller_pda(&seller).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            product_escrow: Some(pda::product_escrow_pda(purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(purchase_id).0),
+            insurance_pool: insured.then(|| pda::insurance_pool_pda(&market.mint).0),
+            buyer_token_account: market.buyer_token_account,
+            provider_account: pda::logistics_provider_pda(&market.provider.pubkey()).0,
+            buyer_order_index: None,
+            seller_order_index: None,
+            stats: None,
+            seller,
+            token_program: token::ID,
+        },
+        instruction::SellerCancelPurchase {
+            purchase_id,
+            reason: CancelReason::OutOfStock,
+        },
+    )
+}
+
+/// Lists another trade from the market's seller that offers insurance, and
+/// opens the mint's insurance pool to collect the premiums.
+async fn list_insured_trade(context: &mut ProgramTestContext, market: &Market) -> TradeAccount {
+    let global_state: GlobalState = fetch(context, &pda::global_state_pda().0).await;
+    let trade_id = global_state.trade_counter + 1;
+    let provider = market.provider.pubkey();
+    let create_insurance_pool = program_instruction(
+        accounts::CreateInsurancePool {
+            allowed_mint: pda::allowed_mint_pda(&market.mint).0,
+            insurance_pool: pda::insurance_pool_pda(&market.mint).0,
+            token_mint: market.mint,
+            payer: context.payer.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateInsurancePool {},
+    );
+    let mut create_trade = program_instruction(
+        accounts::CreateTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller: market.seller.pubkey(),
+            token_mint: market.mint,
+            allowed_mint: pda::allowed_mint_pda(&market.mint).0,
+            category_index: None,
+            storefront: None,
+            admin: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateTrade {
+            product_cost: PRODUCT_COST,
+            logistics_providers: vec![provider],
+            logistics_costs: vec![LOGISTICS_COST],
+            total_quantity: 10,
+            payout_splits: Vec::new(),
+            weight_grams: 0,
+            logistics_rates_per_kg: Vec::new(),
+            category: 0,
+            tag_hashes: Vec::new(),
+            price_feed: None,
+            accepted_mints: Vec::new(),
+            insurance_bps: INSURANCE_BPS,
+            max_per_buyer: None,
+            ship_to_regions: 0,
+            requires_proof_of_delivery: false,
+            confirmation_mode: ConfirmationMode::BuyerConfirms,
+        },
+    );
+    create_trade.accounts.push(AccountMeta::new_readonly(
+        pda::logistics_provider_pda(&provider).0,
+        false,
+    ));
+    send(context, &[create_insurance_pool, create_trade], &[])
+        .await
+        .unwrap();
+    fetch(context, &pda::trade_pda(trade_id).0).await
+}
+
+#[tokio::test]
+async fn seller_cancel_refunds_the_buyer_and_counts_against_a_registered_seller() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let seller = market.seller.pubkey();
+    let buyer_before = token_balance(&mut context, &market.buyer_token_account).await;
+
+    // An unregistered seller has no counter, and still refunds in full
+    let first = buy(&mut context, &market, 2).await;
+    let cancel = seller_cancel_instruction(&market, first, false);
+    send(&mut context, &[cancel], &[&market.seller])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before
+    );
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(first).0).await;
+    assert!(purchase.cancelled);
+    assert_eq!(purchase.cancel_reason, CancelReason::OutOfStock);
+    let trade: TradeAccount = fetch(&mut context, &pda::trade_pda(market.trade_id).0).await;
+    assert_eq!(trade.remaining_quantity, trade.total_quantity);
+
+    // A registered seller's cancellations are counted on their account
+    let register_seller = program_instruction(
+        accounts::RegisterSeller {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            seller,
+            admin: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSeller {},
+    );
+    send(&mut context, &[register_seller], &[]).await.unwrap();
+    for cancellations in 1..=2 {
+        let purchase_id = buy(&mut context, &market, 1).await;
+        let cancel = seller_cancel_instruction(&market, purchase_id, false);
+        send(&mut context, &[cancel], &[&market.seller])
+            .await
+            .unwrap();
+        let seller_account: SellerAccount = fetch(&mut context, &pda::seller_pda(&seller).0).await;
+        assert_eq!(seller_account.cancellations, cancellations);
+        assert!(seller_account.is_registered);
+    }
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before
+    );
+}
+
+#[tokio::test]
+async fn seller_cancel_refunds_the_insurance_premium() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let insured_trade = list_insured_trade(&mut context, &market).await;
+    let market = market.with_trade(insured_trade.trade_id);
+    let insurance_pool = pda::insurance_pool_pda(&market.mint).0;
+    let buyer_before = token_balance(&mut context, &market.buyer_token_account).await;
+
+    let global_state: GlobalState = fetch(&mut context, &pda::global_state_pda().0).await;
+    let purchase_id = global_state.purchase_counter + 1;
+    let buy_trade = insured_buy_instruction(&market, purchase_id, 2);
+    send(&mut context, &[buy_trade], &[&market.buyer])
+        .await
+        .unwrap();
+    let premium = compute_insurance_premium(2 * PRODUCT_COST, INSURANCE_BPS).unwrap();
+    assert_eq!(token_balance(&mut context, &insurance_pool).await, premium);
+
+    let cancel = seller_cancel_instruction(&market, purchase_id, true);
+    send(&mut context, &[cancel], &[&market.seller])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before
+    );
+    assert_eq!(token_balance(&mut context, &insurance_pool).await, 0);
+}
+
+#[tokio::test]
+async fn shipped_purchase_cannot_be_cancelled_by_the_seller() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+
+    let mark_shipped = program_instruction(
+        accounts::MarkShipped {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            authority: market.provider.pubkey(),
+        },
+        instruction::MarkShipped { purchase_id },
+    );
+    send(&mut context, &[mark_shipped], &[&market.provider])
+        .await
+        .unwrap();
+
+    let cancel = seller_cancel_instruction(&market, purchase_id, false);
+    assert!(send(&mut context, &[cancel], &[&market.seller])
+        .await
+        .is_err());
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert!(!purchase.cancelled);
+    assert_eq!(
+        token_balance(&mut context, &pda::product_escrow_pda(purchase_id).0).await,
+        PRODUCT_COST
+    );
+}
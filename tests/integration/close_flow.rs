@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::constants::PURCHASE_RETENTION_SECONDS;
+use dezenmart_rust_smart_contract::{accounts, instruction, pda};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+use crate::fixtures::*;
+use crate::purchase_flow::*;
+
+/// Closes `purchase_id` as `authority`, returning its rent to `payer`.
+pub(crate) fn close_instruction(
+    market: &Market,
+    purchase_id: u64,
+    payer: Pubkey,
+    authority: Pubkey,
+) -> Instruction {
+    program_instruction(
+        accounts::ClosePurchase {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            payer,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            product_escrow: Some(pda::product_escrow_pda(purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(purchase_id).0),
+            authority,
+            token_program: token::ID,
+        },
+        instruction::ClosePurchase { purchase_id },
+    )
+}
+
+async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+#[tokio::test]
+async fn settled_purchase_closes_after_retention_and_refunds_the_payer() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let buyer = market.buyer.pubkey();
+    let seller = market.seller.pubkey();
+    let purchase_id = buy(&mut context, &market, 1).await;
+
+    // An open purchase cannot be closed, however long it has been held
+    advance_clock(&mut context, PURCHASE_RETENTION_SECONDS).await;
+    let close = close_instruction(&market, purchase_id, buyer, seller);
+    assert!(send(&mut context, &[close], &[&market.seller])
+        .await
+        .is_err());
+
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer])
+        .await
+        .unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    let close = close_instruction(&market, purchase_id, buyer, seller);
+    assert!(send(&mut context, &[close], &[&market.seller])
+        .await
+        .is_err());
+
+    // Past the retention window the seller may close it, but the rent goes
+    // back to the buyer who paid it and nobody else
+    advance_clock(&mut context, PURCHASE_RETENTION_SECONDS).await;
+    let close = close_instruction(&market, purchase_id, seller, seller);
+    assert!(send(&mut context, &[close], &[&market.seller])
+        .await
+        .is_err());
+    let purchase_account = pda::purchase_pda(purchase_id).0;
+    let product_escrow = pda::product_escrow_pda(purchase_id).0;
+    let logistics_escrow = pda::logistics_escrow_pda(purchase_id).0;
+    let rent = lamports(&mut context, &purchase_account).await
+        + lamports(&mut context, &product_escrow).await
+        + lamports(&mut context, &logistics_escrow).await;
+    let buyer_before = lamports(&mut context, &buyer).await;
+    let close = close_instruction(&market, purchase_id, buyer, seller);
+    send(&mut context, &[close], &[&market.seller])
+        .await
+        .unwrap();
+
+    assert_eq!(lamports(&mut context, &buyer).await, buyer_before + rent);
+    for closed in [purchase_account, product_escrow, logistics_escrow] {
+        let account = context.banks_client.get_account(closed).await.unwrap();
+        assert!(account.is_none());
+    }
+}
+
+#[tokio::test]
+async fn open_return_keeps_the_purchase() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let buyer = market.buyer.pubkey();
+    let purchase_id = buy(&mut context, &market, 1).await;
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer])
+        .await
+        .unwrap();
+
+    let request_return = program_instruction(
+        accounts::RequestReturn {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            buyer,
+            system_program: system_program::ID,
+        },
+        instruction::RequestReturn {
+            purchase_id,
+            logistics_provider: market.provider.pubkey(),
+        },
+    );
+    send(&mut context, &[request_return], &[&market.buyer])
+        .await
+        .unwrap();
+
+    advance_clock(&mut context, PURCHASE_RETENTION_SECONDS).await;
+    let close = close_instruction(&market, purchase_id, buyer, buyer);
+    assert!(send(&mut context, &[close], &[&market.buyer])
+        .await
+        .is_err());
+    let purchase = context
+        .banks_client
+        .get_account(pda::purchase_pda(purchase_id).0)
+        .await
+        .unwrap();
+    assert!(purchase.is_some());
+}
@@ -0,0 +1,295 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::constants::PURCHASE_RETENTION_SECONDS;
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, pda, DisputeReason, GlobalState, LogisticsProviderAccount,
+    PurchaseAccount, SellerAccount,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+use crate::close_flow::close_instruction;
+use crate::fixtures::*;
+use crate::provider_flow::*;
+use crate::purchase_flow::*;
+
+const SELLER_BOND: u64 = 400_000;
+
+async fn register_bonded_seller(context: &mut ProgramTestContext, market: &Market) {
+    let seller = market.seller.pubkey();
+    let seller_token_account = create_token_account(context, &market.mint, &seller).await;
+    mint_to(context, &market.mint, &seller_token_account, SELLER_BOND).await;
+    let register = program_instruction(
+        accounts::RegisterSellerWithBond {
+            global_state: pda::global_state_pda().0,
+            seller_account: pda::seller_pda(&seller).0,
+            bond_vault: pda::seller_bond_pda(&seller).0,
+            bond_mint: market.mint,
+            seller_token_account,
+            seller,
+            admin: context.payer.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSellerWithBond {
+            bond_amount: SELLER_BOND,
+        },
+    );
+    send(context, &[register], &[&market.seller]).await.unwrap();
+}
+
+async fn raise_dispute(context: &mut ProgramTestContext, market: &Market, purchase_id: u64) {
+    let raise = program_instruction(
+        accounts::RaiseDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            stats: None,
+            user: market.buyer.pubkey(),
+        },
+        instruction::RaiseDispute {
+            purchase_id,
+            reason: DisputeReason::NotAsDescribed,
+        },
+    );
+    send(context, &[raise], &[&market.buyer]).await.unwrap();
+}
+
+/// Resolves `purchase_id` for `winner`, handing a slashed seller bond to the
+/// buyer when `slash_bond` is set and a slashed provider stake when
+/// `slash_provider` is.
+fn resolve_dispute_instruction(
+    market: &Market,
+    purchase_id: u64,
+    winner: Pubkey,
+    slash_bond: bool,
+    slash_provider: bool,
+    authority: Pubkey,
+) -> Instruction {
+    let seller = market.seller.pubkey();
+    let provider = market.provider.pubkey();
+    program_instruction(
+        accounts::ResolveDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            product_escrow: Some(pda::product_escrow_pda(purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(purchase_id).0),
+            buyer_token_account: market.buyer_token_account,
+            seller,
+            logistics_provider: provider,
+            token_mint: market.mint,
+            seller_token_account: None,
+            seller_payout_token_account: None,
+            logistics_token_account: None,
+            logistics_payout_token_account: None,
+            seller_claimable: None,
+            logistics_claimable: None,
+            fee_vault: pda::fee_vault_pda(&market.mint).0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            stake_vault: slash_provider.then(|| pda::stake_vault_pda(&provider).0),
+            buyer_stake_token_account: slash_provider.then_some(market.buyer_token_account),
+            seller_account: pda::seller_pda(&seller).0,
+            bond_vault: slash_bond.then(|| pda::seller_bond_pda(&seller).0),
+            buyer_bond_token_account: slash_bond.then_some(market.buyer_token_account),
+            buyer_order_index: None,
+            seller_order_index: None,
+            stats: None,
+            authority,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ResolveDispute {
+            purchase_id,
+            winner,
+            slash_provider,
+        },
+    )
+}
+
+#[tokio::test]
+async fn buyer_won_dispute_records_the_loss_and_slashes_past_the_threshold() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    register_bonded_seller(&mut context, &market).await;
+    let admin = context.payer.pubkey();
+    let bond_config = program_instruction(
+        accounts::SetSellerBondConfig {
+            global_state: pda::global_state_pda().0,
+            authority: admin,
+        },
+        instruction::SetSellerBondConfig {
+            seller_loss_threshold: 1,
+            seller_bond_slash_bps: 5_000,
+            seller_probation_seconds: 0,
+        },
+    );
+    send(&mut context, &[bond_config], &[]).await.unwrap();
+    let buyer = market.buyer.pubkey();
+    let seller_account = pda::seller_pda(&market.seller.pubkey()).0;
+    let bond_vault = pda::seller_bond_pda(&market.seller.pubkey()).0;
+
+    // The first loss is recorded even though it stays within the threshold
+    let first = buy(&mut context, &market, 1).await;
+    raise_dispute(&mut context, &market, first).await;
+    let resolve = resolve_dispute_instruction(&market, first, buyer, false, false, admin);
+    send(&mut context, &[resolve], &[]).await.unwrap();
+    let seller: SellerAccount = fetch(&mut context, &seller_account).await;
+    assert_eq!(seller.disputes_lost, 1);
+    assert_eq!(token_balance(&mut context, &bond_vault).await, SELLER_BOND);
+
+    // The seller resolves the second ruling against themselves; the account
+    // is always passed, so the loss still lands and crosses the threshold
+    let second = buy(&mut context, &market, 1).await;
+    raise_dispute(&mut context, &market, second).await;
+    let arbitrator = funded_wallet(&mut context, 1_000_000_000).await;
+    let register_arbitrator = program_instruction(
+        accounts::RegisterArbitrator {
+            global_state: pda::global_state_pda().0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator.pubkey()).0,
+            arbitrator: arbitrator.pubkey(),
+            authority: admin,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterArbitrator {},
+    );
+    let arbitrate = program_instruction(
+        accounts::ArbitrateDispute {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(second).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            arbitrator_account: pda::arbitrator_pda(&arbitrator.pubkey()).0,
+            arbitrator: arbitrator.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::ArbitrateDispute {
+            purchase_id: second,
+            winner: buyer,
+            slash_provider: false,
+        },
+    );
+    send(
+        &mut context,
+        &[register_arbitrator, arbitrate],
+        &[&arbitrator],
+    )
+    .await
+    .unwrap();
+    let global_state: GlobalState = fetch(&mut context, &pda::global_state_pda().0).await;
+    advance_clock(&mut context, global_state.escalation_window_seconds + 1).await;
+
+    let buyer_before = token_balance(&mut context, &market.buyer_token_account).await;
+    let resolve =
+        resolve_dispute_instruction(&market, second, buyer, true, false, market.seller.pubkey());
+    send(&mut context, &[resolve], &[&market.seller])
+        .await
+        .unwrap();
+
+    let seller: SellerAccount = fetch(&mut context, &seller_account).await;
+    let slashed = SELLER_BOND / 2;
+    assert_eq!(seller.disputes_lost, 2);
+    assert_eq!(seller.bond_amount, SELLER_BOND - slashed);
+    assert_eq!(
+        token_balance(&mut context, &bond_vault).await,
+        SELLER_BOND - slashed
+    );
+    let refund = PRODUCT_COST + LOGISTICS_COST;
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before + refund + slashed
+    );
+}
+
+#[tokio::test]
+async fn slashed_provider_stake_pays_the_buyer() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let admin = context.payer.pubkey();
+    let buyer = market.buyer.pubkey();
+    let provider_account = pda::logistics_provider_pda(&market.provider.pubkey()).0;
+
+    let purchase_id = buy(&mut context, &market, 1).await;
+    raise_dispute(&mut context, &market, purchase_id).await;
+    let buyer_before = token_balance(&mut context, &market.buyer_token_account).await;
+    let resolve = resolve_dispute_instruction(&market, purchase_id, buyer, false, true, admin);
+    send(&mut context, &[resolve], &[]).await.unwrap();
+
+    let slashed = PROVIDER_STAKE * SLASH_BPS as u64 / 10_000;
+    let refund = PRODUCT_COST + LOGISTICS_COST;
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before + refund + slashed
+    );
+    assert_eq!(
+        token_balance(
+            &mut context,
+            &pda::stake_vault_pda(&market.provider.pubkey()).0
+        )
+        .await,
+        PROVIDER_STAKE - slashed
+    );
+    let provider: LogisticsProviderAccount = fetch(&mut context, &provider_account).await;
+    assert_eq!(provider.staked_amount, PROVIDER_STAKE - slashed);
+    assert_eq!(provider.open_purchases, 0);
+}
+
+#[tokio::test]
+async fn appealed_resolution_keeps_the_purchase_until_reviewed() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let admin = context.payer.pubkey();
+    let buyer = market.buyer.pubkey();
+    let seller = market.seller.pubkey();
+    let unappealed = buy(&mut context, &market, 1).await;
+    let appealed = buy(&mut context, &market, 1).await;
+    for purchase_id in [unappealed, appealed] {
+        raise_dispute(&mut context, &market, purchase_id).await;
+        let resolve = resolve_dispute_instruction(&market, purchase_id, buyer, false, false, admin);
+        send(&mut context, &[resolve], &[]).await.unwrap();
+    }
+
+    // The seller appeals one refund, posting the bond from their account
+    let seller_token_account = get_associated_token_address(&seller, &market.mint);
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(appealed).0).await;
+    let global_state: GlobalState = fetch(&mut context, &pda::global_state_pda().0).await;
+    let appeal_bond = purchase.total_amount * global_state.escalation_fee_bps as u64 / 10_000;
+    mint_to(
+        &mut context,
+        &market.mint,
+        &seller_token_account,
+        appeal_bond,
+    )
+    .await;
+    let appeal = program_instruction(
+        accounts::AppealResolution {
+            global_state: pda::global_state_pda().0,
+            purchase_account: pda::purchase_pda(appealed).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            appellant_token_account: seller_token_account,
+            appellant: seller,
+            token_program: token::ID,
+        },
+        instruction::AppealResolution {
+            purchase_id: appealed,
+        },
+    );
+    send(&mut context, &[appeal], &[&market.seller])
+        .await
+        .unwrap();
+
+    // Past the retention window only the unappealed resolution is final
+    advance_clock(&mut context, PURCHASE_RETENTION_SECONDS).await;
+    let close = close_instruction(&market, appealed, buyer, buyer);
+    assert!(send(&mut context, &[close], &[&market.buyer])
+        .await
+        .is_err());
+    let close = close_instruction(&market, unappealed, buyer, buyer);
+    send(&mut context, &[close], &[&market.buyer])
+        .await
+        .unwrap();
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::spl_token;
+use dezenmart_rust_smart_contract::{accounts, instruction, pda};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+pub const MINT_DECIMALS: u8 = 6;
+
+// Anchor's generated entry ties the account slice to the `'info` lifetime,
+// which `processor!` cannot express, so the slice is leaked for the test run.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    dezenmart_rust_smart_contract::entry(program_id, accounts, data)
+}
+
+/// Starts a validator with the program loaded and the global state initialized
+/// by the context payer, who acts as admin and mint and freeze authority.
+pub async fn setup() -> ProgramTestContext {
+    let program_test = ProgramTest::new(
+        "dezenmart_rust_smart_contract",
+        dezenmart_rust_smart_contract::ID,
+        processor!(process_instruction),
+    );
+    initialize(program_test).await
+}
+
+/// Like `setup`, but loads the program's SBF build from `anchor build`.
+/// Natively run programs are not metered, so compute tests need this one.
+pub async fn setup_sbf() -> ProgramTestContext {
+    let mut program_test = ProgramTest::new(
+        "dezenmart_rust_smart_contract",
+        dezenmart_rust_smart_contract::ID,
+        None,
+    );
+    program_test.prefer_bpf(true);
+    initialize(program_test).await
+}
+
+async fn initialize(program_test: ProgramTest) -> ProgramTestContext {
+    let mut context = program_test.start_with_context().await;
+
+    let admin = context.payer.pubkey();
+    let initialize = program_instruction(
+        accounts::Initialize {
+            global_state: pda::global_state_pda().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::Initialize {},
+    );
+    send(&mut context, &[initialize], &[]).await.unwrap();
+    context
+}
+
+pub fn program_instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: dezenmart_rust_smart_contract::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Signs with the context payer plus `signers` and processes the transaction.
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Simulates the transaction `send` would process and returns the compute
+/// units it consumed, failing the test if it would not succeed.
+pub async fn compute_units(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> u64 {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    simulation
+        .result
+        .expect("simulation should run")
+        .expect("transaction should succeed");
+    simulation
+        .simulation_details
+        .expect("simulation should report details")
+        .units_consumed
+}
+
+/// Creates a funded wallet that can pay for its own accounts.
+pub async fn funded_wallet(context: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let wallet = Keypair::new();
+    let transfer = system_instruction::transfer(&context.payer.pubkey(), &wallet.pubkey(), lamports);
+    send(context, &[transfer], &[]).await.unwrap();
+    wallet
+}
+
+pub async fn create_mint(context: &mut ProgramTestContext) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let payer = context.payer.pubkey();
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &payer,
+            Some(&payer),
+            MINT_DECIMALS,
+        )
+        .unwrap(),
+    ];
+    send(context, &instructions, &[&mint]).await.unwrap();
+    mint.pubkey()
+}
+
+pub async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let token_account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let instructions = [
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &token_account.pubkey(),
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_account3(
+            &spl_token::ID,
+            &token_account.pubkey(),
+            mint,
+            owner,
+        )
+        .unwrap(),
+    ];
+    send(context, &instructions, &[&token_account]).await.unwrap();
+    token_account.pubkey()
+}
+
+pub async fn create_associated_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let token_account = get_associated_token_address(owner, mint);
+    let create = Instruction {
+        program_id: associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(context.payer.pubkey(), true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: Vec::new(),
+    };
+    send(context, &[create], &[]).await.unwrap();
+    token_account
+}
+
+/// Freezes or thaws `token_account` with the context payer's freeze authority.
+pub async fn set_frozen(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    frozen: bool,
+) {
+    let payer = context.payer.pubkey();
+    let instruction = if frozen {
+        spl_token::instruction::freeze_account(&spl_token::ID, token_account, mint, &payer, &[])
+    } else {
+        spl_token::instruction::thaw_account(&spl_token::ID, token_account, mint, &payer, &[])
+    }
+    .unwrap();
+    send(context, &[instruction], &[]).await.unwrap();
+}
+
+pub async fn mint_to(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) {
+    let mint_to = spl_token::instruction::mint_to(
+        &spl_token::ID,
+        mint,
+        destination,
+        &context.payer.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    send(context, &[mint_to], &[]).await.unwrap();
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .expect("token account should exist");
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub async fn fetch<T: AccountDeserialize>(context: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .expect("program account should exist");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Moves the validator clock forward so deadline checks see `seconds` elapse.
+pub async fn advance_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
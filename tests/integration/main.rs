@@ -0,0 +1,11 @@
+//! BanksClient-backed integration tests that run the real program, including
+//! its token CPIs, inside `solana-program-test`.
+
+mod admin_flow;
+mod cancel_flow;
+mod close_flow;
+mod dispute_flow;
+mod fixtures;
+mod provider_flow;
+mod purchase_flow;
+mod shipment_flow;
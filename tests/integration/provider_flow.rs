@@ -0,0 +1,304 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, pda, ConfirmationMode, DeliveryRegion, GlobalState,
+    LogisticsProviderAccount, TradeAccount,
+};
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::fixtures::*;
+use crate::purchase_flow::*;
+
+pub(crate) const PROVIDER_STAKE: u64 = 800_000;
+pub(crate) const SLASH_BPS: u16 = 2_500;
+const STAKE_COOLDOWN_SECONDS: i64 = 86_400;
+
+/// Takes the stake in `mint` and registers `provider` with it.
+pub(crate) async fn register_staked_provider(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    provider: &Keypair,
+) {
+    let set_stake_config = program_instruction(
+        accounts::SetStakeConfig {
+            global_state: pda::global_state_pda().0,
+            stake_mint: *mint,
+            authority: context.payer.pubkey(),
+        },
+        instruction::SetStakeConfig {
+            provider_stake_amount: PROVIDER_STAKE,
+            slash_bps: SLASH_BPS,
+            stake_cooldown_seconds: STAKE_COOLDOWN_SECONDS,
+        },
+    );
+    send(context, &[set_stake_config], &[]).await.unwrap();
+
+    let provider_token_account = create_token_account(context, mint, &provider.pubkey()).await;
+    mint_to(context, mint, &provider_token_account, PROVIDER_STAKE).await;
+    let register = program_instruction(
+        accounts::RegisterLogisticsProvider {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider.pubkey()).0,
+            stake_vault: pda::stake_vault_pda(&provider.pubkey()).0,
+            stake_mint: *mint,
+            provider_token_account,
+            payout_token_account: None,
+            provider: provider.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterLogisticsProvider {},
+    );
+    send(context, &[register], &[provider]).await.unwrap();
+}
+
+async fn provider_token_account(context: &mut ProgramTestContext, market: &Market) -> Pubkey {
+    create_token_account(context, &market.mint, &market.provider.pubkey()).await
+}
+
+fn deregister_instruction(market: &Market) -> Instruction {
+    let provider = market.provider.pubkey();
+    program_instruction(
+        accounts::DeregisterLogisticsProvider {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            provider,
+        },
+        instruction::DeregisterLogisticsProvider {},
+    )
+}
+
+fn withdraw_stake_instruction(market: &Market, provider_token_account: Pubkey) -> Instruction {
+    let provider = market.provider.pubkey();
+    program_instruction(
+        accounts::WithdrawStake {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            stake_vault: pda::stake_vault_pda(&provider).0,
+            provider_token_account,
+            provider,
+            token_program: token::ID,
+        },
+        instruction::WithdrawStake {},
+    )
+}
+
+#[tokio::test]
+async fn stake_stays_locked_through_the_cooldown() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let provider_token_account = provider_token_account(&mut context, &market).await;
+
+    // A registered provider cannot withdraw at all
+    let withdraw = withdraw_stake_instruction(&market, provider_token_account);
+    assert!(send(&mut context, &[withdraw], &[&market.provider])
+        .await
+        .is_err());
+
+    let deregister = deregister_instruction(&market);
+    send(&mut context, &[deregister], &[&market.provider])
+        .await
+        .unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    let withdraw = withdraw_stake_instruction(&market, provider_token_account);
+    assert!(send(&mut context, &[withdraw], &[&market.provider])
+        .await
+        .is_err());
+
+    advance_clock(&mut context, STAKE_COOLDOWN_SECONDS).await;
+    context.get_new_latest_blockhash().await.unwrap();
+    let withdraw = withdraw_stake_instruction(&market, provider_token_account);
+    send(&mut context, &[withdraw], &[&market.provider])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        token_balance(&mut context, &provider_token_account).await,
+        PROVIDER_STAKE
+    );
+    let provider_stake = pda::stake_vault_pda(&market.provider.pubkey()).0;
+    assert_eq!(token_balance(&mut context, &provider_stake).await, 0);
+}
+
+#[tokio::test]
+async fn open_purchase_holds_the_stake_until_it_settles() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let provider_account = pda::logistics_provider_pda(&market.provider.pubkey()).0;
+    let provider_token_account = provider_token_account(&mut context, &market).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+    let provider: LogisticsProviderAccount = fetch(&mut context, &provider_account).await;
+    assert_eq!(provider.open_purchases, 1);
+
+    // Deregistering stops new purchases but the open one keeps the stake
+    // held past the cooldown
+    let deregister = deregister_instruction(&market);
+    send(&mut context, &[deregister], &[&market.provider])
+        .await
+        .unwrap();
+    let buy_trade = buy_instruction(&market, purchase_id + 1, 1);
+    assert!(send(&mut context, &[buy_trade], &[&market.buyer])
+        .await
+        .is_err());
+    advance_clock(&mut context, STAKE_COOLDOWN_SECONDS).await;
+    let withdraw = withdraw_stake_instruction(&market, provider_token_account);
+    assert!(send(&mut context, &[withdraw], &[&market.provider])
+        .await
+        .is_err());
+
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer])
+        .await
+        .unwrap();
+    let provider: LogisticsProviderAccount = fetch(&mut context, &provider_account).await;
+    assert_eq!(provider.open_purchases, 0);
+
+    context.get_new_latest_blockhash().await.unwrap();
+    let withdraw = withdraw_stake_instruction(&market, provider_token_account);
+    send(&mut context, &[withdraw], &[&market.provider])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut context, &provider_token_account).await,
+        PROVIDER_STAKE
+    );
+}
+
+fn set_profile_instruction(
+    market: &Market,
+    regions: u16,
+    base_price: u64,
+    price_per_kg: u64,
+) -> Instruction {
+    let provider = market.provider.pubkey();
+    program_instruction(
+        accounts::SetProviderProfile {
+            global_state: pda::global_state_pda().0,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            provider,
+        },
+        instruction::SetProviderProfile {
+            regions,
+            base_price,
+            price_per_kg,
+        },
+    )
+}
+
+/// Lists another trade from the market's seller with a single provider.
+/// `weight_pricing` is the unit weight in grams and per-kg rate.
+pub(crate) async fn list_trade(
+    context: &mut ProgramTestContext,
+    market: &Market,
+    provider: Pubkey,
+    logistics_cost: u64,
+    weight_pricing: Option<(u64, u64)>,
+    ship_to_regions: u16,
+) -> std::result::Result<TradeAccount, BanksClientError> {
+    let global_state: GlobalState = fetch(context, &pda::global_state_pda().0).await;
+    let trade_id = global_state.trade_counter + 1;
+    let (weight_grams, logistics_rates_per_kg) = match weight_pricing {
+        Some((weight_grams, rate_per_kg)) => (weight_grams, vec![rate_per_kg]),
+        None => (0, Vec::new()),
+    };
+    let mut create_trade = program_instruction(
+        accounts::CreateTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller: market.seller.pubkey(),
+            token_mint: market.mint,
+            allowed_mint: pda::allowed_mint_pda(&market.mint).0,
+            category_index: None,
+            storefront: None,
+            admin: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateTrade {
+            product_cost: PRODUCT_COST,
+            logistics_providers: vec![provider],
+            logistics_costs: vec![logistics_cost],
+            total_quantity: 10,
+            payout_splits: Vec::new(),
+            weight_grams,
+            logistics_rates_per_kg,
+            category: 0,
+            tag_hashes: Vec::new(),
+            price_feed: None,
+            accepted_mints: Vec::new(),
+            insurance_bps: 0,
+            max_per_buyer: None,
+            ship_to_regions,
+            requires_proof_of_delivery: false,
+            confirmation_mode: ConfirmationMode::BuyerConfirms,
+        },
+    );
+    create_trade.accounts.push(AccountMeta::new_readonly(
+        pda::logistics_provider_pda(&provider).0,
+        false,
+    ));
+    send(context, &[create_trade], &[]).await?;
+    Ok(fetch(context, &pda::trade_pda(trade_id).0).await)
+}
+
+#[tokio::test]
+async fn listing_takes_the_providers_published_rate() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let provider = market.provider.pubkey();
+    let regions = DeliveryRegion::Europe.mask() | DeliveryRegion::Africa.mask();
+    let set_profile = set_profile_instruction(&market, regions, 300_000, 50_000);
+    send(&mut context, &[set_profile], &[&market.provider])
+        .await
+        .unwrap();
+
+    // A zero cost or rate takes the published one
+    let europe = DeliveryRegion::Europe.mask();
+    let trade = list_trade(&mut context, &market, provider, 0, None, europe)
+        .await
+        .unwrap();
+    assert_eq!(trade.logistics_costs, vec![300_000]);
+    let trade = list_trade(&mut context, &market, provider, 0, Some((2_000, 0)), 0)
+        .await
+        .unwrap();
+    assert_eq!(trade.logistics_costs, vec![300_000]);
+    assert_eq!(trade.logistics_rates_per_kg, vec![50_000]);
+    let trade = list_trade(&mut context, &market, provider, 300_000, None, 0)
+        .await
+        .unwrap();
+    assert_eq!(trade.logistics_costs, vec![300_000]);
+
+    // Any other rate, or a listing outside the provider's regions, is refused
+    let undercut = list_trade(&mut context, &market, provider, 250_000, None, 0).await;
+    assert!(undercut.is_err());
+    let heavier = list_trade(&mut context, &market, provider, 0, Some((2_000, 40_000)), 0).await;
+    assert!(heavier.is_err());
+    let asia = DeliveryRegion::Asia.mask();
+    let out_of_region = list_trade(&mut context, &market, provider, 0, None, asia).await;
+    assert!(out_of_region.is_err());
+}
+
+#[tokio::test]
+async fn listing_requires_registered_providers() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+
+    let unregistered = list_trade(&mut context, &market, Pubkey::new_unique(), 100, None, 0).await;
+    assert!(unregistered.is_err());
+
+    // A provider without a published profile is listed at the seller's cost
+    let provider = market.provider.pubkey();
+    let trade = list_trade(&mut context, &market, provider, 123_000, None, 0)
+        .await
+        .unwrap();
+    assert_eq!(trade.logistics_costs, vec![123_000]);
+
+    let deregister = deregister_instruction(&market);
+    send(&mut context, &[deregister], &[&market.provider])
+        .await
+        .unwrap();
+    let deregistered = list_trade(&mut context, &market, provider, 123_000, None, 0).await;
+    assert!(deregistered.is_err());
+}
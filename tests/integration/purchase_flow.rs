@@ -0,0 +1,702 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, spl_token};
+use dezenmart_rust_smart_contract::dezenmart_logistics::{ESCROW_FEE_PERCENT, MAX_ESCROW_FEE_BPS};
+use dezenmart_rust_smart_contract::{
+    accounts, instruction, pda, settlement, ClaimableBalance, ConfirmationMode, DeliveryRegion,
+    GlobalState, PurchaseAccount,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::fixtures::*;
+use crate::provider_flow::register_staked_provider;
+
+pub(crate) const PRODUCT_COST: u64 = 1_000_000;
+pub(crate) const LOGISTICS_COST: u64 = 200_000;
+const BUYER_FUNDS: u64 = 5_000_000;
+/// Ceiling on the compute units one `buy_trade` may use. Raise it deliberately
+/// when a change needs the headroom, never to silence a regression.
+const BUY_TRADE_COMPUTE_BUDGET: u64 = 80_000;
+
+pub(crate) struct Market {
+    pub(crate) mint: Pubkey,
+    pub(crate) seller: Keypair,
+    pub(crate) provider: Keypair,
+    pub(crate) buyer: Keypair,
+    pub(crate) buyer_token_account: Pubkey,
+    pub(crate) trade_id: u64,
+}
+
+impl Market {
+    /// The same market, trading through another listing `trade_id`.
+    pub(crate) fn with_trade(&self, trade_id: u64) -> Market {
+        Market {
+            mint: self.mint,
+            seller: self.seller.insecure_clone(),
+            provider: self.provider.insecure_clone(),
+            buyer: self.buyer.insecure_clone(),
+            buyer_token_account: self.buyer_token_account,
+            trade_id,
+        }
+    }
+}
+
+pub(crate) async fn create_market(context: &mut ProgramTestContext) -> Market {
+    let mint = create_mint(context).await;
+    let seller = funded_wallet(context, 1_000_000_000).await;
+    let provider = funded_wallet(context, 1_000_000_000).await;
+    let buyer = funded_wallet(context, 1_000_000_000).await;
+    let buyer_token_account = create_token_account(context, &mint, &buyer.pubkey()).await;
+    mint_to(context, &mint, &buyer_token_account, BUYER_FUNDS).await;
+
+    let add_allowed_mint = program_instruction(
+        accounts::AddAllowedMint {
+            global_state: pda::global_state_pda().0,
+            allowed_mint: pda::allowed_mint_pda(&mint).0,
+            token_mint: mint,
+            authority: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::AddAllowedMint {},
+    );
+    let create_escrow_vault = program_instruction(
+        accounts::CreateEscrowVault {
+            global_state: pda::global_state_pda().0,
+            allowed_mint: pda::allowed_mint_pda(&mint).0,
+            escrow_token_account: pda::escrow_vault_pda(&mint).0,
+            token_mint: mint,
+            payer: context.payer.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateEscrowVault {},
+    );
+    let create_fee_vault = program_instruction(
+        accounts::CreateFeeVault {
+            allowed_mint: pda::allowed_mint_pda(&mint).0,
+            fee_vault: pda::fee_vault_pda(&mint).0,
+            token_mint: mint,
+            payer: context.payer.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateFeeVault {},
+    );
+    send(
+        context,
+        &[add_allowed_mint, create_escrow_vault, create_fee_vault],
+        &[],
+    )
+    .await
+    .unwrap();
+    create_associated_token_account(context, &mint, &seller.pubkey()).await;
+    create_associated_token_account(context, &mint, &provider.pubkey()).await;
+
+    let register_buyer = program_instruction(
+        accounts::RegisterBuyer {
+            buyer_account: pda::buyer_pda(&buyer.pubkey()).0,
+            buyer: buyer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::RegisterBuyer {},
+    );
+    send(context, &[register_buyer], &[&buyer]).await.unwrap();
+    register_staked_provider(context, &mint, &provider).await;
+
+    let global_state: GlobalState = fetch(context, &pda::global_state_pda().0).await;
+    let trade_id = global_state.trade_counter + 1;
+    let mut create_trade = program_instruction(
+        accounts::CreateTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(trade_id).0,
+            seller: seller.pubkey(),
+            token_mint: mint,
+            allowed_mint: pda::allowed_mint_pda(&mint).0,
+            category_index: None,
+            storefront: None,
+            admin: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateTrade {
+            product_cost: PRODUCT_COST,
+            logistics_providers: vec![provider.pubkey()],
+            logistics_costs: vec![LOGISTICS_COST],
+            total_quantity: 10,
+            payout_splits: Vec::new(),
+            weight_grams: 0,
+            logistics_rates_per_kg: Vec::new(),
+            category: 0,
+            tag_hashes: Vec::new(),
+            price_feed: None,
+            accepted_mints: Vec::new(),
+            insurance_bps: 0,
+            max_per_buyer: None,
+            ship_to_regions: 0,
+            requires_proof_of_delivery: false,
+            confirmation_mode: ConfirmationMode::BuyerConfirms,
+        },
+    );
+    create_trade.accounts.push(AccountMeta::new_readonly(
+        pda::logistics_provider_pda(&provider.pubkey()).0,
+        false,
+    ));
+    send(context, &[create_trade], &[]).await.unwrap();
+
+    Market {
+        mint,
+        seller,
+        provider,
+        buyer,
+        buyer_token_account,
+        trade_id,
+    }
+}
+
+pub(crate) async fn buy(context: &mut ProgramTestContext, market: &Market, quantity: u64) -> u64 {
+    let global_state: GlobalState = fetch(context, &pda::global_state_pda().0).await;
+    let purchase_id = global_state.purchase_counter + 1;
+    let buy_trade = buy_instruction(market, purchase_id, quantity);
+    send(context, &[buy_trade], &[&market.buyer]).await.unwrap();
+    purchase_id
+}
+
+pub(crate) fn buy_instruction(market: &Market, purchase_id: u64, quantity: u64) -> Instruction {
+    buy_trade_instruction(market, purchase_id, quantity, false)
+}
+
+/// Buys with the listing's insurance, paying the premium into the mint's pool.
+pub(crate) fn insured_buy_instruction(
+    market: &Market,
+    purchase_id: u64,
+    quantity: u64,
+) -> Instruction {
+    buy_trade_instruction(market, purchase_id, quantity, true)
+}
+
+fn buy_trade_instruction(
+    market: &Market,
+    purchase_id: u64,
+    quantity: u64,
+    insured: bool,
+) -> Instruction {
+    let buyer = market.buyer.pubkey();
+    program_instruction(
+        accounts::BuyTrade {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            buyer_account: pda::buyer_pda(&buyer).0,
+            buyer_token_account: market.buyer_token_account,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            product_escrow: pda::product_escrow_pda(purchase_id).0,
+            logistics_escrow: pda::logistics_escrow_pda(purchase_id).0,
+            token_mint: market.mint,
+            provider_account: pda::logistics_provider_pda(&market.provider.pubkey()).0,
+            allowed_mint: pda::allowed_mint_pda(&market.mint).0,
+            price_feed: None,
+            insurance_pool: insured.then(|| pda::insurance_pool_pda(&market.mint).0),
+            reservation: None,
+            buyer_quota: None,
+            gate_token_account: None,
+            buyer_attestation: None,
+            region_attestation: None,
+            buyer_order_index: None,
+            seller_order_index: None,
+            platform: None,
+            loyalty_account: None,
+            session: None,
+            buyer,
+            authority: buyer,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::BuyTrade {
+            trade_id: market.trade_id,
+            quantity,
+            logistics_provider: market.provider.pubkey(),
+            logistics_provider_index: 0,
+            insured,
+            buyer_region: DeliveryRegion::Europe,
+            memo_hash: None,
+        },
+    )
+}
+
+pub(crate) fn confirm_instruction(market: &Market, purchase_id: u64) -> Instruction {
+    confirm_delivery_instruction(market, purchase_id, false)
+}
+
+/// Confirms without the seller's or provider's token accounts, deferring
+/// their payouts to their claimable balances.
+fn deferred_confirm_instruction(market: &Market, purchase_id: u64) -> Instruction {
+    confirm_delivery_instruction(market, purchase_id, true)
+}
+
+fn confirm_delivery_instruction(market: &Market, purchase_id: u64, deferred: bool) -> Instruction {
+    let seller = market.seller.pubkey();
+    let provider = market.provider.pubkey();
+    program_instruction(
+        accounts::ConfirmDeliveryAndPurchase {
+            purchase_account: pda::purchase_pda(purchase_id).0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            product_escrow: Some(pda::product_escrow_pda(purchase_id).0),
+            logistics_escrow: Some(pda::logistics_escrow_pda(purchase_id).0),
+            seller,
+            logistics_provider: provider,
+            token_mint: market.mint,
+            seller_token_account: (!deferred)
+                .then(|| get_associated_token_address(&seller, &market.mint)),
+            seller_payout_token_account: None,
+            logistics_token_account: (!deferred)
+                .then(|| get_associated_token_address(&provider, &market.mint)),
+            logistics_payout_token_account: None,
+            provider_account: pda::logistics_provider_pda(&provider).0,
+            seller_claimable: deferred.then(|| pda::claimable_pda(&seller, &market.mint).0),
+            logistics_claimable: deferred.then(|| pda::claimable_pda(&provider, &market.mint).0),
+            fee_vault: pda::fee_vault_pda(&market.mint).0,
+            platform_claimable: None,
+            buyer_order_index: None,
+            seller_order_index: None,
+            global_state: pda::global_state_pda().0,
+            seller_account: None,
+            swap_program: None,
+            seller_swap_token_account: None,
+            stats: None,
+            buyer_account: None,
+            session: None,
+            buyer: market.buyer.pubkey(),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ConfirmDeliveryAndPurchase {
+            purchase_id,
+            swap_route: None,
+        },
+    )
+}
+
+fn confirm_batch_instruction(market: &Market, purchase_ids: &[u64]) -> Instruction {
+    let mut ix = program_instruction(
+        accounts::ConfirmDeliveriesBatch {
+            global_state: pda::global_state_pda().0,
+            trade_account: pda::trade_pda(market.trade_id).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            seller: market.seller.pubkey(),
+            logistics_provider: market.provider.pubkey(),
+            token_mint: market.mint,
+            seller_token_account: Some(get_associated_token_address(
+                &market.seller.pubkey(),
+                &market.mint,
+            )),
+            seller_payout_token_account: None,
+            logistics_token_account: Some(get_associated_token_address(
+                &market.provider.pubkey(),
+                &market.mint,
+            )),
+            logistics_payout_token_account: None,
+            provider_account: pda::logistics_provider_pda(&market.provider.pubkey()).0,
+            seller_claimable: None,
+            logistics_claimable: None,
+            fee_vault: pda::fee_vault_pda(&market.mint).0,
+            buyer_order_index: None,
+            seller_order_index: None,
+            seller_account: None,
+            stats: None,
+            buyer: market.buyer.pubkey(),
+            token_program: token::ID,
+        },
+        instruction::ConfirmDeliveriesBatch {
+            trade_id: market.trade_id,
+            purchase_count: purchase_ids.len() as u8,
+        },
+    );
+    for purchase_id in purchase_ids {
+        ix.accounts.extend([
+            AccountMeta::new(pda::purchase_pda(*purchase_id).0, false),
+            AccountMeta::new(pda::product_escrow_pda(*purchase_id).0, false),
+            AccountMeta::new(pda::logistics_escrow_pda(*purchase_id).0, false),
+        ]);
+    }
+    ix
+}
+
+#[tokio::test]
+async fn buy_holds_funds_in_escrow() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+
+    let purchase_id = buy(&mut context, &market, 2).await;
+
+    let total = 2 * (PRODUCT_COST + LOGISTICS_COST);
+    let product_escrow = pda::product_escrow_pda(purchase_id).0;
+    let logistics_escrow = pda::logistics_escrow_pda(purchase_id).0;
+    assert_eq!(
+        token_balance(&mut context, &product_escrow).await,
+        2 * PRODUCT_COST
+    );
+    assert_eq!(
+        token_balance(&mut context, &logistics_escrow).await,
+        2 * LOGISTICS_COST
+    );
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        BUYER_FUNDS - total
+    );
+
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert_eq!(purchase.total_amount, total);
+    assert_eq!(purchase.buyer, market.buyer.pubkey());
+    assert!(!purchase.settled);
+}
+
+#[tokio::test]
+async fn buy_then_confirm_pays_seller_and_provider() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 2).await;
+
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+
+    // Logistics is priced per unit, so two units ship at twice the cost
+    let payouts =
+        settlement::compute_payouts(PRODUCT_COST, 2 * LOGISTICS_COST, 2, ESCROW_FEE_PERCENT)
+            .unwrap();
+    let seller_ata = get_associated_token_address(&market.seller.pubkey(), &market.mint);
+    let provider_ata = get_associated_token_address(&market.provider.pubkey(), &market.mint);
+    let escrow = pda::escrow_vault_pda(&market.mint).0;
+    let fee_vault = pda::fee_vault_pda(&market.mint).0;
+
+    assert_eq!(
+        token_balance(&mut context, &seller_ata).await,
+        payouts.seller_amount
+    );
+    assert_eq!(
+        token_balance(&mut context, &provider_ata).await,
+        payouts.logistics_amount
+    );
+    assert_eq!(token_balance(&mut context, &escrow).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &fee_vault).await,
+        payouts.fee_amount
+    );
+
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert!(purchase.settled);
+    assert!(purchase.delivered_and_confirmed);
+}
+
+#[tokio::test]
+async fn confirm_twice_is_rejected() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+
+    // A fresh blockhash keeps the retry from being deduplicated as the same transaction
+    context.get_new_latest_blockhash().await.unwrap();
+    let confirm = confirm_instruction(&market, purchase_id);
+    assert!(send(&mut context, &[confirm], &[&market.buyer]).await.is_err());
+}
+
+#[tokio::test]
+async fn batch_confirm_settles_every_purchase() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let first = buy(&mut context, &market, 2).await;
+    let second = buy(&mut context, &market, 1).await;
+
+    let confirm = confirm_batch_instruction(&market, &[first, second]);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+
+    let first_payouts =
+        settlement::compute_payouts(PRODUCT_COST, 2 * LOGISTICS_COST, 2, ESCROW_FEE_PERCENT)
+            .unwrap();
+    let second_payouts =
+        settlement::compute_payouts(PRODUCT_COST, LOGISTICS_COST, 1, ESCROW_FEE_PERCENT).unwrap();
+    let seller_ata = get_associated_token_address(&market.seller.pubkey(), &market.mint);
+    let provider_ata = get_associated_token_address(&market.provider.pubkey(), &market.mint);
+    assert_eq!(
+        token_balance(&mut context, &seller_ata).await,
+        first_payouts.seller_amount + second_payouts.seller_amount
+    );
+    assert_eq!(
+        token_balance(&mut context, &provider_ata).await,
+        first_payouts.logistics_amount + second_payouts.logistics_amount
+    );
+    assert_eq!(
+        token_balance(&mut context, &pda::escrow_vault_pda(&market.mint).0).await,
+        0
+    );
+
+    for purchase_id in [first, second] {
+        let purchase: PurchaseAccount =
+            fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+        assert!(purchase.settled);
+        assert!(purchase.delivered_and_confirmed);
+        assert!(!purchase.settlement_locked);
+    }
+
+    // Passing a purchase twice fails on its second settlement
+    let third = buy(&mut context, &market, 1).await;
+    let confirm = confirm_batch_instruction(&market, &[third, third]);
+    assert!(send(&mut context, &[confirm], &[&market.buyer]).await.is_err());
+}
+
+#[tokio::test]
+async fn purchases_lock_in_the_governed_fee() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let before = buy(&mut context, &market, 1).await;
+
+    let set_fee = program_instruction(
+        accounts::SetFee {
+            global_state: pda::global_state_pda().0,
+            authority: context.payer.pubkey(),
+        },
+        instruction::SetFee { fee_bps: 400 },
+    );
+    send(&mut context, &[set_fee], &[]).await.unwrap();
+    let after = buy(&mut context, &market, 1).await;
+
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(before).0).await;
+    assert_eq!(purchase.fee_bps, ESCROW_FEE_PERCENT as u16);
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(after).0).await;
+    assert_eq!(purchase.fee_bps, 400);
+
+    // Only the governing authority sets the fee, and never above the cap
+    let outsider = funded_wallet(&mut context, 1_000_000_000).await;
+    let set_fee = program_instruction(
+        accounts::SetFee {
+            global_state: pda::global_state_pda().0,
+            authority: outsider.pubkey(),
+        },
+        instruction::SetFee { fee_bps: 100 },
+    );
+    assert!(send(&mut context, &[set_fee], &[&outsider]).await.is_err());
+    let set_fee = program_instruction(
+        accounts::SetFee {
+            global_state: pda::global_state_pda().0,
+            authority: context.payer.pubkey(),
+        },
+        instruction::SetFee {
+            fee_bps: MAX_ESCROW_FEE_BPS + 1,
+        },
+    );
+    assert!(send(&mut context, &[set_fee], &[]).await.is_err());
+}
+
+fn set_paused_instruction(authority: Pubkey, paused: bool) -> Instruction {
+    program_instruction(
+        accounts::SetPaused {
+            global_state: pda::global_state_pda().0,
+            authority,
+        },
+        instruction::SetPaused { paused },
+    )
+}
+
+#[tokio::test]
+async fn pause_halts_buying_and_settlement() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 1).await;
+
+    let pause = set_paused_instruction(context.payer.pubkey(), true);
+    send(&mut context, &[pause], &[]).await.unwrap();
+
+    let buy_trade = buy_instruction(&market, purchase_id + 1, 1);
+    assert!(send(&mut context, &[buy_trade], &[&market.buyer]).await.is_err());
+    let confirm = confirm_instruction(&market, purchase_id);
+    assert!(send(&mut context, &[confirm], &[&market.buyer]).await.is_err());
+
+    let resume = set_paused_instruction(context.payer.pubkey(), false);
+    send(&mut context, &[resume], &[]).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+    buy(&mut context, &market, 1).await;
+}
+
+#[tokio::test]
+async fn failed_payout_leg_rolls_back_and_retries_cleanly() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 2).await;
+
+    // The seller is paid before the provider, whose frozen account fails the
+    // second transfer
+    let seller_ata = get_associated_token_address(&market.seller.pubkey(), &market.mint);
+    let provider_ata = get_associated_token_address(&market.provider.pubkey(), &market.mint);
+    set_frozen(&mut context, &market.mint, &provider_ata, true).await;
+
+    let confirm = confirm_instruction(&market, purchase_id);
+    assert!(send(&mut context, &[confirm], &[&market.buyer]).await.is_err());
+
+    // The failed transaction takes the seller's transfer and the committed
+    // settlement lock down with it
+    let product_escrow = pda::product_escrow_pda(purchase_id).0;
+    let logistics_escrow = pda::logistics_escrow_pda(purchase_id).0;
+    assert_eq!(token_balance(&mut context, &seller_ata).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &product_escrow).await,
+        2 * PRODUCT_COST
+    );
+    assert_eq!(
+        token_balance(&mut context, &logistics_escrow).await,
+        2 * LOGISTICS_COST
+    );
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert!(!purchase.settled);
+    assert!(!purchase.settlement_locked);
+
+    // Once the account thaws, the retry pays every leg exactly once
+    set_frozen(&mut context, &market.mint, &provider_ata, false).await;
+    context.get_new_latest_blockhash().await.unwrap();
+    let confirm = confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+
+    let payouts =
+        settlement::compute_payouts(PRODUCT_COST, 2 * LOGISTICS_COST, 2, ESCROW_FEE_PERCENT)
+            .unwrap();
+    assert_eq!(
+        token_balance(&mut context, &seller_ata).await,
+        payouts.seller_amount
+    );
+    assert_eq!(
+        token_balance(&mut context, &provider_ata).await,
+        payouts.logistics_amount
+    );
+    assert_eq!(
+        token_balance(&mut context, &pda::fee_vault_pda(&market.mint).0).await,
+        payouts.fee_amount
+    );
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert!(purchase.settled);
+    assert!(!purchase.settlement_locked);
+}
+
+fn open_claimable_instruction(
+    context: &ProgramTestContext,
+    market: &Market,
+    recipient: Pubkey,
+) -> Instruction {
+    program_instruction(
+        accounts::OpenClaimableBalance {
+            claimable_balance: pda::claimable_pda(&recipient, &market.mint).0,
+            recipient,
+            token_mint: market.mint,
+            payer: context.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::OpenClaimableBalance {},
+    )
+}
+
+fn claim_payout_instruction(
+    market: &Market,
+    recipient: Pubkey,
+    recipient_token_account: Pubkey,
+) -> Instruction {
+    program_instruction(
+        accounts::ClaimPayout {
+            global_state: pda::global_state_pda().0,
+            claimable_balance: pda::claimable_pda(&recipient, &market.mint).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            recipient_token_account,
+            recipient,
+            token_program: token::ID,
+        },
+        instruction::ClaimPayout {},
+    )
+}
+
+#[tokio::test]
+async fn missing_payout_accounts_defer_to_claimable_balances() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let purchase_id = buy(&mut context, &market, 2).await;
+    let seller = market.seller.pubkey();
+    let provider = market.provider.pubkey();
+
+    // Neither the seller nor the provider has a token account to be paid into
+    for wallet in [&market.seller, &market.provider] {
+        let token_account = get_associated_token_address(&wallet.pubkey(), &market.mint);
+        let close = spl_token::instruction::close_account(
+            &spl_token::ID,
+            &token_account,
+            &wallet.pubkey(),
+            &wallet.pubkey(),
+            &[],
+        )
+        .unwrap();
+        send(&mut context, &[close], &[wallet]).await.unwrap();
+    }
+
+    // Settlement needs somewhere to credit them, so their balances are opened
+    let confirm = deferred_confirm_instruction(&market, purchase_id);
+    assert!(send(&mut context, &[confirm], &[&market.buyer]).await.is_err());
+    let open_balances = [seller, provider]
+        .map(|recipient| open_claimable_instruction(&context, &market, recipient));
+    send(&mut context, &open_balances, &[]).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    let confirm = deferred_confirm_instruction(&market, purchase_id);
+    send(&mut context, &[confirm], &[&market.buyer]).await.unwrap();
+
+    // Both payouts wait in the mint vault, credited to their recipients
+    let payouts =
+        settlement::compute_payouts(PRODUCT_COST, 2 * LOGISTICS_COST, 2, ESCROW_FEE_PERCENT)
+            .unwrap();
+    let escrow = pda::escrow_vault_pda(&market.mint).0;
+    assert_eq!(
+        token_balance(&mut context, &escrow).await,
+        payouts.seller_amount + payouts.logistics_amount
+    );
+    let purchase: PurchaseAccount = fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+    assert!(purchase.settled);
+
+    // Each recipient claims into a new account, once
+    for (wallet, amount) in [
+        (&market.seller, payouts.seller_amount),
+        (&market.provider, payouts.logistics_amount),
+    ] {
+        let recipient = wallet.pubkey();
+        let balance: ClaimableBalance = fetch(
+            &mut context,
+            &pda::claimable_pda(&recipient, &market.mint).0,
+        )
+        .await;
+        assert_eq!(balance.amount, amount);
+
+        let token_account = create_token_account(&mut context, &market.mint, &recipient).await;
+        let claim = claim_payout_instruction(&market, recipient, token_account);
+        send(&mut context, &[claim], &[wallet]).await.unwrap();
+        assert_eq!(token_balance(&mut context, &token_account).await, amount);
+
+        context.get_new_latest_blockhash().await.unwrap();
+        let claim = claim_payout_instruction(&market, recipient, token_account);
+        assert!(send(&mut context, &[claim], &[wallet]).await.is_err());
+    }
+    assert_eq!(token_balance(&mut context, &escrow).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build from `anchor build`"]
+async fn buy_trade_stays_within_compute_budget() {
+    let mut context = setup_sbf().await;
+    let market = create_market(&mut context).await;
+    let global_state: GlobalState = fetch(&mut context, &pda::global_state_pda().0).await;
+
+    let buy_trade = buy_instruction(&market, global_state.purchase_counter + 1, 2);
+    let units = compute_units(&mut context, &[buy_trade], &[&market.buyer]).await;
+    assert!(
+        units <= BUY_TRADE_COMPUTE_BUDGET,
+        "buy_trade used {units} compute units, over the {BUY_TRADE_COMPUTE_BUDGET} budget"
+    );
+}
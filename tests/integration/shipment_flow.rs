@@ -0,0 +1,138 @@
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::token;
+use dezenmart_rust_smart_contract::{accounts, instruction, pda, PurchaseAccount, ShipmentAccount};
+use solana_sdk::signature::Signer;
+
+use crate::fixtures::*;
+use crate::provider_flow::list_trade;
+use crate::purchase_flow::*;
+
+/// Merges `purchases`, each paired with its trade id, at `logistics_cost`.
+fn consolidate_instruction(
+    market: &Market,
+    purchases: &[(u64, u64)],
+    logistics_cost: u64,
+) -> Instruction {
+    let provider = market.provider.pubkey();
+    let purchase_ids: Vec<u64> = purchases
+        .iter()
+        .map(|(purchase_id, _)| *purchase_id)
+        .collect();
+    let mut consolidate = program_instruction(
+        accounts::ConsolidateShipments {
+            global_state: pda::global_state_pda().0,
+            shipment_account: pda::shipment_pda(&provider, purchase_ids[0]).0,
+            escrow_token_account: pda::escrow_vault_pda(&market.mint).0,
+            buyer_token_account: market.buyer_token_account,
+            buyer: market.buyer.pubkey(),
+            provider,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ConsolidateShipments {
+            shipment_id: purchase_ids[0],
+            purchase_ids: purchase_ids.clone(),
+            consolidated_logistics_cost: logistics_cost,
+        },
+    );
+    for purchase_id in &purchase_ids {
+        consolidate
+            .accounts
+            .push(AccountMeta::new(pda::purchase_pda(*purchase_id).0, false));
+    }
+    for (_, trade_id) in purchases {
+        consolidate.accounts.push(AccountMeta::new_readonly(
+            pda::trade_pda(*trade_id).0,
+            false,
+        ));
+    }
+    for purchase_id in &purchase_ids {
+        consolidate.accounts.push(AccountMeta::new(
+            pda::logistics_escrow_pda(*purchase_id).0,
+            false,
+        ));
+    }
+    consolidate
+}
+
+#[tokio::test]
+async fn consolidation_merges_one_sellers_listings() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let provider = market.provider.pubkey();
+    let other_listing = list_trade(&mut context, &market, provider, LOGISTICS_COST, None, 0)
+        .await
+        .unwrap();
+    let first = buy(&mut context, &market, 1).await;
+    let second = buy(&mut context, &market.with_trade(other_listing.trade_id), 1).await;
+
+    let buyer_before = token_balance(&mut context, &market.buyer_token_account).await;
+    let purchases = [(first, market.trade_id), (second, other_listing.trade_id)];
+    let consolidate = consolidate_instruction(&market, &purchases, LOGISTICS_COST);
+    send(
+        &mut context,
+        &[consolidate],
+        &[&market.buyer, &market.provider],
+    )
+    .await
+    .unwrap();
+
+    // Both listings ship together, and the saving is refunded to the buyer
+    assert_eq!(
+        token_balance(&mut context, &market.buyer_token_account).await,
+        buyer_before + LOGISTICS_COST
+    );
+    let shipment: ShipmentAccount =
+        fetch(&mut context, &pda::shipment_pda(&provider, first).0).await;
+    assert_eq!(shipment.seller, market.seller.pubkey());
+    assert_eq!(shipment.purchase_ids, vec![first, second]);
+    for purchase_id in [first, second] {
+        let purchase: PurchaseAccount =
+            fetch(&mut context, &pda::purchase_pda(purchase_id).0).await;
+        assert!(purchase.consolidated);
+        assert_eq!(purchase.logistics_cost, LOGISTICS_COST / 2);
+    }
+}
+
+#[tokio::test]
+async fn consolidation_refuses_another_sellers_listing() {
+    let mut context = setup().await;
+    let market = create_market(&mut context).await;
+    let provider = market.provider.pubkey();
+    let mut other_seller = market.with_trade(market.trade_id);
+    other_seller.seller = funded_wallet(&mut context, 1_000_000_000).await;
+    let other_listing = list_trade(
+        &mut context,
+        &other_seller,
+        provider,
+        LOGISTICS_COST,
+        None,
+        0,
+    )
+    .await
+    .unwrap();
+    let first = buy(&mut context, &market, 1).await;
+    let second = buy(&mut context, &market.with_trade(other_listing.trade_id), 1).await;
+
+    let purchases = [(first, market.trade_id), (second, other_listing.trade_id)];
+    let consolidate = consolidate_instruction(&market, &purchases, LOGISTICS_COST);
+    assert!(send(
+        &mut context,
+        &[consolidate],
+        &[&market.buyer, &market.provider]
+    )
+    .await
+    .is_err());
+
+    // Passing the first purchase's trade for both cannot hide the other seller
+    let purchases = [(first, market.trade_id), (second, market.trade_id)];
+    let consolidate = consolidate_instruction(&market, &purchases, LOGISTICS_COST);
+    assert!(send(
+        &mut context,
+        &[consolidate],
+        &[&market.buyer, &market.provider]
+    )
+    .await
+    .is_err());
+}
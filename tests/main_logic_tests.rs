@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod main_logic_tests {
     use anchor_lang::prelude::*;
-    use std::collections::BTreeMap;
 
     // Import all types and constants from main.rs
     use dezenmart_rust_smart_contract::*;
@@ -27,6 +26,7 @@ mod main_logic_tests {
             trade_counter: 0,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         global_state.trade_counter += 1;
@@ -44,6 +44,7 @@ mod main_logic_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // First buyer purchases 4 units with provider 1
@@ -62,6 +63,7 @@ mod main_logic_tests {
             logistics_cost: 100 * 4, // 400
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Update trade
@@ -84,6 +86,7 @@ mod main_logic_tests {
             logistics_cost: 150 * 6, // 900
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Update trade - should become inactive
@@ -96,7 +99,7 @@ mod main_logic_tests {
 
         // Verify state
         assert_eq!(trade_account.remaining_quantity, 0);
-        assert_eq!(trade_account.active, false);
+        assert!(!trade_account.active);
         assert_eq!(trade_account.purchase_ids.len(), 2);
         assert_eq!(purchase1.total_amount, 4400);
         assert_eq!(purchase2.total_amount, 6900);
@@ -121,6 +124,7 @@ mod main_logic_tests {
             logistics_cost: 500,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         let mut trade_account = TradeAccount {
@@ -136,6 +140,7 @@ mod main_logic_tests {
             purchase_ids: vec![1],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Test scenario 1: Buyer wins dispute (refund)
@@ -152,15 +157,15 @@ mod main_logic_tests {
         }
 
         assert_eq!(trade_account.remaining_quantity, 10); // 5 + 5 restored
-        assert_eq!(trade_account.active, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(trade_account.active);
+        assert!(purchase_account.settled);
 
         // Reset for scenario 2: Seller wins dispute
         trade_account.remaining_quantity = 5;
         purchase_account.settled = false;
         purchase_account.delivered_and_confirmed = false;
 
-        let winner = seller;
+        let _winner = seller;
         purchase_account.delivered_and_confirmed = true;
         purchase_account.settled = true;
 
@@ -174,18 +179,18 @@ mod main_logic_tests {
         assert_eq!(seller_amount, 4875); // 5000 - 125
         assert_eq!(logistics_escrow_fee, 12); // 2.5% of 500
         assert_eq!(logistics_payout, 488); // 500 - 12
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.settled);
 
         // Reset for scenario 3: Logistics provider wins dispute
         purchase_account.settled = false;
         purchase_account.delivered_and_confirmed = false;
 
-        let winner = logistics_provider;
+        let _winner = logistics_provider;
         purchase_account.delivered_and_confirmed = true;
         purchase_account.settled = true;
 
         // Same calculation as seller wins
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.settled);
     }
 
     #[test]
@@ -199,6 +204,7 @@ mod main_logic_tests {
             is_registered: true,
             purchase_ids: Vec::new(),
             bump: 255,
+            ..Default::default()
         };
 
         let mut global_state = GlobalState {
@@ -206,6 +212,7 @@ mod main_logic_tests {
             trade_counter: 1,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         let mut trade_account = TradeAccount {
@@ -221,10 +228,11 @@ mod main_logic_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Make multiple purchases
-        for i in 1..=3 {
+        for _i in 1..=3 {
             global_state.purchase_counter += 1;
             let purchase_id = global_state.purchase_counter;
 
@@ -245,7 +253,7 @@ mod main_logic_tests {
         }
 
         assert_eq!(trade_account.remaining_quantity, 4); // 10 - (2*3)
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
         assert_eq!(trade_account.purchase_ids.len(), 3);
         assert_eq!(buyer_account.purchase_ids.len(), 3);
         assert_eq!(global_state.purchase_counter, 3);
@@ -269,6 +277,7 @@ mod main_logic_tests {
             logistics_cost: 800,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         let mut trade_account = TradeAccount {
@@ -284,6 +293,7 @@ mod main_logic_tests {
             purchase_ids: vec![1],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Cancel the purchase
@@ -295,9 +305,9 @@ mod main_logic_tests {
             trade_account.active = true;
         }
 
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.settled);
         assert_eq!(trade_account.remaining_quantity, 10); // 2 + 8 restored
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
     }
 
     #[test]
@@ -318,6 +328,7 @@ mod main_logic_tests {
             purchase_ids: vec![1, 2],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Simulate refund/cancellation that restores quantity
@@ -329,15 +340,15 @@ mod main_logic_tests {
         }
 
         assert_eq!(trade_account.remaining_quantity, 5);
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
     }
 
     #[test]
     fn test_escrow_fee_withdrawal_calculations() {
         // Simulate multiple completed transactions generating fees
-        let product_costs = vec![1000u64, 2000u64, 1500u64];
-        let logistics_costs = vec![200u64, 300u64, 250u64];
-        let quantities = vec![2u64, 1u64, 3u64];
+        let product_costs = [1000u64, 2000u64, 1500u64];
+        let logistics_costs = [200u64, 300u64, 250u64];
+        let quantities = [2u64, 1u64, 3u64];
 
         let mut total_product_fees = 0u64;
         let mut total_logistics_fees = 0u64;
@@ -383,7 +394,6 @@ mod main_logic_tests {
         assert!(result);
 
         // Test MAX_PURCHASE_IDS limit
-        let mut purchase_ids: Vec<u64> = Vec::new();
         let mut trade_account = TradeAccount {
             trade_id: 1,
             seller: create_test_pubkey(1),
@@ -397,6 +407,7 @@ mod main_logic_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Simulate adding purchases up to the limit
@@ -485,6 +496,7 @@ mod main_logic_tests {
             trade_counter: 0,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         // Create multiple logistics providers
@@ -511,16 +523,17 @@ mod main_logic_tests {
                 purchase_ids: Vec::new(),
                 token_mint: create_test_pubkey(20 + i),
                 bump: 255,
+                ..Default::default()
             };
             trades.push(trade);
         }
 
         // Create buyers and make purchases
-        let buyers = vec![create_test_pubkey(30), create_test_pubkey(31), create_test_pubkey(32)];
+        let buyers = [create_test_pubkey(30), create_test_pubkey(31), create_test_pubkey(32)];
         let mut purchases = Vec::new();
 
         for (buyer_idx, buyer) in buyers.iter().enumerate() {
-            for (trade_idx, trade) in trades.iter_mut().enumerate() {
+            for trade in trades.iter_mut() {
                 global_state.purchase_counter += 1;
                 let quantity = 2u64 + buyer_idx as u64;
 
@@ -542,6 +555,7 @@ mod main_logic_tests {
                     logistics_cost: logistics_cost * quantity,
                     settled: false,
                     bump: 255,
+                    ..Default::default()
                 };
 
                 trade.remaining_quantity -= quantity;
@@ -566,7 +580,7 @@ mod main_logic_tests {
         for trade in &trades {
             let expected_remaining = 20 - (2 + 3 + 4); // 11
             assert_eq!(trade.remaining_quantity, expected_remaining);
-            assert_eq!(trade.active, true); // Still active since not sold out
+            assert!(trade.active); // Still active since not sold out
             assert_eq!(trade.purchase_ids.len(), 3);
         }
 
@@ -574,7 +588,7 @@ mod main_logic_tests {
         let mut total_confirmed = 0;
         let mut total_disputed = 0;
 
-        for (i, purchase) in purchases.iter().enumerate() {
+        for (i, _purchase) in purchases.iter().enumerate() {
             if i % 3 == 0 {
                 // Confirm delivery (every 3rd purchase)
                 total_confirmed += 1;
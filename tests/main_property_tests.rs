@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use proptest::prelude::*;
+
+use dezenmart_rust_smart_contract::dezenmart_logistics::*;
+use dezenmart_rust_smart_contract::*;
+
+fn payout_splits(bps: Vec<u16>) -> Vec<PayoutSplit> {
+    bps.into_iter()
+        .map(|bps| PayoutSplit {
+            recipient: Pubkey::new_unique(),
+            bps,
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn settlement_amounts_sum_to_escrowed_amount(
+        product_cost in 0u64..=u64::MAX / 1_000_000,
+        quantity in 1u64..=1_000_000,
+        logistics_cost in any::<u64>(),
+        fee_bps in 0u64..=BASIS_POINTS,
+    ) {
+        // The product cost bound keeps the product total in range, so only
+        // adding logistics can overflow the escrowed amount
+        let product_total = product_cost * quantity;
+        let result = settlement::compute_payouts(product_cost, logistics_cost, quantity, fee_bps);
+
+        match product_total.checked_add(logistics_cost) {
+            None => prop_assert!(result.is_err()),
+            Some(_) => {
+                prop_assert!(result.is_ok());
+                let amounts = result.unwrap();
+                let escrowed = product_total as u128 + logistics_cost as u128;
+                let paid = amounts.seller_amount as u128
+                    + amounts.logistics_amount as u128
+                    + amounts.fee_amount as u128;
+                prop_assert_eq!(paid, escrowed);
+                prop_assert!(amounts.seller_amount <= product_total);
+                prop_assert!(amounts.logistics_amount <= logistics_cost);
+                // Each sub-vault drains exactly into its payout and fee share
+                prop_assert_eq!(amounts.seller_amount + amounts.product_fee, product_total);
+                prop_assert_eq!(amounts.logistics_amount + amounts.logistics_fee, logistics_cost);
+                prop_assert_eq!(amounts.product_fee + amounts.logistics_fee, amounts.fee_amount);
+            }
+        }
+    }
+
+    #[test]
+    fn settlement_fee_never_exceeds_rate(
+        product_cost in 0u64..=u32::MAX as u64,
+        quantity in 1u64..=1_000,
+        logistics_cost in 0u64..=u32::MAX as u64,
+        fee_bps in 0u64..=BASIS_POINTS,
+    ) {
+        let amounts =
+            settlement::compute_payouts(product_cost, logistics_cost, quantity, fee_bps).unwrap();
+        let gross = (product_cost * quantity + logistics_cost) as u128;
+        prop_assert!(amounts.fee_amount as u128 * BASIS_POINTS as u128 <= gross * fee_bps as u128);
+    }
+
+    #[test]
+    fn settlement_rejects_product_total_overflow(
+        product_cost in 2u64..=u64::MAX,
+        fee_bps in 0u64..=BASIS_POINTS,
+    ) {
+        let quantity = u64::MAX / product_cost + 1;
+        prop_assert!(settlement::compute_payouts(product_cost, 0, quantity, fee_bps).is_err());
+    }
+
+    #[test]
+    fn settlement_rejects_fee_above_basis_points(fee_bps in BASIS_POINTS + 1..=u64::MAX) {
+        prop_assert!(settlement::compute_payouts(100, 100, 1, fee_bps).is_err());
+    }
+
+    #[test]
+    fn split_amounts_sum_to_seller_amount(
+        amount in any::<u64>(),
+        weights in prop::collection::vec(1u16..=100, 1..=MAX_PAYOUT_SPLITS),
+    ) {
+        // Scale arbitrary weights so the split basis points total exactly 10_000
+        let total_weight: u32 = weights.iter().map(|weight| *weight as u32).sum();
+        let mut bps: Vec<u16> = weights
+            .iter()
+            .map(|weight| ((*weight as u32 * BASIS_POINTS as u32) / total_weight) as u16)
+            .collect();
+        let assigned: u32 = bps.iter().map(|bps| *bps as u32).sum();
+        bps[0] += (BASIS_POINTS as u32 - assigned) as u16;
+
+        let shares = compute_split_amounts(amount, &payout_splits(bps));
+        let distributed: u128 = shares.iter().map(|share| *share as u128).sum();
+        prop_assert_eq!(distributed, amount as u128);
+    }
+
+    #[test]
+    fn prorated_logistics_costs_sum_to_new_total(
+        costs in prop::collection::vec(0u64..=u32::MAX as u64, 1..=MAX_CONSOLIDATED_PURCHASES),
+        discount_bps in 0u64..=BASIS_POINTS,
+    ) {
+        let total: u64 = costs.iter().sum();
+        let new_total = total * discount_bps / BASIS_POINTS;
+        let prorated = prorate_logistics_costs(&costs, new_total);
+        prop_assert_eq!(prorated.iter().sum::<u64>(), new_total);
+    }
+}
@@ -26,6 +26,7 @@ mod main_simple_tests {
         pub bump: u8,
     }
 
+    #[allow(dead_code)]
     #[derive(Clone)]
     struct TradeAccount {
         pub trade_id: u64,
@@ -42,6 +43,7 @@ mod main_simple_tests {
         pub bump: u8,
     }
 
+    #[allow(dead_code)]
     #[derive(Clone)]
     struct PurchaseAccount {
         pub purchase_id: u64,
@@ -120,7 +122,7 @@ mod main_simple_tests {
         provider_account.bump = 255;
 
         assert_eq!(provider_account.provider, provider);
-        assert_eq!(provider_account.is_registered, true);
+        assert!(provider_account.is_registered);
         assert_eq!(provider_account.bump, 255);
     }
 
@@ -141,7 +143,7 @@ mod main_simple_tests {
         seller_account.bump = 255;
 
         assert_eq!(seller_account.seller, seller);
-        assert_eq!(seller_account.is_registered, true);
+        assert!(seller_account.is_registered);
         assert_eq!(seller_account.bump, 255);
     }
 
@@ -164,7 +166,7 @@ mod main_simple_tests {
         buyer_account.bump = 255;
 
         assert_eq!(buyer_account.buyer, buyer);
-        assert_eq!(buyer_account.is_registered, true);
+        assert!(buyer_account.is_registered);
         assert_eq!(buyer_account.purchase_ids.len(), 0);
         assert_eq!(buyer_account.bump, 255);
     }
@@ -183,10 +185,10 @@ mod main_simple_tests {
         let product_cost = 1000u64;
 
         // Validation checks from main.rs
-        assert_eq!(logistics_providers.len() == logistics_costs.len(), true);
-        assert_eq!(!logistics_providers.is_empty(), true);
-        assert_eq!(logistics_providers.len() <= MAX_LOGISTICS_PROVIDERS, true);
-        assert_eq!(total_quantity > 0, true);
+        assert!(logistics_providers.len() == logistics_costs.len());
+        assert!(!logistics_providers.is_empty());
+        assert!(logistics_providers.len() <= MAX_LOGISTICS_PROVIDERS);
+        assert!(total_quantity > 0);
 
         // Simulate create_trade logic
         let mut global_state = GlobalState {
@@ -224,7 +226,7 @@ mod main_simple_tests {
         assert_eq!(trade_account.escrow_fee, 25); // 2.5% of 1000
         assert_eq!(trade_account.total_quantity, total_quantity);
         assert_eq!(trade_account.remaining_quantity, total_quantity);
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
         assert_eq!(trade_account.purchase_ids.len(), 0);
         assert_eq!(trade_account.token_mint, token_mint);
     }
@@ -254,10 +256,10 @@ mod main_simple_tests {
         let quantity = 3u64;
 
         // Validation logic from main.rs buy_trade function
-        assert_eq!(quantity > 0, true);
-        assert_eq!(trade_account.active, true);
-        assert_eq!(trade_account.remaining_quantity >= quantity, true);
-        assert_eq!(buyer != trade_account.seller, true);
+        assert!(quantity > 0);
+        assert!(trade_account.active);
+        assert!(trade_account.remaining_quantity >= quantity);
+        assert!(buyer != trade_account.seller);
 
         // Find logistics cost logic from main.rs
         let mut chosen_logistics_cost = 0u64;
@@ -269,7 +271,7 @@ mod main_simple_tests {
                 break;
             }
         }
-        assert_eq!(found, true);
+        assert!(found);
         assert_eq!(chosen_logistics_cost, 100);
 
         // Calculate costs logic from main.rs
@@ -322,7 +324,7 @@ mod main_simple_tests {
         assert_eq!(purchase_account.quantity, quantity);
         assert_eq!(purchase_account.total_amount, total_amount);
         assert_eq!(trade_account.remaining_quantity, 7);
-        assert_eq!(trade_account.active, true); // Still active
+        assert!(trade_account.active); // Still active
         assert_eq!(trade_account.purchase_ids.len(), 1);
     }
 
@@ -348,17 +350,17 @@ mod main_simple_tests {
         };
 
         // Validation logic from main.rs confirm_delivery_and_purchase function
-        assert_eq!(buyer == purchase_account.buyer, true);
-        assert_eq!(!purchase_account.delivered_and_confirmed, true);
-        assert_eq!(!purchase_account.disputed, true);
-        assert_eq!(!purchase_account.settled, true);
+        assert!(buyer == purchase_account.buyer);
+        assert!(!purchase_account.delivered_and_confirmed);
+        assert!(!purchase_account.disputed);
+        assert!(!purchase_account.settled);
 
         // Simulate confirmation logic
         purchase_account.delivered_and_confirmed = true;
         purchase_account.settled = true;
 
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.settled);
 
         // Test payment calculation logic from main.rs
         let trade_account = TradeAccount {
@@ -408,13 +410,13 @@ mod main_simple_tests {
         };
 
         // Validation logic from main.rs raise_dispute function
-        assert_eq!(!purchase_account.delivered_and_confirmed, true);
-        assert_eq!(!purchase_account.disputed, true);
+        assert!(!purchase_account.delivered_and_confirmed);
+        assert!(!purchase_account.disputed);
 
         // Simulate raise_dispute logic
         purchase_account.disputed = true;
 
-        assert_eq!(purchase_account.disputed, true);
+        assert!(purchase_account.disputed);
     }
 
     #[test]
@@ -456,13 +458,13 @@ mod main_simple_tests {
         let winner = buyer;
 
         // Validation logic from main.rs resolve_dispute function
-        assert_eq!(purchase_account.disputed, true);
-        assert_eq!(!purchase_account.settled, true);
+        assert!(purchase_account.disputed);
+        assert!(!purchase_account.settled);
 
         let valid_winner = winner == purchase_account.buyer
             || winner == trade_account.seller
             || winner == purchase_account.chosen_logistics_provider;
-        assert_eq!(valid_winner, true);
+        assert!(valid_winner);
 
         // Simulate resolve_dispute logic
         purchase_account.delivered_and_confirmed = true;
@@ -477,8 +479,8 @@ mod main_simple_tests {
         }
 
         assert_eq!(trade_account.remaining_quantity, 10); // 7 + 3 restored
-        assert_eq!(trade_account.active, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(trade_account.active);
+        assert!(purchase_account.settled);
     }
 
     #[test]
@@ -516,10 +518,10 @@ mod main_simple_tests {
         };
 
         // Validation logic from main.rs cancel_purchase function
-        assert_eq!(buyer == purchase_account.buyer, true);
-        assert_eq!(!purchase_account.delivered_and_confirmed, true);
-        assert_eq!(!purchase_account.disputed, true);
-        assert_eq!(!purchase_account.settled, true);
+        assert!(buyer == purchase_account.buyer);
+        assert!(!purchase_account.delivered_and_confirmed);
+        assert!(!purchase_account.disputed);
+        assert!(!purchase_account.settled);
 
         // Simulate cancel_purchase logic
         purchase_account.delivered_and_confirmed = true;
@@ -530,9 +532,9 @@ mod main_simple_tests {
             trade_account.active = true;
         }
 
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.settled);
         assert_eq!(trade_account.remaining_quantity, 10); // 7 + 3 restored
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
     }
 
     #[test]
@@ -541,11 +543,11 @@ mod main_simple_tests {
         let balance = 1000u64;
 
         // Validation logic from main.rs withdraw_escrow_fees function
-        assert_eq!(balance > 0, true);
+        assert!(balance > 0);
 
         // Test with zero balance
         let zero_balance = 0u64;
-        assert_eq!(zero_balance > 0, false); // Should fail validation
+        assert_eq!(zero_balance, 0); // Should fail validation
     }
 
     #[test]
@@ -553,8 +555,8 @@ mod main_simple_tests {
         // Test all error conditions that would trigger in main.rs
 
         // MismatchedArrays
-        let providers = vec![create_test_pubkey(1), create_test_pubkey(2)];
-        let costs = vec![100]; // Mismatched length
+        let providers = [create_test_pubkey(1), create_test_pubkey(2)];
+        let costs = [100]; // Mismatched length
         assert_ne!(providers.len(), costs.len());
 
         // NoLogisticsProviders
@@ -645,7 +647,7 @@ mod main_simple_tests {
 
         // Register logistics provider (register_logistics_provider function)
         let logistics_provider = create_test_pubkey(2);
-        let provider_account = LogisticsProviderAccount {
+        let _provider_account = LogisticsProviderAccount {
             provider: logistics_provider,
             is_registered: true,
             bump: 255,
@@ -653,7 +655,7 @@ mod main_simple_tests {
 
         // Register seller (register_seller function)
         let seller = create_test_pubkey(3);
-        let seller_account = SellerAccount {
+        let _seller_account = SellerAccount {
             seller,
             is_registered: true,
             bump: 255,
@@ -736,10 +738,10 @@ mod main_simple_tests {
         assert_eq!(global_state.trade_counter, 1);
         assert_eq!(global_state.purchase_counter, 1);
         assert_eq!(trade_account.remaining_quantity, 10); // Restored after dispute
-        assert_eq!(trade_account.active, true);
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.disputed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(trade_account.active);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.disputed);
+        assert!(purchase_account.settled);
         assert_eq!(buyer_account.purchase_ids.len(), 1);
 
         // Test escrow fee withdrawal (withdraw_escrow_fees function)
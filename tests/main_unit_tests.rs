@@ -1,5 +1,4 @@
 use anchor_lang::prelude::*;
-use std::collections::BTreeMap;
 
 #[cfg(test)]
 mod main_tests {
@@ -34,6 +33,7 @@ mod main_tests {
             trade_counter: 999,
             purchase_counter: 999,
             bump: 0,
+            ..Default::default()
         };
 
         // Simulate initialize function logic
@@ -57,6 +57,7 @@ mod main_tests {
             provider: Pubkey::default(),
             is_registered: false,
             bump: 0,
+            ..Default::default()
         };
 
         provider_account.provider = provider;
@@ -64,7 +65,7 @@ mod main_tests {
         provider_account.bump = 255;
 
         assert_eq!(provider_account.provider, provider);
-        assert_eq!(provider_account.is_registered, true);
+        assert!(provider_account.is_registered);
         assert_eq!(provider_account.bump, 255);
     }
 
@@ -77,6 +78,7 @@ mod main_tests {
             seller: Pubkey::default(),
             is_registered: false,
             bump: 0,
+            ..Default::default()
         };
 
         seller_account.seller = seller;
@@ -84,7 +86,7 @@ mod main_tests {
         seller_account.bump = 255;
 
         assert_eq!(seller_account.seller, seller);
-        assert_eq!(seller_account.is_registered, true);
+        assert!(seller_account.is_registered);
         assert_eq!(seller_account.bump, 255);
     }
 
@@ -98,6 +100,7 @@ mod main_tests {
             is_registered: false,
             purchase_ids: vec![1, 2, 3], // Should be reset
             bump: 0,
+            ..Default::default()
         };
 
         buyer_account.buyer = buyer;
@@ -106,7 +109,7 @@ mod main_tests {
         buyer_account.bump = 255;
 
         assert_eq!(buyer_account.buyer, buyer);
-        assert_eq!(buyer_account.is_registered, true);
+        assert!(buyer_account.is_registered);
         assert_eq!(buyer_account.purchase_ids.len(), 0);
         assert_eq!(buyer_account.bump, 255);
     }
@@ -119,15 +122,15 @@ mod main_tests {
         let token_mint = create_test_pubkey(8);
 
         // Test validation: mismatched arrays
-        let logistics_providers = vec![logistics_provider1, logistics_provider2];
-        let logistics_costs = vec![100]; // Mismatched length
+        let logistics_providers = [logistics_provider1, logistics_provider2];
+        let logistics_costs = [100]; // Mismatched length
         let result = logistics_providers.len() == logistics_costs.len();
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: no logistics providers
         let empty_providers: Vec<Pubkey> = vec![];
         let result = !empty_providers.is_empty();
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: too many providers
         let mut many_providers = Vec::new();
@@ -135,12 +138,12 @@ mod main_tests {
             many_providers.push(create_test_pubkey(i));
         }
         let result = many_providers.len() <= MAX_LOGISTICS_PROVIDERS;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: invalid quantity
         let total_quantity = 0u64;
         let result = total_quantity > 0;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test valid case
         let logistics_providers = vec![logistics_provider1];
@@ -164,6 +167,7 @@ mod main_tests {
             trade_counter: 0,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         global_state.trade_counter += 1;
@@ -171,7 +175,7 @@ mod main_tests {
 
         let product_escrow_fee = (product_cost * ESCROW_FEE_PERCENT) / BASIS_POINTS;
 
-        let mut trade_account = TradeAccount {
+        let trade_account = TradeAccount {
             trade_id,
             seller,
             logistics_providers: logistics_providers.clone(),
@@ -184,6 +188,7 @@ mod main_tests {
             purchase_ids: Vec::new(),
             token_mint,
             bump: 255,
+            ..Default::default()
         };
 
         assert_eq!(trade_account.trade_id, 1);
@@ -194,7 +199,7 @@ mod main_tests {
         assert_eq!(trade_account.escrow_fee, 25); // 2.5% of 1000
         assert_eq!(trade_account.total_quantity, total_quantity);
         assert_eq!(trade_account.remaining_quantity, total_quantity);
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
         assert_eq!(trade_account.purchase_ids.len(), 0);
         assert_eq!(trade_account.token_mint, token_mint);
     }
@@ -220,17 +225,18 @@ mod main_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Test validation: invalid quantity (zero)
         let quantity = 0u64;
         let result = quantity > 0;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: trade inactive
         trade_account.active = false;
         let result = trade_account.active;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next tests
         trade_account.active = true;
@@ -238,11 +244,11 @@ mod main_tests {
         // Test validation: insufficient quantity
         let quantity = 10u64; // More than remaining
         let result = trade_account.remaining_quantity >= quantity;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: buyer is seller
         let buyer_is_seller = buyer == trade_account.seller;
-        assert_eq!(buyer_is_seller, false); // Should pass (buyer != seller)
+        assert!(!buyer_is_seller); // Should pass (buyer != seller)
 
         // Test validation: invalid logistics provider
         let mut found = false;
@@ -252,7 +258,7 @@ mod main_tests {
                 break;
             }
         }
-        assert_eq!(found, false); // Should fail validation
+        assert!(!found); // Should fail validation
 
         // Test valid case
         let quantity = 3u64;
@@ -296,6 +302,7 @@ mod main_tests {
             trade_counter: 1,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         global_state.purchase_counter += 1;
@@ -313,6 +320,7 @@ mod main_tests {
             logistics_cost: total_logistics_cost,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Update trade state
@@ -331,7 +339,7 @@ mod main_tests {
         assert_eq!(purchase_account.quantity, quantity);
         assert_eq!(purchase_account.total_amount, total_amount);
         assert_eq!(trade_account.remaining_quantity, 2);
-        assert_eq!(trade_account.active, true); // Still active
+        assert!(trade_account.active); // Still active
         assert_eq!(trade_account.purchase_ids.len(), 1);
     }
 
@@ -355,16 +363,17 @@ mod main_tests {
             logistics_cost: 300,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Test validation: wrong buyer
         let result = wrong_buyer == purchase_account.buyer;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: already confirmed
         purchase_account.delivered_and_confirmed = true;
         let result = !purchase_account.delivered_and_confirmed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.delivered_and_confirmed = false;
@@ -372,7 +381,7 @@ mod main_tests {
         // Test validation: disputed
         purchase_account.disputed = true;
         let result = !purchase_account.disputed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.disputed = false;
@@ -380,7 +389,7 @@ mod main_tests {
         // Test validation: already settled
         purchase_account.settled = true;
         let result = !purchase_account.settled;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for valid case
         purchase_account.settled = false;
@@ -400,8 +409,8 @@ mod main_tests {
         purchase_account.delivered_and_confirmed = true;
         purchase_account.settled = true;
 
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.settled);
 
         // Test payment calculation logic
         let trade_account = TradeAccount {
@@ -417,6 +426,7 @@ mod main_tests {
             purchase_ids: vec![1],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         let product_escrow_fee = (trade_account.product_cost * ESCROW_FEE_PERCENT * purchase_account.quantity) / BASIS_POINTS;
@@ -434,7 +444,7 @@ mod main_tests {
     #[test]
     fn test_raise_dispute_logic_main() {
         let buyer = create_test_pubkey(9);
-        let user = create_test_pubkey(12);
+        let _user = create_test_pubkey(12);
 
         // Setup purchase account
         let mut purchase_account = PurchaseAccount {
@@ -449,12 +459,13 @@ mod main_tests {
             logistics_cost: 300,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Test validation: already confirmed
         purchase_account.delivered_and_confirmed = true;
         let result = !purchase_account.delivered_and_confirmed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.delivered_and_confirmed = false;
@@ -462,7 +473,7 @@ mod main_tests {
         // Test validation: already disputed
         purchase_account.disputed = true;
         let result = !purchase_account.disputed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for valid case
         purchase_account.disputed = false;
@@ -477,7 +488,7 @@ mod main_tests {
         // Simulate raise_dispute logic
         purchase_account.disputed = true;
 
-        assert_eq!(purchase_account.disputed, true);
+        assert!(purchase_account.disputed);
     }
 
     #[test]
@@ -500,6 +511,7 @@ mod main_tests {
             logistics_cost: 300,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         let mut trade_account = TradeAccount {
@@ -515,12 +527,13 @@ mod main_tests {
             purchase_ids: vec![1],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Test validation: not disputed
         purchase_account.disputed = false;
         let result = purchase_account.disputed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.disputed = true;
@@ -528,7 +541,7 @@ mod main_tests {
         // Test validation: already settled
         purchase_account.settled = true;
         let result = !purchase_account.settled;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.settled = false;
@@ -537,7 +550,7 @@ mod main_tests {
         let valid_winner = invalid_winner == purchase_account.buyer
             || invalid_winner == trade_account.seller
             || invalid_winner == purchase_account.chosen_logistics_provider;
-        assert_eq!(valid_winner, false); // Should fail validation
+        assert!(!valid_winner); // Should fail validation
 
         // Test valid winners
         let buyer_valid = buyer == purchase_account.buyer
@@ -564,14 +577,14 @@ mod main_tests {
         }
 
         assert_eq!(trade_account.remaining_quantity, 10); // 7 + 3
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
 
         // Simulate resolve_dispute completion
         purchase_account.delivered_and_confirmed = true;
         purchase_account.settled = true;
 
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.settled);
     }
 
     #[test]
@@ -592,6 +605,7 @@ mod main_tests {
             logistics_cost: 300,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         let mut trade_account = TradeAccount {
@@ -607,16 +621,17 @@ mod main_tests {
             purchase_ids: vec![1],
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Test validation: wrong buyer
         let result = wrong_buyer == purchase_account.buyer;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test validation: already confirmed
         purchase_account.delivered_and_confirmed = true;
         let result = !purchase_account.delivered_and_confirmed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.delivered_and_confirmed = false;
@@ -624,7 +639,7 @@ mod main_tests {
         // Test validation: disputed
         purchase_account.disputed = true;
         let result = !purchase_account.disputed;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for next test
         purchase_account.disputed = false;
@@ -632,7 +647,7 @@ mod main_tests {
         // Test validation: already settled
         purchase_account.settled = true;
         let result = !purchase_account.settled;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Reset for valid case
         purchase_account.settled = false;
@@ -657,10 +672,10 @@ mod main_tests {
             trade_account.active = true;
         }
 
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.settled);
         assert_eq!(trade_account.remaining_quantity, 10); // 7 + 3
-        assert_eq!(trade_account.active, true);
+        assert!(trade_account.active);
     }
 
     #[test]
@@ -671,7 +686,7 @@ mod main_tests {
         // Test validation: no fees to withdraw
         let zero_balance = 0u64;
         let result = zero_balance > 0;
-        assert_eq!(result, false); // Should fail validation
+        assert!(!result); // Should fail validation
 
         // Test valid case
         let result = balance > 0;
@@ -686,8 +701,8 @@ mod main_tests {
         // Test all error conditions that would trigger in main.rs
 
         // MismatchedArrays
-        let providers = vec![create_test_pubkey(1), create_test_pubkey(2)];
-        let costs = vec![100]; // Mismatched length
+        let providers = [create_test_pubkey(1), create_test_pubkey(2)];
+        let costs = [100]; // Mismatched length
         assert_ne!(providers.len(), costs.len());
 
         // NoLogisticsProviders
@@ -774,22 +789,25 @@ mod main_tests {
             trade_counter: 0,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         // Register logistics provider
         let logistics_provider = create_test_pubkey(2);
-        let provider_account = LogisticsProviderAccount {
+        let _provider_account = LogisticsProviderAccount {
             provider: logistics_provider,
             is_registered: true,
             bump: 255,
+            ..Default::default()
         };
 
         // Register seller
         let seller = create_test_pubkey(3);
-        let seller_account = SellerAccount {
+        let _seller_account = SellerAccount {
             seller,
             is_registered: true,
             bump: 255,
+            ..Default::default()
         };
 
         // Register buyer
@@ -799,6 +817,7 @@ mod main_tests {
             is_registered: true,
             purchase_ids: Vec::new(),
             bump: 255,
+            ..Default::default()
         };
 
         // Create trade
@@ -822,6 +841,7 @@ mod main_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Buy trade
@@ -843,6 +863,7 @@ mod main_tests {
             logistics_cost: logistics_cost * buy_quantity,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Update trade and buyer accounts
@@ -870,10 +891,10 @@ mod main_tests {
         assert_eq!(global_state.trade_counter, 1);
         assert_eq!(global_state.purchase_counter, 1);
         assert_eq!(trade_account.remaining_quantity, 10); // Restored
-        assert_eq!(trade_account.active, true);
-        assert_eq!(purchase_account.delivered_and_confirmed, true);
-        assert_eq!(purchase_account.disputed, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(trade_account.active);
+        assert!(purchase_account.delivered_and_confirmed);
+        assert!(purchase_account.disputed);
+        assert!(purchase_account.settled);
         assert_eq!(buyer_account.purchase_ids.len(), 1);
     }
 
@@ -886,6 +907,7 @@ mod main_tests {
             trade_counter: 0,
             purchase_counter: 0,
             bump: 255,
+            ..Default::default()
         };
 
         // Create trade
@@ -909,6 +931,7 @@ mod main_tests {
             purchase_ids: Vec::new(),
             token_mint: create_test_pubkey(8),
             bump: 255,
+            ..Default::default()
         };
 
         // Buy trade
@@ -930,6 +953,7 @@ mod main_tests {
             logistics_cost: logistics_cost * buy_quantity,
             settled: false,
             bump: 255,
+            ..Default::default()
         };
 
         // Update trade
@@ -947,8 +971,8 @@ mod main_tests {
 
         // Verify final state
         assert_eq!(trade_account.remaining_quantity, 10); // Restored
-        assert_eq!(trade_account.active, true);
-        assert_eq!(purchase_account.settled, true);
+        assert!(trade_account.active);
+        assert!(purchase_account.settled);
     }
 
     #[test]
@@ -975,4 +999,1580 @@ mod main_tests {
         assert_eq!(seller_amount, 7800); // 8000 - 200
         assert_eq!(logistics_amount, 1950); // 2000 - 50
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_payout_split_amounts_main() {
+        let splits = vec![
+            PayoutSplit { recipient: create_test_pubkey(1), bps: 3333 },
+            PayoutSplit { recipient: create_test_pubkey(2), bps: 3333 },
+            PayoutSplit { recipient: create_test_pubkey(3), bps: 3334 },
+        ];
+
+        // 1000 * 33.33% = 333.3 -> 333, 1000 * 33.34% = 333.4 -> 333, dust of 1 to first
+        let shares = compute_split_amounts(1000, &splits);
+        assert_eq!(shares, vec![334, 333, 333]);
+        assert_eq!(shares.iter().sum::<u64>(), 1000);
+
+        // No splits means nothing to distribute
+        assert!(compute_split_amounts(1000, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_prorate_logistics_costs_main() {
+        // Three purchases with 300 + 200 + 100 logistics consolidated down to 450
+        let adjusted = prorate_logistics_costs(&[300, 200, 100], 450);
+        assert_eq!(adjusted, vec![225, 150, 75]);
+
+        // Rounding dust lands on the last purchase
+        let adjusted = prorate_logistics_costs(&[100, 100, 100], 200);
+        assert_eq!(adjusted, vec![66, 66, 68]);
+        assert_eq!(adjusted.iter().sum::<u64>(), 200);
+
+        // Free shipping purchases stay free
+        assert_eq!(prorate_logistics_costs(&[0, 0], 0), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_account_extension_tlv_main() {
+        let mut extensions: Vec<u8> = Vec::new();
+        assert!(get_extension(&extensions, ExtensionType::Uninitialized).is_none());
+
+        // The reserved type cannot be written
+        assert!(set_extension(&mut extensions, ExtensionType::Uninitialized, &[1, 2, 3]).is_err());
+
+        // A hand-encoded entry (type 7, length 2) is skipped when scanning for another type
+        extensions.extend_from_slice(&[7, 0, 2, 0, 0xAA, 0xBB]);
+        assert!(get_extension(&extensions, ExtensionType::Uninitialized).is_none());
+        assert!(!remove_extension(&mut extensions, ExtensionType::Uninitialized));
+        assert_eq!(extensions.len(), EXTENSION_HEADER_LEN + 2);
+    }
+
+    #[test]
+    fn test_take_published_rate_main() {
+        // A zero cost takes the published rate
+        let mut cost = 0;
+        take_published_rate(&mut cost, 300).unwrap();
+        assert_eq!(cost, 300);
+
+        // A matching cost is kept, any other is refused
+        let mut cost = 300;
+        take_published_rate(&mut cost, 300).unwrap();
+        assert_eq!(cost, 300);
+        let mut cost = 250;
+        assert!(take_published_rate(&mut cost, 300).is_err());
+    }
+
+    #[test]
+    fn test_weight_based_logistics_cost_main() {
+        // Flat pricing: per-unit cost times quantity
+        assert_eq!(compute_logistics_cost(100, 0, 0, 3).unwrap(), 300);
+
+        // 1.5kg at 40 per kg for 2 units: 500 base + 120 = 620
+        assert_eq!(compute_logistics_cost(500, 40, 1500, 2).unwrap(), 620);
+
+        // 250g at 3 per kg = 0.75, rounded up to 1
+        assert_eq!(compute_logistics_cost(0, 3, 250, 1).unwrap(), 1);
+
+        // 333g at 3 per kg for 3 units = 2.997, rounded up to 3
+        assert_eq!(compute_logistics_cost(10, 3, 333, 3).unwrap(), 13);
+
+        // Exact kilograms do not round up
+        assert_eq!(compute_logistics_cost(0, 7, 2000, 1).unwrap(), 14);
+
+        // Overflow is reported instead of wrapping
+        assert!(compute_logistics_cost(u64::MAX, 1, 0, 2).is_err());
+        assert!(compute_logistics_cost(u64::MAX, 1000, 1000, 1).is_err());
+    }
+
+    #[test]
+    fn test_order_index_shard_main() {
+        let mut order_index = OrderIndex {
+            version: ACCOUNT_VERSION,
+            user: create_test_pubkey(1),
+            shard_no: 0,
+            entries: Vec::new(),
+            capacity: MAX_ORDER_INDEX_ENTRIES as u32,
+            bump: 255,
+        };
+
+        for purchase_id in 1..=MAX_ORDER_INDEX_ENTRIES as u64 {
+            order_index.append(purchase_id).unwrap();
+        }
+        assert!(order_index.is_full());
+
+        // A full shard rejects new entries so the client advances to the next shard
+        assert!(order_index.append(MAX_ORDER_INDEX_ENTRIES as u64 + 1).is_err());
+
+        // An added page raises the capacity of the same shard
+        order_index.capacity += (ORDER_INDEX_PAGE_BYTES / ORDER_INDEX_ENTRY_LEN) as u32;
+        assert!(!order_index.is_full());
+        order_index.append(MAX_ORDER_INDEX_ENTRIES as u64 + 1).unwrap();
+
+        // Settlement flips only the matching entry
+        order_index.mark_settled(3);
+        assert!(order_index.entries[2].settled);
+        assert_eq!(order_index.entries.iter().filter(|entry| entry.settled).count(), 1);
+    }
+
+    #[test]
+    fn test_pda_helpers_main() {
+        let (trade, trade_bump) = pda::trade_pda(7);
+        assert_eq!(
+            (trade, trade_bump),
+            Pubkey::find_program_address(&[b"trade", 7u64.to_le_bytes().as_ref()], &ID)
+        );
+        assert_ne!(trade, pda::trade_pda(8).0);
+        assert_ne!(pda::trade_pda(1).0, pda::purchase_pda(1).0);
+
+        let mint = create_test_pubkey(9);
+        assert_eq!(
+            pda::escrow_vault_pda(&mint).0,
+            Pubkey::find_program_address(&[b"escrow", mint.as_ref()], &ID).0
+        );
+        assert_eq!(
+            pda::global_state_pda().0,
+            Pubkey::find_program_address(&[b"global_state"], &ID).0
+        );
+        assert_eq!(
+            pda::product_escrow_pda(7).0,
+            Pubkey::find_program_address(&[b"product_escrow", 7u64.to_le_bytes().as_ref()], &ID).0
+        );
+        assert_ne!(pda::product_escrow_pda(7).0, pda::logistics_escrow_pda(7).0);
+    }
+
+    #[test]
+    fn test_category_index_shard_main() {
+        let mut category_index = CategoryIndex {
+            version: ACCOUNT_VERSION,
+            category: 3,
+            shard_no: 0,
+            trade_ids: Vec::new(),
+            bump: 255,
+        };
+
+        for trade_id in 1..=MAX_CATEGORY_INDEX_ENTRIES as u64 {
+            category_index.append(trade_id).unwrap();
+        }
+        assert!(category_index.is_full());
+        assert!(category_index.append(MAX_CATEGORY_INDEX_ENTRIES as u64 + 1).is_err());
+        assert_eq!(category_index.trade_ids.len(), MAX_CATEGORY_INDEX_ENTRIES);
+    }
+
+    #[test]
+    fn test_usd_cents_to_token_amount_main() {
+        // $1.50 per token (150_000_000 * 10^-8), 6 decimals: $3.00 buys exactly 2 tokens
+        assert_eq!(usd_cents_to_token_amount(300, 150_000_000, -8, 6).unwrap(), 2_000_000);
+
+        // $1.00 at $3.00 per token rounds up to the next base unit
+        assert_eq!(usd_cents_to_token_amount(100, 3, 0, 6).unwrap(), 333_334);
+
+        // Zero or negative prices are rejected
+        assert!(usd_cents_to_token_amount(100, 0, -8, 6).is_err());
+        assert!(usd_cents_to_token_amount(100, -5, -8, 6).is_err());
+
+        // Overflowing conversions error instead of wrapping
+        assert!(usd_cents_to_token_amount(u64::MAX, 1, -8, 18).is_err());
+    }
+
+    #[test]
+    fn test_min_swap_output_main() {
+        // 1% slippage on a 1_000_000 quote
+        assert_eq!(min_swap_output(1_000_000, 100), 990_000);
+
+        // No tolerance requires the full quote; full tolerance accepts anything
+        assert_eq!(min_swap_output(1_000_000, 0), 1_000_000);
+        assert_eq!(min_swap_output(1_000_000, 10_000), 0);
+
+        // Large quotes do not overflow
+        assert_eq!(min_swap_output(u64::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_compute_insurance_premium_main() {
+        // 2% of 1_000_000
+        assert_eq!(compute_insurance_premium(1_000_000, 200).unwrap(), 20_000);
+
+        // Fractional premiums round up
+        assert_eq!(compute_insurance_premium(1, 200).unwrap(), 1);
+        assert_eq!(compute_insurance_premium(0, 200).unwrap(), 0);
+        assert_eq!(compute_insurance_premium(1_000_000, 0).unwrap(), 0);
+
+        // Large amounts do not overflow
+        assert_eq!(compute_insurance_premium(u64::MAX, 1000).unwrap(), u64::MAX / 10 + 1);
+    }
+
+    #[test]
+    fn test_dispute_reason_insured_main() {
+        assert!(DisputeReason::Lost.is_insured());
+        assert!(DisputeReason::Damaged.is_insured());
+        assert!(!DisputeReason::NotAsDescribed.is_insured());
+        assert!(!DisputeReason::Other.is_insured());
+        assert!(!DisputeReason::None.is_insured());
+    }
+
+    #[test]
+    fn test_return_request_extension_main() {
+        let mut extensions: Vec<u8> = Vec::new();
+        let mut request = ReturnRequest {
+            status: ReturnStatus::Requested,
+            logistics_provider: create_test_pubkey(4),
+            logistics_cost: 0,
+            refund_amount: 0,
+            requested_at: 1_700_000_000,
+        };
+        set_extension(&mut extensions, ExtensionType::ReturnRequest, &request.try_to_vec().unwrap()).unwrap();
+        let stored = get_extension(&extensions, ExtensionType::ReturnRequest).unwrap();
+        assert_eq!(ReturnRequest::try_from_slice(stored).unwrap(), request);
+
+        // Updating the request rewrites the entry in place without growing the region
+        let len = extensions.len();
+        request.status = ReturnStatus::Approved;
+        request.refund_amount = 5_000;
+        set_extension(&mut extensions, ExtensionType::ReturnRequest, &request.try_to_vec().unwrap()).unwrap();
+        assert_eq!(extensions.len(), len);
+        let stored = get_extension(&extensions, ExtensionType::ReturnRequest).unwrap();
+        assert_eq!(ReturnRequest::try_from_slice(stored).unwrap(), request);
+    }
+
+    #[test]
+    fn test_compute_partial_logistics_cost_main() {
+        // 7 of 10 units carry 70% of the logistics cost
+        assert_eq!(compute_partial_logistics_cost(1_000, 7, 10), 700);
+
+        // Rounding leaves dust on the remainder, which the final confirmation settles
+        let first = compute_partial_logistics_cost(100, 1, 3);
+        assert_eq!(first, 33);
+        let second = compute_partial_logistics_cost(100 - first, 1, 2);
+        assert_eq!(second, 33);
+        assert_eq!(100 - first - second, 34);
+
+        // Degenerate inputs never exceed the cost
+        assert_eq!(compute_partial_logistics_cost(100, 5, 0), 0);
+        assert_eq!(compute_partial_logistics_cost(100, 12, 10), 100);
+    }
+
+    #[test]
+    fn test_compute_restocking_fee_main() {
+        assert_eq!(compute_restocking_fee(10_000, 500), 500);
+        assert_eq!(compute_restocking_fee(999, 500), 49);
+        assert_eq!(compute_restocking_fee(1_000, 0), 0);
+        assert_eq!(compute_restocking_fee(1_000, 20_000), 1_000);
+    }
+
+    #[test]
+    fn test_cancel_reason_roundtrip_main() {
+        let encoded = CancelReason::OutOfStock.try_to_vec().unwrap();
+        assert_eq!(encoded, vec![4]);
+        assert_eq!(CancelReason::try_from_slice(&encoded).unwrap(), CancelReason::OutOfStock);
+        assert_ne!(CancelReason::ChangedMind, CancelReason::None);
+    }
+
+    #[test]
+    fn test_compute_escalation_fee_main() {
+        assert_eq!(compute_escalation_fee(10_000, 200), 200);
+        assert_eq!(compute_escalation_fee(49, 200), 0);
+        assert_eq!(compute_escalation_fee(1_000, 20_000), 1_000);
+    }
+
+    #[test]
+    fn test_dispute_ruling_extension_main() {
+        let ruling = DisputeRuling {
+            arbitrator: create_test_pubkey(1),
+            winner: create_test_pubkey(2),
+            slash_provider: false,
+            ruled_at: 1_000,
+            escalated_by: Pubkey::default(),
+            escalation_fee: 0,
+        };
+        let mut extensions = Vec::new();
+        set_extension(&mut extensions, ExtensionType::DisputeRuling, &ruling.try_to_vec().unwrap()).unwrap();
+        let stored = get_extension(&extensions, ExtensionType::DisputeRuling).unwrap();
+        assert_eq!(DisputeRuling::try_from_slice(stored).unwrap(), ruling);
+        assert!(get_extension(&extensions, ExtensionType::ReturnRequest).is_none());
+    }
+
+    #[test]
+    fn test_stats_record_volume_main() {
+        let mut stats = Stats {
+            version: ACCOUNT_VERSION,
+            purchases_settled: 0,
+            purchases_cancelled: 0,
+            disputes_raised: 0,
+            disputes_resolved: 0,
+            disputes_won_by_buyer: 0,
+            mint_totals: Vec::new(),
+            bump: 255,
+        };
+        let mint = create_test_pubkey(1);
+        stats.record_volume(mint, 1_000, 25);
+        stats.record_volume(mint, 500, 12);
+        assert_eq!(stats.mint_totals.len(), 1);
+        assert_eq!(stats.mint_totals[0].volume, 1_500);
+        assert_eq!(stats.mint_totals[0].fees, 37);
+
+        for seed in 2..=(MAX_STATS_MINTS as u8 + 1) {
+            stats.record_volume(create_test_pubkey(seed), 10, 1);
+        }
+        assert_eq!(stats.mint_totals.len(), MAX_STATS_MINTS);
+        let untracked = create_test_pubkey(MAX_STATS_MINTS as u8 + 1);
+        assert!(stats.mint_totals.iter().all(|totals| totals.mint != untracked));
+    }
+
+    #[test]
+    fn test_compute_crank_reward_main() {
+        assert_eq!(compute_crank_reward(250, 1000), 25);
+        assert_eq!(compute_crank_reward(9, 1000), 0);
+        assert_eq!(compute_crank_reward(250, u16::MAX), 250);
+    }
+
+    #[test]
+    fn test_auction_bid_amounts_main() {
+        let mut auction = AuctionAccount {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            seller: create_test_pubkey(1),
+            token_mint: create_test_pubkey(2),
+            quantity: 3,
+            start_price: 100,
+            min_increment: 10,
+            end_time: 1_000,
+            leading_bidder: Pubkey::default(),
+            leading_bid: 0,
+            leading_logistics_provider: Pubkey::default(),
+            leading_logistics_cost: 0,
+            bump: 255,
+        };
+        assert_eq!(auction.min_next_bid().unwrap(), 100);
+
+        auction.leading_bidder = create_test_pubkey(3);
+        auction.leading_bid = 120;
+        auction.leading_logistics_cost = 15;
+        assert_eq!(auction.min_next_bid().unwrap(), 130);
+        assert_eq!(auction.leading_amount().unwrap(), 375);
+
+        auction.leading_bid = u64::MAX;
+        assert!(auction.min_next_bid().is_err());
+        assert!(auction.leading_amount().is_err());
+    }
+
+
+    #[test]
+    fn test_offer_escrowed_amount_main() {
+        let mut offer = OfferAccount {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            buyer: create_test_pubkey(1),
+            token_mint: create_test_pubkey(2),
+            unit_price: 90,
+            quantity: 4,
+            logistics_provider: create_test_pubkey(3),
+            logistics_cost: 25,
+            expires_at: 1_000,
+            bump: 255,
+        };
+        assert_eq!(offer.escrowed_amount().unwrap(), 385);
+
+        offer.unit_price = u64::MAX;
+        assert!(offer.escrowed_amount().is_err());
+    }
+
+
+    #[test]
+    fn test_subscription_is_due_main() {
+        let mut subscription = SubscriptionAccount {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            buyer: create_test_pubkey(1),
+            buyer_token_account: create_test_pubkey(2),
+            token_mint: create_test_pubkey(3),
+            quantity: 1,
+            logistics_provider: create_test_pubkey(4),
+            max_cycle_amount: 500,
+            interval_seconds: MIN_SUBSCRIPTION_INTERVAL_SECONDS,
+            cycles_total: 2,
+            cycles_completed: 0,
+            next_cycle_at: 1_000,
+            bump: 255,
+        };
+        assert!(!subscription.is_due(999));
+        assert!(subscription.is_due(1_000));
+
+        subscription.cycles_completed = 2;
+        assert!(!subscription.is_due(5_000));
+    }
+
+
+    #[test]
+    fn test_confirmation_delegate_main() {
+        let delegate = create_test_pubkey(2);
+        let mut buyer_account = BuyerAccount {
+            version: ACCOUNT_VERSION,
+            discriminator: [0u8; 8],
+            buyer: create_test_pubkey(1),
+            is_registered: true,
+            purchase_ids: Vec::new(),
+            confirmation_delegate: None,
+            bump: 255,
+        };
+        assert!(!buyer_account.is_confirmation_delegate(&delegate));
+
+        buyer_account.confirmation_delegate = Some(delegate);
+        assert!(buyer_account.is_confirmation_delegate(&delegate));
+        assert!(!buyer_account.is_confirmation_delegate(&create_test_pubkey(3)));
+    }
+
+
+    #[test]
+    fn test_migrate_legacy_purchase_main() {
+        use anchor_lang::Discriminator;
+        use dezenmart_rust_smart_contract::legacy::{load_legacy, LegacyPurchaseAccount};
+
+        let legacy_purchase = LegacyPurchaseAccount {
+            discriminator: [0u8; 8],
+            purchase_id: 7,
+            trade_id: 3,
+            buyer: create_test_pubkey(1),
+            quantity: 2,
+            total_amount: 250,
+            delivered_and_confirmed: false,
+            disputed: false,
+            chosen_logistics_provider: create_test_pubkey(2),
+            logistics_cost: 50,
+            settled: false,
+            created_at: 1_000,
+            settled_at: 0,
+            payer: create_test_pubkey(1),
+            consolidated: false,
+            cancelled: false,
+            delivered_quantity: 0,
+            shipped_at: 0,
+            token_mint: create_test_pubkey(3),
+            unit_price: 100,
+            fill_price: 0,
+            fill_expo: 0,
+            insurance_premium: 0,
+            insurance_status: InsuranceStatus::None,
+            dispute_reason: DisputeReason::None,
+            cancel_reason: CancelReason::None,
+            extensions: Vec::new(),
+            bump: 254,
+        };
+        let mut data = PurchaseAccount::DISCRIMINATOR.to_vec();
+        data.extend(legacy_purchase.try_to_vec().unwrap());
+
+        let key = create_test_pubkey(9);
+        let owner = dezenmart_rust_smart_contract::ID;
+        let mut lamports = 0u64;
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+
+        let loaded: LegacyPurchaseAccount =
+            load_legacy::<PurchaseAccount, LegacyPurchaseAccount>(&info).unwrap();
+        let upgraded = PurchaseAccount::from(loaded);
+        assert_eq!(upgraded.version, ACCOUNT_VERSION);
+        assert_eq!(upgraded.purchase_id, 7);
+        assert_eq!(upgraded.total_amount, 250);
+        assert_eq!(upgraded.bump, 254);
+
+        info.try_borrow_mut_data().unwrap()[8] = ACCOUNT_VERSION;
+        assert!(load_legacy::<PurchaseAccount, LegacyPurchaseAccount>(&info).is_err());
+    }
+
+    #[test]
+    fn test_compute_platform_share_main() {
+        assert_eq!(compute_platform_share(250, 2000), 50);
+        assert_eq!(compute_platform_share(4, 2000), 0);
+        assert_eq!(compute_platform_share(250, u16::MAX), 250);
+        assert_eq!(compute_platform_share(250, 0), 0);
+    }
+
+    #[test]
+    fn test_order_limits_main() {
+        let limits = OrderLimits {
+            min_order_amount: 1_000,
+            max_order_amount: 50_000,
+        };
+        assert!(limits.is_valid());
+        assert!(limits.check(1_000).is_ok());
+        assert!(limits.check(50_000).is_ok());
+        assert!(limits.check(999).is_err());
+        assert!(limits.check(50_001).is_err());
+
+        // The minimum is a floor, so the order amount is logged on the left
+        let compared = |amount| match limits.check(amount).unwrap_err() {
+            anchor_lang::error::Error::AnchorError(error) => match error.compared_values {
+                Some(anchor_lang::error::ComparedValues::Values(values)) => values,
+                other => panic!("unexpected compared values {other:?}"),
+            },
+            other => panic!("unexpected error {other:?}"),
+        };
+        assert_eq!(compared(999), ("999".to_string(), "1000".to_string()));
+        assert_eq!(compared(50_001), ("50000".to_string(), "50001".to_string()));
+
+        let uncapped = OrderLimits {
+            min_order_amount: 1_000,
+            max_order_amount: 0,
+        };
+        assert!(uncapped.is_valid());
+        assert!(uncapped.check(u64::MAX).is_ok());
+
+        let inverted = OrderLimits {
+            min_order_amount: 2,
+            max_order_amount: 1,
+        };
+        assert!(!inverted.is_valid());
+    }
+
+    #[test]
+    fn test_buyer_quota_consume_main() {
+        let mut quota = BuyerQuota {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            buyer: create_test_pubkey(1),
+            quantity_purchased: 0,
+            last_purchase_slot: 0,
+            bump: 255,
+        };
+        assert!(quota.consume(1, 2).is_ok());
+        assert!(quota.consume(1, 2).is_ok());
+        assert_eq!(quota.quantity_purchased, 2);
+        assert!(quota.consume(1, 2).is_err());
+        assert_eq!(quota.quantity_purchased, 2);
+    }
+
+    #[test]
+    fn test_buyer_quota_cooldown_main() {
+        let mut quota = BuyerQuota {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            buyer: create_test_pubkey(1),
+            quantity_purchased: 0,
+            last_purchase_slot: 0,
+            bump: 255,
+        };
+        // A first purchase is never rate limited
+        assert!(quota.check_cooldown(10, 5).is_ok());
+
+        quota.last_purchase_slot = 100;
+        assert!(quota.check_cooldown(10, 109).is_err());
+        assert!(quota.check_cooldown(10, 110).is_ok());
+    }
+
+    #[test]
+    fn test_buyer_attestation_expiry_main() {
+        let mut attestation = BuyerAttestation {
+            version: ACCOUNT_VERSION,
+            verifier: create_test_pubkey(1),
+            subject: create_test_pubkey(2),
+            issued_at: 100,
+            expires_at: 200,
+            region: Some(DeliveryRegion::Europe),
+            bump: 255,
+        };
+        assert!(attestation.is_valid(199));
+        assert!(!attestation.is_valid(200));
+
+        attestation.expires_at = 0;
+        assert!(attestation.is_valid(i64::MAX));
+    }
+
+    #[test]
+    fn test_tracking_extension_main() {
+        let tracking = TrackingInfo {
+            carrier_code: 3,
+            tracking_hash: [7u8; 32],
+            recorded_at: 1_000,
+        };
+        let mut extensions = Vec::new();
+        set_extension(&mut extensions, ExtensionType::Tracking, &tracking.try_to_vec().unwrap()).unwrap();
+        let updated = TrackingInfo {
+            recorded_at: 2_000,
+            ..tracking
+        };
+        set_extension(&mut extensions, ExtensionType::Tracking, &updated.try_to_vec().unwrap()).unwrap();
+        let stored = get_extension(&extensions, ExtensionType::Tracking).unwrap();
+        assert_eq!(TrackingInfo::try_from_slice(stored).unwrap(), updated);
+        assert_eq!(extensions.len(), EXTENSION_HEADER_LEN + 2 + 32 + 8);
+    }
+
+    #[test]
+    fn test_proof_of_delivery_extension_main() {
+        let pod = ProofOfDelivery {
+            pod_hash: [9u8; 32],
+            submitted_at: 1_000,
+        };
+        let mut extensions = Vec::new();
+        assert!(get_extension(&extensions, ExtensionType::ProofOfDelivery).is_none());
+        set_extension(&mut extensions, ExtensionType::ProofOfDelivery, &pod.try_to_vec().unwrap()).unwrap();
+        let stored = get_extension(&extensions, ExtensionType::ProofOfDelivery).unwrap();
+        assert_eq!(ProofOfDelivery::try_from_slice(stored).unwrap(), pod);
+        assert!(get_extension(&extensions, ExtensionType::Tracking).is_none());
+    }
+
+    #[test]
+    fn test_confirming_party_main() {
+        let buyer = create_test_pubkey(1);
+        let seller = create_test_pubkey(2);
+        let provider = create_test_pubkey(3);
+        let delegate = create_test_pubkey(4);
+
+        assert_eq!(confirming_party(&buyer, &buyer, &seller, &provider), ConfirmingParty::Buyer);
+        assert_eq!(confirming_party(&seller, &buyer, &seller, &provider), ConfirmingParty::Seller);
+        assert_eq!(
+            confirming_party(&provider, &buyer, &seller, &provider),
+            ConfirmingParty::LogisticsProvider
+        );
+        assert_eq!(confirming_party(&delegate, &buyer, &seller, &provider), ConfirmingParty::Buyer);
+
+        let masks = [
+            ConfirmingParty::Buyer.mask(),
+            ConfirmingParty::Seller.mask(),
+            ConfirmingParty::LogisticsProvider.mask(),
+        ];
+        assert_eq!(masks, [1, 2, 4]);
+        assert_eq!((masks[0] | masks[2]).count_ones(), 2);
+    }
+
+    #[test]
+    fn test_stats_record_fee_reversal_main() {
+        let mut stats = Stats {
+            version: ACCOUNT_VERSION,
+            purchases_settled: 0,
+            purchases_cancelled: 0,
+            disputes_raised: 0,
+            disputes_resolved: 0,
+            disputes_won_by_buyer: 0,
+            mint_totals: Vec::new(),
+            bump: 255,
+        };
+        let mint = create_test_pubkey(1);
+        stats.record_volume(mint, 1_000, 25);
+        stats.record_fee_reversal(mint, 10);
+        stats.record_fee_reversal(create_test_pubkey(2), 4);
+
+        assert_eq!(stats.mint_totals.len(), 2);
+        assert_eq!(stats.mint_totals[0].fees, 25);
+        assert_eq!(stats.mint_totals[0].fees_reversed, 10);
+        assert_eq!(stats.mint_totals[1].volume, 0);
+        assert_eq!(stats.mint_totals[1].fees_reversed, 4);
+    }
+
+    #[test]
+    fn test_lending_yield_split_main() {
+        assert_eq!(compute_buyer_yield(1_000, 7_000), 700);
+        assert_eq!(compute_buyer_yield(9, 5_000), 4);
+        assert_eq!(compute_buyer_yield(1_000, u16::MAX), 1_000);
+        assert_eq!(compute_buyer_yield(0, 5_000), 0);
+
+        let position = LendingPosition {
+            principal: 5_000,
+            lending_program: create_test_pubkey(4),
+            deposited_at: 1_700_000_000,
+        };
+        let mut extensions = Vec::new();
+        set_extension(&mut extensions, ExtensionType::LendingPosition, &position.try_to_vec().unwrap()).unwrap();
+        let stored = get_extension(&extensions, ExtensionType::LendingPosition).unwrap();
+        assert_eq!(LendingPosition::try_from_slice(stored).unwrap(), position);
+        assert!(remove_extension(&mut extensions, ExtensionType::LendingPosition));
+        assert!(extensions.is_empty());
+        assert_ne!(pda::lending_receipt_pda(1).0, pda::product_escrow_pda(1).0);
+    }
+
+    #[test]
+    fn test_relayed_order_signing_message_main() {
+        let order = RelayedOrder {
+            buyer: create_test_pubkey(1),
+            trade_id: 7,
+            quantity: 2,
+            logistics_provider: create_test_pubkey(2),
+            buyer_region: DeliveryRegion::Europe,
+            max_total_amount: 2_500,
+            relayer_fee: 10,
+            nonce: 0,
+            expires_at: 1_700_000_000,
+        };
+        let message = order.signing_message();
+        assert!(message.starts_with(RELAYED_ORDER_DOMAIN));
+        assert_eq!(&message[RELAYED_ORDER_DOMAIN.len()..RELAYED_ORDER_DOMAIN.len() + 32], ID.as_ref());
+        // The order part matches its Borsh encoding
+        assert_eq!(&message[RELAYED_ORDER_DOMAIN.len() + 32..], order.try_to_vec().unwrap().as_slice());
+
+        let replayed = RelayedOrder { nonce: 1, ..order };
+        assert_ne!(replayed.signing_message(), message);
+        assert_ne!(pda::relay_nonce_pda(&order.buyer).0, pda::relay_authority_pda().0);
+    }
+
+    #[test]
+    fn test_spending_permit_spend_main() {
+        let buyer = create_test_pubkey(1);
+        let delegate = create_test_pubkey(2);
+        let mut permit = SpendingPermit {
+            version: ACCOUNT_VERSION,
+            buyer,
+            delegate,
+            buyer_token_account: create_test_pubkey(3),
+            remaining_amount: 1_000,
+            max_order_amount: 600,
+            expires_at: 1_700_000_000,
+            bump: 255,
+        };
+
+        permit.spend(600, 1_699_999_999).unwrap();
+        assert_eq!(permit.remaining_amount, 400);
+        // Within the per-order cap but above what is left
+        assert!(permit.spend(500, 1_699_999_999).is_err());
+        assert!(permit.spend(400, 1_700_000_001).is_err());
+        permit.spend(400, 1_700_000_000).unwrap();
+        assert_eq!(permit.remaining_amount, 0);
+
+        assert_ne!(pda::spending_permit_pda(&buyer, &delegate).0, pda::spending_permit_pda(&delegate, &buyer).0);
+    }
+
+    #[test]
+    fn test_session_spend_cap_main() {
+        let buyer = create_test_pubkey(1);
+        let session_key = create_test_pubkey(2);
+        let mut session = SessionAccount {
+            version: ACCOUNT_VERSION,
+            buyer,
+            session_key,
+            buyer_token_account: create_test_pubkey(3),
+            expires_at_slot: 5_000,
+            spend_cap: 1_000,
+            spent: 0,
+            bump: 255,
+        };
+
+        session.spend(700, 4_000).unwrap();
+        assert!(session.spend(301, 4_000).is_err());
+        session.spend(300, 5_000).unwrap();
+        assert_eq!(session.spent, 1_000);
+
+        // Expired sessions may neither spend nor confirm
+        assert!(session.spend(0, 5_001).is_err());
+        assert!(session.check_active(5_001).is_err());
+        assert!(session.check_active(5_000).is_ok());
+        assert_ne!(pda::session_pda(&buyer, &session_key).0, pda::spending_permit_pda(&buyer, &session_key).0);
+    }
+
+    #[test]
+    fn test_settlement_lock_main() {
+        let mut purchase = PurchaseAccount::default();
+        assert!(!purchase.settlement_locked);
+
+        purchase.mark_settled(1_700_000_000);
+        purchase.lock_settlement().unwrap();
+        assert!(purchase.settled);
+        assert_eq!(purchase.settled_at, 1_700_000_000);
+
+        // A second path arriving before the first finishes is refused
+        assert!(purchase.lock_settlement().is_err());
+        purchase.unlock_settlement();
+        assert!(purchase.lock_settlement().is_ok());
+    }
+
+    #[test]
+    fn test_provider_at_main() {
+        let provider = create_test_pubkey(1);
+        let other = create_test_pubkey(2);
+        let trade_account = TradeAccount {
+            logistics_providers: vec![provider, other, provider],
+            ..Default::default()
+        };
+
+        // A provider listed twice is priced at the position the buyer names
+        assert_eq!(trade_account.provider_at(0, &provider), Some(0));
+        assert_eq!(trade_account.provider_at(2, &provider), Some(2));
+        assert_eq!(trade_account.provider_at(1, &provider), None);
+        assert_eq!(trade_account.provider_at(3, &provider), None);
+    }
+
+    #[test]
+    fn test_check_unique_providers_main() {
+        let first = create_test_pubkey(1);
+        let second = create_test_pubkey(2);
+
+        assert!(check_unique_providers(&[first, second]).is_ok());
+        assert!(check_unique_providers(&[first, second, first]).is_err());
+        assert!(check_unique_providers(&[second, second]).is_err());
+    }
+
+    #[test]
+    fn test_provider_status_main() {
+        let provider = create_test_pubkey(1);
+        let mut trade_account = TradeAccount {
+            logistics_providers: vec![create_test_pubkey(2), provider],
+            logistics_costs: vec![100, 200],
+            ..Default::default()
+        };
+
+        trade_account.set_provider_enabled(1, false).unwrap();
+        assert!(trade_account.provider_enabled(0));
+        assert!(!trade_account.provider_enabled(1));
+        assert!(trade_account.primary_logistics_cost(&provider, 1).is_err());
+
+        // Re-enabling the last paused provider drops the extension
+        trade_account.set_provider_enabled(1, true).unwrap();
+        assert_eq!(trade_account.primary_logistics_cost(&provider, 1).unwrap(), 200);
+        assert!(get_extension(&trade_account.extensions, ExtensionType::DisabledProviders).is_none());
+    }
+
+    #[test]
+    fn test_provider_assignment_main() {
+        let mut purchase = PurchaseAccount::default();
+        assert!(purchase.provider_assignment().is_none());
+        assert!(!purchase.provider_declined());
+
+        purchase.set_provider_assignment(&ProviderAssignment { accepted: false, responded_at: 1_700_000_000 }).unwrap();
+        assert!(purchase.provider_declined());
+
+        // Reassignment leaves the new provider's answer pending
+        assert!(purchase.clear_provider_assignment());
+        assert!(!purchase.provider_declined());
+
+        purchase.set_provider_assignment(&ProviderAssignment { accepted: true, responded_at: 1_700_000_100 }).unwrap();
+        assert!(!purchase.provider_declined());
+    }
+
+    #[test]
+    fn test_fee_split_config_main() {
+        let split = |seed, bps| PayoutSplit { recipient: create_test_pubkey(seed), bps };
+
+        // Operations wallet, DAO treasury and insurance pool
+        let config = FeeSplitConfig {
+            splits: vec![split(1, 5000), split(2, 3000), split(3, 2000)],
+        };
+        assert!(config.is_valid());
+        assert_eq!(compute_split_amounts(999, &config.splits), vec![501, 299, 199]);
+
+        // An empty config disables distribution
+        assert!(FeeSplitConfig::default().is_valid());
+
+        // Splits must cover the whole fee, without zero shares
+        let short = FeeSplitConfig { splits: vec![split(1, 5000), split(2, 4000)] };
+        assert!(!short.is_valid());
+        let zero = FeeSplitConfig { splits: vec![split(1, 10000), split(2, 0)] };
+        assert!(!zero.is_valid());
+
+        // At most MAX_FEE_SPLITS recipients
+        let too_many = FeeSplitConfig {
+            splits: (0..=MAX_FEE_SPLITS as u8).map(|seed| split(seed, 1)).collect(),
+        };
+        assert!(!too_many.is_valid());
+    }
+
+    #[test]
+    fn test_governing_authority_main() {
+        let mut global_state = GlobalState {
+            admin: create_test_pubkey(1),
+            ..Default::default()
+        };
+
+        // Without a governance authority the admin governs
+        assert_eq!(global_state.governing_authority(), create_test_pubkey(1));
+
+        // A governance realm takes over from the admin
+        global_state.governance_authority = create_test_pubkey(2);
+        assert_eq!(global_state.governing_authority(), create_test_pubkey(2));
+
+        // Clearing it hands control back to the admin
+        global_state.governance_authority = Pubkey::default();
+        assert_eq!(global_state.governing_authority(), create_test_pubkey(1));
+    }
+
+    #[test]
+    fn test_program_version_compatibility_main() {
+        let semver = |major, minor, patch| SemVer { major, minor, patch };
+        assert!(semver(1, 2, 0) > semver(1, 1, 9));
+        assert!(semver(2, 0, 0) > semver(1, 9, 9));
+
+        let mut version = ProgramVersion {
+            program_version: semver(1, 4, 0),
+            min_client_version: semver(1, 2, 0),
+            ..Default::default()
+        };
+
+        assert!(version.supports_client(semver(1, 2, 0)));
+        assert!(version.supports_client(semver(1, 5, 3)));
+        // Older than the minimum
+        assert!(!version.supports_client(semver(1, 1, 9)));
+
+        // A client built before a major upgrade does not know the new interface
+        version.program_version = semver(2, 0, 0);
+        assert!(!version.supports_client(semver(1, 5, 3)));
+        assert!(version.supports_client(semver(2, 0, 0)));
+    }
+
+    #[test]
+    fn test_purchase_receipt_main() {
+        let mut purchase = PurchaseAccount {
+            purchase_id: 7,
+            trade_id: 3,
+            quantity: 2,
+            total_amount: 250,
+            ..Default::default()
+        };
+        assert!(purchase.receipt().is_none());
+
+        let receipt = PurchaseReceipt { merkle_tree: create_test_pubkey(5), minted_at: 1_700_000_000 };
+        purchase.set_receipt(&receipt).unwrap();
+        assert_eq!(purchase.receipt(), Some(receipt));
+
+        let config = ReceiptConfig {
+            uri_prefix: "https://receipts.dezenmart.com/r".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.receipt_uri(&purchase),
+            "https://receipts.dezenmart.com/r?purchase=7&trade=3&quantity=2&amount=250"
+        );
+    }
+
+    #[test]
+    fn test_loyalty_discount_main() {
+        let config = LoyaltyConfig { points_bps: 100, points_per_discount_bps: 10, max_discount_bps: 5_000 };
+        assert!(config.is_valid());
+        assert!(!LoyaltyConfig { max_discount_bps: 10_001, ..config }.is_valid());
+        // A discount with no point price could be redeemed for free
+        assert!(!LoyaltyConfig { points_per_discount_bps: 0, ..config }.is_valid());
+
+        // 1% of settled volume
+        assert_eq!(compute_loyalty_points(25_000, config.points_bps), 250);
+
+        let mut purchase = PurchaseAccount {
+            fee_bps: ESCROW_FEE_PERCENT as u16,
+            ..Default::default()
+        };
+        assert_eq!(purchase.fee_discount_bps(), 0);
+        assert_eq!(purchase.escrow_fee_bps(), ESCROW_FEE_PERCENT);
+
+        // Half the escrow fee waived
+        purchase.set_fee_discount_bps(5_000).unwrap();
+        assert_eq!(purchase.escrow_fee_bps(), ESCROW_FEE_PERCENT / 2);
+        purchase.set_fee_discount_bps(10_000).unwrap();
+        assert_eq!(purchase.escrow_fee_bps(), 0);
+
+        assert!(purchase.loyalty_points_accrued().is_none());
+        purchase.set_loyalty_points_accrued(250).unwrap();
+        assert_eq!(purchase.loyalty_points_accrued(), Some(250));
+    }
+
+    #[test]
+    fn test_storefront_listing_main() {
+        let mut trade = TradeAccount::default();
+        assert_eq!(trade.storefront_listing(), None);
+
+        trade.set_storefront_listing(true).unwrap();
+        assert_eq!(trade.storefront_listing(), Some(true));
+
+        // Selling out is recorded in place without growing the region
+        let len = trade.extensions.len();
+        trade.set_storefront_listing(false).unwrap();
+        assert_eq!(trade.storefront_listing(), Some(false));
+        assert_eq!(trade.extensions.len(), len);
+    }
+
+    #[test]
+    fn test_low_stock_threshold_main() {
+        let mut trade = TradeAccount {
+            remaining_quantity: 3,
+            ..Default::default()
+        };
+        assert!(!trade.is_low_stock());
+
+        trade.set_low_stock_threshold(2).unwrap();
+        assert_eq!(trade.low_stock_threshold(), Some(2));
+        assert!(!trade.is_low_stock());
+
+        trade.remaining_quantity = 2;
+        assert!(trade.is_low_stock());
+        trade.remaining_quantity = 0;
+        assert!(trade.is_low_stock());
+    }
+
+    #[test]
+    fn test_expected_escrow_main() {
+        let mut purchase = PurchaseAccount {
+            total_amount: 1_050,
+            ..Default::default()
+        };
+        // Legacy purchases share the mint's vault and cannot be audited alone
+        assert_eq!(purchase.expected_escrow(), None);
+
+        purchase.split_escrow = true;
+        assert_eq!(purchase.expected_escrow(), Some(1_050));
+
+        purchase.settlement_locked = true;
+        assert_eq!(purchase.expected_escrow(), None);
+        purchase.settlement_locked = false;
+
+        purchase.settled = true;
+        assert_eq!(purchase.expected_escrow(), None);
+    }
+
+    #[test]
+    fn test_purchase_stage_main() {
+        let mut purchase = PurchaseAccount::default();
+        assert_eq!(purchase.stage(), PurchaseStage::Pending);
+
+        purchase.shipped_at = 1_700_000_000;
+        assert_eq!(purchase.stage(), PurchaseStage::Shipped);
+
+        purchase.disputed = true;
+        assert_eq!(purchase.stage(), PurchaseStage::Disputed);
+
+        purchase.mark_settled(1_700_000_100);
+        assert_eq!(purchase.stage(), PurchaseStage::Settled);
+
+        purchase.cancelled = true;
+        assert_eq!(purchase.stage(), PurchaseStage::Cancelled);
+    }
+
+    #[test]
+    fn test_sealed_drop_allocation_main() {
+        let buyer = create_test_pubkey(3);
+        let salt = [7u8; 32];
+        let commitment = sealed_commitment(&buyer, 4, &salt);
+        assert_eq!(sealed_commitment(&buyer, 4, &salt), commitment);
+        assert_ne!(sealed_commitment(&buyer, 5, &salt), commitment);
+        assert_ne!(sealed_commitment(&create_test_pubkey(4), 4, &salt), commitment);
+
+        let mut sealed_drop = SealedDropAccount {
+            version: ACCOUNT_VERSION,
+            trade_id: 1,
+            seller: create_test_pubkey(1),
+            token_mint: create_test_pubkey(2),
+            quantity: 10,
+            unit_price: 100,
+            max_quantity_per_commit: 6,
+            commit_end: 1_000,
+            reveal_end: 2_000,
+            commit_count: 3,
+            claimed_count: 0,
+            revealed_quantity: 9,
+            allocated_quantity: 0,
+            bump: 255,
+        };
+        // Demand within the lot is filled in full
+        assert_eq!(sealed_drop.allocation(6), 6);
+        assert_eq!(sealed_drop.allocation(0), 0);
+
+        // Oversubscribed: pro rata shares rounded down, dust stays unallocated
+        sealed_drop.revealed_quantity = 15;
+        let allocations: Vec<u64> = [6, 6, 3]
+            .iter()
+            .map(|&revealed| sealed_drop.allocation(revealed))
+            .collect();
+        assert_eq!(allocations, vec![4, 4, 2]);
+        sealed_drop.revealed_quantity = 18;
+        assert_eq!(sealed_drop.allocation(6), 3);
+        assert_eq!(sealed_drop.allocation(0), 0);
+    }
+
+    #[test]
+    fn test_presale_allowlist_proof_main() {
+        let buyers: Vec<Pubkey> = (10..14).map(create_test_pubkey).collect();
+        let leaves: Vec<[u8; 32]> = buyers.iter().map(allowlist_leaf).collect();
+        let left = hash_allowlist_pair(&leaves[0], &leaves[1]);
+        let right = hash_allowlist_pair(&leaves[2], &leaves[3]);
+        let presale = PresaleAllowlist {
+            merkle_root: hash_allowlist_pair(&left, &right),
+            public_sale_start: 1_000,
+        };
+
+        assert!(presale.includes(&buyers[0], &[leaves[1], right]));
+        assert!(presale.includes(&buyers[3], &[leaves[2], left]));
+        // Sibling order does not matter within a pair
+        assert_eq!(hash_allowlist_pair(&leaves[1], &leaves[0]), left);
+        assert!(!presale.includes(&buyers[0], &[leaves[2], right]));
+        assert!(!presale.includes(&create_test_pubkey(20), &[leaves[1], right]));
+        assert!(!presale.includes(&buyers[0], &[]));
+
+        assert!(presale.is_active(999));
+        assert!(!presale.is_active(1_000));
+
+        let mut trade = TradeAccount::default();
+        assert_eq!(trade.presale_allowlist(), None);
+        trade.set_presale_allowlist(&presale).unwrap();
+        assert_eq!(trade.presale_allowlist(), Some(presale));
+    }
+
+    #[test]
+    fn test_seller_stream_vesting_main() {
+        let mut stream = SellerStream {
+            amount: 1_000,
+            released: 0,
+            refunded: 0,
+            started_at: 100,
+            duration_seconds: 400,
+            halted_at: 0,
+        };
+        assert_eq!(stream.vested(50), 0);
+        assert_eq!(stream.vested(200), 250);
+        assert_eq!(stream.vested(500), 1_000);
+        assert_eq!(stream.vested(10_000), 1_000);
+        assert!(stream.is_running(200));
+        assert!(!stream.is_running(500));
+
+        stream.released = 250;
+        assert_eq!(stream.releasable(200), 0);
+        assert_eq!(stream.releasable(300), 250);
+
+        // A halt freezes vesting; a buyer win refunds the unvested part
+        stream.halted_at = 300;
+        assert_eq!(stream.vested(10_000), 500);
+        assert!(!stream.is_running(300));
+        stream.refunded = stream.amount - stream.vested(stream.halted_at);
+        stream.released += stream.releasable(10_000);
+        assert_eq!(stream.released, 500);
+        assert!(stream.is_finished());
+
+        // A seller win resumes the original schedule
+        stream.refunded = 0;
+        stream.halted_at = 0;
+        assert_eq!(stream.releasable(500), 500);
+        assert!(!stream.is_finished());
+
+        let mut purchase = PurchaseAccount::default();
+        assert_eq!(purchase.seller_stream(), None);
+        purchase.set_seller_stream(&stream).unwrap();
+        assert_eq!(purchase.seller_stream(), Some(stream));
+    }
+
+    #[test]
+    fn test_milestone_settlement_main() {
+        let mut trade = TradeAccount::default();
+        assert!(trade.milestones().is_empty());
+        let milestones = vec![
+            Milestone {
+                bps: 3_000,
+                description_hash: [1; 32],
+            },
+            Milestone {
+                bps: 7_000,
+                description_hash: [2; 32],
+            },
+        ];
+        trade.set_milestones(&milestones).unwrap();
+        assert_eq!(trade.milestones(), milestones);
+
+        let mut purchase = PurchaseAccount {
+            unit_price: 1_000,
+            quantity: 2,
+            fee_bps: ESCROW_FEE_PERCENT as u16,
+            ..Default::default()
+        };
+        let gross = purchase.settlement_payouts().unwrap();
+        assert_eq!(gross.seller_amount, 1_950);
+        assert_eq!(gross.fee_amount, 50);
+
+        // The first stage is paid out; settlement owes the seller the rest
+        let progress = MilestoneProgress {
+            confirmed: 1,
+            seller_released: gross.seller_amount * 3_000 / BASIS_POINTS,
+        };
+        purchase.set_milestone_progress(&progress).unwrap();
+        assert!(progress.is_confirmed(0));
+        assert!(!progress.is_confirmed(1));
+        assert_eq!(purchase.milestone_progress(), Some(progress));
+
+        let remaining = purchase.settlement_payouts().unwrap();
+        assert_eq!(remaining.seller_amount, 1_365);
+        assert_eq!(remaining.fee_amount, gross.fee_amount);
+    }
+
+    #[test]
+    fn test_jury_draw_and_verdict_main() {
+        let candidates: Vec<Pubkey> = (1..=9).map(create_test_pubkey).collect();
+        let seed = [7u8; 32];
+        let jurors = draw_jurors(&candidates, &seed, 5);
+        assert_eq!(jurors.len(), 5);
+        assert_eq!(draw_jurors(&candidates, &seed, 5), jurors);
+        for (i, juror) in jurors.iter().enumerate() {
+            assert!(candidates.contains(juror));
+            assert!(!jurors[..i].contains(juror));
+        }
+        assert_eq!(draw_jurors(&candidates, &seed, 20).len(), candidates.len());
+
+        let buyer = create_test_pubkey(100);
+        let seller = create_test_pubkey(101);
+        let case = JuryCase {
+            jurors: jurors[..3].to_vec(),
+            votes: vec![buyer, seller, buyer],
+            voting_ends_at: 0,
+        };
+        assert!(case.all_voted());
+        assert_eq!(case.majority(), Some((buyer, 2)));
+
+        // A split vote with an abstention has no majority
+        let deadlocked = JuryCase {
+            votes: vec![buyer, seller, Pubkey::default()],
+            ..case.clone()
+        };
+        assert!(!deadlocked.all_voted());
+        assert_eq!(deadlocked.majority(), None);
+
+        let mut pool = JuryPool {
+            version: 1,
+            config: JuryConfig {
+                juror_stake: 1_000,
+                jury_size: 3,
+                voting_window_seconds: 86_400,
+                minority_slash_bps: 1_000,
+            },
+            jurors: case
+                .jurors
+                .iter()
+                .map(|juror| JurorEntry {
+                    juror: *juror,
+                    stake: 1_000,
+                    active_cases: 1,
+                })
+                .collect(),
+            vault_bump: 0,
+            bump: 0,
+        };
+        assert!(pool.config.is_valid());
+        assert_eq!(pool.eligible_jurors(&[case.jurors[0]]).len(), 2);
+
+        // The minority juror's slice goes to the two majority jurors
+        assert_eq!(pool.settle_case(&case, Some(buyer)), 100);
+        let stakes: Vec<u64> = pool.jurors.iter().map(|entry| entry.stake).collect();
+        assert_eq!(stakes, vec![1_050, 900, 1_050]);
+        assert!(pool.jurors.iter().all(|entry| entry.active_cases == 0));
+        // Slashed below the juror stake, the minority juror is no longer drawn
+        assert_eq!(pool.eligible_jurors(&[]).len(), 2);
+
+        pool.config.jury_size = 4;
+        assert!(!pool.config.is_valid());
+    }
+
+    #[test]
+    fn test_dispute_resolution_finality_main() {
+        let winner = create_test_pubkey(1);
+        let mut resolution = DisputeResolution {
+            winner,
+            appeal_deadline: 1_000,
+            appealed_by: Pubkey::default(),
+            appeal_bond: 0,
+            is_final: false,
+        };
+        assert!(!resolution.is_final_at(1_000));
+        assert!(resolution.is_final_at(1_001));
+
+        // An appeal keeps the resolution open until it is reviewed
+        resolution.appealed_by = create_test_pubkey(2);
+        resolution.appeal_bond = 25;
+        assert!(resolution.is_appealed());
+        assert!(!resolution.is_final_at(10_000));
+        resolution.is_final = true;
+        assert!(resolution.is_final_at(0));
+
+        let mut purchase = PurchaseAccount::default();
+        assert_eq!(purchase.dispute_resolution(), None);
+        purchase.set_dispute_resolution(&resolution).unwrap();
+        assert_eq!(purchase.dispute_resolution(), Some(resolution));
+    }
+
+    #[test]
+    fn test_event_seq_main() {
+        let mut global_state = GlobalState::default();
+        assert_eq!(global_state.event_seq, 0);
+
+        // Each event takes the next number, starting from 1
+        assert_eq!(global_state.next_event_seq(), 1);
+        assert_eq!(global_state.next_event_seq(), 2);
+        assert_eq!(global_state.event_seq, 2);
+    }
+
+    #[test]
+    fn test_account_init_space_main() {
+        // Fixed-size accounts serialize to exactly their allocation once
+        // optional fields are filled in
+        let purchase = PurchaseAccount {
+            memo_hash: Some([0u8; 64]),
+            ..Default::default()
+        };
+        assert_eq!(purchase.try_to_vec().unwrap().len(), PurchaseAccount::INIT_SPACE);
+        let claimable = ClaimableBalance::default();
+        assert_eq!(claimable.try_to_vec().unwrap().len(), ClaimableBalance::INIT_SPACE);
+
+        // Vectors are sized for their maximum length
+        let mut global_state = GlobalState::default();
+        let empty_len = global_state.try_to_vec().unwrap().len();
+        global_state.platforms = vec![
+            PlatformFeeShare { platform: create_test_pubkey(1), fee_share_bps: 100 };
+            MAX_PLATFORMS
+        ];
+        global_state.fee_split_config.splits =
+            vec![PayoutSplit { recipient: create_test_pubkey(2), bps: 1 }; MAX_FEE_SPLITS];
+        assert!(empty_len < GlobalState::INIT_SPACE);
+        assert_eq!(global_state.try_to_vec().unwrap().len(), GlobalState::INIT_SPACE);
+    }
+
+    #[test]
+    fn test_purchase_terms_snapshot_main() {
+        let mut trade = TradeAccount {
+            product_cost: 1_000,
+            token_mint: Pubkey::new_unique(),
+            ..Default::default()
+        };
+
+        let mut purchase = PurchaseAccount::default();
+        purchase
+            .open(PurchaseTerms {
+                purchase_id: 1,
+                trade_id: 1,
+                buyer: Pubkey::new_unique(),
+                payer: Pubkey::new_unique(),
+                token_mint: trade.token_mint,
+                quantity: 2,
+                unit_price: trade.product_cost,
+                logistics_provider: Pubkey::new_unique(),
+                logistics_cost: 100,
+                fee_bps: ESCROW_FEE_PERCENT as u16,
+                memo_hash: None,
+                fill_price: 0,
+                fill_expo: 0,
+                insurance_premium: 0,
+                insured: false,
+                created_at: 1_700_000_000,
+                fulfillment_start: 0,
+                bump: 255,
+            })
+            .unwrap();
+        assert_eq!(purchase.total_amount, 2_100);
+        let before = purchase.settlement_payouts().unwrap();
+
+        // Editing the listing afterwards leaves the purchase's terms alone
+        trade.product_cost = 5_000;
+        trade.token_mint = Pubkey::new_unique();
+        let after = purchase.settlement_payouts().unwrap();
+        assert_eq!(after.seller_amount, before.seller_amount);
+        assert_eq!(after.logistics_amount, before.logistics_amount);
+        assert_eq!(after.fee_amount, before.fee_amount);
+        assert_eq!(purchase.fee_bps as u64, ESCROW_FEE_PERCENT);
+        assert_ne!(purchase.token_mint, trade.token_mint);
+    }
+
+    #[test]
+    fn test_purchase_memo_hash_main() {
+        let memo_hash = [7u8; 64];
+        let mut purchase = PurchaseAccount::default();
+        assert_eq!(purchase.memo_hash, None);
+        purchase
+            .open(PurchaseTerms {
+                purchase_id: 1,
+                trade_id: 1,
+                buyer: Pubkey::new_unique(),
+                payer: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+                quantity: 1,
+                unit_price: 1_000,
+                logistics_provider: Pubkey::new_unique(),
+                logistics_cost: 100,
+                fee_bps: ESCROW_FEE_PERCENT as u16,
+                memo_hash: Some(memo_hash),
+                fill_price: 0,
+                fill_expo: 0,
+                insurance_premium: 0,
+                insured: false,
+                created_at: 1_700_000_000,
+                fulfillment_start: 0,
+                bump: 255,
+            })
+            .unwrap();
+        assert_eq!(purchase.memo_hash, Some(memo_hash));
+
+        // The memo survives a round trip through the account data
+        let bytes = purchase.try_to_vec().unwrap();
+        let stored = PurchaseAccount::deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(stored.memo_hash, Some(memo_hash));
+        assert_eq!(bytes.len(), PurchaseAccount::INIT_SPACE);
+    }
+
+    #[test]
+    fn test_shipping_info_main() {
+        let shipping_info = ShippingInfo {
+            purchase_id: 7,
+            ephemeral_pubkey: [9u8; 32],
+            seller_ciphertext: vec![1u8; MAX_SHIPPING_CIPHERTEXT_LEN],
+            provider_ciphertext: vec![2u8; MAX_SHIPPING_CIPHERTEXT_LEN],
+            ..Default::default()
+        };
+
+        // Both ciphertexts at their bound still fit the allocation
+        assert_eq!(shipping_info.try_to_vec().unwrap().len(), ShippingInfo::INIT_SPACE);
+
+        // One account per purchase, apart from the purchase itself
+        assert_ne!(pda::shipping_info_pda(7).0, pda::shipping_info_pda(8).0);
+        assert_ne!(pda::shipping_info_pda(7).0, pda::purchase_pda(7).0);
+    }
+
+    #[test]
+    fn test_ruling_error_main() {
+        let buyer = create_test_pubkey(1);
+        let seller = create_test_pubkey(2);
+        let provider = create_test_pubkey(3);
+        let mut purchase = PurchaseAccount {
+            buyer,
+            chosen_logistics_provider: provider,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            purchase.ruling_error(&seller, &buyer, false),
+            Some(LogisticsError::NotDisputed)
+        ));
+        purchase.disputed = true;
+        assert!(purchase.ruling_error(&seller, &buyer, true).is_none());
+        assert!(purchase.ruling_error(&seller, &seller, false).is_none());
+
+        // Only a party to the purchase may win, and a slashed provider cannot
+        assert!(matches!(
+            purchase.ruling_error(&seller, &create_test_pubkey(4), false),
+            Some(LogisticsError::InvalidWinner)
+        ));
+        assert!(matches!(
+            purchase.ruling_error(&seller, &provider, true),
+            Some(LogisticsError::InvalidWinner)
+        ));
+
+        // A batch skips disputes that already carry a ruling
+        purchase
+            .set_dispute_ruling(&DisputeRuling {
+                arbitrator: create_test_pubkey(5),
+                winner: buyer,
+                slash_provider: false,
+                ruled_at: 1_700_000_000,
+                escalated_by: Pubkey::default(),
+                escalation_fee: 0,
+            })
+            .unwrap();
+        let error = purchase.ruling_error(&seller, &buyer, false).unwrap();
+        assert!(matches!(error, LogisticsError::AlreadyRuled));
+        assert_eq!(
+            u32::from(error),
+            anchor_lang::error::ERROR_CODE_OFFSET + LogisticsError::AlreadyRuled as u32
+        );
+    }
+
+    #[test]
+    fn test_unlimited_quantity_main() {
+        let mut trade = TradeAccount {
+            active: true,
+            remaining_quantity: 2,
+            ..Default::default()
+        };
+
+        // Limited listings sell out
+        assert!(!trade.has_stock(3));
+        assert_eq!(
+            trade.check_stock(3).unwrap_err(),
+            LogisticsError::InsufficientQuantity.into()
+        );
+        assert!(trade.record_sale(3).is_err());
+        trade.record_sale(2).unwrap();
+        assert_eq!(trade.remaining_quantity, 0);
+        assert_eq!(trade.sold_quantity, 2);
+        assert!(!trade.active);
+        trade.record_refund(1);
+        assert_eq!(trade.remaining_quantity, 1);
+        assert_eq!(trade.sold_quantity, 1);
+        assert!(trade.active);
+
+        // Made-to-order listings only count sales
+        trade.unlimited = true;
+        trade.set_low_stock_threshold(5).unwrap();
+        assert!(!trade.is_low_stock());
+        assert!(trade.has_stock(u64::MAX));
+        trade.check_stock(u64::MAX).unwrap();
+        trade.record_sale(10).unwrap();
+        assert_eq!(trade.remaining_quantity, 1);
+        assert_eq!(trade.sold_quantity, 11);
+        assert!(trade.active);
+        trade.record_refund(4);
+        assert_eq!(trade.remaining_quantity, 1);
+        assert_eq!(trade.sold_quantity, 7);
+
+        // Lots held for auctions and drops leave no trace either
+        trade.take_stock(100).unwrap();
+        trade.return_stock(100);
+        assert_eq!(trade.remaining_quantity, 1);
+    }
+
+    #[test]
+    fn test_preorder_timers_main() {
+        let mut trade = TradeAccount {
+            fulfillment_start: 1_800_000_000,
+            ..Default::default()
+        };
+        assert_eq!(trade.preorder_start(), 0);
+        trade.preorder = true;
+        assert_eq!(trade.preorder_start(), 1_800_000_000);
+
+        let mut purchase = PurchaseAccount {
+            created_at: 1_700_000_000,
+            shipped_at: 1_700_100_000,
+            ..Default::default()
+        };
+        assert_eq!(purchase.timers_start(), 1_700_000_000);
+        assert_eq!(purchase.auto_confirm_start(), 1_700_100_000);
+
+        // Pre-orders hold every timer until the fulfillment date
+        purchase.fulfillment_start = trade.preorder_start();
+        assert_eq!(purchase.timers_start(), 1_800_000_000);
+        assert_eq!(purchase.auto_confirm_start(), 1_800_000_000);
+
+        // Once fulfillment has started the shipment date counts again
+        purchase.shipped_at = 1_800_500_000;
+        assert_eq!(purchase.auto_confirm_start(), 1_800_500_000);
+    }
+
+    #[test]
+    fn test_prices_for_mint_main() {
+        let primary = create_test_pubkey(1);
+        let alternate = create_test_pubkey(2);
+        let mut trade = TradeAccount {
+            token_mint: primary,
+            product_cost: 1_000,
+            logistics_costs: vec![100],
+            accepted_mints: vec![AcceptedMint {
+                mint: alternate,
+                product_cost: 2_000,
+                logistics_costs: vec![200],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(trade.prices_for_mint(&primary), Some((1_000, &[100u64][..])));
+        assert_eq!(trade.prices_for_mint(&alternate), Some((2_000, &[200u64][..])));
+        assert_eq!(trade.prices_for_mint(&create_test_pubkey(3)), None);
+
+        // Per-kg rates are in primary-mint units, so weight pricing drops the alternates
+        trade.weight_grams = 500;
+        trade.logistics_rates_per_kg = vec![40];
+        assert_eq!(trade.prices_for_mint(&primary), Some((1_000, &[100u64][..])));
+        assert_eq!(trade.prices_for_mint(&alternate), None);
+    }
+
+    #[test]
+    fn test_emergency_vault_address_main() {
+        let mint = create_test_pubkey(1);
+        let owner = create_test_pubkey(2);
+        let cases = [
+            (EmergencyVault::Escrow, pda::escrow_vault_pda(&mint)),
+            (EmergencyVault::ProductEscrow { purchase_id: 7 }, pda::product_escrow_pda(7)),
+            (EmergencyVault::LogisticsEscrow { purchase_id: 7 }, pda::logistics_escrow_pda(7)),
+            (EmergencyVault::StakeVault { provider: owner }, pda::stake_vault_pda(&owner)),
+            (EmergencyVault::SellerBond { seller: owner }, pda::seller_bond_pda(&owner)),
+            (EmergencyVault::InsurancePool, pda::insurance_pool_pda(&mint)),
+            (EmergencyVault::ReturnsReserve { seller: owner }, pda::returns_reserve_pda(&owner, &mint)),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(source.address(&mint), expected);
+        }
+
+        // Purchase escrows are signed for by the mint's escrow vault
+        assert!(EmergencyVault::ProductEscrow { purchase_id: 7 }.escrow_is_authority());
+        assert!(!EmergencyVault::StakeVault { provider: owner }.escrow_is_authority());
+    }
+}